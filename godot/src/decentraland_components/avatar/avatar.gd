@@ -28,6 +28,17 @@ const SCREEN_NOTIFIER_AABB: AABB = AABB(Vector3(-1.0, -0.3, -1.0), Vector3(2.0,
 # Fallback nametag height when no meshes are loaded yet (meters above avatar origin).
 const DEFAULT_NAMETAG_HEIGHT := 1.9
 
+# Head look-at (rfc4 LookAtPosition) is released after this long without updates.
+const LOOK_AT_RELEASE_MS := 3000
+const LOOK_AT_MAX_YAW_DEG := 70.0
+const LOOK_AT_MAX_PITCH_DEG := 40.0
+
+# Remote voice is played from the head with distance attenuation: full volume up to
+# VOICE_UNIT_SIZE meters, inaudible past VOICE_MAX_DISTANCE.
+const VOICE_HEAD_HEIGHT := 1.6
+const VOICE_UNIT_SIZE := 4.0
+const VOICE_MAX_DISTANCE := 40.0
+
 # Maps AvatarAnchorPointType (SDK proto, see avatar_attach.proto) to skeleton
 # bone names. Ids 0 (POSITION) and 1 (NAME_TAG) are non-skeletal and resolved
 # directly in get_anchor_point_global_transform.
@@ -117,8 +128,10 @@ var wearables_by_category: Dictionary = {}
 
 var emote_controller: AvatarEmoteController  # Rust binded. Don't change this variable name
 
-var voice_chat_audio_player: AudioStreamPlayer = null
+var voice_chat_audio_player: AudioStreamPlayer3D = null
 var voice_chat_audio_player_gen: AudioStreamGenerator = null
+var voice_volume: float = 1.0  # set per peer from CommunicationManager.set_peer_voice_volume
+var voice_muted_by_area: bool = false
 
 var mask_material = preload("res://assets/avatar/mask_material.tres")
 
@@ -224,6 +237,12 @@ var _anim_throttle_acc: float = 0.0
 var _anim_throttle_counter: int = 0
 var _anim_throttle_active: bool = false
 
+# Head look-at driven by rfc4 LookAtPosition (set_head_look_at). Created on the
+# first update and released after LOOK_AT_RELEASE_MS without updates.
+var _look_at_modifier: LookAtModifier3D = null
+var _look_at_target: Node3D = null
+var _look_at_last_update_ms: int = 0
+
 @onready var animation_tree = $AnimationTree
 @onready var animation_player = $AnimationPlayer
 
@@ -386,10 +405,11 @@ func _on_set_avatar_modifier_area(area: DclAvatarModifierArea3D):
 		return  # the avatar is not going to be modified
 
 	for modifier in area.avatar_modifiers:
-		if modifier == 0:  # hide avatar
+		if modifier == 0:  # hide avatar (and its voice)
 			hide()
 			_hide_impostor_render()
 			_set_click_area_enabled(false)
+			voice_muted_by_area = true
 		elif modifier == 1:  # disable passport
 			passport_disabled = true
 		elif modifier == 2:  # hide nametag
@@ -460,6 +480,7 @@ func _unset_avatar_modifier_area():
 		_set_click_area_enabled(true)
 	passport_disabled = false
 	nametag_hidden = false
+	voice_muted_by_area = false
 	_apply_nickname_visibility()
 
 
@@ -1388,6 +1409,7 @@ func _process(delta):
 
 	_maybe_update_lod()
 	_tick_animation_throttle(delta)
+	_release_head_look_at_if_stale()
 
 	if _lod_state == LODState.FAR:
 		return
@@ -1574,8 +1596,17 @@ func _play_glider_clip_if_different(clip: String, blend_time: float = -1.0) -> v
 
 
 func spawn_voice_channel(sample_rate, _num_channels, _samples_per_channel):
-	voice_chat_audio_player = AudioStreamPlayer.new()
+	# The track can be republished (reconnections), keep a single player
+	if voice_chat_audio_player != null:
+		voice_chat_audio_player.queue_free()
+
+	voice_chat_audio_player = AudioStreamPlayer3D.new()
 	voice_chat_audio_player.set_bus("VoiceChat")
+	voice_chat_audio_player.position = Vector3(0, VOICE_HEAD_HEIGHT, 0)
+	voice_chat_audio_player.attenuation_model = AudioStreamPlayer3D.ATTENUATION_INVERSE_DISTANCE
+	voice_chat_audio_player.unit_size = VOICE_UNIT_SIZE
+	voice_chat_audio_player.max_distance = VOICE_MAX_DISTANCE
+	voice_chat_audio_player.volume_db = linear_to_db(voice_volume)
 	voice_chat_audio_player_gen = AudioStreamGenerator.new()
 
 	voice_chat_audio_player.set_stream(voice_chat_audio_player_gen)
@@ -1584,7 +1615,20 @@ func spawn_voice_channel(sample_rate, _num_channels, _samples_per_channel):
 	voice_chat_audio_player.play()
 
 
+func set_voice_volume(volume: float):
+	voice_volume = volume
+	if voice_chat_audio_player != null:
+		voice_chat_audio_player.volume_db = linear_to_db(voice_volume)
+
+
+func is_voice_muted() -> bool:
+	return hidden or voice_muted_by_area or voice_volume <= 0.0
+
+
 func push_voice_frame(frame):
+	if voice_chat_audio_player == null or is_voice_muted():
+		return
+
 	if not voice_chat_audio_player.playing:
 		voice_chat_audio_player.play()
 
@@ -1690,6 +1734,45 @@ func async_play_emote(emote_urn: String):
 	await emote_controller.async_play_emote(emote_urn)
 
 
+## Turns the head towards `target` (global position). Called from Rust
+## (AvatarScene::set_avatar_look_at) for rfc4 LookAtPosition.
+func set_head_look_at(target: Vector3) -> void:
+	if body_shape_skeleton_3d == null:
+		return
+	if not is_instance_valid(_look_at_modifier):
+		_look_at_target = Node3D.new()
+		_look_at_target.top_level = true
+		add_child(_look_at_target)
+
+		_look_at_modifier = LookAtModifier3D.new()
+		_look_at_modifier.bone_name = "Avatar_Head"
+		_look_at_modifier.forward_axis = SkeletonModifier3D.BONE_AXIS_PLUS_Z
+		_look_at_modifier.use_angle_limitation = true
+		_look_at_modifier.symmetry_limitation = true
+		_look_at_modifier.primary_limit_angle = deg_to_rad(LOOK_AT_MAX_YAW_DEG) * 2.0
+		_look_at_modifier.secondary_limit_angle = deg_to_rad(LOOK_AT_MAX_PITCH_DEG) * 2.0
+		_look_at_modifier.duration = 0.3
+		body_shape_skeleton_3d.add_child(_look_at_modifier)
+		_look_at_modifier.target_node = _look_at_modifier.get_path_to(_look_at_target)
+
+	_look_at_target.global_position = target
+	_look_at_modifier.active = true
+	_look_at_last_update_ms = Time.get_ticks_msec()
+
+
+func _release_head_look_at_if_stale() -> void:
+	if _look_at_modifier == null:
+		return
+	if Time.get_ticks_msec() - _look_at_last_update_ms < LOOK_AT_RELEASE_MS:
+		return
+	if is_instance_valid(_look_at_modifier):
+		_look_at_modifier.queue_free()
+	if is_instance_valid(_look_at_target):
+		_look_at_target.queue_free()
+	_look_at_modifier = null
+	_look_at_target = null
+
+
 ## Stop a looping emote on network request (rfc4 PlayerEmote.is_stopping /
 ## Pulse EmoteStopped). Called from Rust (AvatarScene::stop_emote).
 func stop_emote_from_network():
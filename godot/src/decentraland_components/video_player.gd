@@ -5,8 +5,9 @@ extends DclVideoPlayer
 ## - ExoPlayer: Android video playback with GPU acceleration
 ## - AVPlayer: iOS video playback (future)
 ## - Noop: Fallback when no backend is available
+## - Software: Desktop Linux decoding in Rust (MP4 / H.264 + AAC), opt-in with --software-video
 
-enum BackendType { LIVEKIT = 0, EXO_PLAYER = 1, AV_PLAYER = 2, NOOP = 3 }
+enum BackendType { LIVEKIT = 0, EXO_PLAYER = 1, AV_PLAYER = 2, NOOP = 3, SOFTWARE = 4 }
 
 # Video state constants (matching Rust VIDEO_STATE_* constants)
 const VIDEO_STATE_NONE = 0
@@ -73,6 +74,8 @@ func _init_backend_impl(backend_type: int, source: String, playing: bool, loopin
 			_async_init_exo_player_backend()
 		BackendType.AV_PLAYER:
 			_async_init_av_player_backend()
+		BackendType.SOFTWARE:
+			_async_init_software_backend()
 		_:
 			_init_noop_backend()
 
@@ -272,6 +275,33 @@ func _async_init_av_player_backend():
 		av_player.play()
 
 
+func _async_init_software_backend():
+	print("VideoPlayer: Initializing software backend for ", _source)
+
+	# Set initial state to loading (Rust updates it from the decoder thread)
+	video_state = VIDEO_STATE_LOADING
+
+	# Frames are uploaded into dcl_texture, an ImageTexture that starts black,
+	# so there is no garbage to hide before the first frame.
+	_has_received_frame = true
+
+	# Decoded audio is pushed by Rust; the mix rate follows the media
+	var audio_stream_generator = AudioStreamGenerator.new()
+	audio_stream_generator.mix_rate = 44100.0
+	audio_stream_generator.buffer_length = 1.0
+	self.set_stream(audio_stream_generator)
+	self.play()
+
+	if _source.begins_with("http://") or _source.begins_with("https://"):
+		return
+
+	# For local files, the decoder thread waits until we resolve the cached path
+	var absolute_file_path = await _async_fetch_local_video()
+	if absolute_file_path.is_empty():
+		return
+	resolve_resource(absolute_file_path)
+
+
 func _init_noop_backend():
 	print("VideoPlayer: Using Noop backend (video playback not available)")
 	video_state = VIDEO_STATE_NONE
@@ -334,7 +364,7 @@ func _update_effective_volume():
 			_update_exo_player_volume()
 		BackendType.AV_PLAYER:
 			_update_av_player_volume()
-		BackendType.LIVEKIT:
+		BackendType.LIVEKIT, BackendType.SOFTWARE:
 			_update_livekit_volume()
 		_:
 			pass
@@ -377,7 +407,7 @@ func _update_av_player_volume():
 	_update_native_player_volume(av_player)
 
 
-## LiveKit uses Godot's AudioStreamPlayer which goes through audio buses
+## LiveKit (and the software backend) use Godot's AudioStreamPlayer which goes through audio buses
 ## Godot buses handle master/scene volume, we only apply video's own volume
 func _update_livekit_volume():
 	var effective_volume: float = 0.0 if (dcl_muted or not _is_playing) else dcl_volume
@@ -400,6 +430,8 @@ func _update_video_state():
 			_update_av_player_state()
 		BackendType.LIVEKIT:
 			_update_livekit_state()
+		BackendType.SOFTWARE:
+			software_poll()
 		_:
 			pass
 
@@ -519,6 +551,8 @@ func _apply_play():
 				av_player.play()
 		BackendType.LIVEKIT:
 			pass  # Rust will resume updating texture when _is_playing is true
+		BackendType.SOFTWARE:
+			software_play()
 		_:
 			pass
 
@@ -549,6 +583,8 @@ func _apply_pause():
 		BackendType.LIVEKIT:
 			# Freeze last frame - Rust will stop updating texture while _is_playing is false
 			video_state = VIDEO_STATE_PAUSED
+		BackendType.SOFTWARE:
+			software_pause()
 		_:
 			pass
 
@@ -562,6 +598,8 @@ func _backend_set_looping(looping: bool):
 		BackendType.AV_PLAYER:
 			if av_player:
 				av_player.set_looping(looping)
+		BackendType.SOFTWARE:
+			software_set_looping(looping)
 		_:
 			pass
 
@@ -576,6 +614,8 @@ func _backend_seek(position_sec: float):
 				av_player.set_position(position_sec)
 		BackendType.LIVEKIT:
 			pass  # LiveKit is a live stream, seeking not supported
+		BackendType.SOFTWARE:
+			software_seek(position_sec)
 		_:
 			pass
 
@@ -590,6 +630,8 @@ func _backend_set_playback_rate(rate: float):
 				av_player.set_playback_rate(rate)
 		BackendType.LIVEKIT:
 			pass  # LiveKit is a live stream, playback rate not supported
+		BackendType.SOFTWARE:
+			pass  # The software decoder only plays at normal speed
 		_:
 			pass
 
@@ -604,6 +646,8 @@ func _backend_dispose():
 			if av_player:
 				av_player.queue_free()
 				av_player = null
+		BackendType.SOFTWARE:
+			pass  # The decoder thread is disposed by Rust (DclVideoPlayer::backend_dispose)
 		_:
 			pass
 
@@ -632,8 +676,8 @@ func _get_backend_texture() -> Texture2D:
 		BackendType.AV_PLAYER:
 			if av_player:
 				return av_player.get_texture()
-		BackendType.LIVEKIT:
-			# LiveKit uses dcl_texture which is set from Rust
+		BackendType.LIVEKIT, BackendType.SOFTWARE:
+			# LiveKit and the software backend use dcl_texture which is set from Rust
 			return dcl_texture
 		_:
 			pass
@@ -567,6 +567,37 @@ func _ready():
 	await _async_clear_cache_if_needed()
 	print("[Startup] global._async_clear_cache end: %dms" % (Time.get_ticks_msec() - _startup_time))
 
+	# Offline CRDT replay of a Scene Inspector session (cargo run -- crdt-replay)
+	if not cli.crdt_replay.is_empty():
+		var output: String = cli.crdt_replay_output
+		if output.is_empty():
+			output = cli.crdt_replay.get_basename() + ".state.json"
+		var error := DclCrdtReplay.dump_session(
+			cli.crdt_replay, cli.crdt_replay_scene, cli.crdt_replay_at, output
+		)
+		if error.is_empty():
+			print("CRDT replay written to ", output)
+		else:
+			printerr("CRDT replay failed: ", error)
+		get_tree().quit(0 if error.is_empty() else 1)
+		return
+
+	# Headless scene smoke test (cargo run -- run --headless-scene <folder>)
+	if not cli.headless_scene.is_empty():
+		var runner := DclHeadlessSceneRunner.new()
+		runner.set_name("headless_scene_runner")
+		add_child(runner)
+		runner.finished.connect(
+			func(success: bool):
+				print("Headless scene ", "passed" if success else "failed")
+				get_tree().quit(0 if success else 1)
+		)
+		if not runner.start(
+			cli.headless_scene, cli.headless_scene_ticks, cli.headless_scene_report
+		):
+			get_tree().quit(1)
+		return
+
 	# #[itest] only needs a godot context, not the all explorer one
 	if cli.test_runner:
 		print("Running godot-tests...")
@@ -800,6 +831,9 @@ func _async_clear_cache_if_needed() -> void:
 		if version_changed:
 			config.local_assets_cache_version = Global.LOCAL_ASSETS_CACHE_VERSION
 			config.save_to_settings_file()
+	elif cli.verify_cache_startup:
+		# Runs in the background, corrupt files are deleted and downloaded again on demand
+		Global.content_provider.verify_cache_folder()
 
 
 func _init_dynamic_graphics_manager() -> void:
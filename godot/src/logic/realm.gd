@@ -137,6 +137,7 @@ func async_clear_realm():
 	realm_scene_urns.clear()
 	realm_global_scene_urns.clear()
 	realm_city_loader_content_base_url = ""
+	enforce_scene_permissions = false
 	realm_name = ""
 	network_id = 0
 	content_base_url = ""
@@ -265,6 +266,7 @@ func async_set_realm(new_realm_string: String, search_new_pos: bool = false) ->
 
 	realm_name = configuration.get("realmName", "no_realm_name")
 	network_id = int(configuration.get("networkId", 1))  # 1=Ethereum
+	enforce_scene_permissions = bool(configuration.get("enforceScenePermissions", false))
 
 	# get minimap
 	var map_config = configuration.get("map", {})
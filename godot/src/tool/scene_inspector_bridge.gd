@@ -148,6 +148,29 @@ func _on_command(cmd: String, args: Dictionary, request_id: String) -> void:
 			_apply_subscribe(args, false)
 			data = {"streams": args.get("streams", [])}
 
+		"put_component", "delete_component":
+			# Write-back: edit a component of a scene entity live. `component` is a
+			# name ("Transform") or id, `value` the JSON shape the CRDT stream uses.
+			# The renderer applies it and echoes it to the scene's JS.
+			var scene_id: int = args.get("scene_id", -1)
+			var entity_id: int = args.get("entity_id", 0)
+			var component = args.get("component", "")
+			if component is float:
+				component = int(component)
+			var error: String
+			if cmd == "put_component":
+				var value_json := JSON.stringify(args.get("value", {}))
+				error = Global.scene_runner.debug_put_component(
+					scene_id, entity_id, str(component), value_json
+				)
+			else:
+				error = Global.scene_runner.debug_delete_component(
+					scene_id, entity_id, str(component)
+				)
+			if not error.is_empty():
+				ok = false
+				data = {"error": error}
+
 		_:
 			# Inspection / eval verbs (ping, scenes, scene, entity, ui_scene,
 			# ui_entity, avatars, avatar, app_ui, focus, eval) run through the
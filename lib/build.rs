@@ -134,6 +134,16 @@ fn generate_enum(proto_components: &Vec<Component>) {
     }
     name_mapping += "        _ => \"Unknown\",\n    }\n}\n";
 
+    // ...and the reverse, for tools reading Scene Inspector logs back
+    name_mapping += "pub fn component_name_to_id(name: &str) -> Option<u32> {\n    match name {\n        \"Transform\" => Some(1),\n        \"InternalPlayerData\" => Some(101),\n";
+    for component in proto_components {
+        name_mapping += &format!(
+            "        \"{}\" => Some({}),\n",
+            component.pascal_name, component.id
+        );
+    }
+    name_mapping += "        _ => None,\n    }\n}\n";
+
     let output_str = format!("impl SceneComponentId {{ {output_str} }}\n\n{name_mapping}");
     generate_file(dest_path, output_str.as_bytes());
 }
@@ -305,18 +315,24 @@ impl SceneCrdtStateProtoComponents {{
 }
 
 /// Generate `deserialize_proto_component_to_json` so the runtime Scene Inspector
-/// can decode any proto component without us hand-maintaining a 60+ entry table.
+/// can decode any proto component without us hand-maintaining a 60+ entry table,
+/// and its inverse `serialize_json_to_proto_component` for the write-back commands.
 /// Stays in sync with the .proto sources automatically.
 fn generate_deserialize_component(proto_components: &Vec<Component>) {
     let out_dir = env::var("OUT_DIR").unwrap();
     let dest_path = Path::new(&out_dir).join("deserialize_component.gen.rs");
 
     let mut arms = String::new();
+    let mut encode_arms = String::new();
     for component in proto_components {
         arms += &format!(
             "        {} => decode_component!(Pb{}),\n",
             component.id, component.pascal_name
         );
+        encode_arms += &format!(
+            "        {} => encode_component!(Pb{}),\n",
+            component.id, component.pascal_name
+        );
     }
 
     let body = format!(
@@ -339,6 +355,25 @@ fn generate_deserialize_component(proto_components: &Vec<Component>) {
 {arms}        \
                 _ => None,\n    \
             }}\n\
+        }}\n\
+        \n\
+        /// Encode any proto component by id from the JSON shape\n\
+        /// `deserialize_proto_component_to_json` produces. None for unknown ids.\n\
+        fn serialize_json_to_proto_component(\n    \
+            component_id: u32,\n    \
+            json: serde_json::Value,\n\
+        ) -> Option<Result<Vec<u8>, serde_json::Error>> {{\n    \
+            use prost::Message;\n    \
+            use sdk::components::*;\n    \
+            macro_rules! encode_component {{\n        \
+                ($type:ty) => {{{{\n            \
+                    Some(serde_json::from_value::<$type>(json).map(|v| v.encode_to_vec()))\n        \
+                }}}};\n    \
+            }}\n    \
+            match component_id {{\n\
+{encode_arms}        \
+                _ => None,\n    \
+            }}\n\
         }}\n"
     );
     generate_file(dest_path, body.as_bytes());
@@ -434,8 +469,10 @@ fn main() -> io::Result<()> {
 
     std::env::set_var("PROTOC", protoc_path);
 
-    // Always derive serde::Serialize on proto types so the runtime Scene Inspector
-    // can serialize component payloads when --scene-inspector is enabled.
+    // Always derive serde::Serialize/Deserialize on proto types so the runtime
+    // Scene Inspector can serialize component payloads when --scene-inspector is
+    // enabled, and decode the ones its write-back commands send. Messages take
+    // `#[serde(default)]` so a command only needs the fields it sets, like proto3.
     //
     // This is intentionally applied to ALL proto types (`"."`) rather than a
     // hand-curated whitelist. Rationale:
@@ -444,7 +481,8 @@ fn main() -> io::Result<()> {
     //   - Maintenance: a whitelist drifts out of sync with the .proto sources
     //     every time a new component is added; blanket-derive avoids that.
     let mut prost_config = prost_build::Config::new();
-    prost_config.type_attribute(".", "#[derive(serde::Serialize)]");
+    prost_config.type_attribute(".", "#[derive(serde::Serialize, serde::Deserialize)]");
+    prost_config.message_attribute(".", "#[serde(default)]");
     prost_config.service_generator(Box::new(dcl_rpc::codegen::RPCServiceGenerator::new()));
     // Emit the descriptor set so build_quant can read the Pulse quantization
     // field options (the .proto stays the single source of truth for the wire ABI).
@@ -218,6 +218,23 @@ pub struct SegmentEventPerformanceMetrics {
     pub optimized_scene_pct: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub optimized_wearable_pct: Option<f32>,
+
+    // Comms scene messages since the previous event (see comms/adapter/scene_batching.rs)
+    // Messages sent by the scenes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comms_scene_message_count: Option<u64>,
+    // Packets they were sent in (fewer than messages when batched)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comms_scene_packet_count: Option<u64>,
+    // Bytes of the messages as the scenes sent them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comms_scene_payload_bytes: Option<u64>,
+    // Bytes put on the wire for them, after batching and compression
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comms_scene_wire_bytes: Option<u64>,
+    // Packets whose batch was deflated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comms_scene_compressed_count: Option<u64>,
 }
 
 #[derive(Serialize, Clone)]
@@ -256,7 +273,8 @@ pub const LOADING_SCHEMA_VERSION: u32 = 1;
 /// its `type`. Values are bucketed/rounded — no PII, no parcel coords, no URLs.
 #[derive(Serialize, Clone, Default)]
 pub struct SegmentEventLoading {
-    /// Discriminator: "started" | "progress" | "completed" | "asset_failure" | "realm_change_failed".
+    /// Discriminator: "started" | "progress" | "completed" | "asset_failure" | "realm_change_failed"
+    /// | "scene_quota_exceeded".
     #[serde(rename = "type")]
     pub event_type: String,
     /// Correlation id for one complete load (episode). Shared by every event of that load.
@@ -353,7 +371,7 @@ pub struct SegmentEventLoading {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub frames: Option<i64>,
 
-    // --- type = "asset_failure" | "realm_change_failed" ---
+    // --- type = "asset_failure" | "realm_change_failed" | "scene_quota_exceeded" ---
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reason: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
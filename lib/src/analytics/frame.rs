@@ -104,6 +104,12 @@ impl Frame {
                     runtime_wearable_count: None,
                     optimized_scene_pct: None,
                     optimized_wearable_pct: None,
+                    // Comms scene message counters (populated by metrics.rs)
+                    comms_scene_message_count: None,
+                    comms_scene_packet_count: None,
+                    comms_scene_payload_bytes: None,
+                    comms_scene_wire_bytes: None,
+                    comms_scene_compressed_count: None,
                 }));
 
             self.dt_ms_vec.resize(0, 0.0);
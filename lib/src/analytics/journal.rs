@@ -0,0 +1,406 @@
+//! On-disk journal of the Segment events not delivered yet, so a crash or an offline session
+//! doesn't lose them.
+//!
+//! One JSON-lines file (`user://analytics_journal.jsonl`). Events are journaled in memory, already
+//! serialized, when they're queued (consent permitting) and dropped once Segment accepted the
+//! batch carrying them. `take_write` hands the pending file update to a background task: the new
+//! lines are appended, and the file is rewritten with only the live events once enough stale
+//! lines piled up. The entries left over by a previous session are handed once to
+//! `Metrics::process_and_send_events`; their `messageId` is kept, so a batch that reached Segment
+//! right before a crash (or before its removal was saved) is deduplicated there.
+
+use std::{
+    collections::{HashSet, VecDeque},
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Oldest events are dropped past this, bounding the file during a long outage
+pub const MAX_JOURNAL_EVENTS: usize = 2000;
+/// Events older than this aren't replayed, stale funnel data skews more than it helps
+pub const MAX_JOURNAL_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+/// Sent or dropped events left in the file before it's compacted, when there are fewer live ones
+const MIN_STALE_LINES_TO_COMPACT: usize = 200;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct JournalEntry {
+    message_id: String,
+    /// Unix seconds when the event was queued
+    created_at: u64,
+    /// The serialized Segment batch item
+    body: String,
+    /// Left over by a previous session and not replayed yet
+    #[serde(skip)]
+    pending_replay: bool,
+}
+
+pub struct EventJournal {
+    path: Option<PathBuf>,
+    entries: VecDeque<JournalEntry>,
+    message_ids: HashSet<String>,
+    /// `(message_id, line)` of the entries recorded since the last `take_write`
+    unsaved: Vec<(String, String)>,
+    /// Lines in the file once the writes taken so far are applied, including the stale ones
+    file_lines: usize,
+    /// The file has malformed lines, or a write failed and its contents are unknown
+    needs_rewrite: bool,
+}
+
+/// A file update taken from the journal, applied off the main thread
+pub struct JournalWrite {
+    path: PathBuf,
+    kind: JournalWriteKind,
+}
+
+enum JournalWriteKind {
+    Append(String),
+    Rewrite(String),
+    Remove,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl EventJournal {
+    /// Journal loaded from `path` (kept in memory only if `None`)
+    pub fn new(path: Option<PathBuf>) -> Self {
+        let mut journal = Self {
+            path,
+            entries: VecDeque::new(),
+            message_ids: HashSet::new(),
+            unsaved: Vec::new(),
+            file_lines: 0,
+            needs_rewrite: false,
+        };
+        if let Err(e) = journal.load() {
+            tracing::warn!("analytics journal: failed to load {:?}: {e}", journal.path);
+        }
+        journal
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn load(&mut self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let file = match fs::File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+
+        // Malformed lines are usually the one a crash cut short
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            self.file_lines += 1;
+            match serde_json::from_str::<JournalEntry>(&line) {
+                Ok(mut entry) if !self.message_ids.contains(&entry.message_id) => {
+                    entry.pending_replay = true;
+                    self.message_ids.insert(entry.message_id.clone());
+                    self.entries.push_back(entry);
+                }
+                _ => self.needs_rewrite = true,
+            }
+        }
+        self.enforce_limits(now_secs());
+        Ok(())
+    }
+
+    /// Journals a serialized event, ignoring message ids already in it
+    pub fn record(&mut self, message_id: String, created_at: u64, body: String) {
+        if self.message_ids.contains(&message_id) {
+            return;
+        }
+        let entry = JournalEntry {
+            message_id: message_id.clone(),
+            created_at,
+            body,
+            pending_replay: false,
+        };
+        if self.path.is_some() {
+            match serde_json::to_string(&entry) {
+                Ok(line) => self.unsaved.push((message_id.clone(), line)),
+                Err(e) => tracing::warn!("analytics journal: failed to serialize: {e}"),
+            }
+        }
+        self.message_ids.insert(message_id);
+        self.entries.push_back(entry);
+        self.enforce_limits(now_secs());
+    }
+
+    /// Drops the events Segment accepted. They stay in the file until it's compacted.
+    pub fn remove_sent<'a>(&mut self, message_ids: impl IntoIterator<Item = &'a str>) {
+        let mut removed = false;
+        for message_id in message_ids {
+            removed |= self.message_ids.remove(message_id);
+        }
+        if removed {
+            self.entries
+                .retain(|entry| self.message_ids.contains(&entry.message_id));
+        }
+    }
+
+    /// The serialized events a previous session left over, oldest first, up to `max_bytes` (at
+    /// least one) so a large backlog is spread over several batches. Each is only returned once,
+    /// they stay journaled until `remove_sent`.
+    pub fn take_replay(&mut self, max_bytes: usize) -> Vec<String> {
+        let mut replay = Vec::new();
+        let mut total_bytes: usize = 0;
+        for entry in self.entries.iter_mut().filter(|entry| entry.pending_replay) {
+            if !replay.is_empty() && total_bytes.saturating_add(entry.body.len()) > max_bytes {
+                break;
+            }
+            total_bytes += entry.body.len();
+            entry.pending_replay = false;
+            replay.push(entry.body.clone());
+        }
+        replay
+    }
+
+    /// Drops the entries over the count or older than the max age
+    fn enforce_limits(&mut self, now: u64) {
+        let min_created_at = now.saturating_sub(MAX_JOURNAL_AGE.as_secs());
+        while self.entries.len() > MAX_JOURNAL_EVENTS
+            || self
+                .entries
+                .front()
+                .is_some_and(|entry| entry.created_at < min_created_at)
+        {
+            if let Some(entry) = self.entries.pop_front() {
+                self.message_ids.remove(&entry.message_id);
+            }
+        }
+    }
+
+    /// The update that brings the file in line with the journal, `None` if it already is. Writes
+    /// must be applied in the order they were taken.
+    pub fn take_write(&mut self) -> Option<JournalWrite> {
+        let path = self.path.clone()?;
+        let unsaved = std::mem::take(&mut self.unsaved);
+        let appended: Vec<String> = unsaved
+            .into_iter()
+            .filter(|(message_id, _)| self.message_ids.contains(message_id))
+            .map(|(_, line)| line)
+            .collect();
+
+        let total_lines = self.file_lines + appended.len();
+        let stale_lines = total_lines.saturating_sub(self.entries.len());
+        let kind = if self.entries.is_empty() {
+            if total_lines == 0 && !self.needs_rewrite {
+                return None;
+            }
+            self.file_lines = 0;
+            JournalWriteKind::Remove
+        } else if self.needs_rewrite
+            || stale_lines >= self.entries.len().max(MIN_STALE_LINES_TO_COMPACT)
+        {
+            let mut buffer = String::new();
+            for entry in &self.entries {
+                match serde_json::to_string(entry) {
+                    Ok(line) => {
+                        buffer.push_str(&line);
+                        buffer.push('\n');
+                    }
+                    Err(e) => tracing::warn!("analytics journal: failed to serialize: {e}"),
+                }
+            }
+            self.file_lines = self.entries.len();
+            JournalWriteKind::Rewrite(buffer)
+        } else if !appended.is_empty() {
+            self.file_lines = total_lines;
+            let mut buffer = String::new();
+            for line in appended {
+                buffer.push_str(&line);
+                buffer.push('\n');
+            }
+            JournalWriteKind::Append(buffer)
+        } else {
+            return None;
+        };
+        self.needs_rewrite = false;
+        Some(JournalWrite { path, kind })
+    }
+
+    /// A taken write failed, the next one rewrites the whole file
+    pub fn write_failed(&mut self) {
+        self.needs_rewrite = true;
+    }
+}
+
+impl JournalWrite {
+    pub fn apply(self) -> io::Result<()> {
+        let path = &self.path;
+        if let JournalWriteKind::Remove = self.kind {
+            return match fs::remove_file(path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            };
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        match self.kind {
+            JournalWriteKind::Append(buffer) => fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?
+                .write_all(buffer.as_bytes()),
+            JournalWriteKind::Rewrite(buffer) => {
+                // Written aside and renamed so a crash mid-write keeps the previous file
+                let tmp_path = path.with_extension("jsonl.tmp");
+                fs::write(&tmp_path, buffer)?;
+                fs::rename(&tmp_path, path)
+            }
+            JournalWriteKind::Remove => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "dcl_analytics_journal_{name}_{}.jsonl",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn record(journal: &mut EventJournal, message_id: &str, created_at: u64) {
+        journal.record(
+            message_id.to_string(),
+            created_at,
+            format!("{{\"messageId\":\"{message_id}\"}}"),
+        );
+    }
+
+    fn save(journal: &mut EventJournal) {
+        if let Some(write) = journal.take_write() {
+            write.apply().unwrap();
+        }
+    }
+
+    fn file_lines(path: &PathBuf) -> usize {
+        fs::read_to_string(path).unwrap().lines().count()
+    }
+
+    #[test]
+    fn replays_unsent_events_once() {
+        let path = temp_path("replay");
+        let mut journal = EventJournal::new(Some(path.clone()));
+        let now = now_secs();
+        record(&mut journal, "a", now);
+        record(&mut journal, "b", now);
+        record(&mut journal, "a", now);
+        record(&mut journal, "c", now);
+        // Nothing is written until the write is taken
+        assert!(!path.exists());
+        // Events of the current session are sent by the regular queue
+        assert!(journal.take_replay(usize::MAX).is_empty());
+        journal.remove_sent(["b"]);
+        save(&mut journal);
+        assert_eq!(file_lines(&path), 2);
+
+        // A crash leaves half a line behind
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{\"message_id\":\"d\",\"crea")
+            .unwrap();
+
+        let mut journal = EventJournal::new(Some(path.clone()));
+        assert_eq!(journal.take_replay(1), vec!["{\"messageId\":\"a\"}"]);
+        assert_eq!(
+            journal.take_replay(usize::MAX),
+            vec!["{\"messageId\":\"c\"}"]
+        );
+        assert!(journal.take_replay(usize::MAX).is_empty());
+        assert_eq!(journal.len(), 2);
+        // The malformed line is compacted away
+        save(&mut journal);
+        assert_eq!(file_lines(&path), 2);
+
+        journal.remove_sent(["a", "c"]);
+        assert!(journal.is_empty());
+        save(&mut journal);
+        assert!(!path.exists());
+        assert!(journal.take_write().is_none());
+    }
+
+    #[test]
+    fn compacts_once_enough_events_were_sent() {
+        let path = temp_path("compact");
+        let mut journal = EventJournal::new(Some(path.clone()));
+        let now = now_secs();
+        for i in 0..300 {
+            record(&mut journal, &format!("event-{i}"), now);
+        }
+        save(&mut journal);
+        assert_eq!(file_lines(&path), 300);
+
+        // Sent events stay in the file while they're few
+        let sent: Vec<String> = (0..100).map(|i| format!("event-{i}")).collect();
+        journal.remove_sent(sent.iter().map(String::as_str));
+        assert!(journal.take_write().is_none());
+        record(&mut journal, "event-300", now);
+        save(&mut journal);
+        assert_eq!(file_lines(&path), 301);
+
+        let sent: Vec<String> = (100..250).map(|i| format!("event-{i}")).collect();
+        journal.remove_sent(sent.iter().map(String::as_str));
+        save(&mut journal);
+        assert_eq!(file_lines(&path), 51);
+
+        let mut journal = EventJournal::new(Some(path.clone()));
+        let replay = journal.take_replay(usize::MAX);
+        assert_eq!(replay.len(), 51);
+        assert_eq!(replay[0], "{\"messageId\":\"event-250\"}");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn drops_expired_and_overflowing_events() {
+        let path = temp_path("limits");
+        let mut journal = EventJournal::new(Some(path.clone()));
+        let now = now_secs();
+        record(&mut journal, "old", now - MAX_JOURNAL_AGE.as_secs() - 1);
+        assert!(journal.is_empty());
+
+        for i in 0..MAX_JOURNAL_EVENTS + 10 {
+            record(&mut journal, &format!("event-{i}"), now);
+        }
+        assert_eq!(journal.len(), MAX_JOURNAL_EVENTS);
+        save(&mut journal);
+        assert_eq!(file_lines(&path), MAX_JOURNAL_EVENTS);
+
+        let mut journal = EventJournal::new(Some(path.clone()));
+        let replay = journal.take_replay(usize::MAX);
+        assert_eq!(replay.len(), MAX_JOURNAL_EVENTS);
+        assert_eq!(replay[0], "{\"messageId\":\"event-10\"}");
+
+        fs::remove_file(&path).unwrap();
+    }
+}
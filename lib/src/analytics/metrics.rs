@@ -1,13 +1,20 @@
 use std::{
     collections::HashMap,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
 };
 
 use chrono::{DateTime, SecondsFormat, Utc};
-use godot::{classes::Timer, prelude::*};
+use godot::{
+    classes::{ProjectSettings, Timer},
+    prelude::*,
+};
 use uuid::Uuid;
 
 use crate::{
+    comms::adapter::scene_batching::drain_scene_batching_metrics,
     godot_classes::{
         dcl_android_plugin::DclAndroidPlugin,
         dcl_global::DclGlobal,
@@ -32,6 +39,7 @@ use super::{
     },
     frame::Frame,
     install_referrer::InstallReferrer,
+    journal::EventJournal,
 };
 
 #[derive(Clone, Copy)]
@@ -74,6 +82,15 @@ pub struct Metrics {
     // event time and Segment deduplicates them. Shared with the detached send task via Arc/Mutex.
     retry_buffer: Arc<Mutex<Vec<String>>>,
 
+    // On-disk copy of every consented event until Segment accepts it, so events of a session that
+    // crashed or never got online are sent on the next launch. Shared with the send task too. Only
+    // the instance that sends (`create_metrics`) has one, two journals on one file would rewrite
+    // each other's state.
+    journal: Option<Arc<Mutex<EventJournal>>>,
+    // A background task is writing the journal file; the next one waits for the next tick so the
+    // writes land in order.
+    journal_saving: Arc<AtomicBool>,
+
     // Which mobile platform is available (checked once at ready)
     mobile_platform: Option<MobilePlatform>,
     // Static mobile device info (fetched once at ready)
@@ -108,6 +125,11 @@ const SEGMENT_BATCH_SIZE_LIMIT_BYTES: usize = 500000;
 // outage; once exceeded we drop the oldest (they're the most likely to be stale) and log it.
 const MAX_RETRY_BUFFER_EVENTS: usize = 1000;
 
+const JOURNAL_PATH: &str = "user://analytics_journal.jsonl";
+// Journaled events of a previous session are replayed a chunk per flush, leaving room in the
+// batch for the live ones.
+const JOURNAL_REPLAY_BYTES_PER_FLUSH: usize = SEGMENT_BATCH_SIZE_LIMIT_BYTES / 2;
+
 // Default flush cadence used outside the lobby. The lobby overrides this to a snappier 2s via
 // set_flush_interval so onboarding/auth events ship fast; the rest of the app batches at 10s.
 const DEFAULT_FLUSH_INTERVAL_SECONDS: f64 = 10.0;
@@ -124,6 +146,8 @@ impl INode for Metrics {
             events: Vec::new(),
             serialized_events: Vec::new(),
             retry_buffer: Arc::new(Mutex::new(Vec::new())),
+            journal: None,
+            journal_saving: Arc::new(AtomicBool::new(false)),
             mobile_platform: None,
             device_info: None,
             debug_level: 0,
@@ -244,6 +268,7 @@ impl Metrics {
         }
 
         self.process_and_send_events(false);
+        self.save_journal();
     }
 
     /// Adjust the periodic auto-flush interval. Restarts the timer so the new cadence takes effect
@@ -283,6 +308,8 @@ impl Metrics {
             events: Vec::new(),
             serialized_events: Vec::new(),
             retry_buffer: Arc::new(Mutex::new(Vec::new())),
+            journal: Some(open_journal()),
+            journal_saving: Arc::new(AtomicBool::new(false)),
             mobile_platform: None,
             device_info: None,
             debug_level: 0,
@@ -601,6 +628,7 @@ impl Metrics {
 
         // Process all events with ignore_batch_limit = true
         self.process_and_send_events(true);
+        self.save_journal();
     }
 
     /// Open the consent gate. Flipping it on auto-flushes any pre-consent events that were queued
@@ -613,6 +641,10 @@ impl Metrics {
         self.eula_accepted = accepted;
         tracing::info!("Metrics EULA gate set to {}", accepted);
         if accepted {
+            // Pre-consent events were only held in memory
+            for queued in &self.events {
+                self.journal_event(queued);
+            }
             self.flush();
         }
     }
@@ -723,6 +755,24 @@ impl Metrics {
             }
         }
 
+        // Events a previous session crashed or went offline with. Each is handed over once and
+        // stays journaled until a send succeeds; it keeps its messageId, so Segment deduplicates
+        // one that was delivered right before the crash.
+        let replay = self
+            .journal
+            .as_ref()
+            .map(|journal| {
+                journal
+                    .lock()
+                    .unwrap()
+                    .take_replay(JOURNAL_REPLAY_BYTES_PER_FLUSH)
+            })
+            .unwrap_or_default();
+        if !replay.is_empty() {
+            tracing::debug!("Replaying {} journaled events", replay.len());
+            self.serialized_events.extend(replay);
+        }
+
         tracing::debug!(
             "process_and_send_events: events={}, serialized={}, ignore_limit={}",
             self.events.len(),
@@ -768,12 +818,14 @@ impl Metrics {
                 let write_key = self.write_key.clone();
                 let serialized_events = std::mem::take(&mut self.serialized_events);
                 let retry_buffer = self.retry_buffer.clone();
+                let journal = self.journal.clone();
                 TokioRuntime::spawn(async move {
                     Self::send_segment_batch(
                         http_requester,
                         &write_key,
                         serialized_events,
                         retry_buffer,
+                        journal,
                     )
                     .await;
                 });
@@ -797,6 +849,7 @@ impl Metrics {
             let write_key = self.write_key.clone();
             let serialized_events = std::mem::take(&mut self.serialized_events);
             let retry_buffer = self.retry_buffer.clone();
+            let journal = self.journal.clone();
             tracing::debug!(
                 "Spawning async task to send {} events",
                 serialized_events.len()
@@ -807,6 +860,7 @@ impl Metrics {
                     &write_key,
                     serialized_events,
                     retry_buffer,
+                    journal,
                 )
                 .await;
             });
@@ -816,6 +870,13 @@ impl Metrics {
     }
 }
 
+fn open_journal() -> Arc<Mutex<EventJournal>> {
+    let path = ProjectSettings::singleton()
+        .globalize_path(JOURNAL_PATH)
+        .to_string();
+    Arc::new(Mutex::new(EventJournal::new(Some(path.into()))))
+}
+
 impl Metrics {
     /// Stamp an event with its creation time and a stable message id, print it in debug mode, and
     /// queue it for the next flush. Every event path goes through here so the per-event
@@ -825,10 +886,69 @@ impl Metrics {
         let created_at = Utc::now();
         let message_id = Uuid::new_v4().to_string();
         self.debug_print_event(event_name, &event, created_at, &message_id);
-        self.events.push(QueuedSegmentEvent {
+        let queued = QueuedSegmentEvent {
             event,
             created_at,
             message_id,
+        };
+        if self.eula_accepted {
+            self.journal_event(&queued);
+        }
+        self.events.push(queued);
+    }
+
+    /// Write an event to the on-disk journal, serialized as it would be sent. Only once the user
+    /// accepted the EULA: nothing is persisted before consent.
+    fn journal_event(&self, queued: &QueuedSegmentEvent) {
+        let Some(journal) = &self.journal else {
+            return;
+        };
+        let body = build_segment_event_batch_item(
+            self.user_id.clone(),
+            &self.common,
+            queued.event.clone(),
+            queued.created_at,
+            queued.message_id.clone(),
+        );
+        let Ok(json_body) = serde_json::to_string(&body) else {
+            return;
+        };
+        // Dropped at flush anyway
+        if json_body.len() > SEGMENT_EVENT_SIZE_LIMIT_BYTES {
+            return;
+        }
+        let created_at = queued.created_at.timestamp().max(0) as u64;
+        journal
+            .lock()
+            .unwrap()
+            .record(queued.message_id.clone(), created_at, json_body);
+    }
+
+    /// Write the journal changes since the last save to disk, off the main thread
+    fn save_journal(&self) {
+        let Some(journal) = self.journal.clone() else {
+            return;
+        };
+        if self.journal_saving.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let journal_saving = self.journal_saving.clone();
+        TokioRuntime::spawn(async move {
+            let result = tokio::task::spawn_blocking(move || {
+                let Some(write) = journal.lock().unwrap().take_write() else {
+                    return Ok(());
+                };
+                write
+                    .apply()
+                    .inspect_err(|_| journal.lock().unwrap().write_failed())
+            })
+            .await;
+            journal_saving.store(false, Ordering::Release);
+            match result {
+                Ok(Err(err)) => tracing::warn!("Failed to save the analytics journal: {:?}", err),
+                Err(err) => tracing::warn!("Analytics journal save task failed: {:?}", err),
+                Ok(Ok(())) => {}
+            }
         });
     }
 
@@ -1000,6 +1120,16 @@ impl Metrics {
                     None
                 };
             }
+
+            // Comms scene message counters, drained so each event covers its own window
+            let scene_comms = drain_scene_batching_metrics();
+            if scene_comms.messages > 0 {
+                metrics.comms_scene_message_count = Some(scene_comms.messages);
+                metrics.comms_scene_packet_count = Some(scene_comms.packets);
+                metrics.comms_scene_payload_bytes = Some(scene_comms.payload_bytes);
+                metrics.comms_scene_wire_bytes = Some(scene_comms.wire_bytes);
+                metrics.comms_scene_compressed_count = Some(scene_comms.compressed_packets);
+            }
         }
     }
 
@@ -1008,16 +1138,21 @@ impl Metrics {
         write_key: &str,
         events: Vec<String>,
         retry_buffer: Arc<Mutex<Vec<String>>>,
+        journal: Option<Arc<Mutex<EventJournal>>>,
     ) {
         // Log the events being sent
         tracing::debug!("Sending segment batch with {} events", events.len());
 
-        // Parse and log each event name
+        // Parse and log each event name, keeping the message ids to clear from the journal
+        let mut message_ids = Vec::with_capacity(events.len());
         for (idx, event) in events.iter().enumerate() {
             if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(event) {
                 if let Some(event_name) = parsed.get("event").and_then(|v| v.as_str()) {
                     tracing::debug!("  Event {}: {}", idx + 1, event_name);
                 }
+                if let Some(message_id) = parsed.get("messageId").and_then(|v| v.as_str()) {
+                    message_ids.push(message_id.to_string());
+                }
             }
         }
 
@@ -1068,6 +1203,14 @@ impl Metrics {
             );
         } else {
             tracing::debug!("Segment batch sent successfully");
+            // HTTP errors count as delivered too, same as for the retry buffer. The file is
+            // updated on the next save.
+            if let Some(journal) = journal {
+                journal
+                    .lock()
+                    .unwrap()
+                    .remove_sent(message_ids.iter().map(String::as_str));
+            }
         }
     }
 }
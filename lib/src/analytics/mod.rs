@@ -1,4 +1,5 @@
 pub mod data_definition;
 pub mod frame;
 pub mod install_referrer;
+pub mod journal;
 pub mod metrics;
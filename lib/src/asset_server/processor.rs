@@ -13,7 +13,7 @@ use tokio::sync::Semaphore;
 use crate::content::content_mapping::{ContentMappingAndUrl, ContentMappingAndUrlRef};
 use crate::content::content_provider::SceneGltfContext;
 use crate::content::gltf::{
-    get_dependencies, load_and_save_emote_gltf, load_and_save_scene_gltf,
+    decoded_gltf_path, get_dependencies, load_and_save_emote_gltf, load_and_save_scene_gltf,
     load_and_save_wearable_gltf,
 };
 use crate::content::packed_array::PackedByteArrayFromVec;
@@ -226,7 +226,7 @@ async fn process_scene_gltf(
     )
     .await?;
 
-    // Decompress if Draco-compressed (overwrites original file)
+    // Decompress if Draco-compressed (to the decoded copy the GLTF pipeline loads)
     decompress_gltf_if_needed(&gltf_file_path).await?;
 
     // Extract actual texture dependencies from the downloaded GLTF file
@@ -281,7 +281,7 @@ async fn process_wearable_gltf(
     )
     .await?;
 
-    // Decompress if Draco-compressed (overwrites original file)
+    // Decompress if Draco-compressed (to the decoded copy the GLTF pipeline loads)
     decompress_gltf_if_needed(&gltf_file_path).await?;
 
     // Extract actual texture dependencies from the downloaded GLTF file
@@ -336,7 +336,7 @@ async fn process_emote_gltf(
     )
     .await?;
 
-    // Decompress if Draco-compressed (overwrites original file)
+    // Decompress if Draco-compressed (to the decoded copy the GLTF pipeline loads)
     decompress_gltf_if_needed(&gltf_file_path).await?;
 
     // Extract actual texture dependencies from the downloaded GLTF file
@@ -594,8 +594,12 @@ const IMAGE_EXTENSIONS: &[&str] = &[
     ".png", ".jpg", ".jpeg", ".webp", ".bmp", ".tga", ".ktx", ".ktx2",
 ];
 
-/// Check if a GLTF/GLB file uses Draco compression and decompress if needed.
-/// Overwrites the original file with the decompressed version.
+/// Check if a GLTF/GLB file uses Draco compression and decompress it with gltf-transform.
+/// The output is written next to the cached file (see `decoded_gltf_path`), which keeps the
+/// bytes of its hash.
+///
+/// The asset server keeps gltf-transform for Draco until the in-process decoder has been
+/// validated against real exporter output; without it the GLTF pipeline decodes it in-process.
 async fn decompress_gltf_if_needed(file_path: &str) -> Result<(), anyhow::Error> {
     let uses_draco = check_for_draco_extension(file_path).await?;
 
@@ -605,23 +609,29 @@ async fn decompress_gltf_if_needed(file_path: &str) -> Result<(), anyhow::Error>
 
     tracing::info!("Decompressing Draco-compressed GLTF: {}", file_path);
 
+    let decoded_path = decoded_gltf_path(file_path);
     // Create temporary output path
-    let temp_path = format!("{}.tmp.glb", file_path);
+    let temp_path = format!("{}.tmp.glb", decoded_path);
 
     // Run gltf-transform copy (removes Draco compression)
-    let output = tokio::process::Command::new("gltf-transform")
+    let output = match tokio::process::Command::new("gltf-transform")
         .args(["copy", file_path, &temp_path])
         .output()
         .await
-        .map_err(|e| {
-            if e.kind() == std::io::ErrorKind::NotFound {
-                anyhow::anyhow!(
-                    "gltf-transform not found. Install with: npm install -g @gltf-transform/cli"
-                )
-            } else {
-                anyhow::anyhow!("Failed to run gltf-transform: {}", e)
-            }
-        })?;
+    {
+        Ok(output) => output,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            // Same answer for every file, say it once
+            static MISSING_WARNING: std::sync::Once = std::sync::Once::new();
+            MISSING_WARNING.call_once(|| {
+                tracing::warn!(
+                    "gltf-transform not found, decoding Draco in-process. Install with: npm install -g @gltf-transform/cli"
+                );
+            });
+            return Ok(());
+        }
+        Err(e) => return Err(anyhow::anyhow!("Failed to run gltf-transform: {}", e)),
+    };
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -636,10 +646,9 @@ async fn decompress_gltf_if_needed(file_path: &str) -> Result<(), anyhow::Error>
         ));
     }
 
-    // Replace original with decompressed version
-    tokio::fs::rename(&temp_path, file_path).await?;
+    tokio::fs::rename(&temp_path, &decoded_path).await?;
 
-    tracing::info!("Draco decompression complete: {}", file_path);
+    tracing::info!("Draco decompression complete: {}", decoded_path);
 
     Ok(())
 }
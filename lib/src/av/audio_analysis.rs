@@ -0,0 +1,283 @@
+//! Renderer-side audio analysis for `PBAudioAnalysis`.
+//!
+//! Each analyzed player is routed through its own audio bus carrying an
+//! `AudioEffectCapture`; the bus sends to the player's original bus, so what the
+//! user hears is unchanged. Every scene tick the captured frames are drained and
+//! reduced to an RMS amplitude plus N log-spaced FFT bands, so scenes can build
+//! visualizers without shipping raw samples to JS.
+
+use godot::{
+    classes::{AudioEffectCapture, AudioServer},
+    prelude::*,
+};
+
+/// FFT window (power of two). ~23ms at 44.1kHz, enough resolution for 8-16 bands.
+pub const FFT_SIZE: usize = 1024;
+/// Lowest/highest frequency covered by the bands.
+const MIN_BAND_HZ: f32 = 20.0;
+const MAX_BAND_HZ: f32 = 20_000.0;
+/// Capture ring length in seconds; only the newest `FFT_SIZE` frames are used.
+const CAPTURE_BUFFER_SECONDS: f32 = 0.1;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AudioAnalysisResult {
+    /// RMS of the newest window, in [0, 1] for non-clipping audio.
+    pub amplitude: f32,
+    /// Average spectrum magnitude per band, lowest frequencies first.
+    pub bands: Vec<f32>,
+}
+
+/// Pure DSP half of the analysis (no Godot objects), fed with mono samples.
+pub struct AudioAnalyzer {
+    band_count: usize,
+    sample_rate: f32,
+    window: Vec<f32>,
+    hann: Vec<f32>,
+}
+
+impl AudioAnalyzer {
+    pub fn new(band_count: usize, sample_rate: f32) -> Self {
+        let hann = (0..FFT_SIZE)
+            .map(|i| {
+                0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / (FFT_SIZE - 1) as f32).cos()
+            })
+            .collect();
+        Self {
+            band_count: band_count.max(1),
+            sample_rate: sample_rate.max(1.0),
+            window: Vec::with_capacity(FFT_SIZE),
+            hann,
+        }
+    }
+
+    pub fn band_count(&self) -> usize {
+        self.band_count
+    }
+
+    /// Replaces the analysis window with the newest samples. An empty slice
+    /// means nothing played since the last tick, which analyzes as silence.
+    pub fn push_samples(&mut self, samples: &[f32]) {
+        if samples.is_empty() {
+            self.window.clear();
+            return;
+        }
+        let start = samples.len().saturating_sub(FFT_SIZE);
+        let keep = FFT_SIZE - (samples.len() - start);
+        if self.window.len() > keep {
+            self.window.drain(..self.window.len() - keep);
+        }
+        self.window.extend_from_slice(&samples[start..]);
+    }
+
+    pub fn analyze(&self) -> AudioAnalysisResult {
+        if self.window.is_empty() {
+            return AudioAnalysisResult {
+                amplitude: 0.0,
+                bands: vec![0.0; self.band_count],
+            };
+        }
+
+        let amplitude =
+            (self.window.iter().map(|s| s * s).sum::<f32>() / self.window.len() as f32).sqrt();
+
+        // Zero-pad on the left when the window isn't full yet.
+        let pad = FFT_SIZE - self.window.len();
+        let mut re = vec![0.0f32; FFT_SIZE];
+        let mut im = vec![0.0f32; FFT_SIZE];
+        for (i, sample) in self.window.iter().enumerate() {
+            re[pad + i] = sample * self.hann[pad + i];
+        }
+        fft_in_place(&mut re, &mut im);
+
+        // Hann window halves the amplitude; scale so a full-scale sine peaks near 1.
+        let norm = 4.0 / FFT_SIZE as f32;
+        let magnitudes: Vec<f32> = (0..FFT_SIZE / 2)
+            .map(|bin| (re[bin] * re[bin] + im[bin] * im[bin]).sqrt() * norm)
+            .collect();
+
+        let bin_hz = self.sample_rate / FFT_SIZE as f32;
+        let max_hz = MAX_BAND_HZ.min(self.sample_rate * 0.5);
+        let ratio = (max_hz / MIN_BAND_HZ).max(1.0);
+        let bands = (0..self.band_count)
+            .map(|band| {
+                let from_hz = MIN_BAND_HZ * ratio.powf(band as f32 / self.band_count as f32);
+                let to_hz = MIN_BAND_HZ * ratio.powf((band + 1) as f32 / self.band_count as f32);
+                let from_bin = ((from_hz / bin_hz).floor() as usize).min(magnitudes.len() - 1);
+                // Low bands can be narrower than one bin: always take at least one.
+                let to_bin =
+                    ((to_hz / bin_hz).ceil() as usize).clamp(from_bin + 1, magnitudes.len());
+                let slice = &magnitudes[from_bin..to_bin];
+                slice.iter().sum::<f32>() / slice.len() as f32
+            })
+            .collect();
+
+        AudioAnalysisResult { amplitude, bands }
+    }
+}
+
+/// Iterative radix-2 Cooley-Tukey FFT. `re.len()` must be a power of two.
+fn fft_in_place(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+    debug_assert!(n.is_power_of_two() && im.len() == n);
+
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * std::f32::consts::PI / len as f32;
+        let (w_im, w_re) = angle.sin_cos();
+        for start in (0..n).step_by(len) {
+            let (mut cur_re, mut cur_im) = (1.0f32, 0.0f32);
+            for k in 0..len / 2 {
+                let a = start + k;
+                let b = a + len / 2;
+                let t_re = re[b] * cur_re - im[b] * cur_im;
+                let t_im = re[b] * cur_im + im[b] * cur_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+                let next_re = cur_re * w_re - cur_im * w_im;
+                cur_im = cur_re * w_im + cur_im * w_re;
+                cur_re = next_re;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// Godot half of the analysis: a dedicated bus with an `AudioEffectCapture`
+/// between the player and its original bus.
+pub struct AudioAnalysisTap {
+    bus_name: StringName,
+    original_bus: StringName,
+    capture: Gd<AudioEffectCapture>,
+    analyzer: AudioAnalyzer,
+}
+
+impl AudioAnalysisTap {
+    /// Creates the tap bus. `owner_id` only has to be unique among live taps.
+    pub fn new(owner_id: InstanceId, original_bus: StringName, band_count: usize) -> Self {
+        let mut audio_server = AudioServer::singleton();
+        let bus_name = format!("DclAudioAnalysis{}", owner_id.to_i64());
+
+        let mut capture = AudioEffectCapture::new_gd();
+        capture.set_buffer_length(CAPTURE_BUFFER_SECONDS);
+
+        audio_server.add_bus();
+        let bus_idx = audio_server.get_bus_count() - 1;
+        audio_server.set_bus_name(bus_idx, bus_name.as_str());
+        audio_server.set_bus_send(bus_idx, &original_bus);
+        audio_server.add_bus_effect(bus_idx, &capture);
+
+        let sample_rate = audio_server.get_mix_rate();
+        Self {
+            bus_name: StringName::from(bus_name.as_str()),
+            original_bus,
+            capture,
+            analyzer: AudioAnalyzer::new(band_count, sample_rate),
+        }
+    }
+
+    pub fn bus_name(&self) -> &StringName {
+        &self.bus_name
+    }
+
+    pub fn original_bus(&self) -> &StringName {
+        &self.original_bus
+    }
+
+    pub fn band_count(&self) -> usize {
+        self.analyzer.band_count()
+    }
+
+    /// Drains whatever was captured since the last call and analyzes it.
+    pub fn analyze(&mut self) -> AudioAnalysisResult {
+        let frames_available = self.capture.get_frames_available();
+        let stereo = self.capture.get_buffer(frames_available);
+        let mono: Vec<f32> = stereo
+            .as_slice()
+            .iter()
+            .map(|frame| (frame.x + frame.y) * 0.5)
+            .collect();
+        self.analyzer.push_samples(&mono);
+        self.analyzer.analyze()
+    }
+}
+
+impl Drop for AudioAnalysisTap {
+    fn drop(&mut self) {
+        let mut audio_server = AudioServer::singleton();
+        let bus_idx = audio_server.get_bus_index(&self.bus_name);
+        if bus_idx != -1 {
+            audio_server.remove_bus(bus_idx);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f32, sample_rate: f32, amplitude: f32) -> Vec<f32> {
+        (0..FFT_SIZE)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * freq * i as f32 / sample_rate).sin())
+            .collect()
+    }
+
+    #[test]
+    fn silence_analyzes_as_zero() {
+        let mut analyzer = AudioAnalyzer::new(8, 44_100.0);
+        analyzer.push_samples(&[]);
+        let result = analyzer.analyze();
+        assert_eq!(result.amplitude, 0.0);
+        assert_eq!(result.bands, vec![0.0; 8]);
+    }
+
+    #[test]
+    fn sine_rms_and_dominant_band() {
+        let mut analyzer = AudioAnalyzer::new(8, 44_100.0);
+        analyzer.push_samples(&sine(1_000.0, 44_100.0, 1.0));
+        let result = analyzer.analyze();
+
+        // RMS of a full-scale sine is 1/sqrt(2)
+        assert!((result.amplitude - std::f32::consts::FRAC_1_SQRT_2).abs() < 0.01);
+
+        let loudest = result
+            .bands
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(index, _)| index)
+            .unwrap();
+        // 20Hz..20kHz in 8 log bands: 1kHz falls in band 4 (632Hz..1.5kHz)
+        assert_eq!(loudest, 4);
+    }
+
+    #[test]
+    fn band_count_is_configurable() {
+        let mut analyzer = AudioAnalyzer::new(16, 48_000.0);
+        analyzer.push_samples(&sine(100.0, 48_000.0, 0.5));
+        assert_eq!(analyzer.analyze().bands.len(), 16);
+    }
+
+    #[test]
+    fn window_keeps_newest_samples() {
+        let mut analyzer = AudioAnalyzer::new(8, 44_100.0);
+        analyzer.push_samples(&vec![1.0; FFT_SIZE]);
+        analyzer.push_samples(&vec![0.0; FFT_SIZE * 2]);
+        assert_eq!(analyzer.analyze().amplitude, 0.0);
+    }
+}
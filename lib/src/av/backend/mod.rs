@@ -15,6 +15,9 @@ pub enum BackendType {
     ExoPlayer,
     /// AVPlayer backend for iOS platform
     AVPlayer,
+    /// Software decoding backend for desktop Linux (MP4 / H.264 + AAC), opt-in
+    /// with `--software-video`
+    Software,
     /// No-op backend when no video playback is available
     #[default]
     Noop,
@@ -39,6 +42,14 @@ impl BackendType {
             return BackendType::AVPlayer;
         }
 
+        // Opt-in until it has been validated more widely
+        #[cfg(target_os = "linux")]
+        if crate::godot_classes::dcl_global::DclGlobal::try_singleton()
+            .is_some_and(|global| global.bind().cli.bind().software_video)
+        {
+            return BackendType::Software;
+        }
+
         #[cfg(not(any(target_os = "android", target_os = "ios")))]
         {
             BackendType::Noop
@@ -52,12 +63,29 @@ impl BackendType {
             BackendType::ExoPlayer => 1,
             BackendType::AVPlayer => 2,
             BackendType::Noop => 3,
+            BackendType::Software => 4,
         }
     }
 }
 
+/// A decoded RGBA8 video frame, sent from the decoder thread to the main thread.
+pub struct VideoFrame {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+/// Decoded stereo audio, sent from the decoder thread to the main thread.
+pub struct AudioFrames {
+    pub sample_rate: u32,
+    pub frames: Vec<Vector2>,
+}
+
 pub struct AudioSink {
     pub command_sender: tokio::sync::mpsc::Sender<AVCommand>,
+    /// Only set by backends that decode audio themselves and need the main
+    /// thread to push it into an `AudioStreamGenerator`.
+    pub frames_receiver: Option<tokio::sync::mpsc::Receiver<AudioFrames>>,
 }
 
 pub struct VideoSink {
@@ -69,6 +97,7 @@ pub struct VideoSink {
     pub length: Option<f64>,
     pub rate: Option<f64>,
     pub stream_data_state_receiver: tokio::sync::mpsc::Receiver<StreamStateData>,
+    pub frame_receiver: tokio::sync::mpsc::Receiver<VideoFrame>,
 }
 
 pub fn av_sinks(
@@ -79,6 +108,20 @@ pub fn av_sinks(
     repeat: bool,
     wait_for_resource: Option<tokio::sync::oneshot::Receiver<String>>,
 ) -> (Option<VideoSink>, AudioSink) {
+    // Audio-only streams still go through the noop backend; the software
+    // backend only handles video containers.
+    #[cfg(target_os = "linux")]
+    if texture.is_some() {
+        return software::av_sinks(
+            source,
+            texture,
+            audio_stream_player,
+            playing,
+            repeat,
+            wait_for_resource,
+        );
+    }
+
     noop::av_sinks(
         source,
         texture,
@@ -89,4 +132,36 @@ pub fn av_sinks(
     )
 }
 
+/// Uploads a decoded frame, reallocating the texture only when the size changes.
+pub fn update_video_texture(texture: &mut Gd<ImageTexture>, frame: &VideoFrame) {
+    use crate::content::packed_array::PackedByteArrayFromVec;
+    use godot::classes::{image::Format, Image};
+
+    let data_arr = PackedByteArray::from_vec(&frame.data);
+    let Some(image) = Image::create_from_data(
+        frame.width as i32,
+        frame.height as i32,
+        false,
+        Format::RGBA8,
+        &data_arr,
+    ) else {
+        tracing::warn!(
+            "invalid video frame {}x{} ({} bytes)",
+            frame.width,
+            frame.height,
+            frame.data.len()
+        );
+        return;
+    };
+
+    let current_size = texture.get_size();
+    if current_size.x != frame.width as f32 || current_size.y != frame.height as f32 {
+        texture.set_image(&image);
+    } else {
+        texture.update(&image);
+    }
+}
+
 pub mod noop;
+#[cfg(target_os = "linux")]
+pub mod software;
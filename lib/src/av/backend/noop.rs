@@ -15,6 +15,7 @@ pub fn av_sinks(
 ) -> (Option<VideoSink>, AudioSink) {
     let (command_sender, _command_receiver) = tokio::sync::mpsc::channel(10);
     let (_stream_data_state_sender, stream_data_state_receiver) = tokio::sync::mpsc::channel(10);
+    let (_frame_sender, frame_receiver) = tokio::sync::mpsc::channel(1);
 
     tracing::warn!("Video playback not available: {}", source);
 
@@ -28,7 +29,11 @@ pub fn av_sinks(
             length: None,
             rate: None,
             stream_data_state_receiver,
+            frame_receiver,
         }),
-        AudioSink { command_sender },
+        AudioSink {
+            command_sender,
+            frames_receiver: None,
+        },
     )
 }
@@ -0,0 +1,464 @@
+use godot::prelude::Vector2;
+use mp4::{MediaType, Mp4Reader, Mp4Track, TrackType};
+use std::path::{Path, PathBuf};
+
+use openh264::{decoder::Decoder as H264Decoder, formats::YUVSource, nal_units};
+use symphonia::core::{
+    audio::SampleBuffer,
+    codecs::{CodecParameters, Decoder as AudioDecoder, DecoderOptions, CODEC_TYPE_AAC},
+    formats::Packet,
+};
+
+use super::{
+    openh264_library::new_h264_decoder,
+    source::{open_media_source, MediaReader},
+};
+use crate::av::backend::{AudioFrames, VideoFrame};
+
+const ANNEXB_START_CODE: [u8; 4] = [0, 0, 0, 1];
+/// MPEG-4 sampling frequency table, indexed by `samplingFrequencyIndex`.
+const AAC_SAMPLE_RATES: [u32; 13] = [
+    96000, 88200, 64000, 48000, 44100, 32000, 24000, 22050, 16000, 12000, 11025, 8000, 7350,
+];
+
+/// Sample timing of a track, built from the `stts`/`stss` tables so seeking
+/// doesn't have to read the samples themselves.
+struct SampleIndex {
+    track_id: u32,
+    timescale: f64,
+    /// Decode time of each sample; `times[n]` is sample id `n + 1`.
+    times: Vec<u64>,
+    /// Sync sample ids (1-based, sorted). `None` means every sample is a sync sample.
+    sync_samples: Option<Vec<u32>>,
+    /// Next sample id to read (1-based).
+    next: u32,
+}
+
+impl SampleIndex {
+    /// `stts` comes from the file, so the samples it expands to are checked
+    /// against `sample_count` (the track's `stsz` count) before allocating.
+    fn new(
+        track_id: u32,
+        timescale: u32,
+        time_to_sample: &[(u32, u32)],
+        sync_samples: Option<Vec<u32>>,
+        sample_count: u32,
+    ) -> anyhow::Result<Self> {
+        let total = time_to_sample
+            .iter()
+            .map(|(count, _)| *count as u64)
+            .sum::<u64>();
+        if total > sample_count as u64 {
+            anyhow::bail!("stts has {total} samples but the track has {sample_count}");
+        }
+
+        let mut times = Vec::with_capacity(total as usize);
+        let mut time = 0u64;
+        for (sample_count, sample_delta) in time_to_sample {
+            for _ in 0..*sample_count {
+                times.push(time);
+                time += *sample_delta as u64;
+            }
+        }
+        Ok(Self {
+            track_id,
+            timescale: timescale.max(1) as f64,
+            times,
+            sync_samples,
+            next: 1,
+        })
+    }
+
+    /// Every sample takes at least a byte, so a track can't have more samples
+    /// than the file has bytes.
+    fn from_track(track: &Mp4Track, file_size: u64) -> anyhow::Result<Self> {
+        let stbl = &track.trak.mdia.minf.stbl;
+        let time_to_sample = stbl
+            .stts
+            .entries
+            .iter()
+            .map(|entry| (entry.sample_count, entry.sample_delta))
+            .collect::<Vec<_>>();
+        let sync_samples = stbl.stss.as_ref().map(|stss| stss.entries.clone());
+        let sample_count = stbl.stsz.sample_count;
+        if sample_count as u64 > file_size {
+            anyhow::bail!("track has {sample_count} samples in a {file_size} byte file");
+        }
+        Self::new(
+            track.track_id(),
+            track.timescale(),
+            &time_to_sample,
+            sync_samples,
+            sample_count,
+        )
+    }
+
+    fn sample_count(&self) -> u32 {
+        self.times.len() as u32
+    }
+
+    fn time_of(&self, sample_id: u32) -> Option<f64> {
+        let index = sample_id.checked_sub(1)? as usize;
+        self.times
+            .get(index)
+            .map(|time| *time as f64 / self.timescale)
+    }
+
+    fn next_time(&self) -> Option<f64> {
+        self.time_of(self.next)
+    }
+
+    /// Last sample id whose decode time is at or before `position`.
+    fn sample_at(&self, position: f64) -> u32 {
+        let target = (position.max(0.0) * self.timescale) as u64;
+        let count = self.times.partition_point(|time| *time <= target);
+        count.max(1) as u32
+    }
+
+    /// Decoding has to restart from the last sync sample before `position`.
+    fn sync_sample_before(&self, position: f64) -> u32 {
+        let sample_id = self.sample_at(position);
+        match &self.sync_samples {
+            Some(sync_samples) => {
+                let count = sync_samples.partition_point(|sync| *sync <= sample_id);
+                sync_samples
+                    .get(count.saturating_sub(1))
+                    .copied()
+                    .unwrap_or(1)
+            }
+            None => sample_id,
+        }
+    }
+}
+
+struct VideoTrack {
+    index: SampleIndex,
+    decoder: H264Decoder,
+    /// SPS + PPS in Annex B form, prepended to every sync sample.
+    parameter_sets: Vec<u8>,
+    nal_length_size: usize,
+    annexb: Vec<u8>,
+}
+
+struct AudioTrack {
+    index: SampleIndex,
+    decoder: Box<dyn AudioDecoder>,
+    sample_rate: u32,
+}
+
+/// An MP4 file with an H.264 video track and an optional AAC audio track.
+pub struct Mp4Media {
+    reader: Mp4Reader<Box<dyn MediaReader>>,
+    video: VideoTrack,
+    audio: Option<AudioTrack>,
+    length: f64,
+    /// Cisco's OpenH264 library the video decoders are created from.
+    openh264_library: PathBuf,
+}
+
+impl Mp4Media {
+    pub fn open(source: &str, openh264_library: &Path) -> anyhow::Result<Self> {
+        let (reader, size) = open_media_source(source)?;
+        let reader = Mp4Reader::read_header(reader, size)?;
+
+        let mut video = None;
+        let mut audio = None;
+        let mut length = 0.0f64;
+        for track in reader.tracks().values() {
+            let Ok(media_type) = track.media_type() else {
+                continue;
+            };
+            match (track.track_type(), media_type) {
+                (Ok(TrackType::Video), MediaType::H264) if video.is_none() => {
+                    video = Some(VideoTrack::new(track, size, openh264_library)?);
+                    length = length.max(track.duration().as_secs_f64());
+                }
+                (Ok(TrackType::Audio), MediaType::AAC) if audio.is_none() => {
+                    match AudioTrack::new(track, size) {
+                        Ok(track_audio) => {
+                            audio = Some(track_audio);
+                            length = length.max(track.duration().as_secs_f64());
+                        }
+                        Err(err) => tracing::warn!("skipping audio track of {source}: {err}"),
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let Some(video) = video else {
+            anyhow::bail!("no H.264 video track found in {source}");
+        };
+
+        Ok(Self {
+            reader,
+            video,
+            audio,
+            length,
+            openh264_library: openh264_library.to_path_buf(),
+        })
+    }
+
+    pub fn length(&self) -> f64 {
+        self.length
+    }
+
+    pub fn next_video_time(&self) -> Option<f64> {
+        self.video.index.next_time()
+    }
+
+    pub fn next_audio_time(&self) -> Option<f64> {
+        self.audio
+            .as_ref()
+            .and_then(|audio| audio.index.next_time())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next_video_time().is_none() && self.next_audio_time().is_none()
+    }
+
+    /// Feeds the next video sample to the decoder. The decoder may need more
+    /// samples before it outputs a frame, in which case `None` is returned.
+    pub fn decode_next_video(&mut self) -> anyhow::Result<Option<VideoFrame>> {
+        let video = &mut self.video;
+        let sample_id = video.index.next;
+        if sample_id > video.index.sample_count() {
+            return Ok(None);
+        }
+        video.index.next += 1;
+
+        let Some(sample) = self.reader.read_sample(video.index.track_id, sample_id)? else {
+            return Ok(None);
+        };
+
+        video.annexb.clear();
+        if sample.is_sync {
+            video.annexb.extend_from_slice(&video.parameter_sets);
+        }
+        avcc_to_annexb(&sample.bytes, video.nal_length_size, &mut video.annexb)?;
+
+        let mut frame = None;
+        for nal in nal_units(&video.annexb) {
+            if let Some(yuv) = video.decoder.decode(nal)? {
+                let (width, height) = yuv.dimensions();
+                let mut data = vec![0; width * height * 4];
+                yuv.write_rgba8(&mut data);
+                frame = Some(VideoFrame {
+                    width: width as u32,
+                    height: height as u32,
+                    data,
+                });
+            }
+        }
+        Ok(frame)
+    }
+
+    pub fn decode_next_audio(&mut self) -> anyhow::Result<Option<AudioFrames>> {
+        let Some(audio) = self.audio.as_mut() else {
+            return Ok(None);
+        };
+        let sample_id = audio.index.next;
+        if sample_id > audio.index.sample_count() {
+            return Ok(None);
+        }
+        audio.index.next += 1;
+
+        let Some(sample) = self.reader.read_sample(audio.index.track_id, sample_id)? else {
+            return Ok(None);
+        };
+
+        let packet = Packet::new_from_slice(
+            audio.index.track_id,
+            sample.start_time,
+            sample.duration as u64,
+            &sample.bytes,
+        );
+        let decoded = audio.decoder.decode(&packet)?;
+        let spec = *decoded.spec();
+        let channels = spec.channels.count().max(1);
+        let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+
+        let frames = buffer
+            .samples()
+            .chunks_exact(channels)
+            .map(|frame| match frame {
+                [mono] => Vector2::new(*mono, *mono),
+                [left, right, ..] => Vector2::new(*left, *right),
+                [] => Vector2::ZERO,
+            })
+            .collect();
+
+        Ok(Some(AudioFrames {
+            sample_rate: if spec.rate > 0 {
+                spec.rate
+            } else {
+                audio.sample_rate
+            },
+            frames,
+        }))
+    }
+
+    /// Moves both tracks to `position`. Video restarts at the previous sync
+    /// sample; the frames before `position` still have to be decoded.
+    pub fn seek(&mut self, position: f64) -> anyhow::Result<()> {
+        self.video.index.next = self.video.index.sync_sample_before(position);
+        // A fresh decoder drops any reference frames from before the seek.
+        self.video.decoder = new_h264_decoder(&self.openh264_library)?;
+
+        if let Some(audio) = self.audio.as_mut() {
+            audio.index.next = audio.index.sample_at(position);
+            audio.decoder.reset();
+        }
+        Ok(())
+    }
+}
+
+impl VideoTrack {
+    fn new(track: &Mp4Track, file_size: u64, openh264_library: &Path) -> anyhow::Result<Self> {
+        let avcc = track
+            .trak
+            .mdia
+            .minf
+            .stbl
+            .stsd
+            .avc1
+            .as_ref()
+            .map(|avc1| &avc1.avcc)
+            .ok_or_else(|| anyhow::anyhow!("H.264 track without avcC box"))?;
+
+        let mut parameter_sets = Vec::new();
+        for nal in avcc
+            .sequence_parameter_sets
+            .iter()
+            .chain(avcc.picture_parameter_sets.iter())
+        {
+            parameter_sets.extend_from_slice(&ANNEXB_START_CODE);
+            parameter_sets.extend_from_slice(&nal.bytes);
+        }
+
+        Ok(Self {
+            index: SampleIndex::from_track(track, file_size)?,
+            decoder: new_h264_decoder(openh264_library)?,
+            parameter_sets,
+            nal_length_size: avcc.length_size_minus_one as usize + 1,
+            annexb: Vec::new(),
+        })
+    }
+}
+
+impl AudioTrack {
+    fn new(track: &Mp4Track, file_size: u64) -> anyhow::Result<Self> {
+        let dec_specific = track
+            .trak
+            .mdia
+            .minf
+            .stbl
+            .stsd
+            .mp4a
+            .as_ref()
+            .and_then(|mp4a| mp4a.esds.as_ref())
+            .map(|esds| &esds.es_desc.dec_config.dec_specific)
+            .ok_or_else(|| anyhow::anyhow!("AAC track without esds box"))?;
+
+        let sample_rate = AAC_SAMPLE_RATES
+            .get(dec_specific.freq_index as usize)
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("invalid AAC frequency index"))?;
+        let extra_data = aac_audio_specific_config(
+            dec_specific.profile,
+            dec_specific.freq_index,
+            dec_specific.chan_conf,
+        );
+
+        let mut params = CodecParameters::new();
+        params
+            .for_codec(CODEC_TYPE_AAC)
+            .with_sample_rate(sample_rate)
+            .with_extra_data(extra_data.into());
+        let decoder = symphonia::default::get_codecs().make(&params, &DecoderOptions::default())?;
+
+        Ok(Self {
+            index: SampleIndex::from_track(track, file_size)?,
+            decoder,
+            sample_rate,
+        })
+    }
+}
+
+/// Rewrites length-prefixed NAL units (as stored in MP4) into Annex B.
+fn avcc_to_annexb(data: &[u8], nal_length_size: usize, out: &mut Vec<u8>) -> anyhow::Result<()> {
+    if !(1..=4).contains(&nal_length_size) {
+        anyhow::bail!("invalid NAL length size {nal_length_size}");
+    }
+
+    let mut rest = data;
+    while !rest.is_empty() {
+        if rest.len() < nal_length_size {
+            anyhow::bail!("truncated NAL length");
+        }
+        let (length_bytes, tail) = rest.split_at(nal_length_size);
+        let length = length_bytes
+            .iter()
+            .fold(0usize, |acc, byte| (acc << 8) | *byte as usize);
+        if tail.len() < length {
+            anyhow::bail!("truncated NAL unit");
+        }
+        out.extend_from_slice(&ANNEXB_START_CODE);
+        out.extend_from_slice(&tail[..length]);
+        rest = &tail[length..];
+    }
+    Ok(())
+}
+
+/// Two-byte AudioSpecificConfig: object type (5 bits), frequency index (4),
+/// channel configuration (4), then three zero flag bits.
+fn aac_audio_specific_config(profile: u8, freq_index: u8, chan_conf: u8) -> Vec<u8> {
+    vec![
+        ((profile & 0x1f) << 3) | ((freq_index & 0x0f) >> 1),
+        ((freq_index & 0x01) << 7) | ((chan_conf & 0x0f) << 3),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn avcc_is_rewritten_to_annexb() {
+        let data = [0, 0, 0, 2, 0x65, 0xaa, 0, 0, 0, 1, 0x41];
+        let mut out = Vec::new();
+        avcc_to_annexb(&data, 4, &mut out).unwrap();
+        assert_eq!(out, [0, 0, 0, 1, 0x65, 0xaa, 0, 0, 0, 1, 0x41]);
+
+        let mut out = Vec::new();
+        assert!(avcc_to_annexb(&[0, 0, 0, 9, 0x65], 4, &mut out).is_err());
+    }
+
+    #[test]
+    fn aac_lc_stereo_44100_config() {
+        // AAC-LC (2), 44.1kHz (index 4), stereo (2) => 0x12 0x10
+        assert_eq!(aac_audio_specific_config(2, 4, 2), vec![0x12, 0x10]);
+    }
+
+    #[test]
+    fn seek_uses_previous_sync_sample() {
+        // 10 samples at 30fps (timescale 30), keyframes at 1 and 6
+        let index = SampleIndex::new(1, 30, &[(10, 1)], Some(vec![1, 6]), 10).unwrap();
+        assert_eq!(index.sample_at(0.0), 1);
+        assert_eq!(index.sample_at(0.2), 7);
+        assert_eq!(index.sync_sample_before(0.2), 6);
+        assert_eq!(index.sync_sample_before(0.1), 1);
+        assert_eq!(index.time_of(11), None);
+
+        let all_sync = SampleIndex::new(1, 30, &[(10, 1)], None, 10).unwrap();
+        assert_eq!(all_sync.sync_sample_before(0.2), 7);
+    }
+
+    #[test]
+    fn rejects_more_timed_samples_than_the_track_has() {
+        let huge = [(u32::MAX, 1), (u32::MAX, 1)];
+        assert!(SampleIndex::new(1, 30, &huge, None, 10).is_err());
+        assert!(SampleIndex::new(1, 30, &[(6, 1), (5, 1)], None, 10).is_err());
+    }
+}
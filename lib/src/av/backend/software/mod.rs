@@ -0,0 +1,411 @@
+//! Pure software video backend for desktop Linux.
+//!
+//! A decoder thread per player demuxes MP4, decodes H.264 (OpenH264) and AAC
+//! (symphonia) and paces the frames with its own clock. Frames, audio and
+//! `StreamStateData` go back to the main thread through the sinks, where
+//! `DclVideoPlayer::software_poll` uploads them. Sources are absolute local
+//! paths (resolved from the content provider through `wait_for_resource`) or
+//! http(s) urls read with range requests. OpenH264 is Cisco's binary, fetched
+//! on first use (see `openh264_library`).
+
+mod media;
+mod openh264_library;
+mod source;
+
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use godot::{
+    classes::{AudioStreamPlayer, ImageTexture, Os},
+    prelude::*,
+};
+use tokio::sync::mpsc::{error::TryRecvError, Receiver, Sender};
+
+use self::media::Mp4Media;
+use super::{AudioFrames, AudioSink, VideoFrame, VideoSink};
+use crate::av::stream_processor::{AVCommand, StreamStateData};
+
+/// How much audio is decoded ahead of the clock (the generator buffers 1s).
+const AUDIO_LEAD_SECONDS: f64 = 0.3;
+/// How often the position is reported while playing.
+const POSITION_REPORT_INTERVAL: Duration = Duration::from_millis(250);
+/// Upper bound of a single sleep, so commands are handled promptly.
+const MAX_SLEEP: Duration = Duration::from_millis(20);
+/// Consecutive failed samples after which playback stops with an error.
+const MAX_CONSECUTIVE_DECODE_ERRORS: u32 = 30;
+
+pub fn av_sinks(
+    source: String,
+    texture: Option<Gd<ImageTexture>>,
+    _audio_stream_player: Gd<AudioStreamPlayer>,
+    playing: bool,
+    repeat: bool,
+    wait_for_resource: Option<tokio::sync::oneshot::Receiver<String>>,
+) -> (Option<VideoSink>, AudioSink) {
+    let (command_sender, command_receiver) = tokio::sync::mpsc::channel(10);
+    let (stream_data_state_sender, stream_data_state_receiver) = tokio::sync::mpsc::channel(10);
+    // Only the newest frame matters; the thread drops frames when this is full.
+    let (frame_sender, frame_receiver) = tokio::sync::mpsc::channel(2);
+    let (audio_sender, audio_receiver) = tokio::sync::mpsc::channel(32);
+
+    let openh264_folder = PathBuf::from(Os::singleton().get_user_data_dir().to_string());
+    let thread_source = source.clone();
+    let spawn_result = std::thread::Builder::new()
+        .name("software video decoder".into())
+        .spawn(move || {
+            let Some(source) = resolve_source(thread_source, wait_for_resource) else {
+                return;
+            };
+            let opened = openh264_library::ensure_openh264_library(&openh264_folder).and_then(
+                |openh264_library| {
+                    SoftwarePlayer::open(
+                        &source,
+                        &openh264_library,
+                        command_receiver,
+                        stream_data_state_sender.clone(),
+                        frame_sender,
+                        audio_sender,
+                        playing,
+                        repeat,
+                    )
+                },
+            );
+            let mut player = match opened {
+                Ok(player) => player,
+                Err(err) => {
+                    tracing::warn!("software video backend failed to open {source}: {err}");
+                    let _ = stream_data_state_sender.try_send(StreamStateData::Error {
+                        message: err.to_string(),
+                    });
+                    return;
+                }
+            };
+            player.run();
+        });
+    if let Err(err) = spawn_result {
+        tracing::error!("failed to spawn the software video decoder thread: {err}");
+    }
+
+    (
+        texture.map(|texture| VideoSink {
+            source,
+            command_sender: command_sender.clone(),
+            size: (0, 0),
+            texture,
+            current_time: 0.0,
+            length: None,
+            rate: None,
+            stream_data_state_receiver,
+            frame_receiver,
+        }),
+        AudioSink {
+            command_sender,
+            frames_receiver: Some(audio_receiver),
+        },
+    )
+}
+
+/// Local files are only known once the content provider has downloaded them.
+fn resolve_source(
+    source: String,
+    wait_for_resource: Option<tokio::sync::oneshot::Receiver<String>>,
+) -> Option<String> {
+    let Some(wait_for_resource) = wait_for_resource else {
+        return Some(source);
+    };
+    match wait_for_resource.blocking_recv() {
+        Ok(path) => Some(path),
+        Err(_) => {
+            tracing::debug!("video player dropped before {source} was resolved");
+            None
+        }
+    }
+}
+
+/// Media position driven by the wall clock while playing.
+struct PlaybackClock {
+    position: f64,
+    started_at: Option<Instant>,
+}
+
+impl PlaybackClock {
+    fn position(&self) -> f64 {
+        match self.started_at {
+            Some(started_at) => self.position + started_at.elapsed().as_secs_f64(),
+            None => self.position,
+        }
+    }
+
+    fn resume(&mut self) {
+        if self.started_at.is_none() {
+            self.started_at = Some(Instant::now());
+        }
+    }
+
+    fn pause(&mut self) {
+        self.position = self.position();
+        self.started_at = None;
+    }
+
+    fn set(&mut self, position: f64) {
+        self.position = position;
+        if self.started_at.is_some() {
+            self.started_at = Some(Instant::now());
+        }
+    }
+}
+
+struct SoftwarePlayer {
+    media: Mp4Media,
+    command_receiver: Receiver<AVCommand>,
+    state_sender: Sender<StreamStateData>,
+    frame_sender: Sender<VideoFrame>,
+    audio_sender: Sender<AudioFrames>,
+    clock: PlaybackClock,
+    playing: bool,
+    repeat: bool,
+    last_report: Instant,
+    /// Samples that failed to decode since the last one that didn't
+    decode_errors: u32,
+}
+
+impl SoftwarePlayer {
+    fn open(
+        source: &str,
+        openh264_library: &Path,
+        command_receiver: Receiver<AVCommand>,
+        state_sender: Sender<StreamStateData>,
+        frame_sender: Sender<VideoFrame>,
+        audio_sender: Sender<AudioFrames>,
+        playing: bool,
+        repeat: bool,
+    ) -> anyhow::Result<Self> {
+        let media = Mp4Media::open(source, openh264_library)?;
+        let _ = state_sender.try_send(StreamStateData::Ready {
+            length: media.length(),
+        });
+
+        let mut player = Self {
+            media,
+            command_receiver,
+            state_sender,
+            frame_sender,
+            audio_sender,
+            clock: PlaybackClock {
+                position: 0.0,
+                started_at: None,
+            },
+            playing: false,
+            repeat,
+            last_report: Instant::now(),
+            decode_errors: 0,
+        };
+
+        if playing {
+            player.play();
+        } else {
+            // Show the first frame as a poster while paused.
+            player.preview_frame(0.0);
+        }
+        Ok(player)
+    }
+
+    fn run(&mut self) {
+        loop {
+            // While paused there is nothing to do until the next command.
+            let command = if self.playing {
+                match self.command_receiver.try_recv() {
+                    Ok(command) => Some(command),
+                    Err(TryRecvError::Empty) => None,
+                    Err(TryRecvError::Disconnected) => return,
+                }
+            } else {
+                match self.command_receiver.blocking_recv() {
+                    Some(command) => Some(command),
+                    None => return,
+                }
+            };
+
+            match command {
+                Some(AVCommand::Play) => self.play(),
+                Some(AVCommand::Pause) => self.pause(),
+                Some(AVCommand::Repeat(repeat)) => self.repeat = repeat,
+                Some(AVCommand::Seek(position)) => self.seek(position),
+                Some(AVCommand::Dispose) => return,
+                None => {}
+            }
+
+            if self.playing {
+                self.step();
+            }
+        }
+    }
+
+    fn play(&mut self) {
+        if self.media.is_finished() {
+            self.seek(0.0);
+        }
+        self.playing = true;
+        self.clock.resume();
+        self.report(StreamStateData::Playing {
+            position: self.clock.position(),
+        });
+    }
+
+    fn pause(&mut self) {
+        self.playing = false;
+        self.clock.pause();
+        self.report(StreamStateData::Paused {
+            position: self.clock.position(),
+        });
+    }
+
+    fn seek(&mut self, position: f64) {
+        let position = position.clamp(0.0, self.media.length());
+        self.report(StreamStateData::Seeking {});
+        if let Err(err) = self.media.seek(position) {
+            self.report(StreamStateData::Error {
+                message: err.to_string(),
+            });
+            self.playing = false;
+            return;
+        }
+        self.clock.set(position);
+
+        if self.playing {
+            self.report(StreamStateData::Playing { position });
+        } else {
+            if self.preview_frame(position) {
+                self.report(StreamStateData::Paused { position });
+            }
+        }
+    }
+
+    /// Decodes up to `position` and sends the frame shown there. Returns
+    /// false if the stream failed to decode and playback stopped.
+    fn preview_frame(&mut self, position: f64) -> bool {
+        let mut latest = None;
+        while let Some(time) = self.media.next_video_time() {
+            if time > position && latest.is_some() {
+                break;
+            }
+            match self.media.decode_next_video() {
+                Ok(frame) => {
+                    self.decode_errors = 0;
+                    latest = frame.or(latest);
+                }
+                Err(err) => {
+                    if self.decode_failed("video", err) {
+                        return false;
+                    }
+                }
+            }
+        }
+        if let Some(frame) = latest {
+            let _ = self.frame_sender.try_send(frame);
+        }
+        true
+    }
+
+    fn step(&mut self) {
+        let position = self.clock.position();
+
+        while let Some(time) = self.media.next_audio_time() {
+            if time > position + AUDIO_LEAD_SECONDS {
+                break;
+            }
+            match self.media.decode_next_audio() {
+                Ok(frames) => {
+                    self.decode_errors = 0;
+                    if let Some(frames) = frames {
+                        let _ = self.audio_sender.try_send(frames);
+                    }
+                }
+                Err(err) => {
+                    if self.decode_failed("audio", err) {
+                        return;
+                    }
+                }
+            }
+        }
+
+        // When decoding falls behind, every due sample is still decoded (they
+        // are references for the next ones) but only the newest frame is sent.
+        let mut latest = None;
+        while let Some(time) = self.media.next_video_time() {
+            if time > position {
+                break;
+            }
+            match self.media.decode_next_video() {
+                Ok(frame) => {
+                    self.decode_errors = 0;
+                    latest = frame.or(latest);
+                }
+                Err(err) => {
+                    if self.decode_failed("video", err) {
+                        return;
+                    }
+                }
+            }
+        }
+        if let Some(frame) = latest {
+            let _ = self.frame_sender.try_send(frame);
+        }
+
+        if self.media.is_finished() && position >= self.media.length() {
+            if self.repeat {
+                self.seek(0.0);
+            } else {
+                self.playing = false;
+                self.clock.pause();
+                self.clock.set(self.media.length());
+                self.report(StreamStateData::Paused {
+                    position: self.media.length(),
+                });
+            }
+            return;
+        }
+
+        if self.last_report.elapsed() >= POSITION_REPORT_INTERVAL {
+            self.report(StreamStateData::Playing { position });
+        }
+
+        let next_time = [self.media.next_video_time(), self.media.next_audio_time()]
+            .into_iter()
+            .flatten()
+            .fold(self.media.length(), f64::min);
+        let wait = Duration::from_secs_f64((next_time - self.clock.position()).max(0.001));
+        std::thread::sleep(wait.min(MAX_SLEEP));
+    }
+
+    /// A broken stream fails on every sample, so only the first failure is
+    /// logged and playback stops once too many fail in a row. Returns whether
+    /// it stopped.
+    fn decode_failed(&mut self, kind: &str, err: anyhow::Error) -> bool {
+        self.decode_errors += 1;
+        if self.decode_errors == 1 {
+            tracing::debug!("{kind} decode error: {err}");
+        }
+        if self.decode_errors < MAX_CONSECUTIVE_DECODE_ERRORS {
+            return false;
+        }
+        tracing::debug!(
+            "stopping playback after {} consecutive decode errors",
+            self.decode_errors
+        );
+        self.decode_errors = 0;
+        self.playing = false;
+        self.clock.pause();
+        self.report(StreamStateData::Error {
+            message: format!("{kind} decode error: {err}"),
+        });
+        true
+    }
+
+    fn report(&mut self, state: StreamStateData) {
+        self.last_report = Instant::now();
+        let _ = self.state_sender.try_send(state);
+    }
+}
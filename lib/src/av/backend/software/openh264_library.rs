@@ -0,0 +1,100 @@
+//! Cisco's patent license only covers OpenH264 when it's their own prebuilt
+//! binary, so it isn't compiled into the explorer: the library is downloaded
+//! from Cisco the first time a video is played, kept in the user data folder,
+//! and loaded at runtime. The library is native code, so it's checked against
+//! a pinned SHA-256 before it's written and again every time it's loaded.
+
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use openh264::{
+    decoder::{Decoder, DecoderConfig},
+    OpenH264API,
+};
+use sha2::{Digest, Sha256};
+
+/// Must match the version the `openh264` crate bindings were generated for.
+const OPENH264_VERSION: &str = "2.4.1";
+const CISCO_BINARY_URL: &str = "https://ciscobinary.openh264.org";
+/// SHA-256 of the decompressed `libopenh264-2.4.1-linux64.7.so`.
+// TODO: pin from Cisco's published 2.4.1 binary, loading fails until this is set
+const LINUX64_SHA256: &str = "";
+/// SHA-256 of the decompressed `libopenh264-2.4.1-linux-arm64.7.so`.
+// TODO: pin from Cisco's published 2.4.1 binary, loading fails until this is set
+const LINUX_ARM64_SHA256: &str = "";
+/// The compressed libraries are about 1 MiB.
+const MAX_DOWNLOAD_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Players starting together download the library once.
+static DOWNLOAD_LOCK: Mutex<()> = Mutex::new(());
+
+/// File name of this platform's library and its pinned SHA-256.
+fn pinned_library() -> anyhow::Result<(String, &'static str)> {
+    let (platform, sha256) = if cfg!(target_arch = "x86_64") {
+        ("linux64", LINUX64_SHA256)
+    } else if cfg!(target_arch = "aarch64") {
+        ("linux-arm64", LINUX_ARM64_SHA256)
+    } else {
+        anyhow::bail!("Cisco doesn't publish OpenH264 for this architecture");
+    };
+    let file_name = format!("libopenh264-{OPENH264_VERSION}-{platform}.7.so");
+    if sha256.is_empty() {
+        anyhow::bail!("no SHA-256 is pinned for {file_name}");
+    }
+    Ok((file_name, sha256))
+}
+
+fn verify_library(library: &[u8], file_name: &str, expected: &str) -> anyhow::Result<()> {
+    let actual = format!("{:x}", Sha256::digest(library));
+    if actual != expected {
+        anyhow::bail!("{file_name} has SHA-256 {actual}, expected {expected}");
+    }
+    Ok(())
+}
+
+/// Path of Cisco's OpenH264 library in `folder`, downloading it first if it
+/// isn't there yet.
+pub fn ensure_openh264_library(folder: &Path) -> anyhow::Result<PathBuf> {
+    let (file_name, sha256) = pinned_library()?;
+    let path = folder.join(&file_name);
+    let _guard = DOWNLOAD_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if let Ok(library) = std::fs::read(&path) {
+        match verify_library(&library, &file_name, sha256) {
+            Ok(()) => return Ok(path),
+            Err(err) => tracing::info!("downloading OpenH264 again: {err}"),
+        }
+    }
+
+    let url = format!("{CISCO_BINARY_URL}/{file_name}.bz2");
+    tracing::info!("downloading OpenH264 {OPENH264_VERSION} from {url}");
+    let response = reqwest::blocking::get(&url)?.error_for_status()?;
+    let mut compressed = Vec::new();
+    response
+        .take(MAX_DOWNLOAD_BYTES + 1)
+        .read_to_end(&mut compressed)?;
+    if compressed.len() as u64 > MAX_DOWNLOAD_BYTES {
+        anyhow::bail!("{url} is larger than {MAX_DOWNLOAD_BYTES} bytes");
+    }
+    let mut library = Vec::new();
+    bzip2::read::BzDecoder::new(compressed.as_slice()).read_to_end(&mut library)?;
+    verify_library(&library, &file_name, sha256)?;
+
+    // Written aside and renamed so a failed download never leaves a truncated library
+    std::fs::create_dir_all(folder)?;
+    let tmp_path = path.with_extension("so.tmp");
+    std::fs::write(&tmp_path, library)?;
+    std::fs::rename(&tmp_path, &path)?;
+    Ok(path)
+}
+
+/// Verifies the cached library again before loading it, it may have been
+/// replaced since it was downloaded.
+pub fn new_h264_decoder(library: &Path) -> anyhow::Result<Decoder> {
+    let (file_name, sha256) = pinned_library()?;
+    verify_library(&std::fs::read(library)?, &file_name, sha256)?;
+    let api = OpenH264API::from_blob_path(library)?;
+    Ok(Decoder::with_api_config(api, DecoderConfig::new())?)
+}
@@ -0,0 +1,192 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::File,
+    io::{BufReader, Cursor, Read, Seek, SeekFrom},
+};
+
+use reqwest::{
+    blocking::Client,
+    header::{CONTENT_RANGE, RANGE},
+    StatusCode,
+};
+
+/// Range requests are done in chunks of this size; MP4 readers jump between
+/// the `moov` box and the sample data, so whole chunks are cached.
+const CHUNK_SIZE: u64 = 1024 * 1024;
+/// Bounds the memory used by a single HTTP source (32 MiB).
+const MAX_CACHED_CHUNKS: usize = 32;
+/// Largest body buffered whole from a server that ignores `Range` (same bound
+/// as the chunk cache).
+const MAX_UNRANGED_BODY: u64 = CHUNK_SIZE * MAX_CACHED_CHUNKS as u64;
+
+pub trait MediaReader: Read + Seek + Send {}
+impl<T: Read + Seek + Send> MediaReader for T {}
+
+/// Opens a local file (absolute path) or an http(s) url.
+/// Returns the reader and the total size in bytes.
+pub fn open_media_source(source: &str) -> anyhow::Result<(Box<dyn MediaReader>, u64)> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        return HttpRangeReader::open(source);
+    }
+
+    let file = File::open(source)?;
+    let size = file.metadata()?.len();
+    Ok((Box::new(BufReader::new(file)), size))
+}
+
+/// `Read + Seek` over an http resource using `Range` requests.
+pub struct HttpRangeReader {
+    client: Client,
+    url: String,
+    length: u64,
+    position: u64,
+    chunks: HashMap<u64, Vec<u8>>,
+    chunk_order: VecDeque<u64>,
+}
+
+impl HttpRangeReader {
+    /// Servers that ignore `Range` get the whole body buffered in memory
+    /// instead, up to `MAX_UNRANGED_BODY`.
+    pub fn open(url: &str) -> anyhow::Result<(Box<dyn MediaReader>, u64)> {
+        let client = Client::new();
+        let response = client
+            .get(url)
+            .header(RANGE, format!("bytes=0-{}", CHUNK_SIZE - 1))
+            .send()?
+            .error_for_status()?;
+
+        if response.status() != StatusCode::PARTIAL_CONTENT {
+            tracing::debug!("{url} doesn't support range requests, buffering it whole");
+            if response
+                .content_length()
+                .is_some_and(|length| length > MAX_UNRANGED_BODY)
+            {
+                anyhow::bail!("{url} doesn't support range requests and is too large to buffer");
+            }
+            let mut body = Vec::new();
+            response
+                .take(MAX_UNRANGED_BODY + 1)
+                .read_to_end(&mut body)?;
+            if body.len() as u64 > MAX_UNRANGED_BODY {
+                anyhow::bail!("{url} doesn't support range requests and is too large to buffer");
+            }
+            let length = body.len() as u64;
+            return Ok((Box::new(Cursor::new(body)), length));
+        }
+
+        let length = response
+            .headers()
+            .get(CONTENT_RANGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_content_range_length)
+            .ok_or_else(|| anyhow::anyhow!("missing or invalid Content-Range from {url}"))?;
+        let first_chunk = response.bytes()?.to_vec();
+
+        let mut reader = Self {
+            client,
+            url: url.to_string(),
+            length,
+            position: 0,
+            chunks: HashMap::new(),
+            chunk_order: VecDeque::new(),
+        };
+        reader.insert_chunk(0, first_chunk);
+        Ok((Box::new(reader), length))
+    }
+
+    fn insert_chunk(&mut self, index: u64, data: Vec<u8>) {
+        if self.chunk_order.len() >= MAX_CACHED_CHUNKS {
+            if let Some(oldest) = self.chunk_order.pop_front() {
+                self.chunks.remove(&oldest);
+            }
+        }
+        self.chunk_order.push_back(index);
+        self.chunks.insert(index, data);
+    }
+
+    fn fetch_chunk(&self, index: u64) -> std::io::Result<Vec<u8>> {
+        let from = index * CHUNK_SIZE;
+        let to = (from + CHUNK_SIZE).min(self.length) - 1;
+        let response = self
+            .client
+            .get(&self.url)
+            .header(RANGE, format!("bytes={from}-{to}"))
+            .send()
+            .and_then(|response| response.error_for_status())
+            .map_err(std::io::Error::other)?;
+        if response.status() != StatusCode::PARTIAL_CONTENT {
+            return Err(std::io::Error::other(format!(
+                "range request to {} returned {}",
+                self.url,
+                response.status()
+            )));
+        }
+        Ok(response.bytes().map_err(std::io::Error::other)?.to_vec())
+    }
+}
+
+impl Read for HttpRangeReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.position >= self.length || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let index = self.position / CHUNK_SIZE;
+        if !self.chunks.contains_key(&index) {
+            let data = self.fetch_chunk(index)?;
+            self.insert_chunk(index, data);
+        }
+
+        let chunk = &self.chunks[&index];
+        let offset = (self.position - index * CHUNK_SIZE) as usize;
+        if offset >= chunk.len() {
+            // short response from the server
+            return Ok(0);
+        }
+        let count = buf.len().min(chunk.len() - offset);
+        buf[..count].copy_from_slice(&chunk[offset..offset + count]);
+        self.position += count as u64;
+        Ok(count)
+    }
+}
+
+impl Seek for HttpRangeReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.length.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+        let Some(position) = position else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "seek before the start of the stream",
+            ));
+        };
+        self.position = position;
+        Ok(position)
+    }
+}
+
+/// Parses the total length out of `bytes 0-1023/146515`.
+fn parse_content_range_length(value: &str) -> Option<u64> {
+    let (_, total) = value.trim().strip_prefix("bytes")?.rsplit_once('/')?;
+    total.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_range_length() {
+        assert_eq!(
+            parse_content_range_length("bytes 0-1023/146515"),
+            Some(146515)
+        );
+        assert_eq!(parse_content_range_length(" bytes 0-0/1 "), Some(1));
+        // unknown total length can't be seeked
+        assert_eq!(parse_content_range_length("bytes 0-1023/*"), None);
+        assert_eq!(parse_content_range_length("items 0-1/2"), None);
+    }
+}
@@ -1,3 +1,4 @@
+pub mod audio_analysis;
 pub mod backend;
 pub mod stream_processor;
 pub mod video_stream;
@@ -12,4 +12,5 @@ pub enum StreamStateData {
     Buffering { position: f64 },
     Seeking {},
     Paused { position: f64 },
+    Error { message: String },
 }
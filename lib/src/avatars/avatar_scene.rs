@@ -10,8 +10,8 @@ use godot::prelude::*;
 
 use crate::{
     auth::wallet::AsH160,
-    avatars::dcl_user_profile::DclUserProfile,
-    comms::profile::UserProfile,
+    avatars::{dcl_user_profile::DclUserProfile, scene_emote::SceneEmoteHash},
+    comms::{adapter::message_processor::PeerVoiceSettings, profile::UserProfile},
     dcl::{
         components::{
             internal_player_data::InternalPlayerData,
@@ -101,6 +101,8 @@ pub struct AvatarScene {
     avatar_entity: HashMap<AvatarAlias, SceneEntityId>,
     avatar_godot_scene: HashMap<SceneEntityId, Gd<DclAvatar>>,
     avatar_address: HashMap<H160, AvatarAlias>,
+    // Local voice volume/mute per address, kept for the whole session (across reconnections)
+    peer_voice_settings: HashMap<H160, PeerVoiceSettings>,
 
     crdt_state: SceneCrdtState,
 
@@ -134,6 +136,7 @@ impl INode for AvatarScene {
             crdt_state: SceneCrdtState::from_proto(),
             avatar_godot_scene: HashMap::new(),
             avatar_address: HashMap::new(),
+            peer_voice_settings: HashMap::new(),
             last_updated_profile: HashMap::new(),
             last_movement_timestamp: HashMap::new(),
             last_position_index: HashMap::new(),
@@ -1684,6 +1687,48 @@ impl AvatarScene {
         }
     }
 
+    /// Play a remote avatar's scene emote (rfc4 `SceneEmote`), already resolved against the
+    /// sender's scene content. The content is registered on the avatar so its scene-emote URN
+    /// loads even when that scene isn't loaded locally. `SceneEmote` has no incremental id, so
+    /// it neither depends on nor affects the `PlayerEmote` ordering.
+    pub fn play_scene_emote(
+        &mut self,
+        alias: u32,
+        scene_entity_id: &str,
+        base_url: &str,
+        emote: &SceneEmoteHash,
+        looping: bool,
+    ) {
+        let Some(entity_id) = self.avatar_entity.get(&alias) else {
+            return;
+        };
+        let Some(avatar_scene) = self.avatar_godot_scene.get_mut(entity_id) else {
+            return;
+        };
+
+        avatar_scene.call(
+            "register_scene_emote_content",
+            &[
+                scene_entity_id.to_variant(),
+                base_url.to_variant(),
+                emote.glb_hash.to_variant(),
+                emote.audio_hash.as_deref().unwrap_or_default().to_variant(),
+            ],
+        );
+        let emote_urn = emote.urn(scene_entity_id, looping);
+        avatar_scene.call("async_play_emote", &[emote_urn.to_variant()]);
+    }
+
+    /// Turn a remote avatar's head towards `target` (rfc4 `LookAtPosition`, Godot space).
+    pub fn set_avatar_look_at(&mut self, alias: u32, target: Vector3) {
+        let Some(entity_id) = self.avatar_entity.get(&alias) else {
+            return;
+        };
+        if let Some(avatar) = self.avatar_godot_scene.get_mut(entity_id) {
+            avatar.call("set_head_look_at", &[target.to_variant()]);
+        }
+    }
+
     /// Stop a remote avatar's looping emote (rfc4 `PlayerEmote.is_stopping` — sent by Unity
     /// peers over LiveKit and synthesized from Pulse `EmoteStopped`). Deliberately does not
     /// touch the incremental-id dedup: a stop must neither depend on nor affect id ordering.
@@ -1814,6 +1859,35 @@ impl AvatarScene {
         );
     }
 
+    pub fn peer_voice_settings(&self, address: &H160) -> PeerVoiceSettings {
+        self.peer_voice_settings
+            .get(address)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Stores the settings and applies them to the peer's avatar if it's connected
+    pub fn set_peer_voice_settings(&mut self, address: H160, settings: PeerVoiceSettings) {
+        if settings == PeerVoiceSettings::default() {
+            self.peer_voice_settings.remove(&address);
+        } else {
+            self.peer_voice_settings.insert(address, settings);
+        }
+        if let Some(alias) = self.avatar_address.get(&address).copied() {
+            self.set_avatar_voice_volume(alias, settings.gain());
+        }
+    }
+
+    /// Local volume of a peer's voice (linear, 0 = muted)
+    pub fn set_avatar_voice_volume(&mut self, alias: u32, volume: f32) {
+        let Some(entity_id) = self.avatar_entity.get(&alias) else {
+            return;
+        };
+        if let Some(avatar) = self.avatar_godot_scene.get_mut(entity_id) {
+            avatar.call("set_voice_volume", &[volume.to_variant()]);
+        }
+    }
+
     pub fn push_voice_frame(&mut self, alias: u32, frame: PackedVector2Array) {
         let entity_id = if let Some(entity_id) = self.avatar_entity.get(&alias) {
             *entity_id
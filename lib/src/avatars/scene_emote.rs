@@ -16,6 +16,15 @@ impl SceneEmoteHash {
             audio_hash,
         }
     }
+
+    /// URN the emote is played and broadcast with:
+    /// `urn:decentraland:off-chain:scene-emote:{scene_entity_id}-{glb_hash}-{loop}`
+    pub fn urn(&self, scene_entity_id: &str, looping: bool) -> String {
+        format!(
+            "urn:decentraland:off-chain:scene-emote:{}-{}-{}",
+            scene_entity_id, self.glb_hash, looping
+        )
+    }
 }
 
 /// Scene emote data passed from Rust to GDScript.
@@ -35,6 +35,7 @@ use super::{
         IncomingMessage, MessageType, Rfc4Message, StreamerAudioFrameData, StreamerAudioInitData,
         VideoFrameData, VideoInitData, VoiceFrameData, VoiceInitData,
     },
+    scene_batching::{SCENE_BATCHING_METADATA_KEY, SCENE_BATCHING_VERSION},
 };
 
 // Constants
@@ -58,12 +59,13 @@ pub struct LivekitRoom {
     mic_sender_to_thread: tokio::sync::mpsc::Sender<Vec<i16>>,
     receiver_from_thread:
         tokio::sync::mpsc::Receiver<crate::comms::adapter::message_processor::IncomingMessage>,
-    #[allow(dead_code)]
     room_id: String,
     message_processor_sender: Option<
         tokio::sync::mpsc::Sender<crate::comms::adapter::message_processor::IncomingMessage>,
     >,
     connection_state: Arc<AtomicU8>,
+    /// Whether a room without auto_subscribe (scene room) receives the participants' voice
+    voice_subscription: tokio::sync::watch::Sender<bool>,
 }
 
 impl LivekitRoom {
@@ -112,6 +114,8 @@ impl LivekitRoom {
         };
         let connection_state = Arc::new(AtomicU8::new(LK_STATE_CONNECTING));
         let connection_state_for_thread = connection_state.clone();
+        let (voice_subscription, voice_subscription_for_thread) =
+            tokio::sync::watch::channel(false);
         let _ = std::thread::Builder::new()
             .name("livekit dcl thread".into())
             .spawn(move || {
@@ -124,6 +128,7 @@ impl LivekitRoom {
                     auto_subscribe,
                     lambdas_endpoint,
                     connection_state_for_thread,
+                    voice_subscription_for_thread,
                 );
             })
             .unwrap();
@@ -136,9 +141,24 @@ impl LivekitRoom {
             room_id,
             message_processor_sender: None,
             connection_state,
+            voice_subscription,
         }
     }
 
+    /// Subscribes to (or drops) the microphone tracks of the participants of a room created
+    /// without auto_subscribe, used for the scene-scoped voice channel.
+    pub fn set_voice_subscription(&self, enabled: bool) {
+        self.voice_subscription.send_if_modified(|current| {
+            let modified = *current != enabled;
+            *current = enabled;
+            modified
+        });
+    }
+
+    pub fn room_id(&self) -> &str {
+        &self.room_id
+    }
+
     pub fn connection_state_str(&self) -> &'static str {
         match self.connection_state.load(Ordering::Relaxed) {
             LK_STATE_CONNECTED => "connected",
@@ -275,6 +295,14 @@ impl Adapter for LivekitRoom {
     }
 }
 
+fn is_voice_publication(publication: &livekit::publication::RemoteTrackPublication) -> bool {
+    matches!(publication.kind(), livekit::track::TrackKind::Audio)
+        && matches!(
+            publication.source(),
+            livekit::track::TrackSource::Microphone
+        )
+}
+
 #[allow(clippy::too_many_arguments)]
 fn spawn_livekit_task(
     remote_address: String,
@@ -285,6 +313,7 @@ fn spawn_livekit_task(
     auto_subscribe: bool,
     lambdas_endpoint: String,
     connection_state: Arc<AtomicU8>,
+    mut voice_subscription: tokio::sync::watch::Receiver<bool>,
 ) {
     let url = Uri::try_from(remote_address).unwrap();
     let address = format!(
@@ -333,7 +362,7 @@ fn spawn_livekit_task(
             }
         };
 
-        // Set participant metadata (version, agent, platform, lambdasEndpoint)
+        // Set participant metadata (version, agent, platform, lambdasEndpoint, capabilities)
         let local_identity = room.local_participant().identity().0.clone();
         {
             let version = DclGlobal::get_version().to_string();
@@ -342,6 +371,7 @@ fn spawn_livekit_task(
                 "agent": "godot",
                 "platform": "mobile",
                 "lambdasEndpoint": lambdas_endpoint,
+                SCENE_BATCHING_METADATA_KEY: SCENE_BATCHING_VERSION,
             }).to_string();
 
             if let Err(e) = room.local_participant().set_metadata(metadata).await {
@@ -413,13 +443,19 @@ fn spawn_livekit_task(
                                 // Check if this is a streamer (identity ends with "-streamer")
                                 if identity_str.ends_with("-streamer") {
                                     tracing::debug!("Found streamer {} with {} publications", identity_str, publications.len());
-                                    for publication in publications {
+                                    for publication in &publications {
                                         tracing::debug!("Subscribing to streamer publication: {:?} (kind: {:?})",
                                             publication.sid(), publication.kind());
                                         publication.set_subscribed(true);
                                     }
                                 }
 
+                                if !auto_subscribe && *voice_subscription.borrow() && identity_str.as_h160().is_some() {
+                                    for publication in publications.iter().filter(|publication| is_voice_publication(publication)) {
+                                        publication.set_subscribed(true);
+                                    }
+                                }
+
                                 // Check metadata from existing participants (for version reporting)
                                 if let Some(address) = identity_str.as_h160() {
                                     let metadata = participant.metadata();
@@ -449,6 +485,12 @@ fn spawn_livekit_task(
                                 tracing::debug!("Streamer {} published track: {:?} (kind: {:?})",
                                     identity_str, publication.sid(), publication.kind());
                                 publication.set_subscribed(true);
+                            } else if !auto_subscribe
+                                && *voice_subscription.borrow()
+                                && identity_str.as_h160().is_some()
+                                && is_voice_publication(&publication)
+                            {
+                                publication.set_subscribed(true);
                             }
                         }
                         livekit::RoomEvent::DataReceived { payload, participant, .. } => {
@@ -838,6 +880,20 @@ fn spawn_livekit_task(
                         _ => { tracing::debug!("Event: {:?}", incoming); }
                     };
                 }
+                Ok(()) = voice_subscription.changed(), if !auto_subscribe => {
+                    let subscribed = *voice_subscription.borrow_and_update();
+                    tracing::debug!("🎙️ scene room '{}' voice subscription: {}", room_id, subscribed);
+                    for participant in room.remote_participants().values() {
+                        if participant.identity().0.as_str().as_h160().is_none() {
+                            continue;
+                        }
+                        for publication in participant.track_publications().values() {
+                            if is_voice_publication(publication) {
+                                publication.set_subscribed(subscribed);
+                            }
+                        }
+                    }
+                }
                 outgoing = receiver.recv() => {
                     let Some(outgoing) = outgoing else {
                         tracing::debug!("🔌 LiveKit session ended - room: '{}', reason: app pipe broken", room_id);
@@ -19,15 +19,25 @@ use crate::{
         },
         profile::{SerializedProfile, UserProfile},
     },
-    content::profile::{
-        prepare_request_requirements, request_lambda_profile, request_registry_profile,
+    content::{
+        content_mapping::ContentMappingAndUrlRef,
+        profile::{prepare_request_requirements, request_lambda_profile, request_registry_profile},
     },
     dcl::components::proto_components::kernel::comms::rfc4,
     godot_classes::{dcl_global::DclGlobal, dcl_social_blacklist::DclSocialBlacklist},
+    http_request::{
+        http_queue_requester::HttpQueueRequester,
+        request_response::{RequestOption, ResponseEnum, ResponseType},
+    },
+    realm::scene_definition::SceneEntityDefinition,
     scene_runner::tokio_runtime::TokioRuntime,
 };
 
 use super::movement_compressed::MovementCompressed;
+use super::network_simulator::{NetworkSimulator, NetworkSimulatorConfig};
+use super::scene_batching::{
+    decode_scene_payload, SCENE_BATCHING_METADATA_KEY, SCENE_BATCHING_VERSION,
+};
 
 /// Represents an incoming message from a communication room
 #[derive(Debug, Clone)]
@@ -111,6 +121,61 @@ pub struct StreamerAudioFrameData {
     pub data: Vec<i16>,
 }
 
+/// Local playback settings of a peer's voice (not shared with the peer)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PeerVoiceSettings {
+    /// Linear gain, 1.0 = unchanged
+    pub volume: f32,
+    pub muted: bool,
+}
+
+impl PeerVoiceSettings {
+    pub const MAX_VOLUME: f32 = 2.0;
+
+    pub fn new(volume: f32, muted: bool) -> Self {
+        Self {
+            volume: if volume.is_finite() {
+                volume.clamp(0.0, Self::MAX_VOLUME)
+            } else {
+                1.0
+            },
+            muted,
+        }
+    }
+
+    /// Gain applied to the peer's audio player
+    pub fn gain(&self) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.volume
+        }
+    }
+}
+
+impl Default for PeerVoiceSettings {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            muted: false,
+        }
+    }
+}
+
+/// Voice restricted to a single room (the scene-scoped voice channel)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VoiceScope {
+    pub room_id: String,
+    /// Players silenced by the scene
+    pub muted: HashSet<H160>,
+}
+
+impl VoiceScope {
+    pub fn allows(&self, address: &H160, room_id: &str) -> bool {
+        room_id == self.room_id && !self.muted.contains(address)
+    }
+}
+
 /// Represents an outgoing message to be sent to communication rooms
 #[derive(Debug, Clone)]
 pub struct OutgoingMessage {
@@ -133,12 +198,21 @@ struct Peer {
     lambdas_endpoint: Option<String>, // Peer's lambda URL from LiveKit metadata (lambdasEndpoint)
     last_movement_timestamp: f32,     // Dedup: last movement timestamp received
     last_emote_incremental_id: u32,   // Dedup: last emote incremental ID received
+    /// Dedup: last SceneEmote/Reaction/ChatReaction received and when. They have no
+    /// incremental id, so the copy of the same packet arriving through another room is
+    /// recognized by content within a short window (see `is_dual_room_copy`).
+    last_scene_emote: Option<(rfc4::SceneEmote, Instant)>,
+    last_reaction: Option<(rfc4::Reaction, Instant)>,
+    last_chat_reaction: Option<(rfc4::ChatReaction, Instant)>,
+    last_look_at_timestamp: f32, // Dedup: last LookAtPosition timestamp received
     /// Transport-preference gate: true while this peer is a live member of the "pulse" room
     /// (set on any pulse-bridged message, cleared by a pulse PeerLeft). While set, this peer's
     /// movement/emotes from LiveKit rooms are DISCARDED — never merged: LiveKit timestamps are
     /// the sender's clock and Pulse timestamps are the server tick, so comparing them starves
     /// one source permanently. On either flip both dedup layers are reset for the same reason.
     pulse_live: bool,
+    /// Advertised `sceneBatching` in its metadata: batched scene messages can be sent to it
+    scene_batching: bool,
 }
 
 struct ProfileUpdate {
@@ -152,6 +226,17 @@ struct ProfileFetchFailure {
     announced_version: u32,
 }
 
+struct SceneEmoteFetch {
+    scene_entity_id: String,
+    content_mapping: Result<ContentMappingAndUrlRef, anyhow::Error>,
+}
+
+/// A SceneEmote waiting for the entity of the sender's scene to be fetched
+struct PendingSceneEmote {
+    peer_alias: u32,
+    source: String,
+}
+
 struct VideoTrackInfo {
     #[allow(dead_code)]
     width: u32,
@@ -193,7 +278,10 @@ pub struct MessageProcessor {
     last_profile_response_sent: Instant,
 
     // Chat and scene messages (bounded to prevent memory exhaustion)
-    chats: VecDeque<(H160, rfc4::Chat)>,
+    /// (sender, room the chat came through, chat)
+    chats: VecDeque<(H160, String, rfc4::Chat)>,
+    reactions: VecDeque<(H160, rfc4::Reaction)>,
+    chat_reactions: VecDeque<(H160, rfc4::ChatReaction)>,
     incoming_scene_messages: HashMap<String, VecDeque<(H160, Vec<u8>)>>,
 
     // Track last chat timestamp per sender to filter duplicates
@@ -207,6 +295,13 @@ pub struct MessageProcessor {
     profile_failure_receiver: mpsc::Receiver<ProfileFetchFailure>,
     profile_failure_sender: mpsc::Sender<ProfileFetchFailure>,
 
+    // Content of remote scenes fetched to resolve their SceneEmotes, and when a fetch failed
+    scene_emote_fetch_receiver: mpsc::Receiver<SceneEmoteFetch>,
+    scene_emote_fetch_sender: mpsc::Sender<SceneEmoteFetch>,
+    remote_scene_mappings: HashMap<String, ContentMappingAndUrlRef>,
+    failed_remote_scenes: HashMap<String, Instant>,
+    pending_scene_emotes: HashMap<String, Vec<PendingSceneEmote>>,
+
     // Configurable realm bounds for movement compression
     realm_min: godot::prelude::Vector2i,
     realm_max: godot::prelude::Vector2i,
@@ -226,6 +321,101 @@ pub struct MessageProcessor {
 
     // Set to true when room metadata indicates the local player is banned
     room_metadata_banned: bool,
+
+    // Simulated bad network applied to the incoming messages (`--network-sim`)
+    network_simulator: Option<NetworkSimulator>,
+
+    // Scene-scoped voice channel, voice from other rooms is ignored while set
+    voice_scope: Option<VoiceScope>,
+}
+
+/// Copies of a packet received through different rooms arrive within this window
+const DUAL_ROOM_DEDUP_WINDOW: Duration = Duration::from_secs(1);
+/// Remote scenes whose content is kept to resolve their SceneEmotes
+const MAX_REMOTE_SCENE_MAPPINGS: usize = 32;
+/// SceneEmotes of a remote scene whose fetch failed are dropped for this long before retrying
+const REMOTE_SCENE_RETRY_DELAY: Duration = Duration::from_secs(30);
+/// Remote scenes fetched at the same time, SceneEmotes needing another one are dropped
+const MAX_PENDING_SCENE_FETCHES: usize = 4;
+/// SceneEmotes kept per scene while it's fetched, one per peer
+const MAX_PENDING_SCENE_EMOTES: usize = 16;
+
+/// For packets without an incremental id or timestamp to order them: true if `message` is the
+/// same as the last one received within `DUAL_ROOM_DEDUP_WINDOW`, otherwise it becomes the last.
+fn is_dual_room_copy<T: PartialEq + Clone>(
+    last: &mut Option<(T, Instant)>,
+    message: &T,
+    now: Instant,
+) -> bool {
+    if let Some((last_message, received_at)) = last {
+        if last_message == message && now.duration_since(*received_at) < DUAL_ROOM_DEDUP_WINDOW {
+            return true;
+        }
+    }
+    *last = Some((message.clone(), now));
+    false
+}
+
+/// Queues a SceneEmote until its scene is fetched: only the newest one of each peer is kept,
+/// and at most `MAX_PENDING_SCENE_EMOTES`.
+fn queue_pending_scene_emote(waiting: &mut Vec<PendingSceneEmote>, pending: PendingSceneEmote) {
+    waiting.retain(|queued| queued.peer_alias != pending.peer_alias);
+    if waiting.len() < MAX_PENDING_SCENE_EMOTES {
+        waiting.push(pending);
+    }
+}
+
+/// Scene entity ids are CIDs (or `b64-` hashes in preview), anything else isn't requested.
+fn is_valid_scene_entity_id(scene_entity_id: &str) -> bool {
+    !scene_entity_id.is_empty()
+        && scene_entity_id.len() <= 128
+        && scene_entity_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+async fn fetch_scene_content_mapping(
+    content_base_url: &str,
+    scene_entity_id: &str,
+    http_requester: std::sync::Arc<HttpQueueRequester>,
+) -> Result<ContentMappingAndUrlRef, anyhow::Error> {
+    let ipfs_content_base_url = format!("{}/contents/", content_base_url.trim_end_matches('/'));
+    let response = http_requester
+        .request(
+            RequestOption::new(
+                0,
+                format!("{ipfs_content_base_url}{scene_entity_id}"),
+                http::Method::GET,
+                ResponseType::AsJson,
+                None,
+                None,
+                None,
+            ),
+            0,
+        )
+        .await
+        .map_err(|err| anyhow::Error::msg(err.error_message))?;
+    let json = match response.response_data {
+        Ok(ResponseEnum::Json(json)) => json?,
+        Ok(_) => return Err(anyhow::Error::msg("unexpected response type")),
+        Err(err) => return Err(anyhow::Error::msg(err)),
+    };
+
+    let scene_entity_definition = SceneEntityDefinition::from_json_ex(
+        Some(scene_entity_id.to_string()),
+        ipfs_content_base_url,
+        false,
+        json,
+    )?;
+    Ok(scene_entity_definition.content_mapping)
+}
+
+/// Queues of UI events (reactions) keep the newest `MAX_CHAT_MESSAGES`
+fn push_bounded<T>(queue: &mut VecDeque<T>, item: T) {
+    if queue.len() >= MAX_CHAT_MESSAGES {
+        queue.pop_front();
+    }
+    queue.push_back(item);
 }
 
 fn compare_f64(a: &f64, b: &f64) -> Ordering {
@@ -258,6 +448,8 @@ impl MessageProcessor {
             mpsc::channel(PROFILE_UPDATE_CHANNEL_SIZE);
         let (profile_failure_sender, profile_failure_receiver) =
             mpsc::channel(PROFILE_UPDATE_CHANNEL_SIZE);
+        let (scene_emote_fetch_sender, scene_emote_fetch_receiver) =
+            mpsc::channel(PROFILE_UPDATE_CHANNEL_SIZE);
 
         Self {
             message_receiver,
@@ -272,12 +464,19 @@ impl MessageProcessor {
             last_profile_request_sent: Instant::now(),
             last_profile_response_sent: Instant::now(),
             chats: VecDeque::new(),
+            reactions: VecDeque::new(),
+            chat_reactions: VecDeque::new(),
             incoming_scene_messages: HashMap::new(),
             last_chat_timestamps: HashMap::new(),
             profile_update_receiver,
             profile_update_sender,
             profile_failure_receiver,
             profile_failure_sender,
+            scene_emote_fetch_receiver,
+            scene_emote_fetch_sender,
+            remote_scene_mappings: HashMap::new(),
+            failed_remote_scenes: HashMap::new(),
+            pending_scene_emotes: HashMap::new(),
             // Default realm bounds
             realm_min: godot::prelude::Vector2i::new(-150, -150),
             realm_max: godot::prelude::Vector2i::new(163, 158),
@@ -287,6 +486,8 @@ impl MessageProcessor {
             active_video_tracks: HashMap::new(),
             disconnect_reason: None,
             room_metadata_banned: false,
+            network_simulator: None,
+            voice_scope: None,
         }
     }
 
@@ -440,6 +641,36 @@ impl MessageProcessor {
         tracing::debug!("Updated realm bounds: min={:?}, max={:?}", min, max);
     }
 
+    /// Simulates the given network conditions on the incoming messages, None (or ideal
+    /// conditions) turns it off and processes the held messages right away
+    pub fn set_network_simulation(&mut self, config: Option<NetworkSimulatorConfig>) {
+        if let Some(mut simulator) = self.network_simulator.take() {
+            for message in simulator.drain_all() {
+                self.process_message(message);
+            }
+        }
+        self.network_simulator = config
+            .filter(|config| !config.is_ideal())
+            .map(NetworkSimulator::new);
+    }
+
+    pub fn network_simulation(&self) -> Option<&NetworkSimulatorConfig> {
+        self.network_simulator
+            .as_ref()
+            .map(|simulator| simulator.config())
+    }
+
+    pub fn set_voice_scope(&mut self, scope: Option<VoiceScope>) {
+        self.voice_scope = scope;
+    }
+
+    /// Whether `address` advertised support for batched scene messages
+    pub fn peer_supports_scene_batching(&self, address: &H160) -> bool {
+        self.peer_identities
+            .get(address)
+            .is_some_and(|peer| peer.scene_batching)
+    }
+
     /// Consumes and returns all pending outgoing messages
     ///
     /// CommunicationManager should call this regularly to retrieve messages
@@ -499,6 +730,44 @@ impl MessageProcessor {
             }
         }
 
+        // Handle the scenes fetched for remote SceneEmotes
+        while let Ok(fetch) = self.scene_emote_fetch_receiver.try_recv() {
+            let waiting = self
+                .pending_scene_emotes
+                .remove(&fetch.scene_entity_id)
+                .unwrap_or_default();
+            match fetch.content_mapping {
+                Ok(content_mapping) => {
+                    if self.remote_scene_mappings.len() >= MAX_REMOTE_SCENE_MAPPINGS {
+                        self.remote_scene_mappings.clear();
+                    }
+                    self.remote_scene_mappings
+                        .insert(fetch.scene_entity_id.clone(), content_mapping);
+                }
+                Err(err) => {
+                    tracing::debug!(
+                        "Failed to fetch scene {} for its SceneEmote: {err}",
+                        fetch.scene_entity_id
+                    );
+                    // Kept only for the retry delay, so a transient failure doesn't stick
+                    if self.failed_remote_scenes.len() >= MAX_REMOTE_SCENE_MAPPINGS {
+                        self.failed_remote_scenes.clear();
+                    }
+                    self.failed_remote_scenes
+                        .insert(fetch.scene_entity_id.clone(), Instant::now());
+                    continue;
+                }
+            }
+
+            for pending in waiting {
+                self.play_scene_emote(
+                    pending.peer_alias,
+                    fetch.scene_entity_id.clone(),
+                    pending.source,
+                );
+            }
+        }
+
         // Handle profile fetch failures
         while let Ok(failure) = self.profile_failure_receiver.try_recv() {
             let mut fetch_state: Option<(u32, i32, bool)> = None;
@@ -533,6 +802,17 @@ impl MessageProcessor {
 
         // Process incoming messages
         while let Ok(message) = self.message_receiver.try_recv() {
+            match self.network_simulator.as_mut() {
+                Some(simulator) => simulator.push(message, Instant::now()),
+                None => self.process_message(message),
+            }
+        }
+        let simulated_arrivals = self
+            .network_simulator
+            .as_mut()
+            .map(|simulator| simulator.drain_due(Instant::now()))
+            .unwrap_or_default();
+        for message in simulated_arrivals {
             self.process_message(message);
         }
 
@@ -724,25 +1004,7 @@ impl MessageProcessor {
                     scene.scene_id,
                     scene.data.len()
                 );
-
-                // Limit the number of scene IDs we track
-                if !self.incoming_scene_messages.contains_key(&scene.scene_id)
-                    && self.incoming_scene_messages.len() >= MAX_SCENE_IDS
-                {
-                    if let Some(oldest_key) = self.incoming_scene_messages.keys().next().cloned() {
-                        self.incoming_scene_messages.remove(&oldest_key);
-                    }
-                }
-
-                let entry = self
-                    .incoming_scene_messages
-                    .entry(scene.scene_id.clone())
-                    .or_default();
-
-                if entry.len() >= MAX_SCENE_MESSAGES_PER_SCENE {
-                    entry.pop_front();
-                }
-                entry.push_back((message.address, scene.data));
+                self.queue_scene_message(message.address, scene);
             } else {
                 tracing::debug!(
                     "📨 Non-player non-Scene message ignored from {:#x} (room '{}')",
@@ -753,6 +1015,56 @@ impl MessageProcessor {
         }
     }
 
+    /// Queue a received scene payload for its scene, unpacking batched envelopes
+    fn queue_scene_message(&mut self, address: H160, scene: rfc4::Scene) {
+        let messages = match decode_scene_payload(scene.data) {
+            Ok(messages) => messages,
+            Err(e) => {
+                tracing::warn!(
+                    "Invalid scene message batch from {:#x} for scene '{}': {}",
+                    address,
+                    scene.scene_id,
+                    e
+                );
+                return;
+            }
+        };
+
+        // Limit the number of scene IDs we track
+        if !self.incoming_scene_messages.contains_key(&scene.scene_id)
+            && self.incoming_scene_messages.len() >= MAX_SCENE_IDS
+        {
+            // Remove the oldest scene ID (arbitrary choice - could use LRU)
+            if let Some(oldest_key) = self.incoming_scene_messages.keys().next().cloned() {
+                self.incoming_scene_messages.remove(&oldest_key);
+                tracing::debug!(
+                    "Scene message map full, dropped messages for scene: {}",
+                    oldest_key
+                );
+            }
+        }
+
+        let entry = self
+            .incoming_scene_messages
+            .entry(scene.scene_id.clone())
+            .or_default();
+
+        for data in messages {
+            // Enforce bounded queue per scene
+            if entry.len() >= MAX_SCENE_MESSAGES_PER_SCENE {
+                let dropped = entry.pop_front();
+                if let Some((addr, _)) = dropped {
+                    tracing::debug!(
+                        "Scene {} message queue full, dropping oldest message from {:#x}",
+                        scene.scene_id,
+                        addr
+                    );
+                }
+            }
+            entry.push_back((address, data));
+        }
+    }
+
     fn process_message(&mut self, message: IncomingMessage) {
         // Skip messages from ourselves (can happen if local participant events leak through)
         if message.address == self.player_address {
@@ -860,7 +1172,12 @@ impl MessageProcessor {
                     lambdas_endpoint: None,
                     last_movement_timestamp: f32::NEG_INFINITY,
                     last_emote_incremental_id: 0,
+                    last_scene_emote: None,
+                    last_reaction: None,
+                    last_chat_reaction: None,
+                    last_look_at_timestamp: f32::NEG_INFINITY,
                     pulse_live: false,
+                    scene_batching: false,
                 },
             );
 
@@ -927,6 +1244,8 @@ impl MessageProcessor {
                     voice_init.num_channels,
                     voice_init.samples_per_channel,
                 );
+                let gain = avatar_scene.peer_voice_settings(&message.address).gain();
+                avatar_scene.set_avatar_voice_volume(peer_alias, gain);
             }
             MessageType::VoiceFrame(voice_frame) => {
                 // Check if user is muted for voice (using cached set for O(1) lookup)
@@ -934,6 +1253,21 @@ impl MessageProcessor {
                 if self.cached_muted.contains(&message.address) {
                     return; // muted/blocked - ignore voice frames
                 }
+                if self
+                    .avatars
+                    .bind()
+                    .peer_voice_settings(&message.address)
+                    .muted
+                {
+                    return; // muted locally for this session
+                }
+                if self
+                    .voice_scope
+                    .as_ref()
+                    .is_some_and(|scope| !scope.allows(&message.address, &room_id))
+                {
+                    return; // outside the scene voice channel, or silenced by the scene
+                }
 
                 // If all the frame.data is less than 10, we skip the frame
                 if voice_frame.data.iter().all(|&c| c.abs() < 10) {
@@ -1011,6 +1345,13 @@ impl MessageProcessor {
                             }
                         }
                     }
+                    let scene_batching = json
+                        .get(SCENE_BATCHING_METADATA_KEY)
+                        .and_then(|v| v.as_u64())
+                        .is_some_and(|v| v >= SCENE_BATCHING_VERSION);
+                    if let Some(peer) = self.peer_identities.get_mut(&message.address) {
+                        peer.scene_batching = scene_batching;
+                    }
                     if let Some(endpoint) = json
                         .get("lambdasEndpoint")
                         .and_then(|v| v.as_str())
@@ -1289,7 +1630,7 @@ impl MessageProcessor {
                 // Enforce bounded queue for chat messages
                 if self.chats.len() >= MAX_CHAT_MESSAGES {
                     let dropped = self.chats.pop_front();
-                    if let Some((addr, _)) = dropped {
+                    if let Some((addr, _, _)) = dropped {
                         tracing::warn!("Chat queue full, dropping oldest message from {:#x}", addr);
                     }
                 }
@@ -1304,7 +1645,7 @@ impl MessageProcessor {
                 } else {
                     chat
                 };
-                self.chats.push_back((address, chat));
+                self.chats.push_back((address, room_id.to_string(), chat));
             }
             rfc4::packet::Message::ProfileVersion(announce_profile_version) => {
                 tracing::debug!(
@@ -1741,37 +2082,7 @@ impl MessageProcessor {
                 }
             }
             rfc4::packet::Message::Scene(scene) => {
-                // Limit the number of scene IDs we track
-                if !self.incoming_scene_messages.contains_key(&scene.scene_id)
-                    && self.incoming_scene_messages.len() >= MAX_SCENE_IDS
-                {
-                    // Remove the oldest scene ID (arbitrary choice - could use LRU)
-                    if let Some(oldest_key) = self.incoming_scene_messages.keys().next().cloned() {
-                        self.incoming_scene_messages.remove(&oldest_key);
-                        tracing::debug!(
-                            "Scene message map full, dropped messages for scene: {}",
-                            oldest_key
-                        );
-                    }
-                }
-
-                let entry = self
-                    .incoming_scene_messages
-                    .entry(scene.scene_id.clone())
-                    .or_default();
-
-                // Enforce bounded queue per scene
-                if entry.len() >= MAX_SCENE_MESSAGES_PER_SCENE {
-                    let dropped = entry.pop_front();
-                    if let Some((addr, _)) = dropped {
-                        tracing::debug!(
-                            "Scene {} message queue full, dropping oldest message from {:#x}",
-                            scene.scene_id,
-                            addr
-                        );
-                    }
-                }
-                entry.push_back((address, scene.data));
+                self.queue_scene_message(address, scene);
             }
             rfc4::packet::Message::Voice(_voice) => {}
             rfc4::packet::Message::PlayerEmote(player_emote) => {
@@ -1810,19 +2121,182 @@ impl MessageProcessor {
                 let mut avatar_scene = avatar_scene_ref.bind_mut();
                 avatar_scene.play_emote(peer_alias, player_emote.incremental_id, &player_emote.urn);
             }
-            rfc4::packet::Message::SceneEmote(_) => {
-                tracing::warn!("Not implemented: SceneEmote handling in message_processor");
+            rfc4::packet::Message::SceneEmote(scene_emote) => {
+                if let Some(peer) = self.peer_identities.get_mut(&address) {
+                    if is_dual_room_copy(&mut peer.last_scene_emote, &scene_emote, Instant::now()) {
+                        tracing::debug!("Discarding duplicate SceneEmote from {:#x}", address);
+                        return;
+                    }
+                }
+
+                tracing::debug!("Received SceneEmote from {:#x}: {:?}", address, scene_emote);
+                self.play_scene_emote(peer_alias, scene_emote.scene_entity_id, scene_emote.source);
+            }
+            rfc4::packet::Message::LookAtPosition(look_at) => {
+                if let Some(peer) = self.peer_identities.get_mut(&address) {
+                    if look_at.timestamp <= peer.last_look_at_timestamp {
+                        return;
+                    }
+                    peer.last_look_at_timestamp = look_at.timestamp;
+                }
+
+                // DCL (left-handed) to Godot space, like the avatar positions
+                let target = godot::prelude::Vector3::new(
+                    look_at.position_x,
+                    look_at.position_y,
+                    -look_at.position_z,
+                );
+                let mut avatar_scene_ref = self.avatars.clone();
+                avatar_scene_ref
+                    .bind_mut()
+                    .set_avatar_look_at(peer_alias, target);
+            }
+            rfc4::packet::Message::Reaction(reaction) => {
+                if self.cached_muted.contains(&address) {
+                    return;
+                }
+                if let Some(peer) = self.peer_identities.get_mut(&address) {
+                    if is_dual_room_copy(&mut peer.last_reaction, &reaction, Instant::now()) {
+                        return;
+                    }
+                }
+                tracing::debug!("Received Reaction from {:#x}: {:?}", address, reaction);
+                push_bounded(&mut self.reactions, (address, reaction));
+            }
+            rfc4::packet::Message::ChatReaction(chat_reaction) => {
+                if self.cached_muted.contains(&address) {
+                    return;
+                }
+                if let Some(peer) = self.peer_identities.get_mut(&address) {
+                    if is_dual_room_copy(
+                        &mut peer.last_chat_reaction,
+                        &chat_reaction,
+                        Instant::now(),
+                    ) {
+                        return;
+                    }
+                }
+                tracing::debug!(
+                    "Received ChatReaction from {:#x}: {:?}",
+                    address,
+                    chat_reaction
+                );
+                push_bounded(&mut self.chat_reactions, (address, chat_reaction));
             }
-            rfc4::packet::Message::LookAtPosition(_)
-            | rfc4::packet::Message::Reaction(_)
-            | rfc4::packet::Message::ChatReaction(_) => {}
         }
     }
 
-    pub fn consume_chats(&mut self) -> Vec<(H160, rfc4::Chat)> {
+    /// Resolves the emote file against the sender's scene: a loaded scene with that entity
+    /// id, or else one of the realm's scenes fetched from its content server (cached per scene,
+    /// failed fetches are retried after `REMOTE_SCENE_RETRY_DELAY`).
+    fn play_scene_emote(&mut self, peer_alias: u32, scene_entity_id: String, source: String) {
+        let content_mapping = DclGlobal::singleton()
+            .bind()
+            .get_scene_runner()
+            .bind()
+            .get_content_mapping_by_entity_id(&scene_entity_id);
+        let content_mapping = match content_mapping {
+            Some(content_mapping) => content_mapping,
+            None => match self.remote_scene_mappings.get(&scene_entity_id) {
+                Some(content_mapping) => content_mapping.clone(),
+                None => {
+                    if let Some(failed_at) = self.failed_remote_scenes.get(&scene_entity_id) {
+                        if failed_at.elapsed() < REMOTE_SCENE_RETRY_DELAY {
+                            return;
+                        }
+                        self.failed_remote_scenes.remove(&scene_entity_id);
+                    }
+                    self.fetch_scene_emote_content(
+                        scene_entity_id,
+                        PendingSceneEmote { peer_alias, source },
+                    );
+                    return;
+                }
+            },
+        };
+
+        let Some(emote_hash) = content_mapping.get_scene_emote_hash(&source) else {
+            tracing::warn!(
+                "SceneEmote '{}' not found in the content of scene {}",
+                source,
+                scene_entity_id
+            );
+            return;
+        };
+
+        let mut avatar_scene_ref = self.avatars.clone();
+        avatar_scene_ref.bind_mut().play_scene_emote(
+            peer_alias,
+            &scene_entity_id,
+            &content_mapping.base_url,
+            &emote_hash,
+            false,
+        );
+    }
+
+    /// The id comes from the peer, so only scenes the realm lists are fetched, a few at a time.
+    fn fetch_scene_emote_content(&mut self, scene_entity_id: String, pending: PendingSceneEmote) {
+        if let Some(waiting) = self.pending_scene_emotes.get_mut(&scene_entity_id) {
+            queue_pending_scene_emote(waiting, pending);
+            return;
+        }
+        if !is_valid_scene_entity_id(&scene_entity_id) {
+            tracing::debug!("Ignoring SceneEmote with invalid scene id {scene_entity_id:?}");
+            return;
+        }
+        if self.pending_scene_emotes.len() >= MAX_PENDING_SCENE_FETCHES {
+            tracing::debug!("Dropping SceneEmote of scene {scene_entity_id}, too many fetches");
+            return;
+        }
+
+        let global = DclGlobal::singleton();
+        let realm = global.bind().get_realm();
+        let realm = realm.bind();
+        let is_realm_scene = realm.get_realm_scene_urns().iter_shared().any(|urn| {
+            urn.get("entityId")
+                .is_some_and(|entity_id| entity_id.to_string() == scene_entity_id)
+        });
+        if !is_realm_scene {
+            tracing::debug!("Ignoring SceneEmote of scene {scene_entity_id}, not in the realm");
+            return;
+        }
+        let content_base_url = realm.get_content_base_url().to_string();
+        drop(realm);
+
+        self.pending_scene_emotes
+            .insert(scene_entity_id.clone(), vec![pending]);
+        let http_requester = global
+            .bind()
+            .get_http_requester()
+            .bind()
+            .get_http_queue_requester();
+        let sender = self.scene_emote_fetch_sender.clone();
+
+        TokioRuntime::spawn(async move {
+            let content_mapping =
+                fetch_scene_content_mapping(&content_base_url, &scene_entity_id, http_requester)
+                    .await;
+            let _ = sender
+                .send(SceneEmoteFetch {
+                    scene_entity_id,
+                    content_mapping,
+                })
+                .await;
+        });
+    }
+
+    pub fn consume_chats(&mut self) -> Vec<(H160, String, rfc4::Chat)> {
         self.chats.drain(..).collect()
     }
 
+    pub fn consume_reactions(&mut self) -> Vec<(H160, rfc4::Reaction)> {
+        self.reactions.drain(..).collect()
+    }
+
+    pub fn consume_chat_reactions(&mut self) -> Vec<(H160, rfc4::ChatReaction)> {
+        self.chat_reactions.drain(..).collect()
+    }
+
     pub fn consume_scene_messages(&mut self, scene_id: &str) -> Vec<(H160, Vec<u8>)> {
         if let Some(messages) = self.incoming_scene_messages.get_mut(scene_id) {
             let result: Vec<_> = messages.drain(..).collect();
@@ -1951,6 +2425,34 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn peer_voice_settings_are_clamped() {
+        assert_eq!(PeerVoiceSettings::new(0.5, false).gain(), 0.5);
+        assert_eq!(PeerVoiceSettings::new(0.5, true).gain(), 0.0);
+        assert_eq!(
+            PeerVoiceSettings::new(10.0, false).volume,
+            PeerVoiceSettings::MAX_VOLUME
+        );
+        assert_eq!(PeerVoiceSettings::new(-1.0, false).volume, 0.0);
+        assert_eq!(
+            PeerVoiceSettings::new(f32::NAN, false),
+            PeerVoiceSettings::default()
+        );
+    }
+
+    #[test]
+    fn voice_scope_only_allows_its_room() {
+        let muted = H160::from_low_u64_be(2);
+        let scope = VoiceScope {
+            room_id: "scene-bafkrei".to_string(),
+            muted: HashSet::from([muted]),
+        };
+        let speaker = H160::from_low_u64_be(1);
+        assert!(scope.allows(&speaker, "scene-bafkrei"));
+        assert!(!scope.allows(&speaker, "archipelago"));
+        assert!(!scope.allows(&muted, "scene-bafkrei"));
+    }
+
     #[test]
     fn lambda_endpoint_comparison_ignores_trailing_slash_style() {
         // Godot metadata carries `…/lambdas/`, Unity metadata carries `…/lambdas` —
@@ -2002,4 +2504,67 @@ mod tests {
             None
         );
     }
+
+    #[test]
+    fn scene_emote_entity_ids_are_validated_before_fetching() {
+        assert!(is_valid_scene_entity_id(
+            "bafkreigxpvqbxp6gjmn5acwxmdlymnvmjkdtq5xa4u5r2rz4vz3otzqyka"
+        ));
+        assert!(is_valid_scene_entity_id("b64-L2hvbWUvdXNlci9zY2VuZQ"));
+        assert!(!is_valid_scene_entity_id(""));
+        assert!(!is_valid_scene_entity_id("../../lambdas/profiles"));
+        assert!(!is_valid_scene_entity_id(
+            "bafk?redirect=https://example.com"
+        ));
+    }
+
+    #[test]
+    fn pending_scene_emotes_keep_the_newest_per_peer() {
+        let pending = |peer_alias: u32, source: &str| PendingSceneEmote {
+            peer_alias,
+            source: source.to_string(),
+        };
+        let mut waiting = Vec::new();
+        queue_pending_scene_emote(&mut waiting, pending(1, "a.glb"));
+        queue_pending_scene_emote(&mut waiting, pending(2, "a.glb"));
+        queue_pending_scene_emote(&mut waiting, pending(1, "b.glb"));
+        let queued: Vec<_> = waiting
+            .iter()
+            .map(|queued| (queued.peer_alias, queued.source.as_str()))
+            .collect();
+        assert_eq!(queued, vec![(2, "a.glb"), (1, "b.glb")]);
+
+        for peer_alias in 0..100 {
+            queue_pending_scene_emote(&mut waiting, pending(peer_alias, "a.glb"));
+        }
+        assert_eq!(waiting.len(), MAX_PENDING_SCENE_EMOTES);
+    }
+
+    #[test]
+    fn dual_room_copies_are_discarded_within_the_window() {
+        let now = Instant::now();
+        let emote = rfc4::SceneEmote {
+            scene_entity_id: "bafkscene".to_string(),
+            source: "emote.glb".to_string(),
+        };
+        let mut last = None;
+        assert!(!is_dual_room_copy(&mut last, &emote, now));
+        assert!(is_dual_room_copy(
+            &mut last,
+            &emote,
+            now + Duration::from_millis(50)
+        ));
+
+        let other = rfc4::SceneEmote {
+            source: "other.glb".to_string(),
+            ..emote.clone()
+        };
+        assert!(!is_dual_room_copy(&mut last, &other, now));
+        // the same emote again after the window is a new one
+        assert!(!is_dual_room_copy(
+            &mut last,
+            &other,
+            now + DUAL_ROOM_DEDUP_WINDOW
+        ));
+    }
 }
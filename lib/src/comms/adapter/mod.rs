@@ -5,4 +5,6 @@ pub mod archipelago;
 pub mod livekit;
 pub mod message_processor;
 pub mod movement_compressed;
+pub mod network_simulator;
+pub mod scene_batching;
 pub mod ws_room;
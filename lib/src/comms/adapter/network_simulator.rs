@@ -0,0 +1,498 @@
+//! Simulated network conditions between the adapters and the `MessageProcessor`.
+//!
+//! Every `IncomingMessage` coming from a room is held back according to the
+//! conditions of its class (latency, jitter, loss, duplication and reordering)
+//! before it's processed, so a bad mobile link can be reproduced on a desktop.
+//!
+//! Configured with `--network-sim <spec>` or `CommunicationManager.set_network_simulation`.
+//! The spec is a `;` separated list of rules, applied in order:
+//!   - a preset: `mobile`, `bad`
+//!   - `key=value,...` for every class
+//!   - `<class>:key=value,...` for one class (movement, media, chat, profile, scene, lifecycle)
+//!
+//! Keys: `latency` and `jitter` in ms, `loss`, `dup` and `reorder` as probabilities (0..1).
+//! e.g. `mobile;movement:loss=0.2,reorder=0.1`
+//!
+//! Lifecycle messages (peer joined/left, disconnections and metadata) come from the
+//! reliable signalling of every transport: only their latency is simulated, and they
+//! never arrive before a message of the same peer that was received earlier.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    fmt,
+    time::{Duration, Instant},
+};
+
+use ethers_core::types::H160;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::dcl::components::proto_components::kernel::comms::rfc4;
+
+use super::message_processor::{IncomingMessage, MessageType};
+
+/// Extra hold of a reordered message, so the ones behind it overtake it
+const REORDER_DELAY: Duration = Duration::from_millis(120);
+
+/// Messages held at once, the newest are dropped past it (like a full socket buffer)
+const MAX_PENDING_MESSAGES: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageClass {
+    Movement,
+    Media,
+    Chat,
+    Profile,
+    Scene,
+    Lifecycle,
+}
+
+const CLASS_COUNT: usize = 6;
+
+impl MessageClass {
+    const ALL: [MessageClass; CLASS_COUNT] = [
+        MessageClass::Movement,
+        MessageClass::Media,
+        MessageClass::Chat,
+        MessageClass::Profile,
+        MessageClass::Scene,
+        MessageClass::Lifecycle,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageClass::Movement => "movement",
+            MessageClass::Media => "media",
+            MessageClass::Chat => "chat",
+            MessageClass::Profile => "profile",
+            MessageClass::Scene => "scene",
+            MessageClass::Lifecycle => "lifecycle",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|class| class.as_str() == name)
+    }
+
+    pub fn of(message: &MessageType) -> Self {
+        match message {
+            MessageType::Rfc4(rfc4) => match &rfc4.message {
+                rfc4::packet::Message::Position(_)
+                | rfc4::packet::Message::Movement(_)
+                | rfc4::packet::Message::MovementCompressed(_)
+                | rfc4::packet::Message::PlayerEmote(_)
+                | rfc4::packet::Message::SceneEmote(_)
+                | rfc4::packet::Message::LookAtPosition(_) => MessageClass::Movement,
+                rfc4::packet::Message::Chat(_)
+                | rfc4::packet::Message::Reaction(_)
+                | rfc4::packet::Message::ChatReaction(_) => MessageClass::Chat,
+                rfc4::packet::Message::ProfileVersion(_)
+                | rfc4::packet::Message::ProfileRequest(_)
+                | rfc4::packet::Message::ProfileResponse(_) => MessageClass::Profile,
+                rfc4::packet::Message::Scene(_) => MessageClass::Scene,
+                rfc4::packet::Message::Voice(_) => MessageClass::Media,
+            },
+            MessageType::InitVoice(_)
+            | MessageType::VoiceFrame(_)
+            | MessageType::InitVideo(_)
+            | MessageType::VideoFrame(_)
+            | MessageType::InitStreamerAudio(_)
+            | MessageType::StreamerAudioFrame(_) => MessageClass::Media,
+            MessageType::PeerJoined
+            | MessageType::PeerLeft
+            | MessageType::Disconnected(_)
+            | MessageType::PeerMetadata(_)
+            | MessageType::RoomMetadataChanged(_) => MessageClass::Lifecycle,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NetworkConditions {
+    pub latency_ms: u32,
+    pub jitter_ms: u32,
+    pub loss: f32,
+    pub duplicate: f32,
+    pub reorder: f32,
+}
+
+impl NetworkConditions {
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let invalid = || format!("invalid value for {key}: {value}");
+        let probability = || -> Result<f32, String> {
+            let value = value.parse::<f32>().map_err(|_| invalid())?;
+            if (0.0..=1.0).contains(&value) {
+                Ok(value)
+            } else {
+                Err(format!("{key} must be between 0 and 1, got {value}"))
+            }
+        };
+        match key {
+            "latency" => self.latency_ms = value.parse().map_err(|_| invalid())?,
+            "jitter" => self.jitter_ms = value.parse().map_err(|_| invalid())?,
+            "loss" => self.loss = probability()?,
+            "dup" => self.duplicate = probability()?,
+            "reorder" => self.reorder = probability()?,
+            _ => return Err(format!("unknown network condition {key}")),
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for NetworkConditions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "latency={},jitter={},loss={},dup={},reorder={}",
+            self.latency_ms, self.jitter_ms, self.loss, self.duplicate, self.reorder
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NetworkSimulatorConfig {
+    classes: [NetworkConditions; CLASS_COUNT],
+}
+
+impl NetworkSimulatorConfig {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut config = Self::default();
+        for rule in spec
+            .split(';')
+            .map(str::trim)
+            .filter(|rule| !rule.is_empty())
+        {
+            if let Some(preset) = Self::preset(rule) {
+                config = preset;
+                continue;
+            }
+
+            let (classes, settings) = match rule.split_once(':') {
+                Some((class, settings)) => {
+                    let class = MessageClass::from_name(class.trim())
+                        .ok_or_else(|| format!("unknown message class {class}"))?;
+                    (vec![class], settings)
+                }
+                None => (MessageClass::ALL.to_vec(), rule),
+            };
+            for setting in settings.split(',').map(str::trim) {
+                let (key, value) = setting
+                    .split_once('=')
+                    .ok_or_else(|| format!("expected key=value, got {setting}"))?;
+                for class in &classes {
+                    config
+                        .conditions_mut(*class)
+                        .set(key.trim(), value.trim())?;
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    fn preset(name: &str) -> Option<Self> {
+        let conditions = match name {
+            "mobile" => NetworkConditions {
+                latency_ms: 150,
+                jitter_ms: 60,
+                loss: 0.02,
+                duplicate: 0.0,
+                reorder: 0.02,
+            },
+            "bad" => NetworkConditions {
+                latency_ms: 350,
+                jitter_ms: 150,
+                loss: 0.1,
+                duplicate: 0.02,
+                reorder: 0.05,
+            },
+            _ => return None,
+        };
+        Some(Self {
+            classes: [conditions; CLASS_COUNT],
+        })
+    }
+
+    pub fn conditions(&self, class: MessageClass) -> &NetworkConditions {
+        &self.classes[class as usize]
+    }
+
+    fn conditions_mut(&mut self, class: MessageClass) -> &mut NetworkConditions {
+        &mut self.classes[class as usize]
+    }
+
+    pub fn is_ideal(&self) -> bool {
+        self.classes
+            .iter()
+            .all(|conditions| *conditions == NetworkConditions::default())
+    }
+}
+
+impl fmt::Display for NetworkSimulatorConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rules = MessageClass::ALL
+            .iter()
+            .filter(|class| *self.conditions(**class) != NetworkConditions::default())
+            .map(|class| format!("{}:{}", class.as_str(), self.conditions(*class)))
+            .collect::<Vec<_>>();
+        write!(f, "{}", rules.join(";"))
+    }
+}
+
+struct PendingMessage {
+    deliver_at: Instant,
+    sequence: u64,
+    message: IncomingMessage,
+}
+
+impl PartialEq for PendingMessage {
+    fn eq(&self, other: &Self) -> bool {
+        (self.deliver_at, self.sequence) == (other.deliver_at, other.sequence)
+    }
+}
+
+impl Eq for PendingMessage {}
+
+impl PartialOrd for PendingMessage {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PendingMessage {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.deliver_at, self.sequence).cmp(&(other.deliver_at, other.sequence))
+    }
+}
+
+pub struct NetworkSimulator {
+    config: NetworkSimulatorConfig,
+    pending: BinaryHeap<Reverse<PendingMessage>>,
+    sequence: u64,
+    rng: StdRng,
+    /// Latest arrival scheduled for each peer (address and room) still pending
+    last_arrival: HashMap<(H160, String), Instant>,
+}
+
+impl NetworkSimulator {
+    pub fn new(config: NetworkSimulatorConfig) -> Self {
+        Self::with_rng(config, StdRng::from_entropy())
+    }
+
+    fn with_rng(config: NetworkSimulatorConfig, rng: StdRng) -> Self {
+        Self {
+            config,
+            pending: BinaryHeap::new(),
+            sequence: 0,
+            rng,
+            last_arrival: HashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &NetworkSimulatorConfig {
+        &self.config
+    }
+
+    /// Holds the message until its simulated arrival (or drops it)
+    pub fn push(&mut self, message: IncomingMessage, now: Instant) {
+        let class = MessageClass::of(&message.message);
+        let conditions = *self.config.conditions(class);
+
+        if class == MessageClass::Lifecycle {
+            let delay = Duration::from_millis(conditions.latency_ms as u64);
+            // e.g. a PeerLeft can't overtake the last position of that peer
+            let after_peer = self
+                .last_arrival
+                .get(&(message.address, message.room_id.clone()))
+                .copied();
+            let deliver_at = after_peer.map_or(now + delay, |at| at.max(now + delay));
+            self.schedule(message, deliver_at);
+            return;
+        }
+
+        if self.rng.gen::<f32>() < conditions.loss {
+            return;
+        }
+        if self.rng.gen::<f32>() < conditions.duplicate {
+            let delay = self.delay(&conditions);
+            self.schedule(message.clone(), now + delay);
+        }
+        let delay = self.delay(&conditions);
+        self.schedule(message, now + delay);
+    }
+
+    /// The messages whose simulated arrival already happened, in arrival order
+    pub fn drain_due(&mut self, now: Instant) -> Vec<IncomingMessage> {
+        let mut due = Vec::new();
+        while self
+            .pending
+            .peek()
+            .is_some_and(|Reverse(pending)| pending.deliver_at <= now)
+        {
+            if let Some(Reverse(pending)) = self.pending.pop() {
+                due.push(pending.message);
+            }
+        }
+        self.last_arrival.retain(|_, at| *at > now);
+        due
+    }
+
+    /// Everything still held, in arrival order (used when the simulation is turned off)
+    pub fn drain_all(&mut self) -> Vec<IncomingMessage> {
+        self.last_arrival.clear();
+        let mut pending = std::mem::take(&mut self.pending).into_sorted_vec();
+        // `Reverse` sorts the latest arrival first
+        pending.reverse();
+        pending
+            .into_iter()
+            .map(|Reverse(pending)| pending.message)
+            .collect()
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    fn delay(&mut self, conditions: &NetworkConditions) -> Duration {
+        let jitter = conditions.jitter_ms as i64;
+        let jitter = if jitter > 0 {
+            self.rng.gen_range(-jitter..=jitter)
+        } else {
+            0
+        };
+        let mut delay =
+            Duration::from_millis((conditions.latency_ms as i64 + jitter).max(0) as u64);
+        if self.rng.gen::<f32>() < conditions.reorder {
+            delay += REORDER_DELAY;
+        }
+        delay
+    }
+
+    fn schedule(&mut self, message: IncomingMessage, deliver_at: Instant) {
+        if self.pending.len() >= MAX_PENDING_MESSAGES {
+            return;
+        }
+        self.sequence += 1;
+        let last_arrival = self
+            .last_arrival
+            .entry((message.address, message.room_id.clone()))
+            .or_insert(deliver_at);
+        *last_arrival = (*last_arrival).max(deliver_at);
+        self.pending.push(Reverse(PendingMessage {
+            deliver_at,
+            sequence: self.sequence,
+            message,
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers_core::types::H160;
+
+    use super::*;
+
+    fn peer_joined(room_id: &str) -> IncomingMessage {
+        IncomingMessage {
+            message: MessageType::PeerJoined,
+            address: H160::zero(),
+            room_id: room_id.to_string(),
+        }
+    }
+
+    fn peer_left() -> IncomingMessage {
+        IncomingMessage {
+            message: MessageType::PeerLeft,
+            address: H160::zero(),
+            room_id: "main".to_string(),
+        }
+    }
+
+    fn voice_frame(sample: i16) -> IncomingMessage {
+        IncomingMessage {
+            message: MessageType::VoiceFrame(super::super::message_processor::VoiceFrameData {
+                data: vec![sample],
+            }),
+            address: H160::zero(),
+            room_id: "main".to_string(),
+        }
+    }
+
+    fn sample(message: &IncomingMessage) -> i16 {
+        match &message.message {
+            MessageType::VoiceFrame(frame) => frame.data[0],
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn parse_spec() {
+        let config =
+            NetworkSimulatorConfig::parse("latency=100,jitter=20; media:loss=0.5").unwrap();
+        assert_eq!(config.conditions(MessageClass::Chat).latency_ms, 100);
+        assert_eq!(config.conditions(MessageClass::Media).latency_ms, 100);
+        assert_eq!(config.conditions(MessageClass::Media).loss, 0.5);
+        assert_eq!(config.conditions(MessageClass::Movement).loss, 0.0);
+        assert_eq!(
+            NetworkSimulatorConfig::parse(&config.to_string()),
+            Ok(config)
+        );
+
+        let config = NetworkSimulatorConfig::parse("bad;movement:dup=0").unwrap();
+        assert_eq!(config.conditions(MessageClass::Chat).latency_ms, 350);
+        assert_eq!(config.conditions(MessageClass::Movement).duplicate, 0.0);
+
+        assert!(NetworkSimulatorConfig::parse("").unwrap().is_ideal());
+        assert!(NetworkSimulatorConfig::parse("loss=2").is_err());
+        assert!(NetworkSimulatorConfig::parse("voice:loss=0.1").is_err());
+        assert!(NetworkSimulatorConfig::parse("bandwidth=10").is_err());
+    }
+
+    #[test]
+    fn latency_loss_and_reordering() {
+        let now = Instant::now();
+        let config = NetworkSimulatorConfig::parse("latency=100;media:loss=1").unwrap();
+        let mut simulator = NetworkSimulator::with_rng(config, StdRng::seed_from_u64(7));
+
+        simulator.push(peer_joined("main"), now);
+        simulator.push(voice_frame(1), now);
+        assert_eq!(simulator.pending_count(), 1);
+        assert!(simulator.drain_due(now).is_empty());
+        assert_eq!(
+            simulator.drain_due(now + Duration::from_millis(100)).len(),
+            1
+        );
+
+        let config = NetworkSimulatorConfig::parse("media:latency=50,reorder=0.5").unwrap();
+        let mut simulator = NetworkSimulator::with_rng(config, StdRng::seed_from_u64(7));
+        for index in 0..100 {
+            simulator.push(voice_frame(index), now);
+        }
+        let on_time = simulator.drain_due(now + Duration::from_millis(50));
+        assert!(!on_time.is_empty() && on_time.len() < 100);
+        assert!(on_time.windows(2).all(|w| sample(&w[0]) < sample(&w[1])));
+        assert_eq!(on_time.len() + simulator.drain_all().len(), 100);
+    }
+
+    #[test]
+    fn lifecycle_never_overtakes_the_peer_messages() {
+        let now = Instant::now();
+        let config =
+            NetworkSimulatorConfig::parse("media:latency=300,jitter=100;lifecycle:latency=10")
+                .unwrap();
+        let mut simulator = NetworkSimulator::with_rng(config, StdRng::seed_from_u64(7));
+        for index in 0..10 {
+            simulator.push(voice_frame(index), now);
+        }
+        simulator.push(peer_left(), now);
+        // Another room isn't held back by them
+        simulator.push(peer_joined("scene"), now);
+
+        let early = simulator.drain_due(now + Duration::from_millis(10));
+        assert_eq!(early.len(), 1);
+        assert_eq!(early[0].room_id, "scene");
+
+        let rest = simulator.drain_all();
+        assert_eq!(rest.len(), 11);
+        assert!(matches!(rest[10].message, MessageType::PeerLeft));
+    }
+}
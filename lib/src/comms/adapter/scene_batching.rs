@@ -0,0 +1,374 @@
+//! Per-tick batching and compression of scene messages (`rfc4::Scene`).
+//!
+//! Scenes with heavy multiplayer sync send dozens of small messages per frame, each one
+//! becoming its own rfc4 packet and hitting the rooms' rate limits. When enabled, the
+//! messages of a tick are grouped by scene and recipient and packed into envelopes:
+//!
+//!   `[BATCH_MARKER][flags][body]`, body = (`varint len` + message bytes)*
+//!
+//! deflated (`FLAG_DEFLATE`) when the body reaches the compression threshold. SDK payloads
+//! always start with a `COMMS_MSG_TYPE_*` byte (1 or 2), so the marker can't be confused with
+//! a plain message.
+//!
+//! Negotiation: every client advertises `"sceneBatching": SCENE_BATCHING_VERSION` in its
+//! LiveKit participant metadata and always decodes envelopes. Envelopes are only sent to a
+//! peer that advertised it; broadcasts (whose recipients can't all be confirmed, e.g. a
+//! participant still joining) and anything else are sent one packet per message like before.
+//!
+//! Enabled with `--scene-batching` / `--scene-compress <bytes>` or
+//! `CommunicationManager.set_scene_message_batching`.
+
+use std::{
+    io::{Read, Write},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+
+use crate::dcl::scene_apis::NetworkMessageRecipient;
+
+/// Participant metadata key advertising that envelopes are understood
+pub const SCENE_BATCHING_METADATA_KEY: &str = "sceneBatching";
+pub const SCENE_BATCHING_VERSION: u64 = 1;
+
+/// Compression threshold used by `--scene-batching` alone
+pub const DEFAULT_COMPRESSION_THRESHOLD: usize = 512;
+
+const BATCH_MARKER: u8 = 0xB7;
+const FLAG_DEFLATE: u8 = 0x01;
+
+/// Body bytes of one envelope before compression, keeps a packet under the LiveKit data limit
+const MAX_BATCH_BYTES: usize = 12 * 1024;
+/// Bound of an inflated body, larger envelopes are rejected
+const MAX_INFLATED_BYTES: usize = 256 * 1024;
+
+static SCENE_MESSAGES: AtomicU64 = AtomicU64::new(0);
+static SCENE_PACKETS: AtomicU64 = AtomicU64::new(0);
+static SCENE_PAYLOAD_BYTES: AtomicU64 = AtomicU64::new(0);
+static SCENE_WIRE_BYTES: AtomicU64 = AtomicU64::new(0);
+static SCENE_COMPRESSED_PACKETS: AtomicU64 = AtomicU64::new(0);
+
+/// Scene message throughput since the last drain, reported with the performance metrics.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SceneBatchingMetricsSnapshot {
+    /// Messages sent by the scenes
+    pub messages: u64,
+    /// rfc4 packets they were sent in
+    pub packets: u64,
+    /// Bytes of the messages as the scenes sent them
+    pub payload_bytes: u64,
+    /// Bytes of the packets' scene data (envelopes included)
+    pub wire_bytes: u64,
+    pub compressed_packets: u64,
+}
+
+pub fn drain_scene_batching_metrics() -> SceneBatchingMetricsSnapshot {
+    SceneBatchingMetricsSnapshot {
+        messages: SCENE_MESSAGES.swap(0, Ordering::Relaxed),
+        packets: SCENE_PACKETS.swap(0, Ordering::Relaxed),
+        payload_bytes: SCENE_PAYLOAD_BYTES.swap(0, Ordering::Relaxed),
+        wire_bytes: SCENE_WIRE_BYTES.swap(0, Ordering::Relaxed),
+        compressed_packets: SCENE_COMPRESSED_PACKETS.swap(0, Ordering::Relaxed),
+    }
+}
+
+/// Accounts a message sent as its own packet (batching disabled or not negotiated)
+pub fn record_unbatched_scene_message(len: usize) {
+    SCENE_MESSAGES.fetch_add(1, Ordering::Relaxed);
+    SCENE_PACKETS.fetch_add(1, Ordering::Relaxed);
+    SCENE_PAYLOAD_BYTES.fetch_add(len as u64, Ordering::Relaxed);
+    SCENE_WIRE_BYTES.fetch_add(len as u64, Ordering::Relaxed);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SceneBatchingConfig {
+    /// Envelope bodies of at least this many bytes are deflated, `None` never compresses
+    pub compression_threshold: Option<usize>,
+}
+
+/// One packet's worth of scene data, ready for `rfc4::Scene`
+#[derive(Debug)]
+pub struct OutgoingScenePayload {
+    pub scene_id: String,
+    pub recipient: NetworkMessageRecipient,
+    pub data: Vec<u8>,
+}
+
+struct PendingGroup {
+    scene_id: String,
+    recipient: NetworkMessageRecipient,
+    messages: Vec<Vec<u8>>,
+}
+
+/// Collects the scene messages of a tick until `flush`
+pub struct SceneMessageBatcher {
+    config: SceneBatchingConfig,
+    groups: Vec<PendingGroup>,
+}
+
+impl SceneMessageBatcher {
+    pub fn new(config: SceneBatchingConfig) -> Self {
+        Self {
+            config,
+            groups: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, scene_id: String, data: Vec<u8>, recipient: NetworkMessageRecipient) {
+        let existing = self.groups.iter_mut().find(|group| {
+            group.scene_id == scene_id && same_recipient(&group.recipient, &recipient)
+        });
+        match existing {
+            Some(group) => group.messages.push(data),
+            None => self.groups.push(PendingGroup {
+                scene_id,
+                recipient,
+                messages: vec![data],
+            }),
+        }
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.groups.iter().map(|group| group.messages.len()).sum()
+    }
+
+    pub fn clear(&mut self) {
+        self.groups.clear();
+    }
+
+    /// Drains the tick's messages in the order their groups were first seen. Groups whose
+    /// recipients don't understand envelopes (`supports_batching` false) are sent one
+    /// payload per message. Records the metrics of everything returned.
+    pub fn flush(
+        &mut self,
+        supports_batching: impl Fn(&NetworkMessageRecipient) -> bool,
+    ) -> Vec<OutgoingScenePayload> {
+        let mut result = Vec::new();
+        for group in self.groups.drain(..) {
+            if !supports_batching(&group.recipient) {
+                for data in group.messages {
+                    record_unbatched_scene_message(data.len());
+                    result.push(OutgoingScenePayload {
+                        scene_id: group.scene_id.clone(),
+                        recipient: group.recipient,
+                        data,
+                    });
+                }
+                continue;
+            }
+
+            for chunk in split_batches(&group.messages) {
+                let payload_bytes: usize = chunk.iter().map(|data| data.len()).sum();
+                SCENE_MESSAGES.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+                SCENE_PAYLOAD_BYTES.fetch_add(payload_bytes as u64, Ordering::Relaxed);
+
+                // A lone message below the threshold gains nothing from an envelope
+                let data = if chunk.len() == 1
+                    && self
+                        .config
+                        .compression_threshold
+                        .is_none_or(|threshold| payload_bytes < threshold)
+                {
+                    chunk[0].clone()
+                } else {
+                    encode_batch(chunk, self.config.compression_threshold)
+                };
+                if data.first() == Some(&BATCH_MARKER) && data[1] & FLAG_DEFLATE != 0 {
+                    SCENE_COMPRESSED_PACKETS.fetch_add(1, Ordering::Relaxed);
+                }
+                SCENE_PACKETS.fetch_add(1, Ordering::Relaxed);
+                SCENE_WIRE_BYTES.fetch_add(data.len() as u64, Ordering::Relaxed);
+
+                result.push(OutgoingScenePayload {
+                    scene_id: group.scene_id.clone(),
+                    recipient: group.recipient,
+                    data,
+                });
+            }
+        }
+        result
+    }
+}
+
+fn same_recipient(a: &NetworkMessageRecipient, b: &NetworkMessageRecipient) -> bool {
+    match (a, b) {
+        (NetworkMessageRecipient::All, NetworkMessageRecipient::All) => true,
+        (NetworkMessageRecipient::AuthServer, NetworkMessageRecipient::AuthServer) => true,
+        (NetworkMessageRecipient::Peer(a), NetworkMessageRecipient::Peer(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Consecutive runs of messages whose bodies fit in `MAX_BATCH_BYTES` (an oversized message
+/// goes alone)
+fn split_batches(messages: &[Vec<u8>]) -> Vec<&[Vec<u8>]> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut size = 0;
+    for (index, data) in messages.iter().enumerate() {
+        let entry_size = varint_len(data.len()) + data.len();
+        if index > start && size + entry_size > MAX_BATCH_BYTES {
+            chunks.push(&messages[start..index]);
+            start = index;
+            size = 0;
+        }
+        size += entry_size;
+    }
+    if start < messages.len() {
+        chunks.push(&messages[start..]);
+    }
+    chunks
+}
+
+pub fn encode_batch(messages: &[Vec<u8>], compression_threshold: Option<usize>) -> Vec<u8> {
+    let mut body = Vec::with_capacity(messages.iter().map(|data| data.len() + 2).sum());
+    for data in messages {
+        write_varint(&mut body, data.len());
+        body.extend_from_slice(data);
+    }
+
+    let mut flags = 0;
+    if compression_threshold.is_some_and(|threshold| body.len() >= threshold) {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::fast());
+        if let Ok(compressed) = encoder.write_all(&body).and_then(|_| encoder.finish()) {
+            // Already compressed payloads (images, encrypted data) can grow
+            if compressed.len() < body.len() {
+                body = compressed;
+                flags |= FLAG_DEFLATE;
+            }
+        }
+    }
+
+    let mut data = Vec::with_capacity(body.len() + 2);
+    data.push(BATCH_MARKER);
+    data.push(flags);
+    data.extend(body);
+    data
+}
+
+/// Splits a received scene payload into the messages it carries: an envelope gives its
+/// entries, anything else is a single plain message.
+pub fn decode_scene_payload(data: Vec<u8>) -> Result<Vec<Vec<u8>>, String> {
+    if data.first() != Some(&BATCH_MARKER) {
+        return Ok(vec![data]);
+    }
+    let Some(&flags) = data.get(1) else {
+        return Err("truncated batch header".to_string());
+    };
+    if flags & !FLAG_DEFLATE != 0 {
+        return Err(format!("unknown batch flags {flags:#x}"));
+    }
+
+    let inflated;
+    let mut body = &data[2..];
+    if flags & FLAG_DEFLATE != 0 {
+        let mut buffer = Vec::new();
+        DeflateDecoder::new(body)
+            .take(MAX_INFLATED_BYTES as u64 + 1)
+            .read_to_end(&mut buffer)
+            .map_err(|e| format!("invalid deflate body: {e}"))?;
+        if buffer.len() > MAX_INFLATED_BYTES {
+            return Err("inflated batch too large".to_string());
+        }
+        inflated = buffer;
+        body = &inflated;
+    }
+
+    let mut messages = Vec::new();
+    while !body.is_empty() {
+        let (len, read) = read_varint(body).ok_or("invalid entry length")?;
+        body = &body[read..];
+        if len > body.len() {
+            return Err("truncated batch entry".to_string());
+        }
+        messages.push(body[..len].to_vec());
+        body = &body[len..];
+    }
+    Ok(messages)
+}
+
+fn varint_len(mut value: usize) -> usize {
+    let mut len = 1;
+    while value >= 0x80 {
+        value >>= 7;
+        len += 1;
+    }
+    len
+}
+
+fn write_varint(buffer: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        buffer.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+/// Returns the value and the bytes it took
+fn read_varint(data: &[u8]) -> Option<(usize, usize)> {
+    let mut value = 0usize;
+    for (index, byte) in data.iter().enumerate().take(4) {
+        value |= ((byte & 0x7f) as usize) << (7 * index);
+        if byte & 0x80 == 0 {
+            return Some((value, index + 1));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers_core::types::H160;
+
+    use super::*;
+
+    #[test]
+    fn batch_roundtrip() {
+        let messages = vec![vec![2, 1, 2, 3], vec![1; 300], vec![2]];
+        for threshold in [None, Some(0)] {
+            let data = encode_batch(&messages, threshold);
+            assert_eq!(data[0], BATCH_MARKER);
+            assert_eq!(data[1] & FLAG_DEFLATE != 0, threshold.is_some());
+            assert_eq!(decode_scene_payload(data).unwrap(), messages);
+        }
+
+        // Plain messages pass through, malformed envelopes are rejected
+        assert_eq!(decode_scene_payload(vec![2, 9]).unwrap(), vec![vec![2, 9]]);
+        assert!(decode_scene_payload(vec![BATCH_MARKER, 0, 5, 1]).is_err());
+    }
+
+    #[test]
+    fn flush_respects_negotiation() {
+        let peer = H160::from_low_u64_be(7);
+        let mut batcher = SceneMessageBatcher::new(SceneBatchingConfig {
+            compression_threshold: None,
+        });
+        batcher.push("a".into(), vec![2, 1], NetworkMessageRecipient::All);
+        batcher.push("a".into(), vec![2, 2], NetworkMessageRecipient::Peer(peer));
+        batcher.push("a".into(), vec![2, 3], NetworkMessageRecipient::All);
+        assert_eq!(batcher.pending_count(), 3);
+
+        let payloads = batcher.flush(|recipient| matches!(recipient, NetworkMessageRecipient::All));
+        assert_eq!(payloads.len(), 2);
+        assert_eq!(
+            decode_scene_payload(payloads[0].data.clone()).unwrap(),
+            vec![vec![2, 1], vec![2, 3]]
+        );
+        assert_eq!(payloads[1].data, vec![2, 2]);
+        assert_eq!(batcher.pending_count(), 0);
+    }
+
+    #[test]
+    fn oversized_groups_are_split() {
+        let messages = vec![vec![2; MAX_BATCH_BYTES / 2]; 3];
+        let chunks = split_batches(&messages);
+        assert_eq!(
+            chunks.iter().map(|chunk| chunk.len()).collect::<Vec<_>>(),
+            vec![1, 1, 1]
+        );
+        let messages = vec![vec![2; 100]; 200];
+        let chunks = split_batches(&messages);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks.iter().map(|chunk| chunk.len()).sum::<usize>(), 200);
+    }
+}
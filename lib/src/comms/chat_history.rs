@@ -0,0 +1,396 @@
+//! Local chat history, so conversations survive restarts and realm changes.
+//!
+//! One JSON-lines file per account (`user://chat_history/<address>.jsonl`). New entries are
+//! appended in batches by `flush`; the file is rewritten with only the retained entries when
+//! it grows past twice the retention, or when the retention changes.
+//!
+//! Entries are keyed by realm, the scene room the chat went through and the sender's address.
+//! The blacklist is applied when querying, so blocking someone also hides what they said
+//! before (and unblocking shows it again).
+
+use std::{
+    collections::VecDeque,
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use ethers_core::types::H160;
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_MAX_ENTRIES: usize = 5000;
+pub const DEFAULT_MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Prefixes of the chats carrying commands instead of text (emotes, pings), see
+/// `chat_handler.gd`. They aren't kept.
+const COMMAND_PREFIXES: [char; 3] = ['␐', '␑', '␆'];
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChatHistoryEntry {
+    pub id: u64,
+    pub realm: String,
+    /// Scene entity id of the room the chat went through, empty for the main room
+    pub scene_id: String,
+    /// Sender
+    pub address: H160,
+    /// Sender's OLE timestamp (`rfc4::Chat::timestamp`)
+    pub timestamp: f64,
+    /// Unix seconds when it was received or sent, used by the retention
+    pub received_at: u64,
+    pub message: String,
+    /// Sent by the local player
+    pub own: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChatRetention {
+    pub max_entries: usize,
+    pub max_age: Duration,
+}
+
+impl Default for ChatRetention {
+    fn default() -> Self {
+        Self {
+            max_entries: DEFAULT_MAX_ENTRIES,
+            max_age: DEFAULT_MAX_AGE,
+        }
+    }
+}
+
+/// Query filter, `None` fields match anything
+#[derive(Debug, Default)]
+pub struct ChatHistoryFilter {
+    pub realm: Option<String>,
+    pub scene_id: Option<String>,
+    pub address: Option<H160>,
+    /// Case-insensitive substring of the message
+    pub text: Option<String>,
+}
+
+impl ChatHistoryFilter {
+    fn matches(&self, entry: &ChatHistoryEntry) -> bool {
+        self.realm
+            .as_ref()
+            .is_none_or(|realm| *realm == entry.realm)
+            && self
+                .scene_id
+                .as_ref()
+                .is_none_or(|scene_id| *scene_id == entry.scene_id)
+            && self.address.is_none_or(|address| address == entry.address)
+            && self
+                .text
+                .as_ref()
+                .is_none_or(|text| entry.message.to_lowercase().contains(&text.to_lowercase()))
+    }
+}
+
+pub struct ChatHistory {
+    owner: H160,
+    path: Option<PathBuf>,
+    entries: VecDeque<ChatHistoryEntry>,
+    /// Entries at the back not written yet
+    unsaved: usize,
+    /// Lines in the file, including the ones the retention already dropped
+    file_lines: usize,
+    next_id: u64,
+    retention: ChatRetention,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl ChatHistory {
+    /// History of the account `owner`, loaded from `path` (kept in memory only if `None`)
+    pub fn new(owner: H160, path: Option<PathBuf>, retention: ChatRetention) -> Self {
+        let mut history = Self {
+            owner,
+            path,
+            entries: VecDeque::new(),
+            unsaved: 0,
+            file_lines: 0,
+            next_id: 1,
+            retention,
+        };
+        if let Err(e) = history.load() {
+            tracing::warn!("chat history: failed to load {:?}: {e}", history.path);
+        }
+        history
+    }
+
+    pub fn owner(&self) -> H160 {
+        self.owner
+    }
+
+    pub fn entry_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn retention(&self) -> ChatRetention {
+        self.retention
+    }
+
+    fn load(&mut self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let file = match fs::File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+
+        let mut malformed = 0;
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            self.file_lines += 1;
+            match serde_json::from_str::<ChatHistoryEntry>(&line) {
+                Ok(entry) => {
+                    self.next_id = self.next_id.max(entry.id + 1);
+                    self.entries.push_back(entry);
+                }
+                Err(_) => malformed += 1,
+            }
+        }
+
+        let dropped = self.enforce_retention(now_secs());
+        if dropped || malformed > 0 {
+            self.rewrite()?;
+        }
+        Ok(())
+    }
+
+    pub fn record(
+        &mut self,
+        realm: String,
+        scene_id: String,
+        address: H160,
+        timestamp: f64,
+        message: String,
+        own: bool,
+    ) -> Option<u64> {
+        if message.starts_with(COMMAND_PREFIXES) {
+            return None;
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.entries.push_back(ChatHistoryEntry {
+            id,
+            realm,
+            scene_id,
+            address,
+            timestamp,
+            received_at: now_secs(),
+            message,
+            own,
+        });
+        self.unsaved += 1;
+        self.enforce_retention(now_secs());
+        Some(id)
+    }
+
+    /// Drops the entries over the count or older than the max age, returns true if any
+    fn enforce_retention(&mut self, now: u64) -> bool {
+        let min_received_at = now.saturating_sub(self.retention.max_age.as_secs());
+        let before = self.entries.len();
+        while self.entries.len() > self.retention.max_entries
+            || self
+                .entries
+                .front()
+                .is_some_and(|entry| entry.received_at < min_received_at)
+        {
+            self.entries.pop_front();
+        }
+        self.unsaved = self.unsaved.min(self.entries.len());
+        self.entries.len() != before
+    }
+
+    pub fn set_retention(&mut self, retention: ChatRetention) -> io::Result<()> {
+        self.retention = retention;
+        self.enforce_retention(now_secs());
+        self.rewrite()
+    }
+
+    pub fn clear(&mut self) -> io::Result<()> {
+        self.entries.clear();
+        self.unsaved = 0;
+        self.file_lines = 0;
+        match &self.path {
+            Some(path) => match fs::remove_file(path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            },
+            None => Ok(()),
+        }
+    }
+
+    /// Appends the unsaved entries to the file (or compacts it when it grew too much)
+    pub fn flush(&mut self) -> io::Result<()> {
+        if self.unsaved == 0 {
+            return Ok(());
+        }
+        let Some(path) = self.path.clone() else {
+            self.unsaved = 0;
+            return Ok(());
+        };
+        if self.file_lines + self.unsaved > self.retention.max_entries.saturating_mul(2) {
+            return self.rewrite();
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        let mut buffer = String::new();
+        for entry in self.entries.range(self.entries.len() - self.unsaved..) {
+            buffer.push_str(&serde_json::to_string(entry)?);
+            buffer.push('\n');
+        }
+        file.write_all(buffer.as_bytes())?;
+        self.file_lines += self.unsaved;
+        self.unsaved = 0;
+        Ok(())
+    }
+
+    fn rewrite(&mut self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            self.unsaved = 0;
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut buffer = String::new();
+        for entry in &self.entries {
+            buffer.push_str(&serde_json::to_string(entry)?);
+            buffer.push('\n');
+        }
+        // Written aside and renamed so a crash mid-write keeps the previous file
+        let tmp_path = path.with_extension("jsonl.tmp");
+        fs::write(&tmp_path, buffer)?;
+        fs::rename(&tmp_path, path)?;
+        self.file_lines = self.entries.len();
+        self.unsaved = 0;
+        Ok(())
+    }
+
+    /// Up to `limit` entries matching `filter` with an id below `before_id` (`None` for the
+    /// newest), skipping the senders `hidden` returns true for. Oldest first, so the first
+    /// entry's id pages further back.
+    pub fn query(
+        &self,
+        filter: &ChatHistoryFilter,
+        before_id: Option<u64>,
+        limit: usize,
+        hidden: impl Fn(&H160) -> bool,
+    ) -> Vec<&ChatHistoryEntry> {
+        let mut result: Vec<_> = self
+            .entries
+            .iter()
+            .rev()
+            .filter(|entry| before_id.is_none_or(|before_id| entry.id < before_id))
+            .filter(|entry| !hidden(&entry.address) && filter.matches(entry))
+            .take(limit)
+            .collect();
+        result.reverse();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "dcl_chat_history_{name}_{}.jsonl",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn record(history: &mut ChatHistory, address: u64, message: &str) -> Option<u64> {
+        history.record(
+            "main".to_string(),
+            "scene-a".to_string(),
+            H160::from_low_u64_be(address),
+            0.0,
+            message.to_string(),
+            false,
+        )
+    }
+
+    #[test]
+    fn persists_and_pages() {
+        let path = temp_path("persist");
+        let owner = H160::from_low_u64_be(1);
+        let mut history = ChatHistory::new(owner, Some(path.clone()), ChatRetention::default());
+        for i in 0..10 {
+            record(&mut history, 100 + i % 2, &format!("hello {i}"));
+        }
+        assert_eq!(record(&mut history, 100, "␐wave 1234"), None);
+        history.flush().unwrap();
+
+        let history = ChatHistory::new(owner, Some(path.clone()), ChatRetention::default());
+        assert_eq!(history.entry_count(), 10);
+
+        let page = history.query(&ChatHistoryFilter::default(), None, 4, |_| false);
+        assert_eq!(
+            page.iter().map(|e| e.message.as_str()).collect::<Vec<_>>(),
+            vec!["hello 6", "hello 7", "hello 8", "hello 9"]
+        );
+        let older = history.query(&ChatHistoryFilter::default(), Some(page[0].id), 4, |_| {
+            false
+        });
+        assert_eq!(older.last().unwrap().message, "hello 5");
+
+        // Search and the blacklist
+        let filter = ChatHistoryFilter {
+            text: Some("HELLO 3".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(history.query(&filter, None, 10, |_| false).len(), 1);
+        let hidden = H160::from_low_u64_be(101);
+        let visible = history.query(&ChatHistoryFilter::default(), None, 10, |a| *a == hidden);
+        assert_eq!(visible.len(), 5);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn retention_drops_and_compacts() {
+        let path = temp_path("retention");
+        let owner = H160::from_low_u64_be(1);
+        let retention = ChatRetention {
+            max_entries: 3,
+            max_age: DEFAULT_MAX_AGE,
+        };
+        let mut history = ChatHistory::new(owner, Some(path.clone()), retention);
+        for i in 0..5 {
+            record(&mut history, 100, &format!("m{i}"));
+            history.flush().unwrap();
+        }
+        assert_eq!(history.entry_count(), 3);
+        assert!(history.file_lines <= 6);
+
+        let mut history = ChatHistory::new(owner, Some(path.clone()), retention);
+        assert_eq!(history.entry_count(), 3);
+        assert!(!history.enforce_retention(now_secs()));
+        assert!(history.enforce_retention(now_secs() + DEFAULT_MAX_AGE.as_secs() + 1));
+        assert_eq!(history.entry_count(), 0);
+
+        history.clear().unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -1,22 +1,32 @@
 use ethers_core::types::H160;
 use godot::prelude::*;
 use http::Uri;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 #[cfg(feature = "use_livekit")]
 use crate::comms::consts::DISABLE_ARCHIPELAGO;
-use crate::{auth::wallet, scene_runner::tokio_runtime::TokioRuntime};
+use crate::{
+    auth::wallet::{self, AsH160},
+    scene_runner::tokio_runtime::TokioRuntime,
+};
 use crate::{
     comms::{
         adapter::{
-            message_processor::MessageProcessor, movement_compressed::MoveKind,
+            message_processor::{MessageProcessor, PeerVoiceSettings, VoiceScope},
+            movement_compressed::MoveKind,
             ws_room::WebSocketRoom,
         },
+        chat_history::{ChatHistory, ChatHistoryEntry, ChatHistoryFilter, ChatRetention},
         consts::DEFAULT_PROTOCOL_VERSION,
+        mock_server::{MockCommsScript, MockCommsServer},
         signed_login::SignedLoginMeta,
     },
-    dcl::{components::proto_components::kernel::comms::rfc4, scene_apis::NetworkMessageRecipient},
+    dcl::{
+        components::proto_components::kernel::comms::rfc4,
+        scene_apis::{NetworkMessageRecipient, SceneVoiceState},
+    },
     godot_classes::dcl_global::DclGlobal,
 };
 use serde::{Deserialize, Serialize};
@@ -28,6 +38,10 @@ use super::{
 };
 
 use crate::comms::adapter::movement_compressed::{Movement, MovementCompressed, Temporal};
+use crate::comms::adapter::network_simulator::NetworkSimulatorConfig;
+use crate::comms::adapter::scene_batching::{
+    SceneBatchingConfig, SceneMessageBatcher, DEFAULT_COMPRESSION_THRESHOLD,
+};
 
 #[derive(Serialize, Deserialize)]
 pub struct GatekeeperResponse {
@@ -98,6 +112,76 @@ fn should_start_scene_room_reconnect(
     !matches!(connect_in_flight, Some(started) if now.duration_since(started) < connect_timeout)
 }
 
+/// Minimum time between two LookAtPosition packets
+const LOOK_AT_SEND_INTERVAL: Duration = Duration::from_millis(200);
+/// Minimum time between two Reaction/ChatReaction packets
+const REACTION_SEND_INTERVAL: Duration = Duration::from_millis(500);
+/// How often new chat history entries are written to disk
+const CHAT_HISTORY_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+/// Most entries a chat history query returns
+const MAX_CHAT_HISTORY_PAGE: usize = 200;
+
+/// Drops sends that come faster than `min_interval`.
+struct SendRateLimiter {
+    min_interval: Duration,
+    last_sent: Option<Instant>,
+}
+
+impl SendRateLimiter {
+    const fn new(min_interval: Duration) -> Self {
+        Self {
+            min_interval,
+            last_sent: None,
+        }
+    }
+
+    fn try_acquire(&mut self, now: Instant) -> bool {
+        if self
+            .last_sent
+            .is_some_and(|last_sent| now.duration_since(last_sent) < self.min_interval)
+        {
+            return false;
+        }
+        self.last_sent = Some(now);
+        true
+    }
+}
+
+/// Mono i16 samples of a recorded stereo frame, None when it's silence
+fn voice_frame_samples(frame: &PackedVector2Array) -> Option<Vec<i16>> {
+    let mut max_value = 0;
+    let samples = frame
+        .as_slice()
+        .iter()
+        .map(|v| {
+            let value = ((0.5 * (v.x + v.y)) * i16::MAX as f32) as i16;
+
+            max_value = std::cmp::max(max_value, value);
+            value
+        })
+        .collect::<Vec<i16>>();
+
+    (max_value > 0).then_some(samples)
+}
+
+/// Voice chat settings a scene asked for through `~system/VoiceChat`
+#[derive(Debug, Clone)]
+struct SceneVoicePolicy {
+    scene_channel: bool,
+    microphone_allowed: bool,
+    muted_players: HashSet<H160>,
+}
+
+impl Default for SceneVoicePolicy {
+    fn default() -> Self {
+        Self {
+            scene_channel: false,
+            microphone_allowed: true,
+            muted_players: HashSet::new(),
+        }
+    }
+}
+
 #[allow(clippy::large_enum_variant)]
 enum MainRoom {
     WebSocket(WebSocketRoom),
@@ -211,6 +295,11 @@ pub struct CommunicationManager {
     last_position_broadcast_index: u64,
     last_emote_incremental_id: u32,
     is_emoting: bool,
+    look_at_rate_limiter: SendRateLimiter,
+    reaction_rate_limiter: SendRateLimiter,
+    chat_reaction_rate_limiter: SendRateLimiter,
+    /// Loopback server started by `--mock-comms`, every adapter is redirected to it
+    mock_comms_server: Option<MockCommsServer>,
     voice_chat_enabled: bool,
     start_time: Instant,
     last_profile_version_broadcast: Instant,
@@ -233,6 +322,23 @@ pub struct CommunicationManager {
     multiplayer_debug: bool,
     multiplayer_debug_last_update: Instant,
 
+    /// Simulated network conditions of the incoming messages (`--network-sim`), kept
+    /// here so they survive the message processor being recreated
+    network_simulation: Option<NetworkSimulatorConfig>,
+
+    // Per-tick batching of the scene messages (`--scene-batching`), None sends them one by one
+    scene_message_batcher: Option<SceneMessageBatcher>,
+
+    // Local chat history of the current account, opened on the first chat
+    chat_history: Option<ChatHistory>,
+    chat_history_retention: ChatRetention,
+    chat_history_flushed_at: Instant,
+
+    /// Scene-scoped voice requested by the scenes, keyed by scene entity id
+    scene_voice_policies: HashMap<String, SceneVoicePolicy>,
+    /// Scene whose voice channel is in use (the player is inside it)
+    active_scene_voice: Option<String>,
+
     // Shared message processor for all adapters
     message_processor: Option<MessageProcessor>,
 
@@ -344,6 +450,10 @@ impl INode for CommunicationManager {
             last_position_broadcast_index: 0,
             last_emote_incremental_id: 0,
             is_emoting: false,
+            look_at_rate_limiter: SendRateLimiter::new(LOOK_AT_SEND_INTERVAL),
+            reaction_rate_limiter: SendRateLimiter::new(REACTION_SEND_INTERVAL),
+            chat_reaction_rate_limiter: SendRateLimiter::new(REACTION_SEND_INTERVAL),
+            mock_comms_server: None,
             voice_chat_enabled: false,
             start_time: Instant::now(),
             last_profile_version_broadcast: Instant::now(),
@@ -354,6 +464,13 @@ impl INode for CommunicationManager {
             saved_adapter_for_resume: GString::default(),
             multiplayer_debug: false,
             multiplayer_debug_last_update: Instant::now(),
+            network_simulation: None,
+            scene_message_batcher: None,
+            chat_history: None,
+            chat_history_retention: ChatRetention::default(),
+            chat_history_flushed_at: Instant::now(),
+            scene_voice_policies: HashMap::new(),
+            active_scene_voice: None,
             message_processor: None,
             main_room: None,
             #[cfg(feature = "use_livekit")]
@@ -405,6 +522,10 @@ impl INode for CommunicationManager {
         self.base_mut().call_deferred("init_rs", &[]);
     }
 
+    fn exit_tree(&mut self) {
+        self.flush_chat_history();
+    }
+
     fn process(&mut self, _dt: f64) {
         // Handle scene access check results
         while let Ok((scene_id, allowed, error_message)) = self.scene_access_receiver.try_recv() {
@@ -472,7 +593,13 @@ impl INode for CommunicationManager {
                 let chats = adapter.consume_chats();
 
                 if !chats.is_empty() {
-                    let chats_variant_array = get_chat_array(chats);
+                    // The adapter is the main room
+                    self.record_chat_history(
+                        chats.iter().map(|(address, chat)| (*address, "", chat)),
+                        false,
+                    );
+                    let chats_variant_array =
+                        get_chat_array(chats.iter().map(|(address, chat)| (address, chat)));
                     self.base_mut()
                         .emit_signal("chat_message", &[chats_variant_array.to_variant()]);
                 }
@@ -493,6 +620,9 @@ impl INode for CommunicationManager {
         // Poll the shared message processor (if active)
         let mut processor_reset = false;
         let mut chat_signals = Vec::new();
+        let mut received_chats = Vec::new();
+        let mut reactions = Vec::new();
+        let mut chat_reactions = Vec::new();
         let mut outgoing_messages = Vec::new();
         let mut disconnect_info: Option<(
             crate::comms::adapter::message_processor::DisconnectReason,
@@ -504,8 +634,13 @@ impl INode for CommunicationManager {
             let chats = processor.consume_chats();
 
             if !chats.is_empty() {
-                chat_signals.push(get_chat_array(chats));
+                chat_signals.push(get_chat_array(
+                    chats.iter().map(|(address, _, chat)| (address, chat)),
+                ));
+                received_chats = chats;
             }
+            reactions = processor.consume_reactions();
+            chat_reactions = processor.consume_chat_reactions();
 
             // Handle outgoing messages from MessageProcessor (like ProfileResponse)
             outgoing_messages = processor.consume_outgoing_messages();
@@ -533,6 +668,7 @@ impl INode for CommunicationManager {
                     self.scene_room_connect_in_flight = None;
                 }
                 self.current_scene_id = None;
+                self.refresh_scene_voice();
                 self.base_mut()
                     .emit_signal("disconnected", &[2i32.to_variant()]);
             }
@@ -544,10 +680,36 @@ impl INode for CommunicationManager {
         }
 
         // Handle chat signals after borrowing is done
+        if !received_chats.is_empty() {
+            self.record_chat_history(
+                received_chats
+                    .iter()
+                    .map(|(address, room_id, chat)| (*address, room_id.as_str(), chat)),
+                false,
+            );
+        }
         for chats_variant_array in chat_signals {
             self.base_mut()
                 .emit_signal("chat_message", &[chats_variant_array.to_variant()]);
         }
+        for (address, reaction) in reactions {
+            let address = format!("{:#x}", address);
+            self.base_mut().emit_signal(
+                "reaction_received",
+                &[address.to_variant(), reaction.emoji.to_variant()],
+            );
+        }
+        for (address, chat_reaction) in chat_reactions {
+            let address = format!("{:#x}", address);
+            self.base_mut().emit_signal(
+                "chat_reaction_received",
+                &[
+                    address.to_variant(),
+                    chat_reaction.message_id.to_variant(),
+                    chat_reaction.emoji.to_variant(),
+                ],
+            );
+        }
 
         // Handle outgoing messages after borrowing is done
         for outgoing in outgoing_messages {
@@ -704,6 +866,14 @@ impl INode for CommunicationManager {
         #[cfg(feature = "use_pulse")]
         self.poll_pulse_room();
 
+        // Send the scene messages batched during this tick
+        #[cfg(feature = "use_livekit")]
+        self.flush_scene_messages();
+
+        if self.chat_history_flushed_at.elapsed() >= CHAT_HISTORY_FLUSH_INTERVAL {
+            self.flush_chat_history();
+        }
+
         // Periodic ProfileVersion broadcasting (every 10 seconds)
         if self.last_profile_version_broadcast.elapsed().as_secs() >= 10 {
             self.broadcast_profile_version();
@@ -728,6 +898,162 @@ impl INode for CommunicationManager {
 }
 
 impl CommunicationManager {
+    /// The chat history of the signed-in account, opened (or switched) on first use
+    fn chat_history_mut(&mut self) -> Option<&mut ChatHistory> {
+        let address = DclGlobal::singleton()
+            .bind()
+            .get_player_identity()
+            .bind()
+            .try_get_address()?;
+        if self
+            .chat_history
+            .as_ref()
+            .is_none_or(|history| history.owner() != address)
+        {
+            self.flush_chat_history();
+            let folder = godot::classes::ProjectSettings::singleton()
+                .globalize_path("user://chat_history")
+                .to_string();
+            let path = std::path::Path::new(&folder).join(format!("{address:#x}.jsonl"));
+            self.chat_history = Some(ChatHistory::new(
+                address,
+                Some(path),
+                self.chat_history_retention,
+            ));
+        }
+        self.chat_history.as_mut()
+    }
+
+    /// Stores `(sender, room it came through, chat)` entries, tagged with the scene of the
+    /// room (empty for the main room)
+    fn record_chat_history<'a>(
+        &mut self,
+        chats: impl IntoIterator<Item = (H160, &'a str, &'a rfc4::Chat)>,
+        own: bool,
+    ) {
+        let realm = DclGlobal::singleton()
+            .bind()
+            .get_realm()
+            .bind()
+            .get_realm_name()
+            .to_string();
+        let Some(history) = self.chat_history_mut() else {
+            return;
+        };
+        for (address, room_id, chat) in chats {
+            let scene_id = room_id.strip_prefix("scene-").unwrap_or_default();
+            history.record(
+                realm.clone(),
+                scene_id.to_string(),
+                address,
+                chat.timestamp,
+                chat.message.clone(),
+                own,
+            );
+        }
+    }
+
+    fn flush_chat_history(&mut self) {
+        self.chat_history_flushed_at = Instant::now();
+        if let Some(history) = &mut self.chat_history {
+            if let Err(e) = history.flush() {
+                tracing::warn!("chat history: failed to save: {e}");
+            }
+        }
+    }
+
+    /// Runs `filter` against the history, hiding blocked and muted senders
+    fn query_chat_history(
+        &mut self,
+        filter: ChatHistoryFilter,
+        before_id: i64,
+        limit: i32,
+    ) -> VarArray {
+        let social_blacklist = DclGlobal::singleton().bind().social_blacklist.clone();
+        let social_blacklist = social_blacklist.bind();
+        let Some(history) = self.chat_history_mut() else {
+            return VarArray::new();
+        };
+        let owner = history.owner();
+        let before_id = u64::try_from(before_id).ok().filter(|id| *id > 0);
+        let limit = usize::try_from(limit)
+            .unwrap_or(0)
+            .min(MAX_CHAT_HISTORY_PAGE);
+
+        let mut result = VarArray::new();
+        for entry in history.query(&filter, before_id, limit, |address| {
+            *address != owner
+                && (social_blacklist.is_blocked_h160(address)
+                    || social_blacklist.is_muted_h160(address))
+        }) {
+            result.push(&chat_history_entry_dict(entry).to_variant());
+        }
+        result
+    }
+
+    /// With `--mock-comms`, replaces the realm adapter with the loopback server
+    /// (started on first use). Offline stays offline.
+    fn mock_comms_adapter(&mut self, adapter: String) -> String {
+        if adapter.starts_with("offline") {
+            return adapter;
+        }
+        if let Some(server) = &self.mock_comms_server {
+            return server.adapter();
+        }
+
+        let script_path = DclGlobal::singleton()
+            .bind()
+            .cli
+            .bind()
+            .mock_comms
+            .to_string();
+        if script_path.is_empty() {
+            return adapter;
+        }
+        let script = match MockCommsScript::load(std::path::Path::new(&script_path)) {
+            Ok(script) => script,
+            Err(err) => {
+                tracing::warn!("--mock-comms: {err}");
+                return adapter;
+            }
+        };
+        let Some(runtime) = TokioRuntime::static_clone_handle() else {
+            tracing::warn!("--mock-comms: no tokio runtime");
+            return adapter;
+        };
+        match MockCommsServer::start(script, &runtime) {
+            Ok(server) => {
+                let mock_adapter = server.adapter();
+                tracing::info!("🧪 comms redirected to the mock server: {mock_adapter}");
+                self.mock_comms_server = Some(server);
+                mock_adapter
+            }
+            Err(err) => {
+                tracing::warn!("--mock-comms: failed to start the server: {err}");
+                adapter
+            }
+        }
+    }
+
+    /// Sends the packet to the main room and, if connected, to the scene room.
+    /// Returns true if any of them took it.
+    fn send_to_main_and_scene_rooms(&mut self, packet: rfc4::Packet, unreliable: bool) -> bool {
+        let mut sent = false;
+
+        // Send to main room if available
+        if let Some(main_room) = &mut self.main_room {
+            sent = main_room.send_rfc4(packet.clone(), unreliable) || sent;
+        }
+
+        // Also send to scene room if available
+        #[cfg(feature = "use_livekit")]
+        if let Some(scene_room) = &mut self.scene_room {
+            sent = scene_room.send_rfc4(packet, unreliable) || sent;
+        }
+
+        sent
+    }
+
     #[cfg(feature = "use_livekit")]
     fn create_fallback_connection(&mut self) {
         tracing::debug!("🔧 Creating fallback MessageProcessor for scene room support");
@@ -769,6 +1095,8 @@ impl CommunicationManager {
             let global = DclGlobal::singleton();
             let global_bind = global.bind();
             processor.set_social_blacklist(global_bind.social_blacklist.clone());
+            processor.set_network_simulation(self.network_simulation.clone());
+            processor.set_voice_scope(self.voice_scope());
 
             let sender = processor.get_message_sender();
             self.message_processor = Some(processor);
@@ -781,6 +1109,132 @@ impl CommunicationManager {
         }
     }
 
+    /// The avatar scene owns the settings: it outlives the message processor
+    fn peer_voice_settings_of(&self, address: GString) -> PeerVoiceSettings {
+        address
+            .to_string()
+            .as_h160()
+            .map(|address| {
+                DclGlobal::singleton()
+                    .bind()
+                    .get_avatars()
+                    .bind()
+                    .peer_voice_settings(&address)
+            })
+            .unwrap_or_default()
+    }
+
+    fn update_peer_voice_settings(
+        &self,
+        address: GString,
+        update: impl FnOnce(PeerVoiceSettings) -> PeerVoiceSettings,
+    ) {
+        let Some(address) = address.to_string().as_h160() else {
+            tracing::warn!("invalid peer address {address}");
+            return;
+        };
+        let mut avatar_scene_ref = DclGlobal::singleton().bind().get_avatars();
+        let mut avatar_scene = avatar_scene_ref.bind_mut();
+        let settings = update(avatar_scene.peer_voice_settings(&address));
+        avatar_scene.set_peer_voice_settings(address, settings);
+    }
+
+    pub fn set_scene_voice_channel(&mut self, scene_id: String, enabled: bool) {
+        self.scene_voice_policies
+            .entry(scene_id)
+            .or_default()
+            .scene_channel = enabled;
+        self.refresh_scene_voice();
+    }
+
+    pub fn set_scene_voice_microphone_allowed(&mut self, scene_id: String, allowed: bool) {
+        self.scene_voice_policies
+            .entry(scene_id)
+            .or_default()
+            .microphone_allowed = allowed;
+    }
+
+    pub fn set_scene_voice_player_muted(&mut self, scene_id: String, address: H160, muted: bool) {
+        let policy = self.scene_voice_policies.entry(scene_id).or_default();
+        if muted {
+            policy.muted_players.insert(address);
+        } else {
+            policy.muted_players.remove(&address);
+        }
+        self.refresh_scene_voice();
+    }
+
+    /// Forgets what an unloaded scene asked for (a reload starts from the defaults)
+    pub fn remove_scene_voice_policy(&mut self, scene_id: &str) {
+        if self.scene_voice_policies.remove(scene_id).is_some() {
+            self.refresh_scene_voice();
+        }
+    }
+
+    pub fn scene_voice_state(&self, scene_id: &str) -> SceneVoiceState {
+        let policy = self
+            .scene_voice_policies
+            .get(scene_id)
+            .cloned()
+            .unwrap_or_default();
+        let mut muted_players = policy
+            .muted_players
+            .iter()
+            .map(|address| format!("{address:#x}"))
+            .collect::<Vec<_>>();
+        muted_players.sort();
+        SceneVoiceState {
+            scene_channel: policy.scene_channel,
+            active: self.active_scene_voice.as_deref() == Some(scene_id),
+            microphone_allowed: policy.microphone_allowed,
+            muted_players,
+        }
+    }
+
+    fn voice_scope(&self) -> Option<VoiceScope> {
+        let scene_id = self.active_scene_voice.as_ref()?;
+        Some(VoiceScope {
+            room_id: format!("scene-{scene_id}"),
+            muted: self
+                .scene_voice_policies
+                .get(scene_id)
+                .map(|policy| policy.muted_players.clone())
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Switches the voice to the channel of the current scene if it asked for one, or back
+    /// to the global channel. Called when the scene, its room or its policy change.
+    fn refresh_scene_voice(&mut self) {
+        let active = self
+            .current_scene_id
+            .as_ref()
+            .map(|scene_id| scene_id.to_string())
+            .filter(|scene_id| {
+                self.scene_voice_policies
+                    .get(scene_id)
+                    .is_some_and(|policy| policy.scene_channel)
+            });
+        let changed = active != self.active_scene_voice;
+        self.active_scene_voice = active;
+
+        let scope = self.voice_scope();
+        if let Some(processor) = self.message_processor.as_mut() {
+            processor.set_voice_scope(scope);
+        }
+        #[cfg(feature = "use_livekit")]
+        if let Some(scene_room) = &self.scene_room {
+            scene_room.set_voice_subscription(self.active_scene_voice.is_some());
+        }
+
+        if changed {
+            let scene_scoped = self.active_scene_voice.is_some();
+            tracing::info!("🎙️ scene voice channel: {scene_scoped}");
+            self.base_mut()
+                .emit_signal("voice_channel_changed", &[scene_scoped.to_variant()]);
+        }
+    }
+
     /// Effective Pulse activation. Precedence: runtime override (deeplink `pulse=` /
     /// `pulse-server=`) > CLI (`--no-pulse` always disables, `--pulse` force-enables) >
     /// server `pulse` feature flag, fail-closed — Pulse stays off until feature_flags.gd
@@ -833,10 +1287,14 @@ impl CommunicationManager {
 
         let processor_sender = self.ensure_message_processor();
         tracing::info!("pulse: room created for {host}:{port}");
-        self.pulse_room = Some(PulseRoom::new(
-            PulseTransportConfig { host, port },
-            processor_sender,
-        ));
+        let mut room = PulseRoom::new(PulseTransportConfig { host, port }, processor_sender);
+        if !cli.pulse_record.is_empty() {
+            let path = cli.pulse_record.to_string();
+            if let Err(err) = room.start_recording(std::path::Path::new(&path)) {
+                tracing::warn!("pulse: can't record to {path}: {err}");
+            }
+        }
+        self.pulse_room = Some(room);
     }
 
     /// Whether LiveKit-backed rooms may be created (see `livekit_runtime_enabled`).
@@ -1047,6 +1505,13 @@ impl CommunicationManager {
         data: Vec<u8>,
         recipient: NetworkMessageRecipient,
     ) {
+        // Held until the end of the tick, see `flush_scene_messages`
+        #[cfg(feature = "use_livekit")]
+        if let (Some(batcher), Some(_)) = (&mut self.scene_message_batcher, &self.scene_room) {
+            batcher.push(scene_id, data, recipient);
+            return;
+        }
+
         let data_len = data.len();
         let scene_message = rfc4::Packet {
             message: Some(rfc4::packet::Message::Scene(rfc4::Scene { scene_id, data })),
@@ -1062,6 +1527,7 @@ impl CommunicationManager {
                 data_len,
                 recipient
             );
+            crate::comms::adapter::scene_batching::record_unbatched_scene_message(data_len);
             scene_room.send_rfc4_targeted(scene_message, false, recipient);
         }
         #[cfg(feature = "use_livekit")]
@@ -1072,6 +1538,47 @@ impl CommunicationManager {
         }
     }
 
+    /// Sends the scene messages queued during the tick, packed into envelopes for the
+    /// recipients that negotiated it (see `scene_batching`)
+    #[cfg(feature = "use_livekit")]
+    fn flush_scene_messages(&mut self) {
+        let Some(batcher) = &mut self.scene_message_batcher else {
+            return;
+        };
+        if batcher.pending_count() == 0 {
+            return;
+        }
+        let Some(scene_room) = &mut self.scene_room else {
+            tracing::warn!(
+                "⚠️ {} batched scene messages dropped, scene_room is None",
+                batcher.pending_count()
+            );
+            batcher.clear();
+            return;
+        };
+
+        // Broadcasts are never batched: a participant that just joined (or whose metadata
+        // hasn't arrived yet) may not decode envelopes
+        let processor = self.message_processor.as_ref();
+        let payloads = batcher.flush(|recipient| match recipient {
+            NetworkMessageRecipient::Peer(address) => {
+                processor.is_some_and(|processor| processor.peer_supports_scene_batching(address))
+            }
+            NetworkMessageRecipient::All | NetworkMessageRecipient::AuthServer => false,
+        });
+
+        for payload in payloads {
+            let packet = rfc4::Packet {
+                message: Some(rfc4::packet::Message::Scene(rfc4::Scene {
+                    scene_id: payload.scene_id,
+                    data: payload.data,
+                })),
+                protocol_version: DEFAULT_PROTOCOL_VERSION,
+            };
+            scene_room.send_rfc4_targeted(packet, false, payload.recipient);
+        }
+    }
+
     pub fn get_pending_messages(&mut self, scene_id: &str) -> Vec<(H160, Vec<u8>)> {
         // Use shared message processor if available
         if let Some(processor) = &mut self.message_processor {
@@ -1211,11 +1718,23 @@ impl CommunicationManager {
     #[signal]
     fn on_adapter_changed(voice_chat_enabled: bool, new_adapter: GString);
 
+    /// Signal emitted when a peer sends a reaction (rfc4 `Reaction`)
+    #[signal]
+    fn reaction_received(address: GString, emoji: GString);
+
+    /// Signal emitted when a peer reacts to a chat message (rfc4 `ChatReaction`)
+    #[signal]
+    fn chat_reaction_received(address: GString, message_id: GString, emoji: GString);
+
     /// Signal emitted when disconnected from the server
     /// reason: 0 = DuplicateIdentity, 1 = RoomClosed, 2 = Kicked, 3 = Other
     #[signal]
     fn disconnected(reason: i32);
 
+    /// Voice switched between the global channel and the scene-scoped one
+    #[signal]
+    fn voice_channel_changed(scene_scoped: bool);
+
     /// Signal emitted with the result of a scene access check
     /// allowed: true if access is granted, false if banned
     /// error_message: non-empty if the check failed (network error, etc.)
@@ -1224,6 +1743,25 @@ impl CommunicationManager {
 
     #[func]
     fn broadcast_voice(&mut self, frame: PackedVector2Array) {
+        // In a scene voice channel the voice only goes to the scene room
+        if let Some(scene_id) = &self.active_scene_voice {
+            let microphone_allowed = self
+                .scene_voice_policies
+                .get(scene_id)
+                .is_some_and(|policy| policy.microphone_allowed);
+            #[cfg(feature = "use_livekit")]
+            if microphone_allowed {
+                if let (Some(scene_room), Some(samples)) =
+                    (&mut self.scene_room, voice_frame_samples(&frame))
+                {
+                    scene_room.broadcast_voice(samples);
+                }
+            }
+            #[cfg(not(feature = "use_livekit"))]
+            let _ = microphone_allowed;
+            return;
+        }
+
         let adapter = if let Some(main_room) = &mut self.main_room {
             match main_room {
                 MainRoom::WebSocket(_) => None, // WebSocket doesn't support voice
@@ -1248,28 +1786,48 @@ impl CommunicationManager {
             return;
         }
 
-        let mut max_value = 0;
-        let vec = frame
-            .as_slice()
-            .iter()
-            .map(|v| {
-                let value = ((0.5 * (v.x + v.y)) * i16::MAX as f32) as i16;
-
-                max_value = std::cmp::max(max_value, value);
-                value
-            })
-            .collect::<Vec<i16>>();
-
-        if max_value > 0 {
-            adapter.broadcast_voice(vec);
+        if let Some(samples) = voice_frame_samples(&frame) {
+            adapter.broadcast_voice(samples);
         }
     }
 
+    /// Whether the voice is limited to the scene the player is in (`~system/VoiceChat`)
+    #[func]
+    pub fn is_scene_voice_channel_active(&self) -> bool {
+        self.active_scene_voice.is_some()
+    }
+
     #[func]
     fn is_voice_chat_enabled(&self) -> bool {
         self.voice_chat_enabled
     }
 
+    /// Local volume of a peer's voice, linear from 0 to `PeerVoiceSettings::MAX_VOLUME`
+    #[func]
+    pub fn set_peer_voice_volume(&mut self, address: GString, volume: f32) {
+        self.update_peer_voice_settings(address, |settings| {
+            PeerVoiceSettings::new(volume, settings.muted)
+        });
+    }
+
+    #[func]
+    pub fn get_peer_voice_volume(&self, address: GString) -> f32 {
+        self.peer_voice_settings_of(address).volume
+    }
+
+    /// Mutes a peer's voice for this session only (the social blacklist mute persists)
+    #[func]
+    pub fn set_peer_voice_muted(&mut self, address: GString, muted: bool) {
+        self.update_peer_voice_settings(address, |settings| {
+            PeerVoiceSettings::new(settings.volume, muted)
+        });
+    }
+
+    #[func]
+    pub fn is_peer_voice_muted(&self, address: GString) -> bool {
+        self.peer_voice_settings_of(address).muted
+    }
+
     /// Dual-channel movement toggle (default ON): whether movement keeps going over LiveKit
     /// while Pulse is established. Turning it off makes movement Pulse-only *while established*;
     /// LiveKit sending auto-resumes if Pulse drops. No-op without the use_pulse feature.
@@ -1807,29 +2365,94 @@ impl CommunicationManager {
 
     #[func]
     fn send_chat(&mut self, text: GString) -> bool {
+        let chat = rfc4::Chat {
+            message: text.to_string(),
+            timestamp: ole_timestamp_now(),
+            forwarded_from: None,
+        };
         let packet = rfc4::Packet {
-            message: Some(rfc4::packet::Message::Chat(rfc4::Chat {
-                message: text.to_string(),
-                timestamp: ole_timestamp_now(),
-                forwarded_from: None,
-            })),
+            message: Some(rfc4::packet::Message::Chat(chat.clone())),
             protocol_version: DEFAULT_PROTOCOL_VERSION,
         };
 
-        let mut sent = false;
+        let sent = self.send_to_main_and_scene_rooms(packet, false);
+        if sent {
+            // Peers in the scene room get it from there too, keep it with that scene
+            #[cfg(feature = "use_livekit")]
+            let room_id = self
+                .scene_room
+                .as_ref()
+                .map(|scene_room| scene_room.room_id().to_string())
+                .unwrap_or_default();
+            #[cfg(not(feature = "use_livekit"))]
+            let room_id = String::new();
+            if let Some(address) = self.chat_history_mut().map(|history| history.owner()) {
+                self.record_chat_history([(address, room_id.as_str(), &chat)], true);
+            }
+        }
+        sent
+    }
 
-        // Send to main room if available
-        if let Some(main_room) = &mut self.main_room {
-            sent = main_room.send_rfc4(packet.clone(), false) || sent;
+    /// Where the local avatar is looking at, in Godot space. Rate limited, returns false
+    /// when dropped or not sent.
+    #[func]
+    pub fn send_look_at(&mut self, position: Vector3) -> bool {
+        if !self.look_at_rate_limiter.try_acquire(Instant::now()) {
+            return false;
         }
 
-        // Also send to scene room if available
-        #[cfg(feature = "use_livekit")]
-        if let Some(scene_room) = &mut self.scene_room {
-            sent = scene_room.send_rfc4(packet, false) || sent;
+        let packet = rfc4::Packet {
+            message: Some(rfc4::packet::Message::LookAtPosition(
+                rfc4::LookAtPosition {
+                    timestamp: self.start_time.elapsed().as_secs_f32(),
+                    position_x: position.x,
+                    position_y: position.y,
+                    position_z: -position.z,
+                    ..Default::default()
+                },
+            )),
+            protocol_version: DEFAULT_PROTOCOL_VERSION,
+        };
+
+        self.send_to_main_and_scene_rooms(packet, true)
+    }
+
+    /// Rate limited, returns false when dropped or not sent.
+    #[func]
+    pub fn send_reaction(&mut self, emoji: GString) -> bool {
+        if !self.reaction_rate_limiter.try_acquire(Instant::now()) {
+            return false;
         }
 
-        sent
+        let packet = rfc4::Packet {
+            message: Some(rfc4::packet::Message::Reaction(rfc4::Reaction {
+                emoji: emoji.to_string(),
+                ..Default::default()
+            })),
+            protocol_version: DEFAULT_PROTOCOL_VERSION,
+        };
+
+        self.send_to_main_and_scene_rooms(packet, false)
+    }
+
+    /// Reacts to the chat message `message_id`. Rate limited, returns false when dropped or
+    /// not sent.
+    #[func]
+    pub fn send_chat_reaction(&mut self, message_id: GString, emoji: GString) -> bool {
+        if !self.chat_reaction_rate_limiter.try_acquire(Instant::now()) {
+            return false;
+        }
+
+        let packet = rfc4::Packet {
+            message: Some(rfc4::packet::Message::ChatReaction(rfc4::ChatReaction {
+                message_id: message_id.to_string(),
+                emoji: emoji.to_string(),
+                ..Default::default()
+            })),
+            protocol_version: DEFAULT_PROTOCOL_VERSION,
+        };
+
+        self.send_to_main_and_scene_rooms(packet, false)
     }
 
     #[func]
@@ -1852,18 +2475,7 @@ impl CommunicationManager {
             protocol_version: DEFAULT_PROTOCOL_VERSION,
         };
 
-        let mut sent = false;
-
-        // Send to main room if available
-        if let Some(main_room) = &mut self.main_room {
-            sent = main_room.send_rfc4(packet.clone(), false) || sent;
-        }
-
-        // Also send to scene room if available
-        #[cfg(feature = "use_livekit")]
-        if let Some(scene_room) = &mut self.scene_room {
-            sent = scene_room.send_rfc4(packet, false) || sent;
-        }
+        let sent = self.send_to_main_and_scene_rooms(packet, false);
 
         // Pulse EmoteStart is deferred to set_emoting (actual playback start), NOT sent here:
         // the trigger fires before the emote is async-loaded and idle-gated, and sending now
@@ -1934,6 +2546,24 @@ impl CommunicationManager {
             &self.base().callable("on_blacklist_changed"),
         );
 
+        let network_sim = global_bind.cli.bind().network_sim.clone();
+        if !network_sim.is_empty() {
+            self.set_network_simulation(network_sim);
+        }
+
+        let (scene_batching, scene_compress) = {
+            let cli = global_bind.cli.bind();
+            (cli.scene_batching, cli.scene_compress)
+        };
+        if scene_batching || scene_compress > 0 {
+            let threshold = if scene_compress > 0 {
+                scene_compress
+            } else {
+                DEFAULT_COMPRESSION_THRESHOLD as i32
+            };
+            self.set_scene_message_batching(true, threshold);
+        }
+
         #[cfg(feature = "use_livekit")]
         {
             let mut scene_runner = DclGlobal::singleton().bind().get_scene_runner();
@@ -2048,7 +2678,7 @@ impl CommunicationManager {
 
     #[func]
     fn change_adapter(&mut self, comms_fixed_adapter_gstr: GString) {
-        let comms_fixed_adapter_str = comms_fixed_adapter_gstr.to_string();
+        let comms_fixed_adapter_str = self.mock_comms_adapter(comms_fixed_adapter_gstr.to_string());
         let Some((protocol, comms_address)) = comms_fixed_adapter_str.as_str().split_once(':')
         else {
             tracing::warn!("unrecognised fixed adapter string: {comms_fixed_adapter_str}");
@@ -2233,6 +2863,8 @@ impl CommunicationManager {
     }
 
     fn clean(&mut self) {
+        self.flush_chat_history();
+
         #[cfg(feature = "use_pulse")]
         if let Some(mut pulse_room) = self.pulse_room.take() {
             pulse_room.clean();
@@ -2274,6 +2906,7 @@ impl CommunicationManager {
             self.scene_room_connect_in_flight = None;
         }
         self.current_scene_id = None;
+        self.refresh_scene_voice();
         self.current_connection = CommsConnection::None;
         self.current_connection_str = GString::default();
         self.archipelago_profile_announced = false;
@@ -2431,6 +3064,7 @@ impl CommunicationManager {
                 self.scene_room_reconnect_at = None;
                 self.scene_room_connect_in_flight = None;
                 self.current_scene_id = None;
+                self.refresh_scene_voice();
                 self.base_mut()
                     .emit_signal("disconnected", &[2i32.to_variant()]);
             }
@@ -2482,6 +3116,7 @@ impl CommunicationManager {
                 self.scene_room = Some(scene_room);
                 self.scene_room_reconnect_at = None;
                 self.scene_room_connect_in_flight = None;
+                self.refresh_scene_voice();
                 // Successful connect: reset the backoff for the next outage.
                 self.scene_room_reconnect_backoff_secs = SCENE_ROOM_RECONNECT_BASE_SECS;
 
@@ -2529,6 +3164,7 @@ impl CommunicationManager {
         self.scene_room_reconnect_at = None;
         self.scene_room_connect_in_flight = None;
         self.current_scene_id = Some(scene_entity_id.clone());
+        self.refresh_scene_voice();
 
         // If loading is in progress, defer scene room creation until release
         if self.comms_on_hold {
@@ -2693,6 +3329,139 @@ impl CommunicationManager {
         self.multiplayer_debug
     }
 
+    /// Debug: simulate a bad network on the incoming messages, see `network_simulator`
+    /// for the spec (e.g. "mobile" or "latency=200;movement:loss=0.1"). Empty turns it off.
+    #[func]
+    pub fn set_network_simulation(&mut self, spec: GString) -> bool {
+        let config = match NetworkSimulatorConfig::parse(&spec.to_string()) {
+            Ok(config) => config,
+            Err(err) => {
+                tracing::warn!("network simulation '{spec}': {err}");
+                return false;
+            }
+        };
+        if config.is_ideal() {
+            tracing::info!("🌐 network simulation off");
+            self.network_simulation = None;
+        } else {
+            tracing::info!("🌐 simulating network conditions: {config}");
+            self.network_simulation = Some(config);
+        }
+        if let Some(processor) = self.message_processor.as_mut() {
+            processor.set_network_simulation(self.network_simulation.clone());
+        }
+        true
+    }
+
+    /// Batch the scene messages of each tick for the peers that support it, deflating
+    /// envelopes of at least `compression_threshold` bytes (negative never compresses)
+    #[func]
+    pub fn set_scene_message_batching(&mut self, enabled: bool, compression_threshold: i32) {
+        if !enabled {
+            tracing::info!("📦 scene message batching off");
+            #[cfg(feature = "use_livekit")]
+            self.flush_scene_messages();
+            self.scene_message_batcher = None;
+            return;
+        }
+        let config = SceneBatchingConfig {
+            compression_threshold: usize::try_from(compression_threshold).ok(),
+        };
+        tracing::info!("📦 scene message batching on: {config:?}");
+        #[cfg(feature = "use_livekit")]
+        self.flush_scene_messages();
+        self.scene_message_batcher = Some(SceneMessageBatcher::new(config));
+    }
+
+    #[func]
+    pub fn is_scene_message_batching(&self) -> bool {
+        self.scene_message_batcher.is_some()
+    }
+
+    /// Local chat history, one page at a time: up to `limit` entries with an id below
+    /// `before_id` (0 for the newest), oldest first. Empty `realm`, `scene_id` and `address`
+    /// match anything. Entries are dictionaries with id, realm, scene_id, address,
+    /// timestamp, message and own; blocked and muted senders are left out.
+    #[func]
+    pub fn get_chat_history(
+        &mut self,
+        realm: GString,
+        scene_id: GString,
+        address: GString,
+        before_id: i64,
+        limit: i32,
+    ) -> VarArray {
+        let non_empty = |value: GString| Some(value.to_string()).filter(|v| !v.is_empty());
+        let address_filter = non_empty(address.clone());
+        let filter = ChatHistoryFilter {
+            realm: non_empty(realm),
+            scene_id: non_empty(scene_id),
+            address: address_filter.as_ref().and_then(|a| a.as_h160()),
+            text: None,
+        };
+        if address_filter.is_some() && filter.address.is_none() {
+            return VarArray::new();
+        }
+        self.query_chat_history(filter, before_id, limit)
+    }
+
+    /// Like `get_chat_history`, for the entries containing `text` (case-insensitive)
+    #[func]
+    pub fn search_chat_history(
+        &mut self,
+        text: GString,
+        realm: GString,
+        before_id: i64,
+        limit: i32,
+    ) -> VarArray {
+        let text = text.to_string();
+        if text.trim().is_empty() {
+            return VarArray::new();
+        }
+        let realm = realm.to_string();
+        let filter = ChatHistoryFilter {
+            realm: Some(realm).filter(|realm| !realm.is_empty()),
+            text: Some(text),
+            ..Default::default()
+        };
+        self.query_chat_history(filter, before_id, limit)
+    }
+
+    /// Keep at most `max_entries` chats, none older than `max_age_days`
+    #[func]
+    pub fn set_chat_history_retention(&mut self, max_entries: i32, max_age_days: i32) {
+        self.chat_history_retention = ChatRetention {
+            max_entries: usize::try_from(max_entries).unwrap_or(0),
+            max_age: Duration::from_secs(u64::try_from(max_age_days).unwrap_or(0) * 24 * 60 * 60),
+        };
+        let retention = self.chat_history_retention;
+        if let Some(history) = &mut self.chat_history {
+            if let Err(e) = history.set_retention(retention) {
+                tracing::warn!("chat history: failed to apply the retention: {e}");
+            }
+        }
+    }
+
+    /// Deletes the chat history of the signed-in account
+    #[func]
+    pub fn clear_chat_history(&mut self) {
+        if let Some(history) = self.chat_history_mut() {
+            if let Err(e) = history.clear() {
+                tracing::warn!("chat history: failed to delete: {e}");
+            }
+        }
+    }
+
+    /// The simulated network conditions in spec form, empty when off
+    #[func]
+    pub fn get_network_simulation(&self) -> GString {
+        self.network_simulation
+            .as_ref()
+            .map(|config| config.to_string())
+            .unwrap_or_default()
+            .to_godot()
+    }
+
     #[func]
     pub fn get_debug_room_info(&self) -> VarDictionary {
         let mut dict = VarDictionary::new();
@@ -3102,7 +3871,7 @@ async fn get_scene_adapter(
     Ok(gatekeeper_response.adapter)
 }
 
-fn ole_timestamp_now() -> f64 {
+pub(crate) fn ole_timestamp_now() -> f64 {
     let unix_seconds = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
@@ -3111,7 +3880,19 @@ fn ole_timestamp_now() -> f64 {
     unix_seconds / 86400.0 + 25569.0
 }
 
-fn get_chat_array(chats: Vec<(H160, rfc4::Chat)>) -> VarArray {
+fn chat_history_entry_dict(entry: &ChatHistoryEntry) -> VarDictionary {
+    let mut dict = VarDictionary::new();
+    dict.set("id", entry.id as i64);
+    dict.set("realm", entry.realm.as_str());
+    dict.set("scene_id", entry.scene_id.as_str());
+    dict.set("address", format!("{:#x}", entry.address));
+    dict.set("timestamp", entry.timestamp);
+    dict.set("message", entry.message.as_str());
+    dict.set("own", entry.own);
+    dict
+}
+
+fn get_chat_array<'a>(chats: impl IntoIterator<Item = (&'a H160, &'a rfc4::Chat)>) -> VarArray {
     let mut chats_variant_array = VarArray::new();
     for (address, chat) in chats {
         let mut chat_arr = VarArray::new();
@@ -3180,6 +3961,19 @@ fn parse_comms_adapter_value(
 mod tests {
     use super::*;
 
+    #[test]
+    fn send_rate_limiter_drops_sends_within_the_interval() {
+        let mut limiter = SendRateLimiter::new(Duration::from_millis(200));
+        let start = Instant::now();
+
+        assert!(limiter.try_acquire(start));
+        assert!(!limiter.try_acquire(start + Duration::from_millis(199)));
+        assert!(limiter.try_acquire(start + Duration::from_millis(200)));
+        // the window restarts from the last accepted send
+        assert!(!limiter.try_acquire(start + Duration::from_millis(300)));
+        assert!(limiter.try_acquire(start + Duration::from_millis(400)));
+    }
+
     // ==========================================
     // Tests for should_start_scene_room_reconnect (issue #2382 in-flight guard)
     // ==========================================
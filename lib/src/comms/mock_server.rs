@@ -0,0 +1,1163 @@
+//! Loopback comms server for multiplayer tests.
+//!
+//! Speaks the ws-room protocol (rfc5) and the archipelago handshake (v3) on
+//! 127.0.0.1, and plays a [`MockCommsScript`]: fake peers that join, move, chat,
+//! announce their profile and send scene messages on a timeline. Every client
+//! connection gets its own copy of the scripted peers, and everything the
+//! clients send is recorded for assertions.
+//!
+//! The archipelago endpoint assigns the client to a LiveKit island, the only
+//! kind the client accepts from archipelago. There is no LiveKit server behind
+//! it, so it tests the handshake and the assignment; the scripted peers are only
+//! played on the ws-room.
+//!
+//! Used by the explorer with `--mock-comms <script.json>` (also forwarded by
+//! `cargo run -- run --mock-comms`), which swaps whatever adapter the realm
+//! asks for with this ws-room, and directly from Rust tests.
+
+use std::{
+    collections::VecDeque,
+    io,
+    net::SocketAddr,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use ethers_core::types::H160;
+use futures_util::{SinkExt, StreamExt};
+use prost::Message as _;
+use serde::Deserialize;
+use tokio::{
+    net::{TcpListener, TcpStream},
+    runtime::Handle,
+    time::Instant,
+};
+use tokio_tungstenite::{
+    tungstenite::{
+        handshake::server::{ErrorResponse, Request, Response},
+        http::{header::SEC_WEBSOCKET_PROTOCOL, HeaderValue},
+        Message,
+    },
+    WebSocketStream,
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    comms::{communication_manager::ole_timestamp_now, profile::SerializedProfile},
+    dcl::components::proto_components::kernel::comms::{
+        rfc4,
+        rfc5::{
+            ws_packet, WsChallengeRequired, WsPacket, WsPeerJoin, WsPeerLeave, WsPeerUpdate,
+            WsWelcome,
+        },
+        v3::{
+            client_packet, server_packet, ChallengeResponseMessage, ClientPacket,
+            IslandChangedMessage, ServerPacket, WelcomeMessage,
+        },
+    },
+};
+
+const WS_ROOM_PATH: &str = "/ws-room";
+const ARCHIPELAGO_PATH: &str = "/archipelago";
+pub const MOCK_ISLAND_ID: &str = "mock-island";
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+type Ws = WebSocketStream<TcpStream>;
+
+/// What the server plays for each client, loaded from JSON:
+/// ```json
+/// { "peers": [{ "name": "alice", "actions": [
+///     { "type": "wait", "ms": 500 },
+///     { "type": "position", "x": 8.0, "y": 0.0, "z": 8.0 },
+///     { "type": "chat", "message": "hi" } ] }] }
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MockCommsScript {
+    pub peers: Vec<MockPeerScript>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MockPeerScript {
+    pub name: String,
+    /// Defaults to an address derived from the peer index
+    pub address: Option<String>,
+    pub profile_version: u32,
+    /// When false, ProfileRequests for this peer time out
+    pub answer_profile_requests: bool,
+    /// 0 = already in the room when the client arrives
+    pub join_after_ms: u64,
+    pub actions: Vec<MockPeerAction>,
+}
+
+impl Default for MockPeerScript {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            address: None,
+            profile_version: 1,
+            answer_profile_requests: true,
+            join_after_ms: 0,
+            actions: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MockPeerAction {
+    Wait {
+        ms: u64,
+    },
+    /// Decentraland coordinates
+    Position {
+        x: f32,
+        y: f32,
+        z: f32,
+    },
+    Chat {
+        message: String,
+    },
+    ProfileVersion {
+        version: u32,
+    },
+    Scene {
+        scene_id: String,
+        data: String,
+    },
+    Leave,
+}
+
+impl MockCommsScript {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+        serde_json::from_str(&content)
+            .map_err(|err| format!("invalid mock comms script {}: {err}", path.display()))
+    }
+
+    fn peer_addresses(&self) -> Result<Vec<H160>, String> {
+        self.peers
+            .iter()
+            .enumerate()
+            .map(|(index, peer)| match &peer.address {
+                Some(address) => address
+                    .parse::<H160>()
+                    .map_err(|_| format!("invalid address for mock peer {}", peer.name)),
+                None => Ok(H160::from_low_u64_be(0xdc1_0000 + index as u64)),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PeerEvent {
+    Join,
+    Action(MockPeerAction),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ScheduledEvent {
+    at: Duration,
+    peer: usize,
+    event: PeerEvent,
+}
+
+/// Flattens the peer scripts into a single timeline ordered by time, `Wait`s
+/// only move the clock of their peer and nothing is played after a `Leave`.
+fn build_timeline(peers: &[MockPeerScript]) -> VecDeque<ScheduledEvent> {
+    let mut timeline = Vec::new();
+    for (peer, script) in peers.iter().enumerate() {
+        let mut at = Duration::from_millis(script.join_after_ms);
+        if script.join_after_ms > 0 {
+            timeline.push(ScheduledEvent {
+                at,
+                peer,
+                event: PeerEvent::Join,
+            });
+        }
+        for action in &script.actions {
+            match action {
+                MockPeerAction::Wait { ms } => at += Duration::from_millis(*ms),
+                action => {
+                    timeline.push(ScheduledEvent {
+                        at,
+                        peer,
+                        event: PeerEvent::Action(action.clone()),
+                    });
+                    if *action == MockPeerAction::Leave {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    // stable, so the events of a peer keep their order
+    timeline.sort_by_key(|event| event.at);
+    timeline.into()
+}
+
+/// A packet a client sent to the server
+#[derive(Debug, Clone)]
+pub struct ReceivedPacket {
+    pub from: H160,
+    pub message: rfc4::packet::Message,
+}
+
+pub struct MockCommsServer {
+    address: SocketAddr,
+    received: Arc<Mutex<Vec<ReceivedPacket>>>,
+    cancel: CancellationToken,
+}
+
+impl MockCommsServer {
+    /// Binds a random loopback port and serves on `runtime` until dropped.
+    pub fn start(script: MockCommsScript, runtime: &Handle) -> io::Result<Self> {
+        let peer_addresses = script
+            .peer_addresses()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        listener.set_nonblocking(true)?;
+        let address = listener.local_addr()?;
+
+        let server = Self {
+            address,
+            received: Default::default(),
+            cancel: CancellationToken::new(),
+        };
+
+        let context = Arc::new(SessionContext {
+            script,
+            peer_addresses,
+            server_address: address,
+            received: server.received.clone(),
+        });
+        let cancel = server.cancel.clone();
+        runtime.spawn(async move {
+            let listener = match TcpListener::from_std(listener) {
+                Ok(listener) => listener,
+                Err(err) => {
+                    tracing::warn!("mock comms: failed to listen: {err}");
+                    return;
+                }
+            };
+            loop {
+                let stream = tokio::select! {
+                    _ = cancel.cancelled() => break,
+                    accepted = listener.accept() => match accepted {
+                        Ok((stream, _)) => stream,
+                        Err(err) => {
+                            tracing::warn!("mock comms: accept failed: {err}");
+                            continue;
+                        }
+                    },
+                };
+                let context = context.clone();
+                let cancel = cancel.clone();
+                tokio::spawn(async move {
+                    tokio::select! {
+                        _ = cancel.cancelled() => {}
+                        result = serve_connection(stream, context) => {
+                            if let Err(err) = result {
+                                tracing::warn!("mock comms: session ended: {err}");
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        tracing::info!("mock comms server listening on {address}");
+        Ok(server)
+    }
+
+    /// Adapter string for `CommunicationManager::change_adapter`
+    pub fn adapter(&self) -> String {
+        format!("ws-room:ws://{}{WS_ROOM_PATH}", self.address)
+    }
+
+    /// Adapter string of the archipelago endpoint, which assigns [`MOCK_ISLAND_ID`]
+    pub fn archipelago_adapter(&self) -> String {
+        format!("archipelago:ws://{}{ARCHIPELAGO_PATH}", self.address)
+    }
+
+    pub fn received_packets(&self) -> Vec<ReceivedPacket> {
+        self.received.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockCommsServer {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+struct SessionContext {
+    script: MockCommsScript,
+    peer_addresses: Vec<H160>,
+    server_address: SocketAddr,
+    received: Arc<Mutex<Vec<ReceivedPacket>>>,
+}
+
+async fn serve_connection(stream: TcpStream, context: Arc<SessionContext>) -> Result<(), String> {
+    let mut path = String::new();
+    let callback = |request: &Request, mut response: Response| -> Result<Response, ErrorResponse> {
+        path = request.uri().path().to_string();
+        // Godot drops the connection if none of its subprotocols is selected
+        let protocol = request
+            .headers()
+            .get(SEC_WEBSOCKET_PROTOCOL)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(',').next())
+            .and_then(|value| HeaderValue::from_str(value.trim()).ok());
+        if let Some(protocol) = protocol {
+            response
+                .headers_mut()
+                .insert(SEC_WEBSOCKET_PROTOCOL, protocol);
+        }
+        Ok(response)
+    };
+    let ws = tokio_tungstenite::accept_hdr_async(stream, callback)
+        .await
+        .map_err(|err| format!("websocket handshake failed: {err}"))?;
+
+    match path.as_str() {
+        WS_ROOM_PATH => run_ws_room(ws, &context).await,
+        ARCHIPELAGO_PATH => run_archipelago(ws, &context).await,
+        _ => Err(format!("unknown path {path}")),
+    }
+}
+
+async fn send<T: prost::Message>(ws: &mut Ws, packet: &T) -> Result<(), String> {
+    ws.send(Message::Binary(packet.encode_to_vec()))
+        .await
+        .map_err(|err| format!("send failed: {err}"))
+}
+
+/// Next binary message, None when the client is gone.
+async fn recv(ws: &mut Ws) -> Option<Vec<u8>> {
+    loop {
+        match ws.next().await? {
+            Ok(Message::Binary(data)) => return Some(data),
+            Ok(Message::Close(_)) | Err(_) => return None,
+            Ok(_) => {}
+        }
+    }
+}
+
+async fn recv_handshake<T: prost::Message + Default>(ws: &mut Ws) -> Result<T, String> {
+    let data = tokio::time::timeout(HANDSHAKE_TIMEOUT, recv(ws))
+        .await
+        .map_err(|_| "handshake timed out".to_string())?
+        .ok_or_else(|| "client left during the handshake".to_string())?;
+    T::decode(data.as_slice()).map_err(|err| format!("invalid handshake packet: {err}"))
+}
+
+fn new_challenge() -> String {
+    format!("dcl-{}", uuid::Uuid::new_v4())
+}
+
+/// The signature isn't checked, only that the client signed this challenge.
+fn check_signed_challenge(auth_chain_json: &str, challenge: &str) -> Result<(), String> {
+    let chain: Vec<serde_json::Value> = serde_json::from_str(auth_chain_json)
+        .map_err(|err| format!("invalid auth chain: {err}"))?;
+    let signed = chain
+        .last()
+        .and_then(|link| link.get("payload"))
+        .and_then(|payload| payload.as_str());
+    if signed == Some(challenge) {
+        Ok(())
+    } else {
+        Err("the auth chain doesn't sign the challenge".to_string())
+    }
+}
+
+struct MockPeer {
+    alias: u32,
+    address: H160,
+    joined: bool,
+    left: bool,
+    position_index: u32,
+}
+
+async fn run_ws_room(mut ws: Ws, context: &SessionContext) -> Result<(), String> {
+    let identification = match recv_handshake::<WsPacket>(&mut ws).await?.message {
+        Some(ws_packet::Message::PeerIdentification(identification)) => identification,
+        other => return Err(format!("expected PeerIdentification, got {other:?}")),
+    };
+    let client_address = identification
+        .address
+        .parse::<H160>()
+        .map_err(|_| format!("invalid client address {}", identification.address))?;
+
+    let challenge = new_challenge();
+    send(
+        &mut ws,
+        &WsPacket {
+            message: Some(ws_packet::Message::ChallengeMessage(WsChallengeRequired {
+                challenge_to_sign: challenge.clone(),
+                already_connected: false,
+            })),
+        },
+    )
+    .await?;
+
+    match recv_handshake::<WsPacket>(&mut ws).await?.message {
+        Some(ws_packet::Message::SignedChallengeForServer(signed)) => {
+            check_signed_challenge(&signed.auth_chain_json, &challenge)?
+        }
+        other => return Err(format!("expected SignedChallengeForServer, got {other:?}")),
+    }
+
+    let script = &context.script;
+    let mut peers = script
+        .peers
+        .iter()
+        .zip(&context.peer_addresses)
+        .enumerate()
+        .map(|(index, (peer, address))| MockPeer {
+            alias: index as u32 + 1,
+            address: *address,
+            joined: peer.join_after_ms == 0,
+            left: false,
+            position_index: 0,
+        })
+        .collect::<Vec<_>>();
+
+    send(
+        &mut ws,
+        &WsPacket {
+            message: Some(ws_packet::Message::WelcomeMessage(WsWelcome {
+                alias: peers.len() as u32 + 1,
+                peer_identities: peers
+                    .iter()
+                    .filter(|peer| peer.joined)
+                    .map(|peer| (peer.alias, format!("{:#x}", peer.address)))
+                    .collect(),
+            })),
+        },
+    )
+    .await?;
+    tracing::debug!("mock comms: {client_address:#x} joined the ws-room");
+
+    for index in 0..peers.len() {
+        if peers[index].joined {
+            announce_profile(&mut ws, &peers[index], script.peers[index].profile_version).await?;
+        }
+    }
+
+    let start = Instant::now();
+    let mut timeline = build_timeline(&script.peers);
+    loop {
+        let next_at = timeline.front().map(|event| start + event.at);
+        tokio::select! {
+            data = recv(&mut ws) => {
+                let Some(data) = data else {
+                    tracing::debug!("mock comms: {client_address:#x} left the ws-room");
+                    return Ok(());
+                };
+                handle_client_packet(&mut ws, context, &peers, client_address, &data).await?;
+            }
+            _ = sleep_until(next_at) => {
+                let event = timeline.pop_front().expect("next_at is set");
+                play_event(&mut ws, script, &mut peers[event.peer], event).await?;
+            }
+        }
+    }
+}
+
+async fn sleep_until(at: Option<Instant>) {
+    match at {
+        Some(at) => tokio::time::sleep_until(at).await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn send_from_peer(
+    ws: &mut Ws,
+    peer: &MockPeer,
+    message: rfc4::packet::Message,
+    unreliable: bool,
+) -> Result<(), String> {
+    let body = rfc4::Packet {
+        message: Some(message),
+        protocol_version: 100,
+    }
+    .encode_to_vec();
+    send(
+        ws,
+        &WsPacket {
+            message: Some(ws_packet::Message::PeerUpdateMessage(WsPeerUpdate {
+                from_alias: peer.alias,
+                body,
+                unreliable,
+            })),
+        },
+    )
+    .await
+}
+
+async fn announce_profile(ws: &mut Ws, peer: &MockPeer, version: u32) -> Result<(), String> {
+    send_from_peer(
+        ws,
+        peer,
+        rfc4::packet::Message::ProfileVersion(rfc4::AnnounceProfileVersion {
+            profile_version: version,
+        }),
+        false,
+    )
+    .await
+}
+
+async fn play_event(
+    ws: &mut Ws,
+    script: &MockCommsScript,
+    peer: &mut MockPeer,
+    event: ScheduledEvent,
+) -> Result<(), String> {
+    if peer.left {
+        return Ok(());
+    }
+    let peer_script = &script.peers[event.peer];
+    let action = match event.event {
+        PeerEvent::Join => {
+            peer.joined = true;
+            send(
+                ws,
+                &WsPacket {
+                    message: Some(ws_packet::Message::PeerJoinMessage(WsPeerJoin {
+                        alias: peer.alias,
+                        address: format!("{:#x}", peer.address),
+                    })),
+                },
+            )
+            .await?;
+            return announce_profile(ws, peer, peer_script.profile_version).await;
+        }
+        PeerEvent::Action(action) => action,
+    };
+
+    let message = match action {
+        MockPeerAction::Wait { .. } => return Ok(()),
+        MockPeerAction::Leave => {
+            peer.left = true;
+            return send(
+                ws,
+                &WsPacket {
+                    message: Some(ws_packet::Message::PeerLeaveMessage(WsPeerLeave {
+                        alias: peer.alias,
+                    })),
+                },
+            )
+            .await;
+        }
+        MockPeerAction::Position { x, y, z } => {
+            peer.position_index += 1;
+            rfc4::packet::Message::Position(rfc4::Position {
+                index: peer.position_index,
+                position_x: x,
+                position_y: y,
+                position_z: z,
+                rotation_w: 1.0,
+                ..Default::default()
+            })
+        }
+        MockPeerAction::Chat { message } => rfc4::packet::Message::Chat(rfc4::Chat {
+            message,
+            timestamp: ole_timestamp_now(),
+            forwarded_from: None,
+        }),
+        MockPeerAction::ProfileVersion { version } => {
+            rfc4::packet::Message::ProfileVersion(rfc4::AnnounceProfileVersion {
+                profile_version: version,
+            })
+        }
+        MockPeerAction::Scene { scene_id, data } => rfc4::packet::Message::Scene(rfc4::Scene {
+            scene_id,
+            data: data.into_bytes(),
+        }),
+    };
+    let unreliable = matches!(message, rfc4::packet::Message::Position(_));
+    send_from_peer(ws, peer, message, unreliable).await
+}
+
+async fn handle_client_packet(
+    ws: &mut Ws,
+    context: &SessionContext,
+    peers: &[MockPeer],
+    client_address: H160,
+    data: &[u8],
+) -> Result<(), String> {
+    let Ok(WsPacket {
+        message: Some(ws_packet::Message::PeerUpdateMessage(update)),
+    }) = WsPacket::decode(data)
+    else {
+        return Ok(());
+    };
+    let Some(message) = rfc4::Packet::decode(update.body.as_slice())
+        .ok()
+        .and_then(|packet| packet.message)
+    else {
+        tracing::warn!("mock comms: invalid rfc4 packet from {client_address:#x}");
+        return Ok(());
+    };
+
+    context.received.lock().unwrap().push(ReceivedPacket {
+        from: client_address,
+        message: message.clone(),
+    });
+
+    let rfc4::packet::Message::ProfileRequest(request) = message else {
+        return Ok(());
+    };
+    let Some((index, peer)) = peers.iter().enumerate().find(|(_, peer)| {
+        peer.joined && !peer.left && request.address.parse::<H160>().ok() == Some(peer.address)
+    }) else {
+        return Ok(());
+    };
+    let peer_script = &context.script.peers[index];
+    if !peer_script.answer_profile_requests {
+        return Ok(());
+    }
+
+    let address = format!("{:#x}", peer.address);
+    let profile = SerializedProfile {
+        user_id: Some(address.clone()),
+        name: peer_script.name.clone(),
+        version: peer_script.profile_version as i64,
+        eth_address: address,
+        ..Default::default()
+    };
+    let serialized_profile = serde_json::to_string(&profile).map_err(|err| err.to_string())?;
+    send_from_peer(
+        ws,
+        peer,
+        rfc4::packet::Message::ProfileResponse(rfc4::ProfileResponse {
+            serialized_profile,
+            base_url: String::new(),
+        }),
+        false,
+    )
+    .await
+}
+
+async fn run_archipelago(mut ws: Ws, context: &SessionContext) -> Result<(), String> {
+    let request = match recv_handshake::<ClientPacket>(&mut ws).await?.message {
+        Some(client_packet::Message::ChallengeRequest(request)) => request,
+        other => return Err(format!("expected ChallengeRequest, got {other:?}")),
+    };
+
+    let challenge = new_challenge();
+    send(
+        &mut ws,
+        &ServerPacket {
+            message: Some(server_packet::Message::ChallengeResponse(
+                ChallengeResponseMessage {
+                    challenge_to_sign: challenge.clone(),
+                    already_connected: false,
+                },
+            )),
+        },
+    )
+    .await?;
+
+    match recv_handshake::<ClientPacket>(&mut ws).await?.message {
+        Some(client_packet::Message::SignedChallenge(signed)) => {
+            check_signed_challenge(&signed.auth_chain_json, &challenge)?
+        }
+        other => return Err(format!("expected SignedChallenge, got {other:?}")),
+    }
+
+    send(
+        &mut ws,
+        &ServerPacket {
+            message: Some(server_packet::Message::Welcome(WelcomeMessage {
+                peer_id: request.address.clone(),
+            })),
+        },
+    )
+    .await?;
+
+    // Like the real server, the island comes once the client reported its position
+    loop {
+        match recv_handshake::<ClientPacket>(&mut ws).await?.message {
+            Some(client_packet::Message::Heartbeat(_)) => break,
+            other => tracing::debug!("mock comms: ignoring {other:?} before the first heartbeat"),
+        }
+    }
+    send(
+        &mut ws,
+        &ServerPacket {
+            message: Some(server_packet::Message::IslandChanged(
+                IslandChangedMessage {
+                    island_id: MOCK_ISLAND_ID.to_string(),
+                    conn_str: format!("livekit:ws://{}?access_token=mock", context.server_address),
+                    ..Default::default()
+                },
+            )),
+        },
+    )
+    .await?;
+    tracing::debug!(
+        "mock comms: {} assigned to {MOCK_ISLAND_ID}",
+        request.address
+    );
+
+    // heartbeats only
+    while recv(&mut ws).await.is_some() {}
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dcl::components::proto_components::kernel::comms::rfc5;
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+    fn script() -> MockCommsScript {
+        serde_json::from_str(
+            r#"{
+                "peers": [
+                    { "name": "alice", "actions": [
+                        { "type": "wait", "ms": 10 },
+                        { "type": "chat", "message": "hi" }
+                    ] },
+                    { "name": "bob", "join_after_ms": 20, "answer_profile_requests": false, "actions": [
+                        { "type": "position", "x": 1.0, "y": 0.0, "z": 2.0 },
+                        { "type": "leave" },
+                        { "type": "chat", "message": "never sent" }
+                    ] }
+                ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn scripts_are_flattened_into_a_timeline() {
+        let timeline = build_timeline(&script().peers);
+        let summary = timeline
+            .iter()
+            .map(|event| (event.at.as_millis(), event.peer, event.event.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    10,
+                    0,
+                    PeerEvent::Action(MockPeerAction::Chat {
+                        message: "hi".to_string()
+                    })
+                ),
+                (20, 1, PeerEvent::Join),
+                (
+                    20,
+                    1,
+                    PeerEvent::Action(MockPeerAction::Position {
+                        x: 1.0,
+                        y: 0.0,
+                        z: 2.0
+                    })
+                ),
+                (20, 1, PeerEvent::Action(MockPeerAction::Leave)),
+            ]
+        );
+    }
+
+    async fn next_packet(
+        ws: &mut WebSocketStream<impl tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin>,
+    ) -> ws_packet::Message {
+        loop {
+            let message = tokio::time::timeout(Duration::from_secs(5), ws.next())
+                .await
+                .expect("timed out waiting for the mock server")
+                .unwrap()
+                .unwrap();
+            if let Message::Binary(data) = message {
+                return WsPacket::decode(data.as_slice()).unwrap().message.unwrap();
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn ws_room_plays_the_script() {
+        let server = MockCommsServer::start(script(), &Handle::current()).unwrap();
+        let url = server
+            .adapter()
+            .strip_prefix("ws-room:")
+            .unwrap()
+            .to_string();
+        let mut request = url.into_client_request().unwrap();
+        request
+            .headers_mut()
+            .insert(SEC_WEBSOCKET_PROTOCOL, HeaderValue::from_static("rfc5"));
+        let (mut ws, response) = tokio_tungstenite::connect_async(request).await.unwrap();
+        assert_eq!(response.headers()[SEC_WEBSOCKET_PROTOCOL], "rfc5");
+
+        let client = H160::from_low_u64_be(42);
+        let send_ws = |message: ws_packet::Message| {
+            Message::Binary(
+                WsPacket {
+                    message: Some(message),
+                }
+                .encode_to_vec(),
+            )
+        };
+        ws.send(send_ws(ws_packet::Message::PeerIdentification(
+            rfc5::WsIdentification {
+                address: format!("{client:#x}"),
+            },
+        )))
+        .await
+        .unwrap();
+        let ws_packet::Message::ChallengeMessage(challenge) = next_packet(&mut ws).await else {
+            panic!("expected a challenge");
+        };
+        let auth_chain_json = serde_json::json!([
+            { "type": "SIGNER", "payload": format!("{client:#x}"), "signature": "" },
+            { "type": "ECDSA_SIGNED_ENTITY", "payload": challenge.challenge_to_sign, "signature": "0x00" }
+        ])
+        .to_string();
+        ws.send(send_ws(ws_packet::Message::SignedChallengeForServer(
+            rfc5::WsSignedChallenge { auth_chain_json },
+        )))
+        .await
+        .unwrap();
+
+        let ws_packet::Message::WelcomeMessage(welcome) = next_packet(&mut ws).await else {
+            panic!("expected the welcome");
+        };
+        let alice = format!("{:#x}", H160::from_low_u64_be(0xdc1_0000));
+        assert_eq!(welcome.alias, 3);
+        assert_eq!(
+            welcome.peer_identities.into_iter().collect::<Vec<_>>(),
+            vec![(1, alice.clone())]
+        );
+
+        let mut updates = Vec::new();
+        loop {
+            match next_packet(&mut ws).await {
+                ws_packet::Message::PeerUpdateMessage(update) => {
+                    let message = rfc4::Packet::decode(update.body.as_slice())
+                        .unwrap()
+                        .message
+                        .unwrap();
+                    updates.push((update.from_alias, message));
+                }
+                ws_packet::Message::PeerJoinMessage(join) => assert_eq!(join.alias, 2),
+                ws_packet::Message::PeerLeaveMessage(leave) => {
+                    assert_eq!(leave.alias, 2);
+                    break;
+                }
+                other => panic!("unexpected {other:?}"),
+            }
+        }
+        assert!(matches!(
+            updates[0],
+            (1, rfc4::packet::Message::ProfileVersion(_))
+        ));
+        assert!(
+            matches!(&updates[1], (1, rfc4::packet::Message::Chat(chat)) if chat.message == "hi")
+        );
+        assert!(matches!(
+            updates[2],
+            (2, rfc4::packet::Message::ProfileVersion(_))
+        ));
+        assert!(matches!(
+            updates[3],
+            (2, rfc4::packet::Message::Position(_))
+        ));
+        assert_eq!(updates.len(), 4);
+
+        let profile_request = |address: &str| {
+            let body = rfc4::Packet {
+                message: Some(rfc4::packet::Message::ProfileRequest(
+                    rfc4::ProfileRequest {
+                        address: address.to_string(),
+                        profile_version: 0,
+                    },
+                )),
+                protocol_version: 100,
+            }
+            .encode_to_vec();
+            send_ws(ws_packet::Message::PeerUpdateMessage(WsPeerUpdate {
+                from_alias: 3,
+                body,
+                unreliable: false,
+            }))
+        };
+        ws.send(profile_request(&alice)).await.unwrap();
+        let ws_packet::Message::PeerUpdateMessage(update) = next_packet(&mut ws).await else {
+            panic!("expected the profile response");
+        };
+        let Some(rfc4::packet::Message::ProfileResponse(response)) =
+            rfc4::Packet::decode(update.body.as_slice())
+                .unwrap()
+                .message
+        else {
+            panic!("expected the profile response");
+        };
+        let profile: SerializedProfile =
+            serde_json::from_str(&response.serialized_profile).unwrap();
+        assert_eq!(profile.name, "alice");
+        assert_eq!(profile.eth_address, alice);
+
+        let received = server.received_packets();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].from, client);
+    }
+}
+
+/// End to end: a `WebSocketRoom` and a `MessageProcessor` wired like
+/// `CommunicationManager` does, connected to the mock server. They need the engine
+/// (avatars, `DclGlobal`), so they run as `#[itest]`s polling inside a single frame.
+mod message_processor_itests {
+    use std::time::{Duration, Instant};
+
+    use ethers_core::types::H160;
+    use ethers_signers::LocalWallet;
+    use godot::prelude::*;
+    use rand::thread_rng;
+
+    use super::{MockCommsScript, MockCommsServer};
+    use crate::{
+        auth::auth_identity::create_local_ephemeral,
+        avatars::avatar_scene::AvatarScene,
+        comms::{
+            adapter::{
+                adapter_trait::Adapter, message_processor::MessageProcessor, ws_room::WebSocketRoom,
+            },
+            consts::INACTIVE_PEER_THRESHOLD_SECS,
+        },
+        dcl::components::proto_components::kernel::comms::rfc4,
+        framework::TestContext,
+        godot_classes::dcl_global::DclGlobal,
+        scene_runner::tokio_runtime::TokioRuntime,
+    };
+
+    const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+    struct MockSession {
+        server: MockCommsServer,
+        room: WebSocketRoom,
+        processor: MessageProcessor,
+        avatars: Gd<AvatarScene>,
+    }
+
+    impl MockSession {
+        fn start(script: &str) -> Self {
+            let script: MockCommsScript = serde_json::from_str(script).unwrap();
+            let runtime = TokioRuntime::static_clone_handle().expect("tokio runtime");
+            let server = MockCommsServer::start(script, &runtime).unwrap();
+
+            let auth_chain = create_local_ephemeral(&LocalWallet::new(&mut thread_rng()));
+            let avatars = DclGlobal::singleton().bind().get_avatars();
+            let processor = MessageProcessor::new(auth_chain.signer(), None, avatars.clone());
+            let url = server.adapter();
+            let mut room = WebSocketRoom::new(
+                url.strip_prefix("ws-room:").unwrap(),
+                "mock-ws-room".to_string(),
+                auth_chain,
+                None,
+                avatars.clone(),
+            );
+            room.set_message_processor_sender(processor.get_message_sender());
+
+            Self {
+                server,
+                room,
+                processor,
+                avatars,
+            }
+        }
+
+        /// Polls the room and the processor (forwarding what the processor sends
+        /// back to the room) until `done` or the timeout.
+        fn poll_until(
+            &mut self,
+            timeout: Duration,
+            mut done: impl FnMut(&mut MessageProcessor) -> bool,
+        ) -> bool {
+            let deadline = Instant::now() + timeout;
+            loop {
+                self.room.poll();
+                self.processor.poll();
+                for outgoing in self.processor.consume_outgoing_messages() {
+                    self.room.send_rfc4(outgoing.packet, outgoing.unreliable);
+                }
+                if done(&mut self.processor) {
+                    return true;
+                }
+                if Instant::now() > deadline {
+                    return false;
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        }
+
+        fn peer_name(&self, address: H160) -> Option<String> {
+            self.processor
+                .get_peer_room_info()
+                .into_iter()
+                .find(|(peer, _, _)| *peer == address)
+                .map(|(_, _, name)| name)
+        }
+    }
+
+    impl Drop for MockSession {
+        fn drop(&mut self) {
+            self.room.clean();
+            self.processor.clean();
+        }
+    }
+
+    fn mock_address(index: u64) -> H160 {
+        H160::from_low_u64_be(0xdc1_0000 + index)
+    }
+
+    #[godot::test::itest]
+    fn test_mock_comms_scripted_peers_become_avatars(_ctx: &TestContext) {
+        let mut session = MockSession::start(
+            r#"{ "peers": [
+                { "name": "alice", "actions": [
+                    { "type": "position", "x": 8.0, "y": 0.0, "z": 8.0 },
+                    { "type": "chat", "message": "hi from alice" }
+                ] },
+                { "name": "bob", "join_after_ms": 200, "actions": [
+                    { "type": "position", "x": 4.0, "y": 0.0, "z": 4.0 }
+                ] }
+            ] }"#,
+        );
+
+        let mut chats = Vec::new();
+        assert!(session.poll_until(CONNECT_TIMEOUT, |processor| {
+            chats.extend(processor.consume_chats());
+            processor.get_peer_room_info().len() == 2 && !chats.is_empty()
+        }));
+
+        for address in [mock_address(0), mock_address(1)] {
+            assert!(session
+                .avatars
+                .bind()
+                .get_avatar_by_address(GString::from(&format!("{address:#x}")))
+                .is_some());
+        }
+        assert_eq!(chats.len(), 1);
+        assert_eq!(chats[0].0, mock_address(0));
+        assert_eq!(chats[0].1, "mock-ws-room");
+        assert_eq!(chats[0].2.message, "hi from alice");
+    }
+
+    #[godot::test::itest]
+    fn test_mock_comms_profiles_are_fetched_from_the_peers(_ctx: &TestContext) {
+        let mut session = MockSession::start(
+            r#"{ "peers": [
+                { "name": "alice", "profile_version": 3, "actions": [
+                    { "type": "position", "x": 8.0, "y": 0.0, "z": 8.0 }
+                ] },
+                { "name": "silent", "answer_profile_requests": false, "actions": [
+                    { "type": "position", "x": 4.0, "y": 0.0, "z": 4.0 }
+                ] }
+            ] }"#,
+        );
+
+        let alice = mock_address(0);
+        let silent = mock_address(1);
+        assert!(session.poll_until(CONNECT_TIMEOUT, |processor| {
+            processor
+                .get_peer_room_info()
+                .iter()
+                .any(|(address, _, name)| *address == alice && name == "alice")
+        }));
+
+        // Asked for, but never answered: the peer stays without a profile
+        assert_eq!(session.peer_name(silent), Some(String::new()));
+        let silent_requested = session.server.received_packets().iter().any(|packet| {
+            matches!(
+                &packet.message,
+                rfc4::packet::Message::ProfileRequest(request)
+                    if request.address == format!("{silent:#x}")
+            )
+        });
+        assert!(silent_requested);
+    }
+
+    #[godot::test::itest]
+    fn test_mock_comms_quiet_peers_time_out(_ctx: &TestContext) {
+        let mut session = MockSession::start(
+            r#"{ "peers": [
+                { "name": "quiet", "answer_profile_requests": false, "actions": [
+                    { "type": "position", "x": 8.0, "y": 0.0, "z": 8.0 }
+                ] }
+            ] }"#,
+        );
+
+        let quiet = mock_address(0);
+        assert!(session.poll_until(CONNECT_TIMEOUT, |processor| {
+            !processor.get_peer_room_info().is_empty()
+        }));
+
+        // It never leaves the room, it just stops sending (not even profile responses)
+        let timeout = Duration::from_secs(INACTIVE_PEER_THRESHOLD_SECS + 3);
+        assert!(session.poll_until(timeout, |processor| {
+            processor.get_peer_room_info().is_empty()
+        }));
+        assert!(session
+            .avatars
+            .bind()
+            .get_avatar_by_address(GString::from(&format!("{quiet:#x}")))
+            .is_none());
+    }
+}
+
+/// A real `ArchipelagoManager` going through the handshake of the mock server
+/// until it is assigned an island.
+#[cfg(feature = "use_livekit")]
+mod archipelago_itests {
+    use std::time::{Duration, Instant};
+
+    use ethers_signers::LocalWallet;
+    use rand::thread_rng;
+
+    use super::{MockCommsScript, MockCommsServer, MOCK_ISLAND_ID};
+    use crate::{
+        auth::auth_identity::create_local_ephemeral,
+        comms::adapter::{archipelago::ArchipelagoManager, message_processor::MessageProcessor},
+        framework::TestContext,
+        godot_classes::dcl_global::DclGlobal,
+        scene_runner::tokio_runtime::TokioRuntime,
+    };
+
+    // The manager waits out its reconnect interval before dialing the first time
+    const ISLAND_TIMEOUT: Duration = Duration::from_secs(20);
+
+    #[godot::test::itest]
+    fn test_mock_comms_archipelago_assigns_an_island(_ctx: &TestContext) {
+        let runtime = TokioRuntime::static_clone_handle().expect("tokio runtime");
+        let server = MockCommsServer::start(MockCommsScript::default(), &runtime).unwrap();
+
+        let auth_chain = create_local_ephemeral(&LocalWallet::new(&mut thread_rng()));
+        let avatars = DclGlobal::singleton().bind().get_avatars();
+        let mut processor = MessageProcessor::new(auth_chain.signer(), None, avatars);
+        let url = server.archipelago_adapter();
+        let mut manager =
+            ArchipelagoManager::new(url.strip_prefix("archipelago:").unwrap(), auth_chain, None);
+        manager.set_shared_processor_sender(processor.get_message_sender());
+
+        let deadline = Instant::now() + ISLAND_TIMEOUT;
+        while manager.island_id().is_none() && Instant::now() < deadline {
+            manager.poll();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(manager.state_name(), "connected");
+        assert_eq!(manager.island_id(), Some(MOCK_ISLAND_ID));
+
+        manager.clean();
+        processor.clean();
+    }
+}
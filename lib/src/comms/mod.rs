@@ -1,6 +1,8 @@
 pub mod adapter;
+pub mod chat_history;
 pub mod communication_manager;
 mod consts;
+pub mod mock_server;
 pub use consts::truncate_utf8_safe;
 pub mod profile;
 #[cfg(feature = "use_pulse")]
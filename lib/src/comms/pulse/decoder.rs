@@ -303,7 +303,10 @@ impl PulseDecoder {
     /// Wallets of every subject currently in the interest set. Used by `PulseRoom::clean()` to
     /// flood synthetic `PeerLeft`s on teardown, so LiveKit-driven rendering resumes immediately.
     pub fn known_wallets(&self) -> Vec<H160> {
-        self.subjects.values().map(|s| s.wallet).collect()
+        // sorted so teardown is reproducible (replays compare the bridged traffic)
+        let mut wallets: Vec<H160> = self.subjects.values().map(|s| s.wallet).collect();
+        wallets.sort();
+        wallets
     }
 
     /// Decode one server message, advancing per-subject state and emitting downstream events.
@@ -5,10 +5,12 @@
 //! - `native` — the `pulse-enet` driver thread (rusty_enet, ENet-CSharp modified protocol)
 //! - [`decoder`] — quantized state → `rfc4::Movement` reconstruction + parcel grid
 //! - [`pulse_room`] — connection state machine, handshake, `MessageProcessor` bridging
+//! - [`recording`] — session capture at the byte seam (`--pulse-record`) and deterministic replay
 
 pub mod decoder;
 mod native;
 pub mod pulse_room;
+pub mod recording;
 pub mod transport;
 
 /// See `comms::consts::PULSE_ROOM_ID` (lives there, unconditional, so MessageProcessor's
@@ -29,9 +29,10 @@ use prost::Message as _;
 use tokio::sync::mpsc;
 
 use super::decoder::{from_movement, PulseDecoder, PulseEvent, PulseParcelGrid};
+use super::recording::PulseRecorder;
 use super::transport::{
-    self, PulseDisconnect, PulseDriverHandle, PulseFrame, PulseLink, PulseReliability, PulseStatus,
-    PulseTransportConfig,
+    self, PulseDisconnect, PulseDriverChannels, PulseDriverHandle, PulseFrame, PulseLink,
+    PulseReliability, PulseStatus, PulseTransportConfig,
 };
 use super::PULSE_ROOM_ID;
 use crate::auth::ephemeral_auth_chain::EphemeralAuthChain;
@@ -120,6 +121,8 @@ pub struct PulseRoom {
     /// EVERY packet fail at the server's full send rate — a Sentry quota burst if warned
     /// per packet. Reset on transport loss so each new connection gets one report.
     warned_decode_failed: bool,
+    /// `--pulse-record` capture of the traffic crossing the link (see `recording`).
+    recorder: Option<PulseRecorder>,
 }
 
 impl PulseRoom {
@@ -146,9 +149,36 @@ impl PulseRoom {
             last_announced_profile_version: None,
             warned_out_of_grid: false,
             warned_decode_failed: false,
+            recorder: None,
         }
     }
 
+    /// A room already past the handshake, wired to in-memory channels instead of a driver —
+    /// the caller plays the driver/server side (`recording::replay`).
+    pub fn new_established(
+        processor_sender: mpsc::Sender<IncomingMessage>,
+    ) -> (Self, PulseDriverChannels) {
+        let mut room = Self::new(
+            PulseTransportConfig {
+                host: "replay.invalid".into(),
+                port: 0,
+            },
+            processor_sender,
+        );
+        let (link, channels) = transport::pulse_channels(LINK_CAPACITY);
+        room.link = Some(link);
+        room.state = Connection::Established;
+        room.established_this_attempt = true;
+        (room, channels)
+    }
+
+    /// Starts capturing the traffic of this room (and its future reconnections) to `path`.
+    pub fn start_recording(&mut self, path: &std::path::Path) -> std::io::Result<()> {
+        self.recorder = Some(PulseRecorder::create(path)?);
+        tracing::info!("pulse: recording to {}", path.display());
+        Ok(())
+    }
+
     pub fn is_established(&self) -> bool {
         matches!(self.state, Connection::Established)
     }
@@ -182,6 +212,9 @@ impl PulseRoom {
         self.drain_status(now, &mut events);
         self.drive_connection(now, identity);
         self.drain_inbound(now, &mut events);
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.flush();
+        }
         events
     }
 
@@ -193,6 +226,9 @@ impl PulseRoom {
         self.link = None;
         self.driver = None;
         self.state = Connection::Dead;
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.flush();
+        }
     }
 
     fn flood_peer_left(&mut self) {
@@ -335,7 +371,11 @@ impl PulseRoom {
             message: Some(message),
         }
         .encode_to_vec();
-        let _ = link.outbound.try_send(PulseFrame { bytes, reliability });
+        let frame = PulseFrame { bytes, reliability };
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.record_outbound(&frame);
+        }
+        let _ = link.outbound.try_send(frame);
     }
 
     fn send_to_processor(&self, address: H160, message: MessageType) {
@@ -362,6 +402,9 @@ impl PulseRoom {
     /// status-then-close sequence idempotent.
     fn drain_status(&mut self, now: Instant, events: &mut Vec<PulseRoomEvent>) {
         while let Some(status) = self.link.as_mut().map(|link| link.status.try_recv()) {
+            if let (Some(recorder), Ok(status)) = (self.recorder.as_mut(), &status) {
+                recorder.record_status(status);
+            }
             match status {
                 Ok(PulseStatus::Connecting) => tracing::debug!("pulse: connecting"),
                 Ok(PulseStatus::Connected) => {
@@ -520,6 +563,9 @@ impl PulseRoom {
     /// Decode + bridge inbound `ServerMessage` bytes into the shared `MessageProcessor`.
     fn drain_inbound(&mut self, now: Instant, events: &mut Vec<PulseRoomEvent>) {
         while let Some(Ok(bytes)) = self.link.as_mut().map(|link| link.inbound.try_recv()) {
+            if let Some(recorder) = self.recorder.as_mut() {
+                recorder.record_inbound(&bytes);
+            }
             let decoded = match pulse::ServerMessage::decode(bytes.as_slice()) {
                 Ok(message) => self.decoder.handle(message),
                 Err(err) => {
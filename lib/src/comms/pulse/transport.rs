@@ -28,14 +28,14 @@ pub enum PulseReliability {
 }
 
 /// One outbound unit of work: an already-encoded `ClientMessage` plus how to deliver it.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PulseFrame {
     pub bytes: Vec<u8>,
     pub reliability: PulseReliability,
 }
 
 /// Connection lifecycle, surfaced from the driver to the protocol layer.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum PulseStatus {
     Connecting,
     Connected,
@@ -116,6 +116,30 @@ impl PulseDisconnect {
         }
     }
 
+    /// Inverse of [`Self::from_code`] (recordings store the code).
+    pub fn code(self) -> u32 {
+        match self {
+            Self::None => 0,
+            Self::Graceful => 1,
+            Self::AuthTimeout => 2,
+            Self::AuthFailed => 3,
+            Self::DuplicateSession => 4,
+            Self::Banned => 5,
+            Self::ServerFull => 6,
+            Self::PreAuthIpLimit => 7,
+            Self::PreAuthBudget => 8,
+            Self::InputRateExceeded => 9,
+            Self::DiscreteEventRateExceeded => 10,
+            Self::InvalidInputField => 11,
+            Self::InvalidEmoteField => 12,
+            Self::InvalidTeleportField => 13,
+            Self::HandshakeReplayRejected => 14,
+            Self::InvalidHandshakeField => 15,
+            Self::PacketCorrupted => 16,
+            Self::Unknown(code) => code,
+        }
+    }
+
     /// Whether reconnecting could plausibly succeed. Only transient, server-side, or
     /// too-slow-this-time reasons are retryable; auth/ban/eviction/misbehaviour reasons (and any
     /// unrecognised code) are terminal — reconnecting against them just loops.
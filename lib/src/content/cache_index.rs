@@ -0,0 +1,331 @@
+//! Persisted index of the content cache folder.
+//!
+//! The `ResourceProvider` keeps its LRU bookkeeping in memory and mirrors every
+//! change into a [`CacheIndexStore`], so the next session can restore sizes and
+//! access order without walking the cache folder. The default store is an
+//! append-only text log that is compacted when it grows too much.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+pub const CACHE_INDEX_FILE_NAME: &str = "cache-index.log";
+
+const HEADER: &str = "DCLCACHEIDX 1";
+
+/// Touches aren't critical, losing the last ones only makes the LRU order
+/// slightly older, so they're flushed in batches.
+const MAX_UNFLUSHED_TOUCHES: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheIndexEntry {
+    /// Path relative to the cache folder (absolute if it's outside)
+    pub file_name: String,
+    /// See `cache_file_base_name`
+    pub base_name: String,
+    pub file_size: i64,
+    /// Unix time in ms, strictly increasing within the index
+    pub last_accessed: u64,
+}
+
+pub enum CacheIndexOp<'a> {
+    Put(&'a CacheIndexEntry),
+    Touch {
+        file_name: &'a str,
+        last_accessed: u64,
+    },
+    Remove {
+        file_name: &'a str,
+    },
+}
+
+#[derive(Debug)]
+pub enum CacheIndexError {
+    /// First run, or the index was deleted
+    Missing,
+    Corrupt(String),
+    Io(io::Error),
+}
+
+impl fmt::Display for CacheIndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheIndexError::Missing => write!(f, "cache index not found"),
+            CacheIndexError::Corrupt(reason) => write!(f, "cache index is corrupt: {reason}"),
+            CacheIndexError::Io(err) => write!(f, "cache index io error: {err}"),
+        }
+    }
+}
+
+pub trait CacheIndexStore: Send + Sync {
+    /// Entries left by the previous session.
+    fn load(&self) -> Result<Vec<CacheIndexEntry>, CacheIndexError>;
+    /// Appends a change, errors are logged and ignored (the index is rebuilt
+    /// from the folder if it ends up unreadable).
+    fn record(&self, op: CacheIndexOp<'_>);
+    /// Records written since the last `rewrite`, used to decide when to compact.
+    fn pending_records(&self) -> usize;
+    /// Replaces the whole index with `entries`.
+    fn rewrite(&self, entries: &[CacheIndexEntry]);
+}
+
+struct LogWriter {
+    file: Option<BufWriter<File>>,
+    records: usize,
+    unflushed_touches: usize,
+}
+
+/// `CacheIndexStore` backed by a line-based log:
+/// ```text
+/// DCLCACHEIDX 1
+/// P\t<last_accessed>\t<file_size>\t<base_name>\t<file_name>
+/// T\t<last_accessed>\t<file_name>
+/// R\t<file_name>
+/// ```
+pub struct AppendLogCacheIndex {
+    path: PathBuf,
+    writer: Mutex<LogWriter>,
+}
+
+impl AppendLogCacheIndex {
+    pub fn new(cache_folder: &Path) -> Self {
+        Self {
+            path: cache_folder.join(CACHE_INDEX_FILE_NAME),
+            writer: Mutex::new(LogWriter {
+                file: None,
+                records: 0,
+                unflushed_touches: 0,
+            }),
+        }
+    }
+
+    fn open_append(&self) -> io::Result<BufWriter<File>> {
+        let file = OpenOptions::new().append(true).open(&self.path)?;
+        Ok(BufWriter::new(file))
+    }
+
+    fn write_all(&self, entries: &[CacheIndexEntry]) -> io::Result<()> {
+        let tmp_path = self.path.with_extension("log.tmp");
+        {
+            let mut file = BufWriter::new(File::create(&tmp_path)?);
+            writeln!(file, "{HEADER}")?;
+            for entry in entries {
+                write_op(&mut file, &CacheIndexOp::Put(entry))?;
+            }
+            file.into_inner()
+                .map_err(|err| err.into_error())?
+                .sync_all()?;
+        }
+        fs::rename(&tmp_path, &self.path)
+    }
+}
+
+impl CacheIndexStore for AppendLogCacheIndex {
+    fn load(&self) -> Result<Vec<CacheIndexEntry>, CacheIndexError> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Err(CacheIndexError::Missing)
+            }
+            Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+                return Err(CacheIndexError::Corrupt("not valid utf-8".to_string()))
+            }
+            Err(err) => return Err(CacheIndexError::Io(err)),
+        };
+        let (entries, records) = parse_index_log(&content).map_err(CacheIndexError::Corrupt)?;
+
+        let mut writer = self.writer.lock().unwrap();
+        writer.file = Some(self.open_append().map_err(CacheIndexError::Io)?);
+        writer.records = records;
+        Ok(entries.into_values().collect())
+    }
+
+    fn record(&self, op: CacheIndexOp<'_>) {
+        let mut writer = self.writer.lock().unwrap();
+        if writer.file.is_none() {
+            match self.open_append() {
+                Ok(file) => writer.file = Some(file),
+                Err(err) => {
+                    tracing::warn!("cache index not writable: {err}");
+                    return;
+                }
+            }
+        }
+
+        let is_touch = matches!(op, CacheIndexOp::Touch { .. });
+        let file = writer.file.as_mut().expect("opened above");
+        let mut result = write_op(file, &op);
+        writer.records += 1;
+        if is_touch && writer.unflushed_touches < MAX_UNFLUSHED_TOUCHES {
+            writer.unflushed_touches += 1;
+        } else if result.is_ok() {
+            writer.unflushed_touches = 0;
+            result = writer.file.as_mut().expect("opened above").flush();
+        }
+
+        if let Err(err) = result {
+            tracing::warn!("failed to write the cache index: {err}");
+            writer.file = None;
+        }
+    }
+
+    fn pending_records(&self) -> usize {
+        self.writer.lock().unwrap().records
+    }
+
+    fn rewrite(&self, entries: &[CacheIndexEntry]) {
+        let mut writer = self.writer.lock().unwrap();
+        // the old log is replaced, pending touches are already in `entries`
+        writer.file = None;
+        writer.unflushed_touches = 0;
+        if let Err(err) = self.write_all(entries) {
+            tracing::warn!("failed to rewrite the cache index: {err}");
+            return;
+        }
+        writer.records = 0;
+        writer.file = self.open_append().ok();
+    }
+}
+
+fn write_op(writer: &mut impl Write, op: &CacheIndexOp<'_>) -> io::Result<()> {
+    match op {
+        CacheIndexOp::Put(entry) => writeln!(
+            writer,
+            "P\t{}\t{}\t{}\t{}",
+            entry.last_accessed, entry.file_size, entry.base_name, entry.file_name
+        ),
+        CacheIndexOp::Touch {
+            file_name,
+            last_accessed,
+        } => writeln!(writer, "T\t{last_accessed}\t{file_name}"),
+        CacheIndexOp::Remove { file_name } => writeln!(writer, "R\t{file_name}"),
+    }
+}
+
+/// Replays the log, returns the live entries by file name and the number of
+/// records read. A last line without its newline is a write cut by a crash and
+/// is ignored, anything else that doesn't parse makes the whole index corrupt.
+pub fn parse_index_log(content: &str) -> Result<(HashMap<String, CacheIndexEntry>, usize), String> {
+    let mut lines = content.split_inclusive('\n');
+    if lines.next().map(str::trim_end) != Some(HEADER) {
+        return Err("unknown header".to_string());
+    }
+
+    let mut entries = HashMap::new();
+    let mut records = 0;
+    for (index, line) in lines.enumerate() {
+        let Some(line) = line.strip_suffix('\n') else {
+            break;
+        };
+        let line_number = index + 2;
+        let invalid = || format!("invalid record at line {line_number}");
+
+        let mut fields = line.split('\t');
+        match fields.next() {
+            Some("P") => {
+                let (Some(last_accessed), Some(file_size), Some(base_name), Some(file_name)) =
+                    (fields.next(), fields.next(), fields.next(), fields.next())
+                else {
+                    return Err(invalid());
+                };
+                let entry = CacheIndexEntry {
+                    file_name: file_name.to_string(),
+                    base_name: base_name.to_string(),
+                    file_size: file_size.parse().map_err(|_| invalid())?,
+                    last_accessed: last_accessed.parse().map_err(|_| invalid())?,
+                };
+                entries.insert(entry.file_name.clone(), entry);
+            }
+            Some("T") => {
+                let (Some(last_accessed), Some(file_name)) = (fields.next(), fields.next()) else {
+                    return Err(invalid());
+                };
+                let last_accessed = last_accessed.parse().map_err(|_| invalid())?;
+                if let Some(entry) = entries.get_mut(file_name) {
+                    entry.last_accessed = last_accessed;
+                }
+            }
+            Some("R") => {
+                let Some(file_name) = fields.next() else {
+                    return Err(invalid());
+                };
+                entries.remove(file_name);
+            }
+            _ => return Err(invalid()),
+        }
+        if fields.next().is_some() {
+            return Err(invalid());
+        }
+        records += 1;
+    }
+
+    Ok((entries, records))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(file_name: &str, file_size: i64, last_accessed: u64) -> CacheIndexEntry {
+        CacheIndexEntry {
+            file_name: file_name.to_string(),
+            base_name: file_name.split('.').next().unwrap().to_string(),
+            file_size,
+            last_accessed,
+        }
+    }
+
+    #[test]
+    fn test_log_replay() {
+        let a = entry("bafka.scn", 10, 1);
+        let b = entry("bafkb", 20, 2);
+        let mut log = format!("{HEADER}\n").into_bytes();
+        write_op(&mut log, &CacheIndexOp::Put(&a)).unwrap();
+        write_op(&mut log, &CacheIndexOp::Put(&b)).unwrap();
+        write_op(
+            &mut log,
+            &CacheIndexOp::Touch {
+                file_name: "bafka.scn",
+                last_accessed: 3,
+            },
+        )
+        .unwrap();
+        write_op(&mut log, &CacheIndexOp::Remove { file_name: "bafkb" }).unwrap();
+        // torn write of the last record
+        log.extend_from_slice(b"P\t4\t3");
+
+        let (entries, records) = parse_index_log(std::str::from_utf8(&log).unwrap()).unwrap();
+        assert_eq!(records, 4);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries["bafka.scn"], entry("bafka.scn", 10, 3));
+    }
+
+    #[test]
+    fn test_corrupt_log() {
+        assert!(parse_index_log("").is_err());
+        assert!(parse_index_log("DCLCACHEIDX 0\n").is_err());
+        assert!(parse_index_log(&format!("{HEADER}\nP\tx\t1\tbafk\tbafk\n")).is_err());
+        assert!(parse_index_log(&format!("{HEADER}\nX\tbafk\n")).is_err());
+        assert!(parse_index_log(&format!("{HEADER}\nR\tbafk\textra\n")).is_err());
+    }
+
+    #[test]
+    fn test_rewrite_and_load() {
+        let dir = std::env::temp_dir().join(format!("dcl-cache-index-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let index = AppendLogCacheIndex::new(&dir);
+
+        index.rewrite(&[entry("bafka", 1, 1), entry("bafkb", 2, 2)]);
+        index.record(CacheIndexOp::Remove { file_name: "bafka" });
+        assert_eq!(index.pending_records(), 1);
+
+        let reopened = AppendLogCacheIndex::new(&dir);
+        assert_eq!(reopened.load().unwrap(), vec![entry("bafkb", 2, 2)]);
+        assert_eq!(reopened.pending_records(), 3);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
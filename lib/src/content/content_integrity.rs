@@ -0,0 +1,176 @@
+//! Checks content against the CID it was requested by.
+//!
+//! Only sha2-256 CIDs are checked:
+//!   - raw (`bafkrei…`): the multihash is the digest of the bytes.
+//!   - dag-pb (`Qm…`, CIDv0): the digest of a UnixFS node wrapping the bytes. Files
+//!     bigger than one UnixFS chunk are a DAG of nodes, those can't be checked
+//!     without rebuilding it and are reported as unverifiable.
+//!
+//! Anything else (url hashes, preview `b64-` hashes, processed files) is unverifiable.
+
+use cid::Cid;
+use sha2::{Digest, Sha256};
+
+const SHA2_256: u64 = 0x12;
+const RAW: u64 = 0x55;
+const DAG_PB: u64 = 0x70;
+
+/// Default chunk size of the UnixFS importer
+const UNIXFS_CHUNK_SIZE: usize = 262_144;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegrityCheck {
+    Valid,
+    Mismatch,
+    Unverifiable,
+}
+
+pub enum ContentVerifier {
+    Raw {
+        hasher: Sha256,
+        expected: Vec<u8>,
+    },
+    UnixFsLeaf {
+        /// None once the content is bigger than a single chunk
+        data: Option<Vec<u8>>,
+        expected: Vec<u8>,
+    },
+}
+
+impl ContentVerifier {
+    /// None when the hash isn't a CID that can be checked.
+    pub fn for_hash(hash: &str) -> Option<Self> {
+        let cid = Cid::try_from(hash).ok()?;
+        let multihash = cid.hash();
+        if multihash.code() != SHA2_256 {
+            return None;
+        }
+        let expected = multihash.digest().to_vec();
+        match cid.codec() {
+            RAW => Some(Self::Raw {
+                hasher: Sha256::new(),
+                expected,
+            }),
+            DAG_PB => Some(Self::UnixFsLeaf {
+                data: Some(Vec::new()),
+                expected,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn update(&mut self, chunk: &[u8]) {
+        match self {
+            Self::Raw { hasher, .. } => hasher.update(chunk),
+            Self::UnixFsLeaf { data, .. } => {
+                if let Some(buffer) = data {
+                    if buffer.len() + chunk.len() > UNIXFS_CHUNK_SIZE {
+                        *data = None;
+                    } else {
+                        buffer.extend_from_slice(chunk);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn finish(self) -> IntegrityCheck {
+        let (digest, expected) = match self {
+            Self::Raw { hasher, expected } => (hasher.finalize(), expected),
+            Self::UnixFsLeaf { data, expected } => {
+                let Some(data) = data else {
+                    return IntegrityCheck::Unverifiable;
+                };
+                (Sha256::digest(unixfs_leaf_node(&data)), expected)
+            }
+        };
+        if digest.as_slice() == expected.as_slice() {
+            IntegrityCheck::Valid
+        } else {
+            IntegrityCheck::Mismatch
+        }
+    }
+}
+
+pub fn verify_content(hash: &str, bytes: &[u8]) -> IntegrityCheck {
+    let Some(mut verifier) = ContentVerifier::for_hash(hash) else {
+        return IntegrityCheck::Unverifiable;
+    };
+    verifier.update(bytes);
+    verifier.finish()
+}
+
+/// dag-pb `PBNode { Data: UnixFS { Type: File, Data: data, filesize } }`
+fn unixfs_leaf_node(data: &[u8]) -> Vec<u8> {
+    let mut unixfs = vec![0x08, 0x02];
+    if !data.is_empty() {
+        unixfs.push(0x12);
+        push_varint(&mut unixfs, data.len() as u64);
+        unixfs.extend_from_slice(data);
+    }
+    unixfs.push(0x18);
+    push_varint(&mut unixfs, data.len() as u64);
+
+    let mut node = vec![0x0a];
+    push_varint(&mut node, unixfs.len() as u64);
+    node.extend_from_slice(&unixfs);
+    node
+}
+
+fn push_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buffer.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use multihash_codetable::MultihashDigest;
+
+    #[test]
+    fn test_raw_cid() {
+        let bytes = b"some scene content";
+        let hash = Cid::new_v1(RAW, multihash_codetable::Code::Sha2_256.digest(bytes)).to_string();
+
+        let mut verifier = ContentVerifier::for_hash(&hash).unwrap();
+        for chunk in bytes.chunks(5) {
+            verifier.update(chunk);
+        }
+        assert_eq!(verifier.finish(), IntegrityCheck::Valid);
+        assert_eq!(
+            verify_content(&hash, b"truncated"),
+            IntegrityCheck::Mismatch
+        );
+    }
+
+    #[test]
+    fn test_unixfs_cid() {
+        // `ipfs add` of "hello world\n" and of an empty file
+        let hello = "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o";
+        assert_eq!(
+            verify_content(hello, b"hello world\n"),
+            IntegrityCheck::Valid
+        );
+        assert_eq!(
+            verify_content(hello, b"hello world"),
+            IntegrityCheck::Mismatch
+        );
+        assert_eq!(
+            verify_content("QmbFMke1KXqnYyBBWxB74N4c5SBnJMVAiMNRcGu6x1AwQH", b""),
+            IntegrityCheck::Valid
+        );
+        assert_eq!(
+            verify_content(hello, &vec![0; UNIXFS_CHUNK_SIZE + 1]),
+            IntegrityCheck::Unverifiable
+        );
+    }
+
+    #[test]
+    fn test_unverifiable_hashes() {
+        assert!(ContentVerifier::for_hash("hashed_a1b2c3_q2").is_none());
+        assert!(ContentVerifier::for_hash("b64-L2hvbWUvdXNlci9zY2VuZQ").is_none());
+    }
+}
@@ -1870,6 +1870,27 @@ impl ContentProvider {
         promise
     }
 
+    /// Checks the cached content against its hashes in the background and
+    /// deletes the corrupt files.
+    #[func]
+    pub fn verify_cache_folder(&mut self) -> Gd<Promise> {
+        let (promise, get_promise) = Promise::make_to_async();
+        let resource_provider = self.resource_provider.clone();
+
+        TokioRuntime::spawn(async move {
+            let report = resource_provider.verify_cached_files().await;
+            tracing::info!(
+                "verify_cache_folder: {} valid, {} corrupt (deleted), {} skipped",
+                report.valid,
+                report.corrupt,
+                report.skipped
+            );
+            then_promise(get_promise, Ok(None));
+        });
+
+        promise
+    }
+
     #[func]
     pub fn set_cache_folder_max_size(&mut self, size: i64) {
         self.resource_provider.set_max_cache_size(size)
@@ -27,6 +27,10 @@ use super::super::{
     file_string::get_base_dir,
     texture::create_compressed_texture,
 };
+use super::{
+    document::GltfDocument, draco::decompress_draco_primitives,
+    meshopt_compression::decompress_meshopt_buffer_views, quantization::dequantize_attributes,
+};
 
 #[cfg(feature = "use_resource_tracking")]
 use crate::godot_classes::dcl_resource_tracker::{
@@ -916,17 +920,78 @@ pub(super) fn count_nodes(node: Gd<Node>) -> i32 {
     count
 }
 
+/// Where the decoded copy of the cached GLTF `file_path` is written. The cached file keeps the
+/// bytes of its hash, so the integrity check and the tracked cache size stay right.
+pub fn decoded_gltf_path(file_path: &str) -> String {
+    format!("{file_path}.decoded")
+}
+
+/// Rewrites the compressed (`EXT_meshopt_compression`, `KHR_draco_mesh_compression`) and
+/// quantized (`KHR_mesh_quantization`) data of the cached GLTF into plain buffer views and float
+/// accessors, and returns the path to load. External buffers are read from the cache, so the
+/// dependencies must be downloaded.
+///
+/// The result goes to `decoded_gltf_path` through a temporary file, so a concurrent load of
+/// the same hash never reads it half-written. An existing decoded copy (another load, or the
+/// asset server's gltf-transform pass) is used as the input.
+async fn decode_gltf_extensions_if_needed(
+    file_path: &str,
+    content_folder: &str,
+    dependencies: &[(String, String)],
+) -> Result<String, anyhow::Error> {
+    let buffer_paths: std::collections::HashMap<String, String> = dependencies
+        .iter()
+        .map(|(uri, hash)| (uri.clone(), cache_file_path(content_folder, hash)))
+        .collect();
+    let decoded_path = decoded_gltf_path(file_path);
+    let source_path = if tokio::fs::try_exists(&decoded_path).await.unwrap_or(false) {
+        decoded_path.clone()
+    } else {
+        file_path.to_string()
+    };
+    tokio::task::spawn_blocking(move || -> Result<String, anyhow::Error> {
+        let data = std::fs::read(&source_path)?;
+        let mut gltf = GltfDocument::parse(&data, |uri| {
+            buffer_paths
+                .get(uri)
+                .and_then(|path| std::fs::read(path).ok())
+        })?;
+        let mut decode = || -> Result<bool, anyhow::Error> {
+            // Meshopt first, the other passes read the buffer views it decodes
+            let meshopt = decompress_meshopt_buffer_views(&mut gltf)?;
+            let draco = decompress_draco_primitives(&mut gltf)?;
+            let quantization = dequantize_attributes(&mut gltf)?;
+            Ok(meshopt || draco || quantization)
+        };
+        let rewritten =
+            decode().map_err(|err| anyhow::anyhow!("Error decoding gltf {source_path}: {err}"))?;
+        if !rewritten {
+            return Ok(source_path);
+        }
+        // Leftover `.tmp` files are deleted by the cache folder scan
+        let tmp_path = format!("{decoded_path}.{}.tmp", uuid::Uuid::new_v4());
+        std::fs::write(&tmp_path, gltf.finish()?)?;
+        if let Err(err) = std::fs::rename(&tmp_path, &decoded_path) {
+            let _ = std::fs::remove_file(&tmp_path);
+            return Err(err.into());
+        }
+        Ok(decoded_path)
+    })
+    .await?
+}
+
 /// Common GLTF loading pipeline.
 ///
 /// This handles the shared logic for loading scenes, wearables, and emotes:
 /// 1. Download main GLTF file
 /// 2. Parse and download dependencies
-/// 3. Acquire Godot thread safety guard
-/// 4. Load GltfDocument
-/// 5. Post-process textures
-/// 6. Rotate node 180° Y
-/// 7. Call processor function for type-specific processing
-/// 8. Cleanup source file
+/// 3. Decode compressed and quantized meshes into a copy of the file
+/// 4. Acquire Godot thread safety guard
+/// 5. Load GltfDocument
+/// 6. Post-process textures
+/// 7. Rotate node 180° Y
+/// 8. Call processor function for type-specific processing
+/// 9. Cleanup source file and its decoded copy
 ///
 /// The processor function receives the loaded Node3D and should return
 /// a tuple of (result, file_size). The caller is responsible for cache registration.
@@ -1023,6 +1088,14 @@ where
         )));
     }
 
+    // Godot's importer can't read compressed or quantized meshes, decode them to a copy
+    let import_file_path = decode_gltf_extensions_if_needed(
+        &absolute_file_path,
+        &ctx.content_folder,
+        &dependencies_hash,
+    )
+    .await?;
+
     // Acquire thread safety guard for Godot API access
     let _thread_guard = GodotThreadSafetyGuard::acquire(&ctx.godot_single_thread)
         .await
@@ -1053,7 +1126,7 @@ where
         // truthy value when it wants the placeholder branch.
         new_gltf_state.set_additional_data("placeholder_image", &Variant::nil());
 
-        let file_path_gstr = GString::from(absolute_file_path.as_str());
+        let file_path_gstr = GString::from(import_file_path.as_str());
         let base_path_gstr = GString::from(ctx.content_folder.as_str());
         let err = new_gltf
             .append_from_file_ex(&file_path_gstr, &new_gltf_state.clone())
@@ -1127,6 +1200,9 @@ where
     ctx.resource_provider
         .try_delete_file_by_hash(&file_hash)
         .await;
+    if import_file_path != absolute_file_path {
+        let _ = tokio::fs::remove_file(&import_file_path).await;
+    }
 
     #[cfg(feature = "use_resource_tracking")]
     report_resource_loaded(&file_hash);
@@ -0,0 +1,522 @@
+//! In-memory glTF/GLB document used by the pre-import passes (Draco, meshopt, quantization)
+//! that rewrite extensions Godot's `GltfDocument` can't read into plain buffer views and
+//! accessors.
+
+use base64::Engine;
+use serde_json::{json, Value};
+
+pub const GLB_MAGIC: &[u8] = b"glTF";
+pub const GLB_HEADER_SIZE: usize = 12;
+pub const CHUNK_JSON: u32 = 0x4E4F_534A;
+pub const CHUNK_BIN: u32 = 0x004E_4942;
+
+/// Largest buffer the passes allocate. The sizes come from the (untrusted) JSON, and a failed
+/// allocation aborts the process instead of failing the load.
+pub const MAX_DECODED_SIZE: usize = 256 * 1024 * 1024;
+
+pub const ARRAY_BUFFER: u32 = 34962;
+pub const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+pub const BYTE: u64 = 5120;
+pub const UNSIGNED_BYTE: u64 = 5121;
+pub const SHORT: u64 = 5122;
+pub const UNSIGNED_SHORT: u64 = 5123;
+pub const UNSIGNED_INT: u64 = 5125;
+pub const FLOAT: u64 = 5126;
+
+/// Size in bytes of an accessor component type
+pub fn component_size(component_type: u64) -> Result<usize, anyhow::Error> {
+    Ok(match component_type {
+        BYTE | UNSIGNED_BYTE => 1,
+        SHORT | UNSIGNED_SHORT => 2,
+        UNSIGNED_INT | FLOAT => 4,
+        other => anyhow::bail!("unsupported accessor component type {other}"),
+    })
+}
+
+/// Divisor mapping a normalized integer component type to [-1, 1] or [0, 1]
+pub fn normalization_scale(component_type: u64) -> f64 {
+    match component_type {
+        BYTE => f64::from(i8::MAX),
+        UNSIGNED_BYTE => f64::from(u8::MAX),
+        SHORT => f64::from(i16::MAX),
+        UNSIGNED_SHORT => f64::from(u16::MAX),
+        _ => f64::from(u32::MAX),
+    }
+}
+
+/// Number of components of an accessor `type`, matrices aren't vertex attributes
+pub fn num_components(accessor_type: Option<&str>) -> Result<usize, anyhow::Error> {
+    Ok(match accessor_type {
+        Some("SCALAR") => 1,
+        Some("VEC2") => 2,
+        Some("VEC3") => 3,
+        Some("VEC4") => 4,
+        other => anyhow::bail!("unsupported accessor type {other:?}"),
+    })
+}
+
+/// JSON and binary chunks of a GLB
+pub fn split_glb(data: &[u8]) -> Result<(&[u8], Option<&[u8]>), anyhow::Error> {
+    let mut json = None;
+    let mut bin = None;
+    let mut offset = GLB_HEADER_SIZE;
+    while offset + 8 <= data.len() {
+        let length = u32::from_le_bytes(data[offset..offset + 4].try_into()?) as usize;
+        let chunk_type = u32::from_le_bytes(data[offset + 4..offset + 8].try_into()?);
+        let chunk = data
+            .get(offset + 8..offset + 8 + length)
+            .ok_or_else(|| anyhow::anyhow!("GLB chunk extends beyond file"))?;
+        match chunk_type {
+            CHUNK_JSON if json.is_none() => json = Some(chunk),
+            CHUNK_BIN if bin.is_none() => bin = Some(chunk),
+            _ => {}
+        }
+        offset += 8 + length;
+    }
+    Ok((
+        json.ok_or_else(|| anyhow::anyhow!("GLB without JSON chunk"))?,
+        bin,
+    ))
+}
+
+pub fn pad_to_4(data: &mut Vec<u8>, pad: u8) {
+    while !data.len().is_multiple_of(4) {
+        data.push(pad);
+    }
+}
+
+/// Reads the data of an external buffer from its `uri`
+type BufferLoader<'a> = Box<dyn FnMut(&str) -> Option<Vec<u8>> + 'a>;
+
+pub struct GltfDocument<'a> {
+    pub json: Value,
+    is_glb: bool,
+    /// Original GLB binary chunk
+    bin: Option<Vec<u8>>,
+    /// Contents of the buffers read so far
+    buffers: Vec<Option<Vec<u8>>>,
+    load_buffer: BufferLoader<'a>,
+    /// Buffer receiving the rewritten data, and its contents. For GLBs it's the binary chunk,
+    /// the data is appended to it
+    output_buffer: Option<usize>,
+    output: Vec<u8>,
+}
+
+impl<'a> GltfDocument<'a> {
+    /// Parses a `.gltf` or `.glb` file, buffers referenced by `uri` are read through
+    /// `load_buffer` when needed
+    pub fn parse(
+        data: &[u8],
+        load_buffer: impl FnMut(&str) -> Option<Vec<u8>> + 'a,
+    ) -> Result<Self, anyhow::Error> {
+        let is_glb = data.starts_with(GLB_MAGIC);
+        let (json, bin) = if is_glb {
+            let (json, bin) = split_glb(data)?;
+            (
+                serde_json::from_slice::<Value>(json)?,
+                bin.map(<[u8]>::to_vec),
+            )
+        } else {
+            (serde_json::from_slice::<Value>(data)?, None)
+        };
+        let num_buffers = json["buffers"].as_array().map_or(0, Vec::len);
+        // The GLB binary chunk is the first buffer, without uri
+        let glb_buffer = is_glb && num_buffers > 0 && json["buffers"][0]["uri"].is_null();
+        let (output_buffer, output) = if glb_buffer {
+            (Some(0), bin.clone().unwrap_or_default())
+        } else {
+            (None, Vec::new())
+        };
+        Ok(Self {
+            json,
+            is_glb,
+            bin,
+            buffers: vec![None; num_buffers],
+            load_buffer: Box::new(load_buffer),
+            output_buffer,
+            output,
+        })
+    }
+
+    pub fn array(&self, key: &str) -> &[Value] {
+        self.json[key].as_array().map_or(&[], Vec::as_slice)
+    }
+
+    pub fn array_mut(&mut self, key: &str) -> &mut Vec<Value> {
+        if !self.json[key].is_array() {
+            self.json[key] = json!([]);
+        }
+        self.json[key].as_array_mut().expect("array")
+    }
+
+    /// Whether the document lists `extension` as used
+    pub fn uses_extension(&self, extension: &str) -> bool {
+        self.array("extensionsUsed")
+            .iter()
+            .any(|used| used.as_str() == Some(extension))
+    }
+
+    /// Drops `extension` from `extensionsUsed` and `extensionsRequired`
+    pub fn remove_extension(&mut self, extension: &str) {
+        for key in ["extensionsUsed", "extensionsRequired"] {
+            if let Some(extensions) = self.json[key].as_array_mut() {
+                extensions.retain(|used| used.as_str() != Some(extension));
+                if extensions.is_empty() {
+                    self.json.as_object_mut().expect("gltf object").remove(key);
+                }
+            }
+        }
+    }
+
+    /// Contents of `buffer`, loading it on first use
+    fn buffer_data(&mut self, buffer: usize) -> Result<&[u8], anyhow::Error> {
+        if self.output_buffer == Some(buffer) {
+            return Ok(&self.output);
+        }
+        let uri = self
+            .array("buffers")
+            .get(buffer)
+            .ok_or_else(|| anyhow::anyhow!("invalid buffer {buffer}"))?["uri"]
+            .as_str()
+            .map(str::to_string);
+        if self.buffers.len() <= buffer {
+            self.buffers.resize(buffer + 1, None);
+        }
+        if self.buffers[buffer].is_none() {
+            let data = match uri {
+                None if self.is_glb && buffer == 0 => self.bin.clone().unwrap_or_default(),
+                None => anyhow::bail!("buffer {buffer} without data"),
+                Some(uri) if uri.starts_with("data:") => {
+                    let (_, encoded) = uri
+                        .split_once(',')
+                        .ok_or_else(|| anyhow::anyhow!("invalid data uri"))?;
+                    base64::engine::general_purpose::STANDARD.decode(encoded)?
+                }
+                Some(uri) => (self.load_buffer)(&uri)
+                    .ok_or_else(|| anyhow::anyhow!("missing buffer {uri}"))?,
+            };
+            self.buffers[buffer] = Some(data);
+        }
+        Ok(self.buffers[buffer].as_deref().unwrap_or_default())
+    }
+
+    /// `byteLength` bytes from `byteOffset` of `buffer`, as described by a buffer view (or its
+    /// `EXT_meshopt_compression` extension)
+    pub fn buffer_range(&mut self, range: &Value) -> Result<Vec<u8>, anyhow::Error> {
+        let buffer = range["buffer"].as_u64().unwrap_or(0) as usize;
+        let offset = range["byteOffset"].as_u64().unwrap_or(0) as usize;
+        let length = range["byteLength"].as_u64().unwrap_or(0) as usize;
+        let end = offset
+            .checked_add(length)
+            .ok_or_else(|| anyhow::anyhow!("buffer range out of bounds"))?;
+        self.buffer_data(buffer)?
+            .get(offset..end)
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| anyhow::anyhow!("buffer range out of bounds"))
+    }
+
+    pub fn buffer_view_data(&mut self, buffer_view: &Value) -> Result<Vec<u8>, anyhow::Error> {
+        let view = buffer_view
+            .as_u64()
+            .and_then(|index| self.array("bufferViews").get(index as usize))
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("invalid bufferView {buffer_view}"))?;
+        self.buffer_range(&view)
+    }
+
+    /// Raw components of every element of `accessor`, without normalization
+    pub fn read_accessor(&mut self, accessor: usize) -> Result<Vec<f64>, anyhow::Error> {
+        let json = self
+            .array("accessors")
+            .get(accessor)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("invalid accessor {accessor}"))?;
+        if json.get("sparse").is_some() {
+            anyhow::bail!("sparse accessor {accessor}");
+        }
+        let component_type = json["componentType"].as_u64().unwrap_or(FLOAT);
+        let component_size = component_size(component_type)?;
+        let num_components = num_components(json["type"].as_str())?;
+        let count = json["count"].as_u64().unwrap_or(0) as usize;
+        let element_size = component_size * num_components;
+        let num_values = count
+            .checked_mul(num_components)
+            .filter(|num_values| *num_values <= MAX_DECODED_SIZE / std::mem::size_of::<f64>())
+            .ok_or_else(|| anyhow::anyhow!("accessor {accessor} count {count} too large"))?;
+        if json["bufferView"].is_null() {
+            return Ok(vec![0.0; num_values]);
+        }
+        let stride = self
+            .array("bufferViews")
+            .get(json["bufferView"].as_u64().unwrap_or(0) as usize)
+            .ok_or_else(|| anyhow::anyhow!("invalid bufferView {}", json["bufferView"]))?
+            ["byteStride"]
+            .as_u64()
+            .map_or(element_size, |stride| stride as usize);
+        let data = self.buffer_view_data(&json["bufferView"])?;
+        let offset = json["byteOffset"].as_u64().unwrap_or(0) as usize;
+        // The last element has to fit in the view before allocating for all of them
+        if count > 0 {
+            let end = (count - 1)
+                .checked_mul(stride)
+                .and_then(|last| last.checked_add(offset))
+                .and_then(|last| last.checked_add(element_size));
+            if end.is_none_or(|end| end > data.len()) {
+                anyhow::bail!("accessor {accessor} out of bounds");
+            }
+        }
+        let mut values = Vec::with_capacity(num_values);
+        for element in 0..count {
+            let start = offset + element * stride;
+            let bytes = data
+                .get(start..start + element_size)
+                .ok_or_else(|| anyhow::anyhow!("accessor {accessor} out of bounds"))?;
+            for component in bytes.chunks_exact(component_size) {
+                values.push(match component_type {
+                    BYTE => f64::from(component[0] as i8),
+                    UNSIGNED_BYTE => f64::from(component[0]),
+                    SHORT => f64::from(i16::from_le_bytes([component[0], component[1]])),
+                    UNSIGNED_SHORT => f64::from(u16::from_le_bytes([component[0], component[1]])),
+                    UNSIGNED_INT => f64::from(u32::from_le_bytes(component.try_into()?)),
+                    _ => f64::from(f32::from_le_bytes(component.try_into()?)),
+                });
+            }
+        }
+        Ok(values)
+    }
+
+    /// Index of the accessor to overwrite: `accessor` itself, or a copy when it was already
+    /// `rewritten` (or a new one if there was none)
+    pub fn claim_accessor(
+        &mut self,
+        accessor: Option<u64>,
+        rewritten: &mut std::collections::HashSet<usize>,
+    ) -> usize {
+        let template = accessor
+            .and_then(|index| self.array("accessors").get(index as usize))
+            .cloned();
+        let accessors = self.array_mut("accessors");
+        let index =
+            match (accessor, template) {
+                (Some(index), Some(_)) if !rewritten.contains(&(index as usize)) => index as usize,
+                (_, template) => {
+                    accessors.push(template.unwrap_or_else(
+                        || json!({ "componentType": UNSIGNED_INT, "type": "SCALAR" }),
+                    ));
+                    accessors.len() - 1
+                }
+            };
+        rewritten.insert(index);
+        index
+    }
+
+    /// Appends `data` to the output buffer, returns its `(buffer, byteOffset)`
+    pub fn push_data(&mut self, data: &[u8]) -> (usize, usize) {
+        let buffer = match self.output_buffer {
+            Some(buffer) => buffer,
+            None => {
+                let buffers = self.array_mut("buffers");
+                buffers.push(json!({ "byteLength": 0 }));
+                let buffer = buffers.len() - 1;
+                self.output_buffer = Some(buffer);
+                buffer
+            }
+        };
+        pad_to_4(&mut self.output, 0);
+        let offset = self.output.len();
+        self.output.extend_from_slice(data);
+        (buffer, offset)
+    }
+
+    /// Appends `data` as a new buffer view, returns its index
+    pub fn push_buffer_view(&mut self, data: &[u8], stride: Option<usize>, target: u32) -> usize {
+        let (buffer, offset) = self.push_data(data);
+        let mut view = json!({
+            "buffer": buffer,
+            "byteOffset": offset,
+            "byteLength": data.len(),
+            "target": target,
+        });
+        if let Some(stride) = stride {
+            view["byteStride"] = json!(stride);
+        }
+        let views = self.array_mut("bufferViews");
+        views.push(view);
+        views.len() - 1
+    }
+
+    fn set_accessor_data(&mut self, accessor: usize, buffer_view: usize, count: usize) {
+        let accessor = self.array_mut("accessors")[accessor]
+            .as_object_mut()
+            .expect("accessor object");
+        accessor.insert("bufferView".to_string(), json!(buffer_view));
+        accessor.insert("count".to_string(), json!(count));
+        accessor.remove("byteOffset");
+        accessor.remove("sparse");
+    }
+
+    pub fn write_indices(&mut self, accessor: usize, indices: &[u32]) {
+        let max = indices.iter().copied().max().unwrap_or(0);
+        let requested = self.array("accessors")[accessor]["componentType"]
+            .as_u64()
+            .unwrap_or(UNSIGNED_INT);
+        let component_type = match requested {
+            UNSIGNED_BYTE if max <= u32::from(u8::MAX) => UNSIGNED_BYTE,
+            UNSIGNED_SHORT if max <= u32::from(u16::MAX) => UNSIGNED_SHORT,
+            _ => UNSIGNED_INT,
+        };
+        let mut data = Vec::with_capacity(indices.len() * 4);
+        for index in indices {
+            match component_type {
+                UNSIGNED_BYTE => data.push(*index as u8),
+                UNSIGNED_SHORT => data.extend_from_slice(&(*index as u16).to_le_bytes()),
+                _ => data.extend_from_slice(&index.to_le_bytes()),
+            }
+        }
+        let view = self.push_buffer_view(&data, None, ELEMENT_ARRAY_BUFFER);
+        self.set_accessor_data(accessor, view, indices.len());
+        let json = self.array_mut("accessors")[accessor]
+            .as_object_mut()
+            .expect("accessor object");
+        json.insert("componentType".to_string(), json!(component_type));
+        json.insert("type".to_string(), json!("SCALAR"));
+        if json.contains_key("min") || json.contains_key("max") {
+            let min = indices.iter().copied().min().unwrap_or(0);
+            json.insert("min".to_string(), json!([min]));
+            json.insert("max".to_string(), json!([max]));
+        }
+    }
+
+    /// Writes `count` elements in the accessor's component type, `value` gives the components
+    /// of each. Values are divided by `source_scale` when they are normalized integers, and
+    /// converted between normalized integers and floats when the accessor disagrees.
+    pub fn write_attribute<'v>(
+        &mut self,
+        accessor: usize,
+        count: usize,
+        source_scale: Option<f64>,
+        value: impl Fn(usize) -> Result<&'v [f64], anyhow::Error>,
+    ) -> Result<(), anyhow::Error> {
+        let json = &self.array("accessors")[accessor];
+        let component_type = json["componentType"].as_u64().unwrap_or(FLOAT);
+        let normalized = json["normalized"].as_bool().unwrap_or(false);
+        let num_components = num_components(json["type"].as_str())?;
+        let component_size = component_size(component_type)?;
+        // Vertex attribute elements must be 4 byte aligned
+        let element_size = component_size * num_components;
+        let stride = element_size.div_ceil(4) * 4;
+        let target_scale = normalization_scale(component_type);
+
+        let mut data = Vec::with_capacity(stride * count);
+        let mut min = vec![f64::INFINITY; num_components];
+        let mut max = vec![f64::NEG_INFINITY; num_components];
+        for element in 0..count {
+            let components = value(element)?;
+            if components.len() < num_components {
+                anyhow::bail!(
+                    "attribute has {} components, the accessor {}",
+                    components.len(),
+                    num_components
+                );
+            }
+            for (component, value) in components.iter().take(num_components).enumerate() {
+                let mut value = *value;
+                match (component_type, source_scale) {
+                    (FLOAT, Some(scale)) => value /= scale,
+                    (FLOAT, None) => {}
+                    (_, None) if normalized => value = (value * target_scale).round(),
+                    _ => value = value.round(),
+                }
+                min[component] = min[component].min(value);
+                max[component] = max[component].max(value);
+                match component_type {
+                    BYTE => data.push(value as i8 as u8),
+                    UNSIGNED_BYTE => data.push(value as u8),
+                    SHORT => data.extend_from_slice(&(value as i16).to_le_bytes()),
+                    UNSIGNED_SHORT => data.extend_from_slice(&(value as u16).to_le_bytes()),
+                    UNSIGNED_INT => data.extend_from_slice(&(value as u32).to_le_bytes()),
+                    _ => data.extend_from_slice(&(value as f32).to_le_bytes()),
+                }
+            }
+            data.resize(data.len() + stride - element_size, 0);
+        }
+
+        let view = self.push_buffer_view(
+            &data,
+            (stride != element_size).then_some(stride),
+            ARRAY_BUFFER,
+        );
+        self.set_accessor_data(accessor, view, count);
+        let json = self.array_mut("accessors")[accessor]
+            .as_object_mut()
+            .expect("accessor object");
+        if count > 0 && (json.contains_key("min") || json.contains_key("max")) {
+            let bound = |values: Vec<f64>| -> Value {
+                if component_type == FLOAT {
+                    json!(values.iter().map(|value| *value as f32).collect::<Vec<_>>())
+                } else {
+                    json!(values.iter().map(|value| *value as i64).collect::<Vec<_>>())
+                }
+            };
+            json.insert("min".to_string(), bound(min));
+            json.insert("max".to_string(), bound(max));
+        }
+        Ok(())
+    }
+
+    /// Serializes the document back, in the input format
+    pub fn finish(mut self) -> Result<Vec<u8>, anyhow::Error> {
+        pad_to_4(&mut self.output, 0);
+        let output = std::mem::take(&mut self.output);
+        let output_buffer = self.output_buffer;
+        if let Some(index) = output_buffer {
+            let embedded = !self.is_glb || index != 0;
+            let buffer = &mut self.array_mut("buffers")[index];
+            buffer["byteLength"] = json!(output.len());
+            if embedded {
+                buffer["uri"] = json!(format!(
+                    "data:application/octet-stream;base64,{}",
+                    base64::engine::general_purpose::STANDARD.encode(&output)
+                ));
+            }
+        }
+
+        let json = serde_json::to_vec(&self.json)?;
+        if !self.is_glb {
+            return Ok(json);
+        }
+        let bin = if output_buffer == Some(0) {
+            output
+        } else {
+            self.bin.take().unwrap_or_default()
+        };
+        Ok(assemble_glb(json, bin))
+    }
+}
+
+/// GLB container with a JSON and an optional binary chunk
+fn assemble_glb(mut json: Vec<u8>, mut bin: Vec<u8>) -> Vec<u8> {
+    pad_to_4(&mut json, b' ');
+    pad_to_4(&mut bin, 0);
+    let total_length =
+        GLB_HEADER_SIZE + 8 + json.len() + if bin.is_empty() { 0 } else { 8 + bin.len() };
+    let mut glb = Vec::with_capacity(total_length);
+    glb.extend_from_slice(GLB_MAGIC);
+    glb.extend_from_slice(&2u32.to_le_bytes());
+    glb.extend_from_slice(&(total_length as u32).to_le_bytes());
+    glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    glb.extend_from_slice(&CHUNK_JSON.to_le_bytes());
+    glb.extend_from_slice(&json);
+    if !bin.is_empty() {
+        glb.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+        glb.extend_from_slice(&CHUNK_BIN.to_le_bytes());
+        glb.extend_from_slice(&bin);
+    }
+    glb
+}
+
+#[cfg(test)]
+pub fn build_glb(json: &Value, bin: &[u8]) -> Vec<u8> {
+    assemble_glb(serde_json::to_vec(json).unwrap(), bin.to_vec())
+}
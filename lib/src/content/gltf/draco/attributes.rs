@@ -0,0 +1,701 @@
+//! Attribute decoding (`SequentialAttributeDecodersController` and the sequential attribute
+//! decoders). Every attributes decoder walks the mesh to fix the order of its values, decodes
+//! them in their portable (integer) form and finally converts them back to the original type.
+
+use super::{
+    buffer::DecoderBuffer,
+    corner_table::{next, previous, CornerView, INVALID},
+    edgebreaker::EdgebreakerConnectivity,
+    prediction::{MeshData, Octahedron, PositionData, PredictionContext, PredictionScheme},
+    rans::decode_symbols,
+    DracoAttribute, DracoError, DracoResult,
+};
+
+const MESH_VERTEX_ATTRIBUTE: u8 = 0;
+const MESH_CORNER_ATTRIBUTE: u8 = 1;
+
+const TRAVERSAL_DEPTH_FIRST: u8 = 0;
+const TRAVERSAL_PREDICTION_DEGREE: u8 = 1;
+
+const DECODER_GENERIC: u8 = 0;
+const DECODER_INTEGER: u8 = 1;
+const DECODER_QUANTIZATION: u8 = 2;
+const DECODER_NORMALS: u8 = 3;
+
+const ATTRIBUTE_POSITION: u8 = 0;
+const NAMED_ATTRIBUTES_COUNT: u8 = 5;
+
+const PREDICTION_NONE: i8 = -2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataType {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Int64,
+    UInt64,
+    Float32,
+    Float64,
+    Bool,
+}
+
+impl DataType {
+    fn from_id(id: u8) -> Option<Self> {
+        Some(match id {
+            1 => Self::Int8,
+            2 => Self::UInt8,
+            3 => Self::Int16,
+            4 => Self::UInt16,
+            5 => Self::Int32,
+            6 => Self::UInt32,
+            7 => Self::Int64,
+            8 => Self::UInt64,
+            9 => Self::Float32,
+            10 => Self::Float64,
+            11 => Self::Bool,
+            _ => return None,
+        })
+    }
+
+    /// Largest value of the integer types, the scale of normalized values
+    pub fn normalization_scale(self) -> Option<f64> {
+        Some(match self {
+            Self::Int8 => f64::from(i8::MAX),
+            Self::UInt8 => f64::from(u8::MAX),
+            Self::Int16 => f64::from(i16::MAX),
+            Self::UInt16 => f64::from(u16::MAX),
+            Self::Int32 => f64::from(i32::MAX),
+            Self::UInt32 => f64::from(u32::MAX),
+            _ => return None,
+        })
+    }
+
+    fn read_value(self, buffer: &mut DecoderBuffer) -> DracoResult<f64> {
+        Ok(match self {
+            Self::Int8 => f64::from(buffer.read_i8()?),
+            Self::UInt8 | Self::Bool => f64::from(buffer.read_u8()?),
+            Self::Int16 => f64::from(buffer.read_u16()? as i16),
+            Self::UInt16 => f64::from(buffer.read_u16()?),
+            Self::Int32 => f64::from(buffer.read_i32()?),
+            Self::UInt32 => f64::from(buffer.read_u32()?),
+            Self::Int64 | Self::UInt64 | Self::Float64 => {
+                let bytes: [u8; 8] = buffer
+                    .read_bytes(8)?
+                    .try_into()
+                    .map_err(|_| DracoError::UnexpectedEnd)?;
+                match self {
+                    Self::Int64 => i64::from_le_bytes(bytes) as f64,
+                    Self::UInt64 => u64::from_le_bytes(bytes) as f64,
+                    _ => f64::from_le_bytes(bytes),
+                }
+            }
+            Self::Float32 => f64::from(buffer.read_f32()?),
+        })
+    }
+
+    /// Portable integer value stored as this type (`static_cast` in `StoreValues`)
+    fn cast_portable(self, value: i32) -> f64 {
+        match self {
+            Self::Int8 => f64::from(value as i8),
+            Self::UInt8 => f64::from(value as u8),
+            Self::Int16 => f64::from(value as i16),
+            Self::UInt16 => f64::from(value as u16),
+            Self::Int32 | Self::Int64 | Self::Float64 => f64::from(value),
+            Self::UInt32 => f64::from(value as u32),
+            Self::UInt64 => value as i64 as u64 as f64,
+            Self::Float32 => f64::from(value as f32),
+            Self::Bool => f64::from(u8::from(value != 0)),
+        }
+    }
+}
+
+/// Connectivity the attributes are decoded against
+pub struct Connectivity<'a> {
+    /// Faces in point ids
+    pub faces: &'a [[u32; 3]],
+    pub num_points: usize,
+    /// Corner tables of Edgebreaker meshes, sequential meshes store values in point order
+    pub edgebreaker: Option<&'a EdgebreakerConnectivity>,
+}
+
+/// Order in which an attributes decoder visits the values (`MeshAttributeIndicesEncodingData`
+/// plus the point ids of the sequencer)
+#[derive(Default)]
+struct Sequence {
+    point_ids: Vec<u32>,
+    data_to_corner: Vec<u32>,
+    vertex_to_data: Vec<i32>,
+}
+
+struct Traverser<'a> {
+    table: &'a dyn CornerView,
+    faces: &'a [[u32; 3]],
+    visited_faces: Vec<bool>,
+    visited_vertices: Vec<bool>,
+    sequence: Sequence,
+}
+
+impl<'a> Traverser<'a> {
+    fn new(table: &'a dyn CornerView, faces: &'a [[u32; 3]]) -> Self {
+        Self {
+            table,
+            faces,
+            visited_faces: vec![false; table.num_corners() / 3],
+            visited_vertices: vec![false; table.num_vertices()],
+            sequence: Sequence {
+                vertex_to_data: vec![-1; table.num_vertices()],
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Whether the face of `corner` was visited, missing faces count as visited
+    fn is_face_visited(&self, corner: u32) -> bool {
+        corner == INVALID
+            || self
+                .visited_faces
+                .get((corner / 3) as usize)
+                .copied()
+                .unwrap_or(true)
+    }
+
+    fn mark_face_visited(&mut self, corner: u32) -> DracoResult<()> {
+        let visited = self
+            .visited_faces
+            .get_mut((corner / 3) as usize)
+            .ok_or(DracoError::Malformed("traversal left the mesh"))?;
+        *visited = true;
+        Ok(())
+    }
+
+    fn is_vertex_visited(&self, vertex: u32) -> bool {
+        self.visited_vertices
+            .get(vertex as usize)
+            .copied()
+            .unwrap_or(true)
+    }
+
+    fn is_on_boundary(&self, vertex: u32) -> bool {
+        let corner = self.table.left_most_corner(vertex);
+        corner == INVALID || self.table.swing_left(corner) == INVALID
+    }
+
+    /// Assigns the next value to `vertex` (`MeshAttributeIndicesEncodingObserver`)
+    fn visit_vertex(&mut self, vertex: u32, corner: u32) -> DracoResult<()> {
+        if self.is_vertex_visited(vertex) {
+            return Ok(());
+        }
+        let point = self
+            .faces
+            .get((corner / 3) as usize)
+            .map(|face| face[(corner % 3) as usize])
+            .ok_or(DracoError::Malformed("traversal left the mesh"))?;
+        self.visited_vertices[vertex as usize] = true;
+        self.sequence.vertex_to_data[vertex as usize] = self.sequence.point_ids.len() as i32;
+        self.sequence.point_ids.push(point);
+        self.sequence.data_to_corner.push(corner);
+        Ok(())
+    }
+
+    /// Visits the vertices of the starting face's edge opposite to `corner`
+    fn start_from_corner(&mut self, corner: u32) -> DracoResult<()> {
+        let next_corner = next(corner);
+        let previous_corner = previous(corner);
+        let next_vertex = self.table.vertex(next_corner);
+        let previous_vertex = self.table.vertex(previous_corner);
+        if next_vertex == INVALID || previous_vertex == INVALID {
+            return Err(DracoError::Malformed("traversal start face"));
+        }
+        self.visit_vertex(next_vertex, next_corner)?;
+        self.visit_vertex(previous_vertex, previous_corner)
+    }
+
+    /// `DepthFirstTraverser::TraverseFromCorner`
+    fn depth_first(&mut self, start: u32) -> DracoResult<()> {
+        if self.is_face_visited(start) {
+            return Ok(());
+        }
+        self.start_from_corner(start)?;
+        let mut stack = vec![start];
+        while let Some(&top) = stack.last() {
+            if self.is_face_visited(top) {
+                stack.pop();
+                continue;
+            }
+            let mut corner = top;
+            loop {
+                if corner == INVALID {
+                    return Err(DracoError::Malformed("traversal left the mesh"));
+                }
+                self.mark_face_visited(corner)?;
+                let vertex = self.table.vertex(corner);
+                if vertex == INVALID {
+                    return Err(DracoError::Malformed("traversal vertex"));
+                }
+                if !self.is_vertex_visited(vertex) {
+                    let on_boundary = self.is_on_boundary(vertex);
+                    self.visit_vertex(vertex, corner)?;
+                    if !on_boundary {
+                        corner = self.table.opposite(next(corner));
+                        continue;
+                    }
+                }
+                let right = self.table.opposite(next(corner));
+                let left = self.table.opposite(previous(corner));
+                match (self.is_face_visited(right), self.is_face_visited(left)) {
+                    (true, true) => {
+                        stack.pop();
+                        break;
+                    }
+                    (true, false) => corner = left,
+                    (false, true) => corner = right,
+                    (false, false) => {
+                        *stack.last_mut().unwrap() = left;
+                        stack.push(right);
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// `MaxPredictionDegreeTraverser::TraverseFromCorner`: prefers the corners whose vertex
+    /// can be predicted from the most already decoded neighbours
+    fn max_prediction_degree(
+        &mut self,
+        start: u32,
+        prediction_degree: &mut [u32],
+    ) -> DracoResult<()> {
+        const MAX_PRIORITY: usize = 3;
+        if self.is_face_visited(start) {
+            return Ok(());
+        }
+        let mut stacks: [Vec<u32>; MAX_PRIORITY] = Default::default();
+        let mut best_priority = 0;
+        stacks[0].push(start);
+        self.start_from_corner(start)?;
+        let tip = self.table.vertex(start);
+        if tip == INVALID {
+            return Err(DracoError::Malformed("traversal start face"));
+        }
+        self.visit_vertex(tip, start)?;
+
+        let mut compute_priority = |traverser: &Self, corner: u32| {
+            let tip = traverser.table.vertex(corner);
+            if traverser.is_vertex_visited(tip) {
+                return 0;
+            }
+            let degree = &mut prediction_degree[tip as usize];
+            *degree += 1;
+            if *degree > 1 {
+                1
+            } else {
+                2
+            }
+        };
+
+        while let Some(priority) = (best_priority..MAX_PRIORITY).find(|p| !stacks[*p].is_empty()) {
+            best_priority = priority;
+            let mut corner = stacks[priority].pop().unwrap();
+            if self.is_face_visited(corner) {
+                continue;
+            }
+            loop {
+                self.mark_face_visited(corner)?;
+                let vertex = self.table.vertex(corner);
+                if vertex == INVALID {
+                    return Err(DracoError::Malformed("traversal vertex"));
+                }
+                self.visit_vertex(vertex, corner)?;
+                let right = self.table.opposite(next(corner));
+                let left = self.table.opposite(previous(corner));
+                let is_right_visited = self.is_face_visited(right);
+                if !self.is_face_visited(left) {
+                    let priority = compute_priority(self, left);
+                    if is_right_visited && priority <= best_priority {
+                        corner = left;
+                        continue;
+                    }
+                    stacks[priority].push(left);
+                    best_priority = best_priority.min(priority);
+                }
+                if !is_right_visited {
+                    let priority = compute_priority(self, right);
+                    if priority <= best_priority {
+                        corner = right;
+                        continue;
+                    }
+                    stacks[priority].push(right);
+                    best_priority = best_priority.min(priority);
+                }
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn traverse(mut self, method: u8) -> DracoResult<Sequence> {
+        let num_faces = self.visited_faces.len() as u32;
+        match method {
+            TRAVERSAL_DEPTH_FIRST => {
+                for face in 0..num_faces {
+                    self.depth_first(face * 3)?;
+                }
+            }
+            TRAVERSAL_PREDICTION_DEGREE => {
+                let mut prediction_degree = vec![0; self.visited_vertices.len()];
+                for face in 0..num_faces {
+                    self.max_prediction_degree(face * 3, &mut prediction_degree)?;
+                }
+            }
+            _ => return Err(DracoError::Unsupported(format!("mesh traversal {method}"))),
+        }
+        Ok(self.sequence)
+    }
+}
+
+/// Decoder of one attribute's values
+struct AttributeDecoder {
+    attribute_type: u8,
+    data_type: DataType,
+    num_components: usize,
+    normalized: bool,
+    unique_id: u32,
+    decoder_type: u8,
+    /// Values in their portable form, `portable_components` per entry
+    portable: Vec<i32>,
+    /// Original values, `num_components` per entry
+    values: Vec<f64>,
+    point_to_value: Vec<u32>,
+    decoded: bool,
+}
+
+impl AttributeDecoder {
+    fn portable_components(&self) -> usize {
+        if self.decoder_type == DECODER_NORMALS {
+            2
+        } else {
+            self.num_components
+        }
+    }
+}
+
+/// Decoder of a group of attributes sharing a traversal
+struct AttributesDecoder {
+    /// Connectivity data of the attribute (`att_data_id`), -1 for the position connectivity
+    attribute_data_id: i8,
+    decoder_type: u8,
+    traversal_method: u8,
+    attributes: Vec<usize>,
+}
+
+/// Decodes the attributes following the connectivity
+pub fn decode_attributes(
+    buffer: &mut DecoderBuffer,
+    connectivity: &Connectivity,
+) -> DracoResult<Vec<DracoAttribute>> {
+    let num_decoders = buffer.read_u8()?;
+    let mut decoders = Vec::with_capacity(num_decoders as usize);
+    for _ in 0..num_decoders {
+        let mut decoder = AttributesDecoder {
+            attribute_data_id: -1,
+            decoder_type: MESH_VERTEX_ATTRIBUTE,
+            traversal_method: TRAVERSAL_DEPTH_FIRST,
+            attributes: Vec::new(),
+        };
+        if let Some(edgebreaker) = connectivity.edgebreaker {
+            decoder.attribute_data_id = buffer.read_i8()?;
+            decoder.decoder_type = buffer.read_u8()?;
+            decoder.traversal_method = buffer.read_u8()?;
+            if decoder.attribute_data_id >= 0
+                && decoder.attribute_data_id as usize >= edgebreaker.attribute_data.len()
+            {
+                return Err(DracoError::Malformed("attribute data id"));
+            }
+            match decoder.decoder_type {
+                MESH_VERTEX_ATTRIBUTE => {}
+                MESH_CORNER_ATTRIBUTE
+                    if decoder.attribute_data_id >= 0
+                        && decoder.traversal_method == TRAVERSAL_DEPTH_FIRST => {}
+                _ => return Err(DracoError::Malformed("attributes decoder type")),
+            }
+        }
+        decoders.push(decoder);
+    }
+
+    let mut attributes: Vec<AttributeDecoder> = Vec::new();
+    for decoder in decoders.iter_mut() {
+        let num_attributes = buffer.read_varint_u32()? as usize;
+        if num_attributes == 0 || num_attributes > 5 * buffer.remaining() {
+            return Err(DracoError::Malformed("attribute count"));
+        }
+        for _ in 0..num_attributes {
+            let attribute_type = buffer.read_u8()?;
+            let data_type = DataType::from_id(buffer.read_u8()?)
+                .ok_or(DracoError::Malformed("attribute data type"))?;
+            let num_components = buffer.read_u8()? as usize;
+            let normalized = buffer.read_u8()? > 0;
+            let unique_id = buffer.read_varint_u32()?;
+            if attribute_type >= NAMED_ATTRIBUTES_COUNT || num_components == 0 {
+                return Err(DracoError::Malformed("attribute descriptor"));
+            }
+            decoder.attributes.push(attributes.len());
+            attributes.push(AttributeDecoder {
+                attribute_type,
+                data_type,
+                num_components,
+                normalized,
+                unique_id,
+                decoder_type: DECODER_GENERIC,
+                portable: Vec::new(),
+                values: Vec::new(),
+                point_to_value: Vec::new(),
+                decoded: false,
+            });
+        }
+        for &attribute in &decoder.attributes {
+            let decoder_type = buffer.read_u8()?;
+            if decoder_type > DECODER_NORMALS {
+                return Err(DracoError::Unsupported(format!(
+                    "sequential attribute decoder {decoder_type}"
+                )));
+            }
+            if decoder_type == DECODER_NORMALS && attributes[attribute].num_components != 3 {
+                return Err(DracoError::Malformed("normals need 3 components"));
+            }
+            attributes[attribute].decoder_type = decoder_type;
+        }
+    }
+
+    let position = attributes
+        .iter()
+        .position(|attribute| attribute.attribute_type == ATTRIBUTE_POSITION);
+    for decoder in &decoders {
+        decode_attributes_values(buffer, connectivity, decoder, &mut attributes, position)?;
+    }
+
+    Ok(attributes
+        .into_iter()
+        .map(|attribute| DracoAttribute {
+            unique_id: attribute.unique_id,
+            data_type: attribute.data_type,
+            num_components: attribute.num_components,
+            normalized: attribute.normalized,
+            values: attribute.values,
+            point_to_value: attribute.point_to_value,
+        })
+        .collect())
+}
+
+/// Decodes the values of one attributes decoder: sequence, portable values, transform data and
+/// the conversion to the original format
+fn decode_attributes_values(
+    buffer: &mut DecoderBuffer,
+    connectivity: &Connectivity,
+    decoder: &AttributesDecoder,
+    attributes: &mut [AttributeDecoder],
+    position: Option<usize>,
+) -> DracoResult<()> {
+    let attribute_view;
+    let (sequence, table): (Sequence, Option<&dyn CornerView>) = match connectivity.edgebreaker {
+        None => (
+            Sequence {
+                point_ids: (0..connectivity.num_points as u32).collect(),
+                ..Default::default()
+            },
+            None,
+        ),
+        Some(edgebreaker) => {
+            let table: &dyn CornerView = if decoder.decoder_type == MESH_CORNER_ATTRIBUTE {
+                attribute_view = edgebreaker.attribute_data[decoder.attribute_data_id as usize]
+                    .table
+                    .view(&edgebreaker.corner_table);
+                &attribute_view
+            } else {
+                &edgebreaker.corner_table
+            };
+            let sequence =
+                Traverser::new(table, connectivity.faces).traverse(decoder.traversal_method)?;
+            (sequence, Some(table))
+        }
+    };
+
+    let point_to_value = match table {
+        None => sequence.point_ids.clone(),
+        Some(table) => {
+            let mut point_to_value = vec![0; connectivity.num_points];
+            for (corner, point) in connectivity.faces.iter().flatten().enumerate() {
+                let entry = sequence
+                    .vertex_to_data
+                    .get(table.vertex(corner as u32) as usize)
+                    .and_then(|entry| u32::try_from(*entry).ok())
+                    .ok_or(DracoError::Malformed("point without value"))?;
+                *point_to_value
+                    .get_mut(*point as usize)
+                    .ok_or(DracoError::Malformed("point out of range"))? = entry;
+            }
+            point_to_value
+        }
+    };
+
+    for &attribute in &decoder.attributes {
+        let positions = position
+            .filter(|position| attributes[*position].decoded)
+            .map(|position| PositionData {
+                values: &attributes[position].portable,
+                point_to_value: &attributes[position].point_to_value,
+            });
+        let ctx = PredictionContext {
+            mesh: table.map(|table| MeshData {
+                table,
+                data_to_corner: &sequence.data_to_corner,
+                vertex_to_data: &sequence.vertex_to_data,
+            }),
+            entry_to_point: &sequence.point_ids,
+            positions,
+        };
+        let num_entries = sequence.point_ids.len();
+        let (portable, values) = decode_portable_values(
+            buffer,
+            &attributes[attribute],
+            num_entries,
+            table.map_or(0, |table| table.num_corners()),
+            &ctx,
+        )?;
+        let attribute = &mut attributes[attribute];
+        attribute.portable = portable;
+        attribute.values = values;
+        attribute.point_to_value = point_to_value.clone();
+        attribute.decoded = true;
+    }
+
+    for &attribute in &decoder.attributes {
+        let attribute = &mut attributes[attribute];
+        match attribute.decoder_type {
+            DECODER_QUANTIZATION => {
+                let min_values = (0..attribute.num_components)
+                    .map(|_| buffer.read_f32())
+                    .collect::<DracoResult<Vec<_>>>()?;
+                let range = buffer.read_f32()?;
+                let bits = buffer.read_u8()?;
+                if !(1..=30).contains(&bits) {
+                    return Err(DracoError::Malformed("quantization bits"));
+                }
+                let max_quantized_value = ((1u32 << bits) - 1) as f32;
+                let delta = range / max_quantized_value;
+                attribute.values = attribute
+                    .portable
+                    .iter()
+                    .zip(min_values.iter().cycle())
+                    .map(|(value, min)| f64::from(*value as f32 * delta + min))
+                    .collect();
+            }
+            DECODER_NORMALS => {
+                let bits = u32::from(buffer.read_u8()?);
+                if !(2..=30).contains(&bits) {
+                    return Err(DracoError::Malformed("normal quantization bits"));
+                }
+                let octahedron = Octahedron::from_quantization_bits(bits);
+                attribute.values = attribute
+                    .portable
+                    .chunks_exact(2)
+                    .flat_map(|coords| {
+                        octahedron.quantized_coords_to_unit_vector(coords[0], coords[1])
+                    })
+                    .map(f64::from)
+                    .collect();
+            }
+            DECODER_INTEGER => {
+                let data_type = attribute.data_type;
+                attribute.values = attribute
+                    .portable
+                    .iter()
+                    .map(|value| data_type.cast_portable(*value))
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Reads the values of one attribute, returns the portable and (for generic attributes) the
+/// original values
+fn decode_portable_values(
+    buffer: &mut DecoderBuffer,
+    attribute: &AttributeDecoder,
+    num_entries: usize,
+    num_corners: usize,
+    ctx: &PredictionContext,
+) -> DracoResult<(Vec<i32>, Vec<f64>)> {
+    if attribute.decoder_type == DECODER_GENERIC {
+        let values = (0..num_entries * attribute.num_components)
+            .map(|_| attribute.data_type.read_value(buffer))
+            .collect::<DracoResult<Vec<_>>>()?;
+        let portable = values.iter().map(|value| *value as i32).collect();
+        return Ok((portable, values));
+    }
+
+    let num_components = attribute.portable_components();
+    let method = buffer.read_i8()?;
+    let transform_type = if method != PREDICTION_NONE {
+        buffer.read_i8()?
+    } else {
+        0
+    };
+    let mut scheme = PredictionScheme::create(
+        method,
+        transform_type,
+        attribute.decoder_type == DECODER_NORMALS,
+        ctx.mesh.is_some(),
+    )?;
+    if scheme
+        .as_ref()
+        .is_some_and(|scheme| scheme.needs_positions())
+        && ctx.positions.is_none()
+    {
+        return Err(DracoError::Malformed("prediction needs decoded positions"));
+    }
+
+    let num_values = num_entries * num_components;
+    let mut values: Vec<i32> = if buffer.read_u8()? > 0 {
+        decode_symbols(num_values, num_components, buffer)?
+            .into_iter()
+            .map(|value| value as i32)
+            .collect()
+    } else {
+        let num_bytes = buffer.read_u8()? as usize;
+        if !(1..=4).contains(&num_bytes) {
+            return Err(DracoError::Malformed("raw value size"));
+        }
+        (0..num_values)
+            .map(|_| {
+                let mut bytes = [0u8; 4];
+                bytes[..num_bytes].copy_from_slice(buffer.read_bytes(num_bytes)?);
+                Ok(i32::from_le_bytes(bytes))
+            })
+            .collect::<DracoResult<_>>()?
+    };
+    if !scheme
+        .as_ref()
+        .is_some_and(|scheme| scheme.are_corrections_positive())
+    {
+        for value in values.iter_mut() {
+            let symbol = *value as u32;
+            *value = if symbol & 1 == 0 {
+                (symbol >> 1) as i32
+            } else {
+                -((symbol >> 1) as i32) - 1
+            };
+        }
+    }
+    if let Some(scheme) = scheme.as_mut() {
+        scheme.decode_prediction_data(buffer, num_corners)?;
+        values = scheme.compute_original_values(&values, num_components, ctx)?;
+    }
+    Ok((values, Vec::new()))
+}
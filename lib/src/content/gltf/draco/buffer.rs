@@ -0,0 +1,134 @@
+//! Byte and bit readers over the Draco bitstream (`DecoderBuffer` in the reference decoder).
+
+use super::{DracoError, DracoResult};
+
+/// Reads little-endian values, LEB128 varints and LSB-first bit runs from a byte slice.
+#[derive(Clone)]
+pub struct DecoderBuffer<'a> {
+    data: &'a [u8],
+    pos: usize,
+    /// Bit reader started by `start_bit_decoding`, as an absolute bit offset into `data`
+    bit_pos: Option<usize>,
+}
+
+impl<'a> DecoderBuffer<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            bit_pos: None,
+        }
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    pub fn read_bytes(&mut self, len: usize) -> DracoResult<&'a [u8]> {
+        if len > self.remaining() {
+            return Err(DracoError::UnexpectedEnd);
+        }
+        let bytes = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    pub fn advance(&mut self, len: usize) -> DracoResult<()> {
+        self.read_bytes(len).map(|_| ())
+    }
+
+    pub fn read_u8(&mut self) -> DracoResult<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    pub fn read_i8(&mut self) -> DracoResult<i8> {
+        Ok(self.read_u8()? as i8)
+    }
+
+    pub fn read_u16(&mut self) -> DracoResult<u16> {
+        let bytes = self.read_bytes(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    pub fn read_u32(&mut self) -> DracoResult<u32> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    pub fn read_i32(&mut self) -> DracoResult<i32> {
+        Ok(self.read_u32()? as i32)
+    }
+
+    pub fn read_f32(&mut self) -> DracoResult<f32> {
+        Ok(f32::from_bits(self.read_u32()?))
+    }
+
+    pub fn read_varint(&mut self) -> DracoResult<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.read_u8()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(DracoError::Malformed("varint too long"))
+    }
+
+    pub fn read_varint_u32(&mut self) -> DracoResult<u32> {
+        u32::try_from(self.read_varint()?).map_err(|_| DracoError::Malformed("varint overflow"))
+    }
+
+    /// Starts reading bits from the current position, optionally preceded by a varint with the
+    /// size in bytes of the bit data (returned, 0 otherwise)
+    pub fn start_bit_decoding(&mut self, decode_size: bool) -> DracoResult<u64> {
+        let size = if decode_size { self.read_varint()? } else { 0 };
+        self.bit_pos = Some(self.pos * 8);
+        Ok(size)
+    }
+
+    /// Skips the whole bytes touched by the bit reader
+    pub fn end_bit_decoding(&mut self) {
+        if let Some(bit_pos) = self.bit_pos.take() {
+            self.pos = bit_pos.div_ceil(8).min(self.data.len());
+        }
+    }
+
+    /// Reads `bits` bits, least significant first. Past the end of the data the bits read as 0,
+    /// like the reference decoder.
+    pub fn read_bits(&mut self, bits: u32) -> DracoResult<u32> {
+        let Some(bit_pos) = self.bit_pos.as_mut() else {
+            return Err(DracoError::Malformed("bit decoding not started"));
+        };
+        let mut value = 0u32;
+        for bit in 0..bits.min(32) {
+            let byte = *bit_pos >> 3;
+            if byte < self.data.len() {
+                value |= u32::from((self.data[byte] >> (*bit_pos & 7)) & 1) << bit;
+                *bit_pos += 1;
+            }
+        }
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_varints_and_bits() {
+        let data = [0xac, 0x02, 0x05, 0b1011_0110, 0xff, 0x2a];
+        let mut buffer = DecoderBuffer::new(&data);
+        assert_eq!(buffer.read_varint().unwrap(), 300);
+        assert_eq!(buffer.read_varint().unwrap(), 5);
+
+        buffer.start_bit_decoding(false).unwrap();
+        assert_eq!(buffer.read_bits(1).unwrap(), 0);
+        assert_eq!(buffer.read_bits(2).unwrap(), 0b11);
+        assert_eq!(buffer.read_bits(7).unwrap(), 0b11_10110);
+        buffer.end_bit_decoding();
+        assert_eq!(buffer.read_u8().unwrap(), 0x2a);
+        assert!(buffer.read_u8().is_err());
+    }
+}
@@ -0,0 +1,280 @@
+//! Corner tables: the half-edge-like connectivity the Edgebreaker decoder builds and the mesh
+//! prediction schemes walk. Corner `c` belongs to face `c / 3`, ids are `u32` with
+//! [`INVALID`] for "none".
+
+pub const INVALID: u32 = u32::MAX;
+
+#[inline]
+pub fn next(corner: u32) -> u32 {
+    if corner == INVALID {
+        INVALID
+    } else if corner % 3 == 2 {
+        corner - 2
+    } else {
+        corner + 1
+    }
+}
+
+#[inline]
+pub fn previous(corner: u32) -> u32 {
+    if corner == INVALID {
+        INVALID
+    } else if corner.is_multiple_of(3) {
+        corner + 2
+    } else {
+        corner - 1
+    }
+}
+
+/// Read access shared by the position connectivity and the per-attribute connectivity
+pub trait CornerView {
+    fn num_corners(&self) -> usize;
+    fn num_vertices(&self) -> usize;
+    fn vertex(&self, corner: u32) -> u32;
+    fn opposite(&self, corner: u32) -> u32;
+    fn left_most_corner(&self, vertex: u32) -> u32;
+
+    fn swing_left(&self, corner: u32) -> u32 {
+        next(self.opposite(next(corner)))
+    }
+
+    fn swing_right(&self, corner: u32) -> u32 {
+        previous(self.opposite(previous(corner)))
+    }
+
+    /// Corners around the vertex of `corner`, starting at `corner`: first swinging left, then
+    /// (when a boundary is hit) swinging right from the start (`VertexCornersIterator`)
+    fn vertex_corners(&self, corner: u32) -> Vec<u32> {
+        let mut corners = Vec::new();
+        let start = corner;
+        let mut current = start;
+        let mut left = true;
+        while current != INVALID {
+            corners.push(current);
+            if left {
+                current = self.swing_left(current);
+                if current == INVALID {
+                    current = self.swing_right(start);
+                    left = false;
+                } else if current == start {
+                    current = INVALID;
+                }
+            } else {
+                current = self.swing_right(current);
+            }
+            if corners.len() > self.num_corners() {
+                // Corrupted connectivity
+                break;
+            }
+        }
+        corners
+    }
+}
+
+#[derive(Default)]
+pub struct CornerTable {
+    corner_to_vertex: Vec<u32>,
+    opposite_corners: Vec<u32>,
+    vertex_corners: Vec<u32>,
+}
+
+impl CornerTable {
+    pub fn new(num_faces: usize, vertex_capacity: usize) -> Self {
+        Self {
+            corner_to_vertex: vec![INVALID; num_faces * 3],
+            opposite_corners: vec![INVALID; num_faces * 3],
+            vertex_corners: Vec::with_capacity(vertex_capacity),
+        }
+    }
+
+    pub fn num_faces(&self) -> usize {
+        self.corner_to_vertex.len() / 3
+    }
+
+    pub fn add_new_vertex(&mut self) -> u32 {
+        self.vertex_corners.push(INVALID);
+        (self.vertex_corners.len() - 1) as u32
+    }
+
+    pub fn map_corner_to_vertex(&mut self, corner: u32, vertex: u32) {
+        self.corner_to_vertex[corner as usize] = vertex;
+    }
+
+    pub fn set_left_most_corner(&mut self, vertex: u32, corner: u32) {
+        if vertex != INVALID {
+            self.vertex_corners[vertex as usize] = corner;
+        }
+    }
+
+    pub fn set_opposite_corners(&mut self, a: u32, b: u32) {
+        self.opposite_corners[a as usize] = b;
+        self.opposite_corners[b as usize] = a;
+    }
+
+    pub fn make_vertex_isolated(&mut self, vertex: u32) {
+        self.vertex_corners[vertex as usize] = INVALID;
+    }
+}
+
+impl CornerView for CornerTable {
+    fn num_corners(&self) -> usize {
+        self.corner_to_vertex.len()
+    }
+
+    fn num_vertices(&self) -> usize {
+        self.vertex_corners.len()
+    }
+
+    fn vertex(&self, corner: u32) -> u32 {
+        if corner == INVALID {
+            INVALID
+        } else {
+            self.corner_to_vertex[corner as usize]
+        }
+    }
+
+    fn opposite(&self, corner: u32) -> u32 {
+        if corner == INVALID {
+            INVALID
+        } else {
+            self.opposite_corners[corner as usize]
+        }
+    }
+
+    fn left_most_corner(&self, vertex: u32) -> u32 {
+        self.vertex_corners
+            .get(vertex as usize)
+            .copied()
+            .unwrap_or(INVALID)
+    }
+}
+
+/// Connectivity of an attribute with seams, e.g. UVs split where the texture is cut
+/// (`MeshAttributeCornerTable`). Seam edges act as boundaries, so a position vertex on a seam
+/// maps to one attribute vertex per side.
+pub struct AttributeCornerTable {
+    is_edge_on_seam: Vec<bool>,
+    is_vertex_on_seam: Vec<bool>,
+    corner_to_vertex: Vec<u32>,
+    vertex_to_left_most_corner: Vec<u32>,
+}
+
+impl AttributeCornerTable {
+    pub fn new(base: &CornerTable) -> Self {
+        Self {
+            is_edge_on_seam: vec![false; base.num_corners()],
+            is_vertex_on_seam: vec![false; base.num_vertices()],
+            corner_to_vertex: vec![INVALID; base.num_corners()],
+            vertex_to_left_most_corner: Vec::new(),
+        }
+    }
+
+    pub fn add_seam_edge(&mut self, base: &CornerTable, corner: u32) {
+        self.is_edge_on_seam[corner as usize] = true;
+        self.mark_seam_vertices(base, corner);
+        let opposite = base.opposite(corner);
+        if opposite != INVALID {
+            self.is_edge_on_seam[opposite as usize] = true;
+            self.mark_seam_vertices(base, opposite);
+        }
+    }
+
+    fn mark_seam_vertices(&mut self, base: &CornerTable, corner: u32) {
+        for vertex in [base.vertex(next(corner)), base.vertex(previous(corner))] {
+            if let Some(on_seam) = self.is_vertex_on_seam.get_mut(vertex as usize) {
+                *on_seam = true;
+            }
+        }
+    }
+
+    pub fn is_corner_on_seam(&self, base: &CornerTable, corner: u32) -> bool {
+        self.is_vertex_on_seam
+            .get(base.vertex(corner) as usize)
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// Splits the base vertices along the seams (`RecomputeVertices`)
+    pub fn recompute_vertices(&mut self, base: &CornerTable) -> bool {
+        self.vertex_to_left_most_corner.clear();
+        let mut num_new_vertices = 0u32;
+        for vertex in 0..base.num_vertices() as u32 {
+            let corner = base.left_most_corner(vertex);
+            if corner == INVALID {
+                continue;
+            }
+            let mut vertex_id = num_new_vertices;
+            num_new_vertices += 1;
+
+            // Start from the first seam edge found swinging left
+            let mut first_corner = corner;
+            if self.is_vertex_on_seam[vertex as usize] {
+                let mut current = self.view(base).swing_left(first_corner);
+                while current != INVALID {
+                    first_corner = current;
+                    current = self.view(base).swing_left(current);
+                    if current == corner {
+                        return false;
+                    }
+                }
+            }
+            self.corner_to_vertex[first_corner as usize] = vertex_id;
+            self.vertex_to_left_most_corner.push(first_corner);
+
+            let mut current = base.swing_right(first_corner);
+            while current != INVALID && current != first_corner {
+                if self.is_edge_on_seam[next(current) as usize] {
+                    vertex_id = num_new_vertices;
+                    num_new_vertices += 1;
+                    self.vertex_to_left_most_corner.push(current);
+                }
+                self.corner_to_vertex[current as usize] = vertex_id;
+                current = base.swing_right(current);
+            }
+        }
+        true
+    }
+
+    pub fn view<'a>(&'a self, base: &'a CornerTable) -> AttributeCornerView<'a> {
+        AttributeCornerView { base, table: self }
+    }
+}
+
+pub struct AttributeCornerView<'a> {
+    base: &'a CornerTable,
+    table: &'a AttributeCornerTable,
+}
+
+impl CornerView for AttributeCornerView<'_> {
+    fn num_corners(&self) -> usize {
+        self.base.num_corners()
+    }
+
+    fn num_vertices(&self) -> usize {
+        self.table.vertex_to_left_most_corner.len()
+    }
+
+    fn vertex(&self, corner: u32) -> u32 {
+        if corner == INVALID {
+            INVALID
+        } else {
+            self.table.corner_to_vertex[corner as usize]
+        }
+    }
+
+    fn opposite(&self, corner: u32) -> u32 {
+        if corner == INVALID || self.table.is_edge_on_seam[corner as usize] {
+            INVALID
+        } else {
+            self.base.opposite(corner)
+        }
+    }
+
+    fn left_most_corner(&self, vertex: u32) -> u32 {
+        self.table
+            .vertex_to_left_most_corner
+            .get(vertex as usize)
+            .copied()
+            .unwrap_or(INVALID)
+    }
+}
@@ -0,0 +1,663 @@
+//! Edgebreaker connectivity decoding (`MeshEdgebreakerDecoderImpl`), standard and valence
+//! traversals. The faces are rebuilt in reverse from the traversal symbols, then the
+//! per-attribute seams split the vertices into the mesh points.
+
+use std::collections::HashMap;
+
+use super::{
+    buffer::DecoderBuffer,
+    corner_table::{next, previous, AttributeCornerTable, CornerTable, CornerView, INVALID},
+    rans::{decode_symbols, RAnsBitDecoder},
+    DracoError, DracoResult,
+};
+
+const STANDARD_ENCODING: u8 = 0;
+const VALENCE_ENCODING: u8 = 2;
+
+const TOPOLOGY_C: u32 = 0;
+const TOPOLOGY_S: u32 = 1;
+const TOPOLOGY_L: u32 = 3;
+const TOPOLOGY_R: u32 = 5;
+const TOPOLOGY_E: u32 = 7;
+const TOPOLOGY_INVALID: u32 = 9;
+
+/// Valence contexts, symbol ids to topology
+const VALENCE_SYMBOLS: [u32; 5] = [TOPOLOGY_C, TOPOLOGY_S, TOPOLOGY_L, TOPOLOGY_R, TOPOLOGY_E];
+const MIN_VALENCE: i32 = 2;
+const MAX_VALENCE: i32 = 7;
+
+const RIGHT_FACE_EDGE: u32 = 1;
+
+/// Connectivity of a non-position attribute
+pub struct AttributeData {
+    pub table: AttributeCornerTable,
+    seam_corners: Vec<u32>,
+}
+
+pub struct EdgebreakerConnectivity {
+    pub corner_table: CornerTable,
+    pub attribute_data: Vec<AttributeData>,
+    pub faces: Vec<[u32; 3]>,
+    pub num_points: usize,
+}
+
+struct TopologySplit {
+    source_symbol_id: u32,
+    split_symbol_id: u32,
+    source_edge: u32,
+}
+
+enum SymbolSource<'a> {
+    Standard(DecoderBuffer<'a>),
+    Valence {
+        context_symbols: Vec<Vec<u32>>,
+        context_counters: Vec<usize>,
+        active_context: Option<usize>,
+        vertex_valences: Vec<i32>,
+        last_symbol: u32,
+    },
+}
+
+impl SymbolSource<'_> {
+    fn decode_symbol(&mut self) -> DracoResult<u32> {
+        match self {
+            SymbolSource::Standard(buffer) => {
+                let symbol = buffer.read_bits(1)?;
+                if symbol == TOPOLOGY_C {
+                    return Ok(symbol);
+                }
+                Ok(symbol | (buffer.read_bits(2)? << 1))
+            }
+            SymbolSource::Valence {
+                context_symbols,
+                context_counters,
+                active_context,
+                last_symbol,
+                ..
+            } => {
+                *last_symbol = match *active_context {
+                    Some(context) => {
+                        if context_counters[context] == 0 {
+                            return Ok(TOPOLOGY_INVALID);
+                        }
+                        context_counters[context] -= 1;
+                        let symbol_id = context_symbols[context][context_counters[context]];
+                        VALENCE_SYMBOLS
+                            .get(symbol_id as usize)
+                            .copied()
+                            .unwrap_or(TOPOLOGY_INVALID)
+                    }
+                    None => TOPOLOGY_E,
+                };
+                Ok(*last_symbol)
+            }
+        }
+    }
+
+    fn new_active_corner_reached(&mut self, table: &CornerTable, corner: u32) {
+        let SymbolSource::Valence {
+            active_context,
+            vertex_valences,
+            last_symbol,
+            ..
+        } = self
+        else {
+            return;
+        };
+        let vertex = |c: u32| table.vertex(c) as usize;
+        let (at_corner, at_next, at_prev) = match *last_symbol {
+            TOPOLOGY_C | TOPOLOGY_S => (0, 1, 1),
+            TOPOLOGY_R => (1, 1, 2),
+            TOPOLOGY_L => (1, 2, 1),
+            TOPOLOGY_E => (2, 2, 2),
+            _ => (0, 0, 0),
+        };
+        for (c, increment) in [
+            (corner, at_corner),
+            (next(corner), at_next),
+            (previous(corner), at_prev),
+        ] {
+            if let Some(valence) = vertex_valences.get_mut(vertex(c)) {
+                *valence += increment;
+            }
+        }
+        let active_valence = vertex_valences
+            .get(vertex(next(corner)))
+            .copied()
+            .unwrap_or(0);
+        *active_context =
+            Some((active_valence.clamp(MIN_VALENCE, MAX_VALENCE) - MIN_VALENCE) as usize);
+    }
+
+    fn merge_vertices(&mut self, dest: u32, source: u32) {
+        if let SymbolSource::Valence {
+            vertex_valences, ..
+        } = self
+        {
+            let source = vertex_valences.get(source as usize).copied().unwrap_or(0);
+            if let Some(dest) = vertex_valences.get_mut(dest as usize) {
+                *dest += source;
+            }
+        }
+    }
+}
+
+struct TraversalDecoder<'a> {
+    symbols: SymbolSource<'a>,
+    start_faces: RAnsBitDecoder<'a>,
+    seams: Vec<RAnsBitDecoder<'a>>,
+}
+
+/// Decodes the connectivity that follows the header, `buffer` ends up at the attributes
+pub fn decode_connectivity(
+    buffer: &mut DecoderBuffer,
+    max_points: usize,
+) -> DracoResult<EdgebreakerConnectivity> {
+    let traversal_type = buffer.read_u8()?;
+    if traversal_type != STANDARD_ENCODING && traversal_type != VALENCE_ENCODING {
+        return Err(DracoError::Unsupported(format!(
+            "edgebreaker traversal {traversal_type}"
+        )));
+    }
+
+    let num_encoded_vertices = buffer.read_varint_u32()? as usize;
+    let num_faces = buffer.read_varint_u32()? as usize;
+    if num_faces > (u32::MAX / 3) as usize || num_encoded_vertices > num_faces * 3 {
+        return Err(DracoError::Malformed("face count"));
+    }
+    // Every vertex is at least one point
+    if num_encoded_vertices > max_points {
+        return Err(DracoError::Malformed("vertex count"));
+    }
+    let num_attribute_data = buffer.read_u8()? as usize;
+    let num_encoded_symbols = buffer.read_varint_u32()? as usize;
+    if num_faces < num_encoded_symbols || num_faces > num_encoded_symbols + num_encoded_symbols / 3
+    {
+        return Err(DracoError::Malformed("symbol count"));
+    }
+    // Each symbol takes at least a bit of what is left, which bounds the corner table below
+    if num_encoded_symbols > buffer.remaining().saturating_mul(8) {
+        return Err(DracoError::Malformed("symbol count"));
+    }
+    let num_encoded_split_symbols = buffer.read_varint_u32()? as usize;
+    if num_encoded_split_symbols > num_encoded_symbols {
+        return Err(DracoError::Malformed("split symbol count"));
+    }
+
+    let max_num_vertices = num_encoded_vertices + num_encoded_split_symbols;
+    let mut corner_table = CornerTable::new(num_faces, max_num_vertices);
+    let mut topology_splits = decode_topology_splits(buffer, num_faces)?;
+
+    // Traversal data
+    let traversal_size = match traversal_type {
+        STANDARD_ENCODING => buffer.read_varint()?,
+        _ => 0,
+    };
+    let symbol_buffer = {
+        let mut symbol_buffer = buffer.clone();
+        symbol_buffer.start_bit_decoding(false)?;
+        symbol_buffer
+    };
+    buffer.advance(
+        usize::try_from(traversal_size).map_err(|_| DracoError::Malformed("traversal size"))?,
+    )?;
+    let start_faces = RAnsBitDecoder::start(buffer)?;
+    let seams = (0..num_attribute_data)
+        .map(|_| RAnsBitDecoder::start(buffer))
+        .collect::<DracoResult<Vec<_>>>()?;
+    let symbols = if traversal_type == STANDARD_ENCODING {
+        SymbolSource::Standard(symbol_buffer)
+    } else {
+        let num_contexts = (MAX_VALENCE - MIN_VALENCE + 1) as usize;
+        let mut context_symbols = Vec::with_capacity(num_contexts);
+        for _ in 0..num_contexts {
+            let num_symbols = buffer.read_varint_u32()? as usize;
+            if num_symbols > num_faces {
+                return Err(DracoError::Malformed("valence context size"));
+            }
+            context_symbols.push(decode_symbols(num_symbols, 1, buffer)?);
+        }
+        SymbolSource::Valence {
+            context_counters: context_symbols.iter().map(Vec::len).collect(),
+            context_symbols,
+            active_context: None,
+            vertex_valences: vec![0; max_num_vertices],
+            last_symbol: TOPOLOGY_INVALID,
+        }
+    };
+    let mut traversal = TraversalDecoder {
+        symbols,
+        start_faces,
+        seams,
+    };
+
+    let mut is_vert_hole = vec![true; max_num_vertices];
+    let num_connectivity_verts = decode_faces(
+        &mut corner_table,
+        &mut traversal,
+        &mut topology_splits,
+        &mut is_vert_hole,
+        num_encoded_symbols,
+        num_attribute_data == 0,
+    )?;
+
+    let mut attribute_data: Vec<_> = (0..num_attribute_data)
+        .map(|_| AttributeData {
+            table: AttributeCornerTable::new(&corner_table),
+            seam_corners: Vec::new(),
+        })
+        .collect();
+    if !attribute_data.is_empty() {
+        decode_attribute_seams(&corner_table, &mut traversal, &mut attribute_data);
+    }
+    for data in &mut attribute_data {
+        for corner in std::mem::take(&mut data.seam_corners) {
+            data.table.add_seam_edge(&corner_table, corner);
+        }
+        if !data.table.recompute_vertices(&corner_table) {
+            return Err(DracoError::Malformed("attribute seams"));
+        }
+    }
+
+    let (faces, num_points) = assign_points_to_corners(
+        &corner_table,
+        &attribute_data,
+        &is_vert_hole,
+        num_connectivity_verts,
+    )?;
+    if num_points > max_points {
+        return Err(DracoError::Malformed("point count"));
+    }
+    Ok(EdgebreakerConnectivity {
+        corner_table,
+        attribute_data,
+        faces,
+        num_points,
+    })
+}
+
+fn decode_topology_splits(
+    buffer: &mut DecoderBuffer,
+    num_faces: usize,
+) -> DracoResult<Vec<TopologySplit>> {
+    let num_topology_splits = buffer.read_varint_u32()? as usize;
+    if num_topology_splits > num_faces {
+        return Err(DracoError::Malformed("topology split count"));
+    }
+    let mut splits = Vec::with_capacity(num_topology_splits);
+    let mut last_source_symbol_id = 0u32;
+    for _ in 0..num_topology_splits {
+        let source_symbol_id = buffer
+            .read_varint_u32()?
+            .checked_add(last_source_symbol_id)
+            .ok_or(DracoError::Malformed("topology split source"))?;
+        let delta = buffer.read_varint_u32()?;
+        if delta > source_symbol_id {
+            return Err(DracoError::Malformed("topology split delta"));
+        }
+        splits.push(TopologySplit {
+            source_symbol_id,
+            split_symbol_id: source_symbol_id - delta,
+            source_edge: 0,
+        });
+        last_source_symbol_id = source_symbol_id;
+    }
+    if !splits.is_empty() {
+        buffer.start_bit_decoding(false)?;
+        for split in &mut splits {
+            split.source_edge = buffer.read_bits(1)?;
+        }
+        buffer.end_bit_decoding();
+    }
+    Ok(splits)
+}
+
+/// Rebuilds the faces from the traversal symbols (`DecodeConnectivity(num_symbols)`), returns
+/// the number of vertices
+fn decode_faces(
+    table: &mut CornerTable,
+    traversal: &mut TraversalDecoder,
+    topology_splits: &mut Vec<TopologySplit>,
+    is_vert_hole: &mut [bool],
+    num_symbols: usize,
+    remove_invalid_vertices: bool,
+) -> DracoResult<usize> {
+    let max_num_vertices = is_vert_hole.len();
+    let corrupted = || DracoError::Malformed("edgebreaker connectivity");
+    let mut active_corners: Vec<u32> = Vec::new();
+    let mut topology_split_corners: HashMap<usize, u32> = HashMap::new();
+    let mut invalid_vertices = Vec::new();
+    let mut num_faces = 0usize;
+
+    for symbol_id in 0..num_symbols {
+        let corner = (3 * num_faces) as u32;
+        num_faces += 1;
+        let mut check_topology_split = false;
+        match traversal.symbols.decode_symbol()? {
+            TOPOLOGY_C => {
+                let corner_a = *active_corners.last().ok_or_else(corrupted)?;
+                let vertex_x = table.vertex(next(corner_a));
+                let corner_b = next(table.left_most_corner(vertex_x));
+                if vertex_x == INVALID
+                    || corner_b == INVALID
+                    || corner_a == corner_b
+                    || table.opposite(corner_a) != INVALID
+                    || table.opposite(corner_b) != INVALID
+                {
+                    return Err(corrupted());
+                }
+                table.set_opposite_corners(corner_a, corner + 1);
+                table.set_opposite_corners(corner_b, corner + 2);
+
+                let vert_a_prev = table.vertex(previous(corner_a));
+                let vert_b_next = table.vertex(next(corner_b));
+                if vertex_x == vert_a_prev || vertex_x == vert_b_next {
+                    return Err(corrupted());
+                }
+                table.map_corner_to_vertex(corner, vertex_x);
+                table.map_corner_to_vertex(corner + 1, vert_b_next);
+                table.map_corner_to_vertex(corner + 2, vert_a_prev);
+                table.set_left_most_corner(vert_a_prev, corner + 2);
+                is_vert_hole[vertex_x as usize] = false;
+                *active_corners.last_mut().ok_or_else(corrupted)? = corner;
+            }
+            symbol @ (TOPOLOGY_R | TOPOLOGY_L) => {
+                let corner_a = *active_corners.last().ok_or_else(corrupted)?;
+                if table.opposite(corner_a) != INVALID {
+                    return Err(corrupted());
+                }
+                let (opp_corner, corner_l, corner_r) = if symbol == TOPOLOGY_R {
+                    (corner + 2, corner + 1, corner)
+                } else {
+                    (corner + 1, corner, corner + 2)
+                };
+                table.set_opposite_corners(opp_corner, corner_a);
+                let new_vertex = table.add_new_vertex();
+                if table.num_vertices() > max_num_vertices {
+                    return Err(corrupted());
+                }
+                table.map_corner_to_vertex(opp_corner, new_vertex);
+                table.set_left_most_corner(new_vertex, opp_corner);
+
+                let vertex_r = table.vertex(previous(corner_a));
+                table.map_corner_to_vertex(corner_r, vertex_r);
+                table.set_left_most_corner(vertex_r, corner_r);
+                table.map_corner_to_vertex(corner_l, table.vertex(next(corner_a)));
+                *active_corners.last_mut().ok_or_else(corrupted)? = corner;
+                check_topology_split = true;
+            }
+            TOPOLOGY_S => {
+                let corner_b = active_corners.pop().ok_or_else(corrupted)?;
+                if let Some(split_corner) = topology_split_corners.get(&symbol_id) {
+                    active_corners.push(*split_corner);
+                }
+                let corner_a = *active_corners.last().ok_or_else(corrupted)?;
+                if corner_a == corner_b
+                    || table.opposite(corner_a) != INVALID
+                    || table.opposite(corner_b) != INVALID
+                {
+                    return Err(corrupted());
+                }
+                table.set_opposite_corners(corner_a, corner + 2);
+                table.set_opposite_corners(corner_b, corner + 1);
+
+                let vertex_p = table.vertex(previous(corner_a));
+                table.map_corner_to_vertex(corner, vertex_p);
+                table.map_corner_to_vertex(corner + 1, table.vertex(next(corner_a)));
+                let vert_b_prev = table.vertex(previous(corner_b));
+                table.map_corner_to_vertex(corner + 2, vert_b_prev);
+                table.set_left_most_corner(vert_b_prev, corner + 2);
+
+                let mut corner_n = next(corner_b);
+                let vertex_n = table.vertex(corner_n);
+                if vertex_n == INVALID || vertex_p == INVALID {
+                    return Err(corrupted());
+                }
+                traversal.symbols.merge_vertices(vertex_p, vertex_n);
+                table.set_left_most_corner(vertex_p, table.left_most_corner(vertex_n));
+
+                // Every corner on vertex "n" now belongs to "p"
+                let first_corner = corner_n;
+                while corner_n != INVALID {
+                    table.map_corner_to_vertex(corner_n, vertex_p);
+                    corner_n = table.swing_left(corner_n);
+                    if corner_n == first_corner {
+                        return Err(corrupted());
+                    }
+                }
+                table.make_vertex_isolated(vertex_n);
+                if remove_invalid_vertices {
+                    invalid_vertices.push(vertex_n);
+                }
+                *active_corners.last_mut().ok_or_else(corrupted)? = corner;
+            }
+            TOPOLOGY_E => {
+                let first_vertex = table.add_new_vertex();
+                table.map_corner_to_vertex(corner, first_vertex);
+                let second_vertex = table.add_new_vertex();
+                table.map_corner_to_vertex(corner + 1, second_vertex);
+                let third_vertex = table.add_new_vertex();
+                table.map_corner_to_vertex(corner + 2, third_vertex);
+                if table.num_vertices() > max_num_vertices {
+                    return Err(corrupted());
+                }
+                table.set_left_most_corner(first_vertex, corner);
+                table.set_left_most_corner(second_vertex, corner + 1);
+                table.set_left_most_corner(third_vertex, corner + 2);
+                active_corners.push(corner);
+                check_topology_split = true;
+            }
+            _ => return Err(corrupted()),
+        }
+        let active_corner = *active_corners.last().ok_or_else(corrupted)?;
+        traversal
+            .symbols
+            .new_active_corner_reached(table, active_corner);
+
+        if check_topology_split {
+            // The encoder numbers the symbols in reverse
+            let encoder_symbol_id = (num_symbols - symbol_id - 1) as u32;
+            while let Some(split) = topology_splits.last() {
+                if split.source_symbol_id > encoder_symbol_id {
+                    return Err(corrupted());
+                }
+                if split.source_symbol_id != encoder_symbol_id {
+                    break;
+                }
+                let new_active_corner = if split.source_edge == RIGHT_FACE_EDGE {
+                    next(active_corner)
+                } else {
+                    previous(active_corner)
+                };
+                let decoder_split_symbol_id = num_symbols
+                    .checked_sub(split.split_symbol_id as usize + 1)
+                    .ok_or_else(corrupted)?;
+                topology_split_corners.insert(decoder_split_symbol_id, new_active_corner);
+                topology_splits.pop();
+            }
+        }
+    }
+    if table.num_vertices() > max_num_vertices {
+        return Err(corrupted());
+    }
+
+    // Start faces close the remaining open edges, either as a new interior face or by leaving
+    // a boundary
+    while let Some(corner) = active_corners.pop() {
+        if !traversal.start_faces.decode_next_bit() {
+            continue;
+        }
+        if num_faces >= table.num_faces() {
+            return Err(corrupted());
+        }
+        let vert_n = table.vertex(next(corner));
+        let corner_b = next(table.left_most_corner(vert_n));
+        let vert_x = table.vertex(next(corner_b));
+        let corner_c = next(table.left_most_corner(vert_x));
+        if corner == corner_b
+            || corner == corner_c
+            || corner_b == corner_c
+            || table.opposite(corner) != INVALID
+            || table.opposite(corner_b) != INVALID
+            || table.opposite(corner_c) != INVALID
+        {
+            return Err(corrupted());
+        }
+        let vert_p = table.vertex(next(corner_c));
+        if corner_b == INVALID || corner_c == INVALID {
+            return Err(corrupted());
+        }
+
+        let new_corner = (3 * num_faces) as u32;
+        num_faces += 1;
+        table.set_opposite_corners(new_corner, corner);
+        table.set_opposite_corners(new_corner + 1, corner_b);
+        table.set_opposite_corners(new_corner + 2, corner_c);
+        table.map_corner_to_vertex(new_corner, vert_x);
+        table.map_corner_to_vertex(new_corner + 1, vert_p);
+        table.map_corner_to_vertex(new_corner + 2, vert_n);
+        for vertex in [vert_x, vert_p, vert_n] {
+            if let Some(hole) = is_vert_hole.get_mut(vertex as usize) {
+                *hole = false;
+            }
+        }
+    }
+    if num_faces != table.num_faces() {
+        return Err(corrupted());
+    }
+
+    // Move the last valid vertices into the slots of the merged ones, so every vertex below
+    // the returned count is in use
+    let mut num_vertices = table.num_vertices();
+    for invalid_vertex in invalid_vertices {
+        let mut src_vertex = num_vertices as u32 - 1;
+        while table.left_most_corner(src_vertex) == INVALID {
+            num_vertices -= 1;
+            src_vertex = num_vertices as u32 - 1;
+        }
+        if src_vertex < invalid_vertex {
+            continue;
+        }
+        for corner in table.vertex_corners(table.left_most_corner(src_vertex)) {
+            if table.vertex(corner) != src_vertex {
+                return Err(corrupted());
+            }
+            table.map_corner_to_vertex(corner, invalid_vertex);
+        }
+        table.set_left_most_corner(invalid_vertex, table.left_most_corner(src_vertex));
+        table.make_vertex_isolated(src_vertex);
+        is_vert_hole[invalid_vertex as usize] = is_vert_hole[src_vertex as usize];
+        is_vert_hole[src_vertex as usize] = false;
+        num_vertices -= 1;
+    }
+    Ok(num_vertices)
+}
+
+/// Seam flags for every interior edge, boundary edges are always seams
+fn decode_attribute_seams(
+    table: &CornerTable,
+    traversal: &mut TraversalDecoder,
+    attribute_data: &mut [AttributeData],
+) {
+    for face in 0..table.num_faces() as u32 {
+        let corner = face * 3;
+        for c in [corner, next(corner), previous(corner)] {
+            let opposite = table.opposite(c);
+            if opposite == INVALID {
+                for data in attribute_data.iter_mut() {
+                    data.seam_corners.push(c);
+                }
+                continue;
+            }
+            if opposite / 3 < face {
+                continue;
+            }
+            for (data, seams) in attribute_data.iter_mut().zip(traversal.seams.iter_mut()) {
+                if seams.decode_next_bit() {
+                    data.seam_corners.push(c);
+                }
+            }
+        }
+    }
+}
+
+/// Splits the vertices into points wherever any attribute has a seam
+/// (`AssignPointsToCorners`), returns the faces in point ids and the point count
+fn assign_points_to_corners(
+    table: &CornerTable,
+    attribute_data: &[AttributeData],
+    is_vert_hole: &[bool],
+    num_connectivity_verts: usize,
+) -> DracoResult<(Vec<[u32; 3]>, usize)> {
+    let num_faces = table.num_faces();
+    if attribute_data.is_empty() {
+        let faces = (0..num_faces as u32)
+            .map(|f| {
+                [
+                    table.vertex(3 * f),
+                    table.vertex(3 * f + 1),
+                    table.vertex(3 * f + 2),
+                ]
+            })
+            .collect();
+        return Ok((faces, num_connectivity_verts));
+    }
+
+    let views: Vec<_> = attribute_data
+        .iter()
+        .map(|data| data.table.view(table))
+        .collect();
+    let mut num_points = 0u32;
+    let mut corner_to_point = vec![0u32; table.num_corners()];
+    for vertex in 0..table.num_vertices() as u32 {
+        let corner = table.left_most_corner(vertex);
+        if corner == INVALID {
+            continue;
+        }
+        // Interior vertices start at the first seam of any attribute
+        let mut first_corner = corner;
+        if !is_vert_hole[vertex as usize] {
+            'attributes: for (data, view) in attribute_data.iter().zip(&views) {
+                if !data.table.is_corner_on_seam(table, corner) {
+                    continue;
+                }
+                let vertex_id = view.vertex(corner);
+                let mut current = table.swing_right(corner);
+                while current != corner {
+                    if current == INVALID {
+                        return Err(DracoError::Malformed("attribute seam traversal"));
+                    }
+                    if view.vertex(current) != vertex_id {
+                        first_corner = current;
+                        break 'attributes;
+                    }
+                    current = table.swing_right(current);
+                }
+            }
+        }
+
+        corner_to_point[first_corner as usize] = num_points;
+        num_points += 1;
+        let mut previous_corner = first_corner;
+        let mut current = table.swing_right(first_corner);
+        while current != INVALID && current != first_corner {
+            let on_seam = views
+                .iter()
+                .any(|view| view.vertex(current) != view.vertex(previous_corner));
+            corner_to_point[current as usize] = if on_seam {
+                num_points += 1;
+                num_points - 1
+            } else {
+                corner_to_point[previous_corner as usize]
+            };
+            previous_corner = current;
+            current = table.swing_right(current);
+        }
+    }
+
+    let faces = corner_to_point
+        .chunks_exact(3)
+        .map(|face| [face[0], face[1], face[2]])
+        .collect();
+    Ok((faces, num_points as usize))
+}
@@ -0,0 +1,297 @@
+//! Draco mesh decoder for `KHR_draco_mesh_compression` primitives.
+//!
+//! A port of the parts of the reference decoder the glTF encoders produce: triangular meshes
+//! in bitstream 2.2 with sequential or Edgebreaker (standard and valence) connectivity, and
+//! generic, integer, quantized and octahedral normal attributes with all their prediction
+//! schemes. Older bitstreams and point clouds are rejected.
+
+mod attributes;
+mod buffer;
+mod corner_table;
+mod edgebreaker;
+mod prediction;
+mod rans;
+mod rewrite;
+
+pub use attributes::DataType;
+pub use rewrite::decompress_draco_primitives;
+
+use attributes::Connectivity;
+use buffer::DecoderBuffer;
+use rans::decode_symbols;
+
+const DRACO_MAGIC: &[u8] = b"DRACO";
+const SUPPORTED_VERSION: (u8, u8) = (2, 2);
+const TRIANGULAR_MESH: u8 = 1;
+const MESH_SEQUENTIAL_ENCODING: u8 = 0;
+const MESH_EDGEBREAKER_ENCODING: u8 = 1;
+const METADATA_FLAG_MASK: u16 = 0x8000;
+
+const SEQUENTIAL_COMPRESSED_INDICES: u8 = 0;
+
+#[derive(Debug)]
+pub enum DracoError {
+    UnexpectedEnd,
+    Malformed(&'static str),
+    Unsupported(String),
+}
+
+impl std::fmt::Display for DracoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DracoError::UnexpectedEnd => write!(f, "unexpected end of draco data"),
+            DracoError::Malformed(what) => write!(f, "malformed draco data: {what}"),
+            DracoError::Unsupported(what) => write!(f, "unsupported draco feature: {what}"),
+        }
+    }
+}
+
+impl std::error::Error for DracoError {}
+
+pub type DracoResult<T> = Result<T, DracoError>;
+
+/// Decoded triangle mesh, faces index points and every attribute has a value per point
+pub struct DracoMesh {
+    pub faces: Vec<[u32; 3]>,
+    pub num_points: usize,
+    pub attributes: Vec<DracoAttribute>,
+}
+
+impl DracoMesh {
+    /// Attribute with the id the glTF `KHR_draco_mesh_compression.attributes` map refers to
+    pub fn attribute(&self, unique_id: u32) -> Option<&DracoAttribute> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.unique_id == unique_id)
+    }
+}
+
+pub struct DracoAttribute {
+    pub unique_id: u32,
+    pub data_type: DataType,
+    pub num_components: usize,
+    pub normalized: bool,
+    /// Unique values, `num_components` each
+    values: Vec<f64>,
+    point_to_value: Vec<u32>,
+}
+
+impl DracoAttribute {
+    /// Components of the value of `point`
+    pub fn value(&self, point: usize) -> DracoResult<&[f64]> {
+        let offset = *self
+            .point_to_value
+            .get(point)
+            .ok_or(DracoError::Malformed("point out of range"))? as usize
+            * self.num_components;
+        self.values
+            .get(offset..offset + self.num_components)
+            .ok_or(DracoError::Malformed("attribute value out of range"))
+    }
+}
+
+/// Decodes a Draco mesh, as stored in the buffer view of a compressed primitive.
+///
+/// `max_points` is the vertex count of the primitive's accessors, the header counts are checked
+/// against it before anything is allocated from them.
+pub fn decode_draco_mesh(data: &[u8], max_points: usize) -> DracoResult<DracoMesh> {
+    let mut buffer = DecoderBuffer::new(data);
+    if buffer.read_bytes(DRACO_MAGIC.len())? != DRACO_MAGIC {
+        return Err(DracoError::Malformed("missing DRACO magic"));
+    }
+    let version = (buffer.read_u8()?, buffer.read_u8()?);
+    if version != SUPPORTED_VERSION {
+        return Err(DracoError::Unsupported(format!(
+            "bitstream version {}.{}",
+            version.0, version.1
+        )));
+    }
+    let encoder_type = buffer.read_u8()?;
+    if encoder_type != TRIANGULAR_MESH {
+        return Err(DracoError::Unsupported(format!(
+            "geometry type {encoder_type}"
+        )));
+    }
+    let method = buffer.read_u8()?;
+    let flags = buffer.read_u16()?;
+    if flags & METADATA_FLAG_MASK != 0 {
+        skip_metadata(&mut buffer)?;
+    }
+
+    match method {
+        MESH_SEQUENTIAL_ENCODING => {
+            let (faces, num_points) = decode_sequential_connectivity(&mut buffer, max_points)?;
+            let connectivity = Connectivity {
+                faces: &faces,
+                num_points,
+                edgebreaker: None,
+            };
+            let attributes = attributes::decode_attributes(&mut buffer, &connectivity)?;
+            Ok(DracoMesh {
+                faces,
+                num_points,
+                attributes,
+            })
+        }
+        MESH_EDGEBREAKER_ENCODING => {
+            let mut edgebreaker = edgebreaker::decode_connectivity(&mut buffer, max_points)?;
+            let faces = std::mem::take(&mut edgebreaker.faces);
+            let connectivity = Connectivity {
+                faces: &faces,
+                num_points: edgebreaker.num_points,
+                edgebreaker: Some(&edgebreaker),
+            };
+            let attributes = attributes::decode_attributes(&mut buffer, &connectivity)?;
+            Ok(DracoMesh {
+                num_points: edgebreaker.num_points,
+                faces,
+                attributes,
+            })
+        }
+        _ => Err(DracoError::Unsupported(format!("encoding method {method}"))),
+    }
+}
+
+/// Skips the geometry metadata, glTF keeps its own
+fn skip_metadata(buffer: &mut DecoderBuffer) -> DracoResult<()> {
+    let skip_name = |buffer: &mut DecoderBuffer| -> DracoResult<()> {
+        let len = buffer.read_u8()? as usize;
+        buffer.advance(len)
+    };
+    // Attribute metadata are preceded by the attribute id, then comes the geometry metadata
+    let num_attribute_metadata = buffer.read_varint_u32()?;
+    for index in 0..=num_attribute_metadata {
+        if index < num_attribute_metadata {
+            buffer.read_varint_u32()?;
+        }
+        // Each metadata is a tree, nested metadata are decoded after their parent
+        let mut pending = 1u64;
+        let mut is_root = true;
+        while pending > 0 {
+            pending -= 1;
+            if !is_root {
+                skip_name(buffer)?;
+            }
+            is_root = false;
+            for _ in 0..buffer.read_varint_u32()? {
+                skip_name(buffer)?;
+                let size = buffer.read_varint_u32()? as usize;
+                if size == 0 {
+                    return Err(DracoError::Malformed("empty metadata entry"));
+                }
+                buffer.advance(size)?;
+            }
+            let num_sub_metadata = u64::from(buffer.read_varint_u32()?);
+            if num_sub_metadata > buffer.remaining() as u64 {
+                return Err(DracoError::Malformed("sub metadata count"));
+            }
+            pending += num_sub_metadata;
+        }
+    }
+    Ok(())
+}
+
+/// `MeshSequentialDecoder::DecodeConnectivity`, returns the faces and the point count
+fn decode_sequential_connectivity(
+    buffer: &mut DecoderBuffer,
+    max_points: usize,
+) -> DracoResult<(Vec<[u32; 3]>, usize)> {
+    let num_faces = buffer.read_varint_u32()? as usize;
+    let num_points = buffer.read_varint_u32()?;
+    if num_faces > buffer.remaining() / 3 {
+        return Err(DracoError::Malformed("face count"));
+    }
+    if num_points as usize > max_points {
+        return Err(DracoError::Malformed("point count"));
+    }
+    let method = buffer.read_u8()?;
+    let mut indices = Vec::with_capacity(num_faces * 3);
+    if method == SEQUENTIAL_COMPRESSED_INDICES {
+        // Delta coded indices, sign in the lowest bit
+        let mut last_index = 0i64;
+        for symbol in decode_symbols(num_faces * 3, 1, buffer)? {
+            let diff = i64::from(symbol >> 1);
+            last_index += if symbol & 1 != 0 { -diff } else { diff };
+            indices
+                .push(u32::try_from(last_index).map_err(|_| DracoError::Malformed("face index"))?);
+        }
+    } else {
+        for _ in 0..num_faces * 3 {
+            indices.push(match num_points {
+                0..=0xff => u32::from(buffer.read_u8()?),
+                0x100..=0xffff => u32::from(buffer.read_u16()?),
+                0x1_0000..=0x1f_ffff => buffer.read_varint_u32()?,
+                _ => buffer.read_u32()?,
+            });
+        }
+    }
+    if indices.iter().any(|index| *index >= num_points) {
+        return Err(DracoError::Malformed("face index out of range"));
+    }
+    let faces = indices
+        .chunks_exact(3)
+        .map(|face| [face[0], face[1], face[2]])
+        .collect();
+    Ok((faces, num_points as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn varint(mut value: u32, data: &mut Vec<u8>) {
+        while value >= 0x80 {
+            data.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        data.push(value as u8);
+    }
+
+    fn header(method: u8) -> Vec<u8> {
+        let mut data = DRACO_MAGIC.to_vec();
+        data.extend_from_slice(&[2, 2, TRIANGULAR_MESH, method, 0, 0]);
+        data
+    }
+
+    fn assert_malformed(result: DracoResult<DracoMesh>, expected: &str) {
+        match result {
+            Err(DracoError::Malformed(what)) => assert_eq!(what, expected),
+            Err(err) => panic!("expected malformed {expected}, got {err}"),
+            Ok(_) => panic!("expected malformed {expected}, got a mesh"),
+        }
+    }
+
+    #[test]
+    fn rejects_sequential_point_count_above_the_accessors() {
+        // 1 face over u32::MAX points, raw indices
+        let mut data = header(MESH_SEQUENTIAL_ENCODING);
+        varint(1, &mut data);
+        varint(u32::MAX, &mut data);
+        data.extend_from_slice(&[1, 0, 1, 2]);
+        assert_malformed(decode_draco_mesh(&data, 3), "point count");
+    }
+
+    #[test]
+    fn rejects_edgebreaker_counts_before_allocating() {
+        let edgebreaker = |num_vertices: u32, num_symbols: u32| {
+            let mut data = header(MESH_EDGEBREAKER_ENCODING);
+            data.push(0);
+            varint(num_vertices, &mut data);
+            varint(num_symbols, &mut data);
+            data.push(0);
+            varint(num_symbols, &mut data);
+            varint(0, &mut data);
+            data.extend_from_slice(&[0; 16]);
+            data
+        };
+        // A corner table for 200M faces would take 4.8GB, from a few bytes of data
+        assert_malformed(
+            decode_draco_mesh(&edgebreaker(3, 200_000_000), 3),
+            "symbol count",
+        );
+        assert_malformed(
+            decode_draco_mesh(&edgebreaker(1_000_000, 200_000_000), 3),
+            "vertex count",
+        );
+    }
+}
@@ -0,0 +1,927 @@
+//! Prediction schemes and their correction transforms: the attribute values are stored as
+//! corrections to a prediction made from the values decoded before them (the previous value,
+//! a parallelogram over the neighbouring triangle, the surface normal...).
+
+use super::{
+    buffer::DecoderBuffer,
+    corner_table::{next, previous, CornerView, INVALID},
+    rans::RAnsBitDecoder,
+    DracoError, DracoResult,
+};
+
+const PREDICTION_NONE: i8 = -2;
+const PREDICTION_DIFFERENCE: i8 = 0;
+const MESH_PREDICTION_PARALLELOGRAM: i8 = 1;
+const MESH_PREDICTION_MULTI_PARALLELOGRAM: i8 = 2;
+const MESH_PREDICTION_TEX_COORDS_DEPRECATED: i8 = 3;
+const MESH_PREDICTION_CONSTRAINED_MULTI_PARALLELOGRAM: i8 = 4;
+const MESH_PREDICTION_TEX_COORDS_PORTABLE: i8 = 5;
+const MESH_PREDICTION_GEOMETRIC_NORMAL: i8 = 6;
+
+const TRANSFORM_WRAP: i8 = 1;
+const TRANSFORM_NORMAL_OCTAHEDRON: i8 = 2;
+const TRANSFORM_NORMAL_OCTAHEDRON_CANONICALIZED: i8 = 3;
+
+const MAX_NUM_PARALLELOGRAMS: usize = 4;
+
+/// Connectivity the mesh schemes walk, with the traversal order of the attribute values
+pub struct MeshData<'a> {
+    pub table: &'a dyn CornerView,
+    /// Corner each value was reached from, in decoding order
+    pub data_to_corner: &'a [u32],
+    pub vertex_to_data: &'a [i32],
+}
+
+/// Quantized positions, parent of the tex coord and normal predictions
+pub struct PositionData<'a> {
+    pub values: &'a [i32],
+    pub point_to_value: &'a [u32],
+}
+
+pub struct PredictionContext<'a> {
+    pub mesh: Option<MeshData<'a>>,
+    /// Point of each decoded value
+    pub entry_to_point: &'a [u32],
+    pub positions: Option<PositionData<'a>>,
+}
+
+impl PredictionContext<'_> {
+    fn position_for_entry(&self, entry: usize) -> DracoResult<[i64; 3]> {
+        let positions = self
+            .positions
+            .as_ref()
+            .ok_or(DracoError::Malformed("missing positions"))?;
+        let value = self
+            .entry_to_point
+            .get(entry)
+            .and_then(|point| positions.point_to_value.get(*point as usize))
+            .map(|value| *value as usize * 3)
+            .filter(|offset| offset + 3 <= positions.values.len())
+            .ok_or(DracoError::Malformed("position out of range"))?;
+        Ok([
+            i64::from(positions.values[value]),
+            i64::from(positions.values[value + 1]),
+            i64::from(positions.values[value + 2]),
+        ])
+    }
+}
+
+fn data_id(mesh: &MeshData, corner: u32) -> Option<i32> {
+    let vertex = mesh.table.vertex(corner);
+    if vertex == INVALID {
+        return None;
+    }
+    mesh.vertex_to_data.get(vertex as usize).copied()
+}
+
+enum Transform {
+    Wrap {
+        min: i32,
+        max: i32,
+    },
+    Octahedron {
+        octahedron: Octahedron,
+        canonicalized: bool,
+    },
+}
+
+impl Transform {
+    fn decode_transform_data(&mut self, buffer: &mut DecoderBuffer) -> DracoResult<()> {
+        match self {
+            Transform::Wrap { min, max } => {
+                *min = buffer.read_i32()?;
+                *max = buffer.read_i32()?;
+                let dif = i64::from(*max) - i64::from(*min);
+                if !(0..i64::from(i32::MAX)).contains(&dif) {
+                    return Err(DracoError::Malformed("wrap bounds"));
+                }
+            }
+            Transform::Octahedron { octahedron, .. } => {
+                let max_quantized_value = buffer.read_i32()?;
+                let bits = 32 - max_quantized_value.leading_zeros();
+                if max_quantized_value % 2 == 0 || !(2..=30).contains(&bits) {
+                    return Err(DracoError::Malformed("octahedron max value"));
+                }
+                *octahedron = Octahedron::from_quantization_bits(bits);
+            }
+        }
+        Ok(())
+    }
+
+    fn are_corrections_positive(&self) -> bool {
+        matches!(self, Transform::Octahedron { .. })
+    }
+
+    fn compute_original_value(&self, predicted: &[i32], corrections: &[i32], out: &mut [i32]) {
+        match *self {
+            Transform::Wrap { min, max } => {
+                let max_dif = max.wrapping_sub(min).wrapping_add(1);
+                for ((out, predicted), correction) in out.iter_mut().zip(predicted).zip(corrections)
+                {
+                    let mut value = (*predicted).clamp(min, max).wrapping_add(*correction);
+                    if value > max {
+                        value = value.wrapping_sub(max_dif);
+                    } else if value < min {
+                        value = value.wrapping_add(max_dif);
+                    }
+                    *out = value;
+                }
+            }
+            Transform::Octahedron {
+                octahedron,
+                canonicalized,
+            } => {
+                let value = if canonicalized {
+                    octahedron.canonicalized_original_value(
+                        [predicted[0], predicted[1]],
+                        [corrections[0], corrections[1]],
+                    )
+                } else {
+                    octahedron.original_value(
+                        [predicted[0], predicted[1]],
+                        [corrections[0], corrections[1]],
+                    )
+                };
+                out[..2].copy_from_slice(&value);
+            }
+        }
+    }
+}
+
+/// Octahedral normal coordinates helpers (`OctahedronToolBox`)
+#[derive(Clone, Copy, Default)]
+pub struct Octahedron {
+    max_quantized_value: i32,
+    max_value: i32,
+    center: i32,
+}
+
+impl Octahedron {
+    pub fn from_quantization_bits(bits: u32) -> Self {
+        let max_quantized_value = (1i32 << bits) - 1;
+        let max_value = max_quantized_value - 1;
+        Self {
+            max_quantized_value,
+            max_value,
+            center: max_value / 2,
+        }
+    }
+
+    fn is_in_diamond(&self, s: i32, t: i32) -> bool {
+        i64::from(s).abs() + i64::from(t).abs() <= i64::from(self.center)
+    }
+
+    /// Mirrors a point outside the central diamond into it (and back), expects the center at 0
+    fn invert_diamond(&self, s: &mut i32, t: &mut i32) {
+        let (sign_s, sign_t): (i32, i32) = if *s >= 0 && *t >= 0 {
+            (1, 1)
+        } else if *s <= 0 && *t <= 0 {
+            (-1, -1)
+        } else {
+            (if *s > 0 { 1 } else { -1 }, if *t > 0 { 1 } else { -1 })
+        };
+        let corner_s = sign_s.wrapping_mul(self.center) as u32;
+        let corner_t = sign_t.wrapping_mul(self.center) as u32;
+        let mut us = *s as u32;
+        let mut ut = *t as u32;
+        us = us.wrapping_add(us).wrapping_sub(corner_s);
+        ut = ut.wrapping_add(ut).wrapping_sub(corner_t);
+        if sign_s * sign_t >= 0 {
+            let temp = us;
+            us = ut.wrapping_neg();
+            ut = temp.wrapping_neg();
+        } else {
+            std::mem::swap(&mut us, &mut ut);
+        }
+        us = us.wrapping_add(corner_s);
+        ut = ut.wrapping_add(corner_t);
+        *s = (us as i32) / 2;
+        *t = (ut as i32) / 2;
+    }
+
+    fn mod_max(&self, x: i32) -> i32 {
+        if x > self.center {
+            x - self.max_quantized_value
+        } else if x < -self.center {
+            x + self.max_quantized_value
+        } else {
+            x
+        }
+    }
+
+    fn original_value(&self, predicted: [i32; 2], correction: [i32; 2]) -> [i32; 2] {
+        let mut pred = [predicted[0] - self.center, predicted[1] - self.center];
+        let in_diamond = self.is_in_diamond(pred[0], pred[1]);
+        if !in_diamond {
+            let [s, t] = &mut pred;
+            self.invert_diamond(s, t);
+        }
+        let mut orig = [
+            self.mod_max(pred[0].wrapping_add(correction[0])),
+            self.mod_max(pred[1].wrapping_add(correction[1])),
+        ];
+        if !in_diamond {
+            let [s, t] = &mut orig;
+            self.invert_diamond(s, t);
+        }
+        [orig[0] + self.center, orig[1] + self.center]
+    }
+
+    fn canonicalized_original_value(&self, predicted: [i32; 2], correction: [i32; 2]) -> [i32; 2] {
+        let mut pred = [predicted[0] - self.center, predicted[1] - self.center];
+        let in_diamond = self.is_in_diamond(pred[0], pred[1]);
+        if !in_diamond {
+            let [s, t] = &mut pred;
+            self.invert_diamond(s, t);
+        }
+        let in_bottom_left = (pred[0] == 0 && pred[1] == 0) || (pred[0] < 0 && pred[1] <= 0);
+        let rotation_count = match (pred[0].signum(), pred[1]) {
+            (0, 0) => 0,
+            (0, y) if y > 0 => 3,
+            (0, _) => 1,
+            (1, y) if y >= 0 => 2,
+            (1, _) => 1,
+            (_, y) if y <= 0 => 0,
+            _ => 3,
+        };
+        if !in_bottom_left {
+            pred = rotate(pred, rotation_count);
+        }
+        let mut orig = [
+            self.mod_max(pred[0].wrapping_add(correction[0])),
+            self.mod_max(pred[1].wrapping_add(correction[1])),
+        ];
+        if !in_bottom_left {
+            orig = rotate(orig, (4 - rotation_count) % 4);
+        }
+        if !in_diamond {
+            let [s, t] = &mut orig;
+            self.invert_diamond(s, t);
+        }
+        [orig[0] + self.center, orig[1] + self.center]
+    }
+
+    /// Scales an integer vector to an L1 norm of `center`
+    fn canonicalize_integer_vector(&self, vec: &mut [i64; 3]) {
+        let abs_sum = vec[0].abs() + vec[1].abs() + vec[2].abs();
+        let center = i64::from(self.center);
+        if abs_sum == 0 {
+            vec[0] = center;
+        } else {
+            vec[0] = vec[0] * center / abs_sum;
+            vec[1] = vec[1] * center / abs_sum;
+            let rest = center - vec[0].abs() - vec[1].abs();
+            vec[2] = if vec[2] >= 0 { rest } else { -rest };
+        }
+    }
+
+    fn integer_vector_to_quantized_coords(&self, vec: &[i64; 3]) -> [i32; 2] {
+        let [x, y, z] = vec.map(|v| v as i32);
+        let (s, t) = if x >= 0 {
+            (y + self.center, z + self.center)
+        } else {
+            (
+                if y < 0 {
+                    z.abs()
+                } else {
+                    self.max_value - z.abs()
+                },
+                if z < 0 {
+                    y.abs()
+                } else {
+                    self.max_value - y.abs()
+                },
+            )
+        };
+        self.canonicalize_coords(s, t)
+    }
+
+    fn canonicalize_coords(&self, mut s: i32, mut t: i32) -> [i32; 2] {
+        let (max, center) = (self.max_value, self.center);
+        if (s == 0 && (t == 0 || t == max)) || (s == max && t == 0) {
+            s = max;
+            t = max;
+        } else if s == 0 && t > center {
+            t = center - (t - center);
+        } else if s == max && t < center {
+            t = center + (center - t);
+        } else if t == max && s < center {
+            s = center + (center - s);
+        } else if t == 0 && s > center {
+            s = center - (s - center);
+        }
+        [s, t]
+    }
+
+    /// Unit vector of quantized octahedral coordinates
+    pub fn quantized_coords_to_unit_vector(&self, s: i32, t: i32) -> [f32; 3] {
+        let scale = 2.0 / self.max_value as f32;
+        let mut y = s as f32 * scale - 1.0;
+        let mut z = t as f32 * scale - 1.0;
+        let x = 1.0 - y.abs() - z.abs();
+        let x_offset = (-x).max(0.0);
+        y += if y < 0.0 { x_offset } else { -x_offset };
+        z += if z < 0.0 { x_offset } else { -x_offset };
+        let norm_squared = x * x + y * y + z * z;
+        if norm_squared < 1e-6 {
+            return [0.0; 3];
+        }
+        let d = 1.0 / norm_squared.sqrt();
+        [x * d, y * d, z * d]
+    }
+}
+
+fn rotate(p: [i32; 2], rotation_count: i32) -> [i32; 2] {
+    match rotation_count {
+        1 => [p[1], -p[0]],
+        2 => [-p[0], -p[1]],
+        3 => [-p[1], p[0]],
+        _ => p,
+    }
+}
+
+enum Method<'a> {
+    Delta,
+    Parallelogram,
+    MultiParallelogram,
+    ConstrainedMultiParallelogram {
+        is_crease_edge: [Vec<bool>; MAX_NUM_PARALLELOGRAMS],
+    },
+    TexCoordsPortable {
+        orientations: Vec<bool>,
+    },
+    GeometricNormal {
+        flip_normal: RAnsBitDecoder<'a>,
+    },
+}
+
+pub struct PredictionScheme<'a> {
+    method: Method<'a>,
+    transform: Transform,
+}
+
+impl<'a> PredictionScheme<'a> {
+    /// Scheme for the decoded method and transform ids, `None` for `PREDICTION_NONE`. Without
+    /// connectivity (or for combinations the reference decoder doesn't build) the mesh
+    /// methods fall back to delta coding, like `CreatePredictionSchemeForDecoder`.
+    pub fn create(
+        method: i8,
+        transform_type: i8,
+        is_normal: bool,
+        has_mesh: bool,
+    ) -> DracoResult<Option<Self>> {
+        if method == PREDICTION_NONE {
+            return Ok(None);
+        }
+        let transform = match (transform_type, is_normal) {
+            (TRANSFORM_WRAP, false) => Transform::Wrap { min: 0, max: 0 },
+            (TRANSFORM_NORMAL_OCTAHEDRON | TRANSFORM_NORMAL_OCTAHEDRON_CANONICALIZED, true) => {
+                Transform::Octahedron {
+                    octahedron: Octahedron::default(),
+                    canonicalized: transform_type == TRANSFORM_NORMAL_OCTAHEDRON_CANONICALIZED,
+                }
+            }
+            _ => {
+                return Err(DracoError::Unsupported(format!(
+                    "prediction transform {transform_type}"
+                )))
+            }
+        };
+        let method = match (method, has_mesh, is_normal) {
+            (MESH_PREDICTION_GEOMETRIC_NORMAL, true, true) => Method::GeometricNormal {
+                flip_normal: RAnsBitDecoder::empty(),
+            },
+            (_, true, true) => Method::Delta,
+            (MESH_PREDICTION_PARALLELOGRAM, true, false) => Method::Parallelogram,
+            (MESH_PREDICTION_MULTI_PARALLELOGRAM, true, false) => Method::MultiParallelogram,
+            (MESH_PREDICTION_CONSTRAINED_MULTI_PARALLELOGRAM, true, false) => {
+                Method::ConstrainedMultiParallelogram {
+                    is_crease_edge: Default::default(),
+                }
+            }
+            (MESH_PREDICTION_TEX_COORDS_PORTABLE, true, false) => Method::TexCoordsPortable {
+                orientations: Vec::new(),
+            },
+            (MESH_PREDICTION_TEX_COORDS_DEPRECATED, true, false) => {
+                return Err(DracoError::Unsupported(
+                    "deprecated tex coord prediction".to_string(),
+                ))
+            }
+            (PREDICTION_DIFFERENCE.., _, _) if method <= MESH_PREDICTION_GEOMETRIC_NORMAL => {
+                Method::Delta
+            }
+            _ => return Err(DracoError::Unsupported(format!("prediction {method}"))),
+        };
+        Ok(Some(Self { method, transform }))
+    }
+
+    /// Whether the scheme predicts from the positions
+    pub fn needs_positions(&self) -> bool {
+        matches!(
+            self.method,
+            Method::TexCoordsPortable { .. } | Method::GeometricNormal { .. }
+        )
+    }
+
+    pub fn are_corrections_positive(&self) -> bool {
+        self.transform.are_corrections_positive()
+    }
+
+    /// Reads the data following the corrections
+    pub fn decode_prediction_data(
+        &mut self,
+        buffer: &mut DecoderBuffer<'a>,
+        num_corners: usize,
+    ) -> DracoResult<()> {
+        match &mut self.method {
+            Method::ConstrainedMultiParallelogram { is_crease_edge } => {
+                for flags in is_crease_edge.iter_mut() {
+                    let num_flags = buffer.read_varint_u32()? as usize;
+                    if num_flags > num_corners {
+                        return Err(DracoError::Malformed("crease edge count"));
+                    }
+                    if num_flags > 0 {
+                        let mut decoder = RAnsBitDecoder::start(buffer)?;
+                        *flags = (0..num_flags).map(|_| decoder.decode_next_bit()).collect();
+                    }
+                }
+            }
+            Method::TexCoordsPortable { orientations } => {
+                let num_orientations = usize::try_from(buffer.read_i32()?)
+                    .map_err(|_| DracoError::Malformed("orientation count"))?;
+                let mut decoder = RAnsBitDecoder::start(buffer)?;
+                let mut last_orientation = true;
+                *orientations = (0..num_orientations)
+                    .map(|_| {
+                        if !decoder.decode_next_bit() {
+                            last_orientation = !last_orientation;
+                        }
+                        last_orientation
+                    })
+                    .collect();
+            }
+            Method::GeometricNormal { flip_normal } => {
+                self.transform.decode_transform_data(buffer)?;
+                *flip_normal = RAnsBitDecoder::start(buffer)?;
+                return Ok(());
+            }
+            Method::Delta | Method::Parallelogram | Method::MultiParallelogram => {}
+        }
+        self.transform.decode_transform_data(buffer)
+    }
+
+    /// Reverts the prediction, `corrections` holds `num_components` values per entry
+    pub fn compute_original_values(
+        &mut self,
+        corrections: &[i32],
+        num_components: usize,
+        ctx: &PredictionContext,
+    ) -> DracoResult<Vec<i32>> {
+        let mut out = vec![0i32; corrections.len()];
+        if out.is_empty() {
+            return Ok(out);
+        }
+        if matches!(self.transform, Transform::Octahedron { .. }) && num_components != 2 {
+            return Err(DracoError::Malformed("octahedral values need 2 components"));
+        }
+        let nc = num_components;
+        let num_entries = corrections.len() / nc;
+        let zero = vec![0i32; nc];
+
+        let mesh = match (&self.method, &ctx.mesh) {
+            (Method::Delta, _) | (_, None) => {
+                self.transform
+                    .compute_original_value(&zero, &corrections[..nc], &mut out[..nc]);
+                for i in (nc..corrections.len()).step_by(nc) {
+                    let (done, rest) = out.split_at_mut(i);
+                    self.transform.compute_original_value(
+                        &done[i - nc..],
+                        &corrections[i..i + nc],
+                        &mut rest[..nc],
+                    );
+                }
+                return Ok(out);
+            }
+            (_, Some(mesh)) => mesh,
+        };
+        if mesh.data_to_corner.len() < num_entries {
+            return Err(DracoError::Malformed("prediction entries"));
+        }
+
+        match &mut self.method {
+            Method::Delta => unreachable!(),
+            Method::Parallelogram | Method::MultiParallelogram => {
+                let multi = matches!(self.method, Method::MultiParallelogram);
+                self.transform
+                    .compute_original_value(&zero, &corrections[..nc], &mut out[..nc]);
+                let mut predicted = vec![0i32; nc];
+                let mut parallelogram = vec![0i32; nc];
+                for p in 1..num_entries {
+                    let start_corner = mesh.data_to_corner[p];
+                    let mut count = 0;
+                    if multi {
+                        predicted.fill(0);
+                        let mut corner = start_corner;
+                        while corner != INVALID {
+                            if parallelogram_prediction(
+                                p,
+                                corner,
+                                mesh,
+                                &out,
+                                nc,
+                                &mut parallelogram,
+                            ) {
+                                for (sum, value) in predicted.iter_mut().zip(&parallelogram) {
+                                    *sum = sum.wrapping_add(*value);
+                                }
+                                count += 1;
+                            }
+                            corner = mesh.table.swing_right(corner);
+                            if corner == start_corner {
+                                break;
+                            }
+                        }
+                        for value in predicted.iter_mut() {
+                            *value = value.checked_div(count).unwrap_or(*value);
+                        }
+                    } else if parallelogram_prediction(
+                        p,
+                        start_corner,
+                        mesh,
+                        &out,
+                        nc,
+                        &mut predicted,
+                    ) {
+                        count = 1;
+                    }
+                    apply_prediction(
+                        &self.transform,
+                        &mut out,
+                        corrections,
+                        p,
+                        nc,
+                        (count > 0).then_some(predicted.as_slice()),
+                    );
+                }
+            }
+            Method::ConstrainedMultiParallelogram { is_crease_edge } => {
+                self.transform
+                    .compute_original_value(&zero, &corrections[..nc], &mut out[..nc]);
+                let mut predictions = vec![vec![0i32; nc]; MAX_NUM_PARALLELOGRAMS];
+                let mut crease_positions = [0usize; MAX_NUM_PARALLELOGRAMS];
+                let mut multi_predicted = vec![0i32; nc];
+                for p in 1..num_entries {
+                    let start_corner = mesh.data_to_corner[p];
+                    let mut corner = start_corner;
+                    let mut num_parallelograms = 0;
+                    let mut first_pass = true;
+                    while corner != INVALID {
+                        if parallelogram_prediction(
+                            p,
+                            corner,
+                            mesh,
+                            &out,
+                            nc,
+                            &mut predictions[num_parallelograms],
+                        ) {
+                            num_parallelograms += 1;
+                            if num_parallelograms == MAX_NUM_PARALLELOGRAMS {
+                                break;
+                            }
+                        }
+                        corner = if first_pass {
+                            mesh.table.swing_left(corner)
+                        } else {
+                            mesh.table.swing_right(corner)
+                        };
+                        if corner == start_corner {
+                            break;
+                        }
+                        if corner == INVALID && first_pass {
+                            first_pass = false;
+                            corner = mesh.table.swing_right(start_corner);
+                        }
+                    }
+
+                    let mut num_used = 0;
+                    multi_predicted.fill(0);
+                    for prediction in predictions.iter().take(num_parallelograms) {
+                        let context = num_parallelograms - 1;
+                        let position = crease_positions[context];
+                        crease_positions[context] += 1;
+                        let is_crease = *is_crease_edge[context]
+                            .get(position)
+                            .ok_or(DracoError::Malformed("crease edge flags"))?;
+                        if !is_crease {
+                            num_used += 1;
+                            for (sum, value) in multi_predicted.iter_mut().zip(prediction) {
+                                *sum = sum.wrapping_add(*value);
+                            }
+                        }
+                    }
+                    for value in multi_predicted.iter_mut() {
+                        *value = value.checked_div(num_used).unwrap_or(*value);
+                    }
+                    apply_prediction(
+                        &self.transform,
+                        &mut out,
+                        corrections,
+                        p,
+                        nc,
+                        (num_used > 0).then_some(multi_predicted.as_slice()),
+                    );
+                }
+            }
+            Method::TexCoordsPortable { orientations } => {
+                if nc != 2 {
+                    return Err(DracoError::Malformed("tex coords need 2 components"));
+                }
+                for p in 0..num_entries {
+                    let predicted = predict_tex_coord(p, mesh, ctx, &out, orientations)?;
+                    let offset = p * 2;
+                    self.transform.compute_original_value(
+                        &predicted,
+                        &corrections[offset..offset + 2],
+                        &mut out[offset..offset + 2],
+                    );
+                }
+            }
+            Method::GeometricNormal { flip_normal } => {
+                let Transform::Octahedron { octahedron, .. } = self.transform else {
+                    return Err(DracoError::Malformed("geometric normal transform"));
+                };
+                for p in 0..num_entries {
+                    let corner = mesh.data_to_corner[p];
+                    let mut normal = predict_normal(corner, mesh, ctx)?;
+                    octahedron.canonicalize_integer_vector(&mut normal);
+                    if flip_normal.decode_next_bit() {
+                        normal = normal.map(|v| -v);
+                    }
+                    let predicted = octahedron.integer_vector_to_quantized_coords(&normal);
+                    let offset = p * 2;
+                    self.transform.compute_original_value(
+                        &predicted,
+                        &corrections[offset..offset + 2],
+                        &mut out[offset..offset + 2],
+                    );
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Applies the prediction to entry `p`, or the previous entry (delta coding) without one
+fn apply_prediction(
+    transform: &Transform,
+    out: &mut [i32],
+    corrections: &[i32],
+    p: usize,
+    nc: usize,
+    predicted: Option<&[i32]>,
+) {
+    let offset = p * nc;
+    let (done, rest) = out.split_at_mut(offset);
+    let predicted = predicted.unwrap_or(&done[offset - nc..]);
+    transform.compute_original_value(
+        predicted,
+        &corrections[offset..offset + nc],
+        &mut rest[..nc],
+    );
+}
+
+/// Parallelogram over the triangle opposite to `corner`, if all its values are decoded
+fn parallelogram_prediction(
+    entry: usize,
+    corner: u32,
+    mesh: &MeshData,
+    data: &[i32],
+    nc: usize,
+    prediction: &mut [i32],
+) -> bool {
+    let opposite = mesh.table.opposite(corner);
+    if opposite == INVALID {
+        return false;
+    }
+    let (Some(opp), Some(next_entry), Some(prev_entry)) = (
+        data_id(mesh, opposite),
+        data_id(mesh, next(opposite)),
+        data_id(mesh, previous(opposite)),
+    ) else {
+        return false;
+    };
+    let entry = entry as i64;
+    if [opp, next_entry, prev_entry]
+        .iter()
+        .any(|id| *id < 0 || i64::from(*id) >= entry)
+    {
+        return false;
+    }
+    let (opp, next_entry, prev_entry) = (
+        opp as usize * nc,
+        next_entry as usize * nc,
+        prev_entry as usize * nc,
+    );
+    for c in 0..nc {
+        let value = i64::from(data[next_entry + c]) + i64::from(data[prev_entry + c])
+            - i64::from(data[opp + c]);
+        prediction[c] = value as i32;
+    }
+    true
+}
+
+fn int_sqrt(number: u64) -> u64 {
+    if number == 0 {
+        return 0;
+    }
+    let mut act_number = number;
+    let mut square_root = 1u64;
+    while act_number >= 2 {
+        square_root *= 2;
+        act_number /= 4;
+    }
+    loop {
+        square_root = (square_root + number / square_root) / 2;
+        if square_root.wrapping_mul(square_root) <= number {
+            return square_root;
+        }
+    }
+}
+
+/// `MeshPredictionSchemeTexCoordsPortablePredictor::ComputePredictedValue`
+fn predict_tex_coord(
+    entry: usize,
+    mesh: &MeshData,
+    ctx: &PredictionContext,
+    data: &[i32],
+    orientations: &mut Vec<bool>,
+) -> DracoResult<[i32; 2]> {
+    let corner = mesh.data_to_corner[entry];
+    let next_entry =
+        data_id(mesh, next(corner)).ok_or(DracoError::Malformed("tex coord corner"))?;
+    let prev_entry =
+        data_id(mesh, previous(corner)).ok_or(DracoError::Malformed("tex coord corner"))?;
+    let entry_id = entry as i32;
+    let uv = |id: i32| {
+        [
+            i64::from(data[id as usize * 2]),
+            i64::from(data[id as usize * 2 + 1]),
+        ]
+    };
+
+    if prev_entry < entry_id && next_entry < entry_id && prev_entry >= 0 && next_entry >= 0 {
+        let n_uv = uv(next_entry);
+        let p_uv = uv(prev_entry);
+        if p_uv == n_uv {
+            return Ok([p_uv[0] as i32, p_uv[1] as i32]);
+        }
+        let tip_pos = ctx.position_for_entry(entry)?;
+        let next_pos = ctx.position_for_entry(next_entry as usize)?;
+        let prev_pos = ctx.position_for_entry(prev_entry as usize)?;
+        let pn = sub3(prev_pos, next_pos);
+        let pn_norm2_squared = dot3(pn, pn) as u64;
+        if pn_norm2_squared != 0 {
+            let cn = sub3(tip_pos, next_pos);
+            let cn_dot_pn = dot3(pn, cn);
+            let pn_uv = [p_uv[0] - n_uv[0], p_uv[1] - n_uv[1]];
+            let norm = pn_norm2_squared as i64;
+
+            let n_uv_absmax = n_uv[0].abs().max(n_uv[1].abs());
+            let pn_uv_absmax = pn_uv[0].abs().max(pn_uv[1].abs());
+            let pn_absmax = pn[0].abs().max(pn[1].abs()).max(pn[2].abs());
+            if n_uv_absmax > i64::MAX / norm
+                || (pn_uv_absmax != 0 && cn_dot_pn > i64::MAX / pn_uv_absmax)
+                || (pn_absmax != 0 && cn_dot_pn > i64::MAX / pn_absmax)
+            {
+                return Err(DracoError::Malformed("tex coord prediction overflow"));
+            }
+            let x_uv = [
+                n_uv[0] * norm + cn_dot_pn * pn_uv[0],
+                n_uv[1] * norm + cn_dot_pn * pn_uv[1],
+            ];
+            let x_pos = [
+                next_pos[0] + cn_dot_pn * pn[0] / norm,
+                next_pos[1] + cn_dot_pn * pn[1] / norm,
+                next_pos[2] + cn_dot_pn * pn[2] / norm,
+            ];
+            let cx = sub3(tip_pos, x_pos);
+            let cx_norm2_squared = dot3(cx, cx) as u64;
+            let norm_squared = int_sqrt(cx_norm2_squared.wrapping_mul(pn_norm2_squared)) as i64;
+            let cx_uv = [
+                pn_uv[1].wrapping_mul(norm_squared),
+                (-pn_uv[0]).wrapping_mul(norm_squared),
+            ];
+            let orientation = orientations
+                .pop()
+                .ok_or(DracoError::Malformed("missing tex coord orientation"))?;
+            let predicted = if orientation {
+                [
+                    x_uv[0].wrapping_add(cx_uv[0]),
+                    x_uv[1].wrapping_add(cx_uv[1]),
+                ]
+            } else {
+                [
+                    x_uv[0].wrapping_sub(cx_uv[0]),
+                    x_uv[1].wrapping_sub(cx_uv[1]),
+                ]
+            };
+            return Ok([(predicted[0] / norm) as i32, (predicted[1] / norm) as i32]);
+        }
+    }
+
+    // Not enough data for the position based prediction, use a neighbour. Like the reference
+    // predictor the previous corner is never picked over the last decoded value.
+    let source = if next_entry < entry_id && next_entry >= 0 {
+        next_entry
+    } else if entry > 0 {
+        entry_id - 1
+    } else {
+        return Ok([0, 0]);
+    };
+    let [u, v] = uv(source);
+    Ok([u as i32, v as i32])
+}
+
+fn sub3(a: [i64; 3], b: [i64; 3]) -> [i64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot3(a: [i64; 3], b: [i64; 3]) -> i64 {
+    a[0].wrapping_mul(b[0])
+        .wrapping_add(a[1].wrapping_mul(b[1]))
+        .wrapping_add(a[2].wrapping_mul(b[2]))
+}
+
+/// Area weighted normal of the triangles around the vertex of `corner`
+/// (`MeshPredictionSchemeGeometricNormalPredictorArea`)
+fn predict_normal(corner: u32, mesh: &MeshData, ctx: &PredictionContext) -> DracoResult<[i64; 3]> {
+    let position = |c: u32| {
+        let entry = data_id(mesh, c).ok_or(DracoError::Malformed("normal corner"))?;
+        let entry = usize::try_from(entry).map_err(|_| DracoError::Malformed("normal corner"))?;
+        ctx.position_for_entry(entry)
+    };
+    let center = position(corner)?;
+    let mut normal = [0i64; 3];
+    for c in mesh.table.vertex_corners(corner) {
+        let delta_next = sub3(position(next(c))?, center);
+        let delta_prev = sub3(position(previous(c))?, center);
+        let cross = [
+            delta_next[1].wrapping_mul(delta_prev[2]) - delta_next[2].wrapping_mul(delta_prev[1]),
+            delta_next[2].wrapping_mul(delta_prev[0]) - delta_next[0].wrapping_mul(delta_prev[2]),
+            delta_next[0].wrapping_mul(delta_prev[1]) - delta_next[1].wrapping_mul(delta_prev[0]),
+        ];
+        for (sum, value) in normal.iter_mut().zip(cross) {
+            *sum = sum.wrapping_add(value);
+        }
+    }
+    const UPPER_BOUND: i64 = 1 << 29;
+    let abs_sum = normal
+        .iter()
+        .fold(0i64, |sum, value| sum.wrapping_add(value.wrapping_abs()));
+    if abs_sum > UPPER_BOUND {
+        let quotient = abs_sum / UPPER_BOUND;
+        normal = normal.map(|value| value / quotient);
+    }
+    Ok(normal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn octahedral_coords_round_trip() {
+        let octahedron = Octahedron::from_quantization_bits(8);
+        for normal in [[0i64, 0, 1], [1, 0, 0], [-3, 2, -1], [0, -1, 0]] {
+            let mut vec = normal;
+            octahedron.canonicalize_integer_vector(&mut vec);
+            let [s, t] = octahedron.integer_vector_to_quantized_coords(&vec);
+            let decoded = octahedron.quantized_coords_to_unit_vector(s, t);
+            let length = normal.iter().map(|v| (v * v) as f32).sum::<f32>().sqrt();
+            for (decoded, expected) in decoded.iter().zip(normal) {
+                assert!((decoded - expected as f32 / length).abs() < 0.03);
+            }
+        }
+
+        // Zero corrections give the prediction back, inside and outside the diamond
+        for canonicalized in [false, true] {
+            let transform = Transform::Octahedron {
+                octahedron,
+                canonicalized,
+            };
+            for predicted in [[127, 127], [10, 20], [250, 3], [200, 240]] {
+                let mut out = [0; 2];
+                transform.compute_original_value(&predicted, &[0, 0], &mut out);
+                assert_eq!(out, predicted);
+            }
+        }
+    }
+
+    #[test]
+    fn wrap_transform_wraps_around_bounds() {
+        let transform = Transform::Wrap { min: 0, max: 9 };
+        let mut out = [0; 3];
+        transform.compute_original_value(&[8, 12, 0], &[3, -1, -2], &mut out);
+        assert_eq!(out, [1, 8, 8]);
+    }
+}
@@ -0,0 +1,333 @@
+//! Entropy decoders: the binary rABS coder used for flags and the rANS symbol coder used for
+//! connectivity and attribute values (`DecodeSymbols`).
+
+use super::{buffer::DecoderBuffer, DracoError, DracoResult};
+
+const ANS_IO_BASE: u32 = 256;
+const RABS_PRECISION: u32 = 256;
+const RABS_L_BASE: u32 = 4096;
+
+const SYMBOL_CODING_TAGGED: u8 = 0;
+const SYMBOL_CODING_RAW: u8 = 1;
+/// Bit length of the tags in the tagged scheme
+const TAG_SYMBOLS_BIT_LENGTH: u32 = 5;
+const MAX_RAW_SYMBOLS_BIT_LENGTH: u8 = 18;
+
+/// Reads the final state stored at the end of `data`, returns (state, bytes left before it)
+fn read_init(data: &[u8], l_base: u32) -> DracoResult<(u32, usize)> {
+    let len = data.len();
+    let Some(&last) = data.last() else {
+        return Err(DracoError::Malformed("empty ans data"));
+    };
+    let (state, offset) = match last >> 6 {
+        0 => (u32::from(last & 0x3f), len - 1),
+        1 if len >= 2 => (
+            u32::from(u16::from_le_bytes([data[len - 2], last])) & 0x3fff,
+            len - 2,
+        ),
+        2 if len >= 3 => (
+            u32::from_le_bytes([data[len - 3], data[len - 2], last, 0]) & 0x3f_ffff,
+            len - 3,
+        ),
+        3 if len >= 4 => (
+            u32::from_le_bytes([data[len - 4], data[len - 3], data[len - 2], last]) & 0x3fff_ffff,
+            len - 4,
+        ),
+        _ => return Err(DracoError::Malformed("truncated ans state")),
+    };
+    let state = state + l_base;
+    if u64::from(state) >= u64::from(l_base) * u64::from(ANS_IO_BASE) {
+        return Err(DracoError::Malformed("ans state out of range"));
+    }
+    Ok((state, offset))
+}
+
+/// Binary decoder with an 8-bit probability of zero (`RAnsBitDecoder`).
+pub struct RAnsBitDecoder<'a> {
+    data: &'a [u8],
+    offset: usize,
+    state: u32,
+    prob_zero: u8,
+}
+
+impl<'a> RAnsBitDecoder<'a> {
+    /// Empty decoder, every bit reads as 0
+    pub fn empty() -> Self {
+        Self {
+            data: &[],
+            offset: 0,
+            state: RABS_L_BASE,
+            prob_zero: 0,
+        }
+    }
+
+    pub fn start(buffer: &mut DecoderBuffer<'a>) -> DracoResult<Self> {
+        let prob_zero = buffer.read_u8()?;
+        let size = buffer.read_varint_u32()? as usize;
+        let data = buffer.read_bytes(size)?;
+        let (state, offset) = read_init(data, RABS_L_BASE)?;
+        Ok(Self {
+            data,
+            offset,
+            state,
+            prob_zero,
+        })
+    }
+
+    pub fn decode_next_bit(&mut self) -> bool {
+        if self.data.is_empty() {
+            return false;
+        }
+        let p = RABS_PRECISION - u32::from(self.prob_zero);
+        if self.state < RABS_L_BASE && self.offset > 0 {
+            self.offset -= 1;
+            self.state = self.state * ANS_IO_BASE + u32::from(self.data[self.offset]);
+        }
+        let quot = self.state / RABS_PRECISION;
+        let rem = self.state % RABS_PRECISION;
+        let xn = quot * p;
+        let bit = rem < p;
+        if bit {
+            self.state = xn + rem;
+        } else {
+            self.state -= xn + p;
+        }
+        bit
+    }
+}
+
+/// rANS decoder over a decoded probability table (`RAnsSymbolDecoder`).
+struct RAnsSymbolDecoder<'a> {
+    precision_bits: u32,
+    l_base: u32,
+    /// (probability, cumulative probability) per symbol
+    symbols: Vec<(u32, u32)>,
+    /// Symbol for every slot of the precision range
+    lookup: Vec<u32>,
+    data: &'a [u8],
+    offset: usize,
+    state: u32,
+}
+
+impl<'a> RAnsSymbolDecoder<'a> {
+    /// Reads the probability table, `symbols_bit_length` selects the coder precision
+    fn create(buffer: &mut DecoderBuffer<'a>, symbols_bit_length: u32) -> DracoResult<Self> {
+        let precision_bits = ((3 * symbols_bit_length) / 2).clamp(12, 20);
+        let precision = 1u32 << precision_bits;
+        let num_symbols = buffer.read_varint_u32()? as usize;
+        if num_symbols / 64 > buffer.remaining() {
+            return Err(DracoError::Malformed("too many symbols"));
+        }
+
+        let mut probabilities = vec![0u32; num_symbols];
+        let mut i = 0;
+        while i < num_symbols {
+            let prob_data = buffer.read_u8()?;
+            let token = prob_data & 3;
+            if token == 3 {
+                // Run of zero probabilities
+                let run = usize::from(prob_data >> 2) + 1;
+                if i + run > num_symbols {
+                    return Err(DracoError::Malformed("probability run overflow"));
+                }
+                i += run;
+                continue;
+            }
+            let mut prob = u32::from(prob_data >> 2);
+            for b in 0..u32::from(token) {
+                prob |= u32::from(buffer.read_u8()?) << (8 * (b + 1) - 2);
+            }
+            probabilities[i] = prob;
+            i += 1;
+        }
+
+        let mut symbols = Vec::with_capacity(num_symbols);
+        let mut lookup = Vec::new();
+        let mut cum_prob = 0u32;
+        if num_symbols > 0 {
+            lookup.resize(precision as usize, 0);
+            for (symbol, &prob) in probabilities.iter().enumerate() {
+                symbols.push((prob, cum_prob));
+                let next = cum_prob
+                    .checked_add(prob)
+                    .filter(|next| *next <= precision)
+                    .ok_or(DracoError::Malformed("probabilities exceed precision"))?;
+                lookup[cum_prob as usize..next as usize].fill(symbol as u32);
+                cum_prob = next;
+            }
+            if cum_prob != precision {
+                return Err(DracoError::Malformed("probabilities don't add up"));
+            }
+        }
+
+        Ok(Self {
+            precision_bits,
+            l_base: precision * 4,
+            symbols,
+            lookup,
+            data: &[],
+            offset: 0,
+            state: 0,
+        })
+    }
+
+    fn start(&mut self, buffer: &mut DecoderBuffer<'a>) -> DracoResult<()> {
+        let size = usize::try_from(buffer.read_varint()?)
+            .map_err(|_| DracoError::Malformed("rans data size"))?;
+        self.data = buffer.read_bytes(size)?;
+        let (state, offset) = read_init(self.data, self.l_base)?;
+        self.state = state;
+        self.offset = offset;
+        Ok(())
+    }
+
+    fn decode_symbol(&mut self) -> u32 {
+        while self.state < self.l_base && self.offset > 0 {
+            self.offset -= 1;
+            self.state = self.state * ANS_IO_BASE + u32::from(self.data[self.offset]);
+        }
+        let quo = self.state >> self.precision_bits;
+        let rem = self.state & ((1 << self.precision_bits) - 1);
+        let symbol = self.lookup[rem as usize];
+        let (prob, cum_prob) = self.symbols[symbol as usize];
+        self.state = quo * prob + rem - cum_prob;
+        symbol
+    }
+}
+
+/// Decodes `num_values` unsigned symbols written by `EncodeSymbols`
+pub fn decode_symbols(
+    num_values: usize,
+    num_components: usize,
+    buffer: &mut DecoderBuffer,
+) -> DracoResult<Vec<u32>> {
+    if num_values == 0 {
+        return Ok(Vec::new());
+    }
+    match buffer.read_u8()? {
+        SYMBOL_CODING_TAGGED => decode_tagged_symbols(num_values, num_components, buffer),
+        SYMBOL_CODING_RAW => decode_raw_symbols(num_values, buffer),
+        _ => Err(DracoError::Malformed("unknown symbol coding")),
+    }
+}
+
+fn decode_raw_symbols(num_values: usize, buffer: &mut DecoderBuffer) -> DracoResult<Vec<u32>> {
+    let max_bit_length = buffer.read_u8()?;
+    if max_bit_length == 0 || max_bit_length > MAX_RAW_SYMBOLS_BIT_LENGTH {
+        return Err(DracoError::Malformed("raw symbols bit length"));
+    }
+    let mut decoder = RAnsSymbolDecoder::create(buffer, u32::from(max_bit_length))?;
+    if decoder.symbols.is_empty() {
+        return Err(DracoError::Malformed("no symbols"));
+    }
+    decoder.start(buffer)?;
+    Ok((0..num_values).map(|_| decoder.decode_symbol()).collect())
+}
+
+fn decode_tagged_symbols(
+    num_values: usize,
+    num_components: usize,
+    buffer: &mut DecoderBuffer,
+) -> DracoResult<Vec<u32>> {
+    let mut tag_decoder = RAnsSymbolDecoder::create(buffer, TAG_SYMBOLS_BIT_LENGTH)?;
+    tag_decoder.start(buffer)?;
+    if tag_decoder.symbols.is_empty() {
+        return Err(DracoError::Malformed("no tag symbols"));
+    }
+
+    // The values follow the tag data as raw bits
+    buffer.start_bit_decoding(false)?;
+    let num_components = num_components.max(1);
+    let mut values = Vec::with_capacity(num_values);
+    while values.len() < num_values {
+        let bit_length = tag_decoder.decode_symbol();
+        for _ in 0..num_components.min(num_values - values.len()) {
+            values.push(buffer.read_bits(bit_length)?);
+        }
+    }
+    buffer.end_bit_decoding();
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Inverse of `read_init` + the renormalization in the decoders
+    fn ans_encode(symbols: &[(u32, u32)], precision_bits: u32, l_base: u32) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut state = l_base;
+        for &(prob, cum_prob) in symbols.iter().rev() {
+            while u64::from(state) >= u64::from(l_base >> precision_bits) * 256 * u64::from(prob) {
+                out.push(state as u8);
+                state >>= 8;
+            }
+            state = ((state / prob) << precision_bits) + cum_prob + state % prob;
+        }
+        let state = state - l_base;
+        match state {
+            s if s < 1 << 6 => out.push(s as u8),
+            s if s < 1 << 14 => out.extend_from_slice(&((1 << 14) + s as u16).to_le_bytes()),
+            s if s < 1 << 22 => out.extend_from_slice(&((2 << 22) + s).to_le_bytes()[..3]),
+            s => out.extend_from_slice(&((3 << 30) + s).to_le_bytes()),
+        }
+        out
+    }
+
+    #[test]
+    fn decodes_rabs_bits() {
+        let bits = [
+            true, false, false, true, true, true, false, true, false, false,
+        ];
+        let prob_zero = 100u32;
+        let encoded: Vec<_> = bits
+            .iter()
+            .map(|bit| {
+                if *bit {
+                    (RABS_PRECISION - prob_zero, 0)
+                } else {
+                    (prob_zero, RABS_PRECISION - prob_zero)
+                }
+            })
+            .collect();
+        let data = ans_encode(&encoded, 8, RABS_L_BASE);
+
+        let mut stream = vec![prob_zero as u8, data.len() as u8];
+        stream.extend_from_slice(&data);
+        let mut buffer = DecoderBuffer::new(&stream);
+        let mut decoder = RAnsBitDecoder::start(&mut buffer).unwrap();
+        let decoded: Vec<_> = bits.iter().map(|_| decoder.decode_next_bit()).collect();
+        assert_eq!(decoded, bits);
+        assert_eq!(buffer.remaining(), 0);
+    }
+
+    #[test]
+    fn decodes_raw_symbols() {
+        // 3 symbols with 12-bit precision: 2048, 0 (run-length coded), 2048
+        let values = [0u32, 2, 2, 0, 2, 0, 0, 0, 2, 2, 2];
+        let table = [(2048u32, 0u32), (0, 2048), (2048, 2048)];
+        let data = ans_encode(
+            &values
+                .iter()
+                .map(|v| table[*v as usize])
+                .collect::<Vec<_>>(),
+            12,
+            4 << 12,
+        );
+
+        let mut stream = vec![SYMBOL_CODING_RAW, 1, 3];
+        // 2048 = 0x800: token 1 (one extra byte), the low 6 bits (all zero) in the first byte
+        stream.extend_from_slice(&[1, (2048 >> 6) as u8]);
+        stream.push(3); // run of 1 zero
+        stream.extend_from_slice(&[1, (2048 >> 6) as u8]);
+        stream.push(data.len() as u8);
+        stream.extend_from_slice(&data);
+
+        let mut buffer = DecoderBuffer::new(&stream);
+        assert_eq!(
+            decode_symbols(values.len(), 1, &mut buffer).unwrap(),
+            values
+        );
+        assert_eq!(buffer.remaining(), 0);
+    }
+}
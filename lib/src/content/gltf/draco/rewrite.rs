@@ -0,0 +1,262 @@
+//! Rewrites the `KHR_draco_mesh_compression` primitives of a glTF/GLB into plain accessors, so
+//! importers without Draco support (Godot's `GltfDocument`) can load it.
+
+use std::collections::HashSet;
+
+use serde_json::json;
+
+use super::super::document::{GltfDocument, MAX_DECODED_SIZE};
+use super::decode_draco_mesh;
+
+pub const DRACO_EXTENSION: &str = "KHR_draco_mesh_compression";
+
+/// Decodes every Draco compressed primitive of the document into regular accessors, returns
+/// whether it used the extension.
+///
+/// The compressed buffer views are left in place, unreferenced.
+pub fn decompress_draco_primitives(gltf: &mut GltfDocument) -> Result<bool, anyhow::Error> {
+    if !gltf.uses_extension(DRACO_EXTENSION) {
+        return Ok(false);
+    }
+    let mut rewritten_accessors = HashSet::new();
+
+    let num_meshes = gltf.array("meshes").len();
+    for mesh in 0..num_meshes {
+        let num_primitives = gltf.json["meshes"][mesh]["primitives"]
+            .as_array()
+            .map_or(0, Vec::len);
+        for primitive in 0..num_primitives {
+            let extension = gltf.json["meshes"][mesh]["primitives"][primitive]["extensions"]
+                [DRACO_EXTENSION]
+                .clone();
+            if extension.is_null() {
+                continue;
+            }
+            let primitive_json = &gltf.json["meshes"][mesh]["primitives"][primitive];
+            // The decoded attributes fill the primitive's accessors, so no more points than
+            // they hold
+            let max_points = primitive_json["attributes"]
+                .as_object()
+                .into_iter()
+                .flatten()
+                .filter_map(|(_, accessor)| {
+                    gltf.array("accessors").get(accessor.as_u64()? as usize)?["count"].as_u64()
+                })
+                .min()
+                .unwrap_or(0)
+                .min((MAX_DECODED_SIZE / std::mem::size_of::<f64>()) as u64)
+                as usize;
+            let compressed = gltf.buffer_view_data(&extension["bufferView"])?;
+            let draco = decode_draco_mesh(&compressed, max_points)
+                .map_err(|err| anyhow::anyhow!("mesh {mesh} primitive {primitive}: {err}"))?;
+
+            let primitive_json = &gltf.json["meshes"][mesh]["primitives"][primitive];
+            let indices_accessor = primitive_json["indices"].as_u64();
+            let attribute_accessors: Vec<(u64, u32)> = extension["attributes"]
+                .as_object()
+                .into_iter()
+                .flatten()
+                .filter_map(|(semantic, unique_id)| {
+                    Some((
+                        primitive_json["attributes"][semantic].as_u64()?,
+                        unique_id.as_u64()? as u32,
+                    ))
+                })
+                .collect();
+
+            let indices: Vec<u32> = draco.faces.iter().flatten().copied().collect();
+            let accessor = gltf.claim_accessor(indices_accessor, &mut rewritten_accessors);
+            gltf.write_indices(accessor, &indices);
+            gltf.json["meshes"][mesh]["primitives"][primitive]["indices"] = json!(accessor);
+
+            for (accessor, unique_id) in attribute_accessors {
+                let attribute = draco.attribute(unique_id).ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "mesh {mesh} primitive {primitive}: missing draco attribute {unique_id}"
+                    )
+                })?;
+                let claimed = gltf.claim_accessor(Some(accessor), &mut rewritten_accessors);
+                let source_scale = attribute
+                    .normalized
+                    .then(|| attribute.data_type.normalization_scale())
+                    .flatten();
+                gltf.write_attribute(claimed, draco.num_points, source_scale, |point| {
+                    Ok(attribute.value(point)?)
+                })
+                .map_err(|err| {
+                    anyhow::anyhow!(
+                        "mesh {mesh} primitive {primitive} attribute {unique_id}: {err}"
+                    )
+                })?;
+                if claimed != accessor as usize {
+                    let attributes = gltf.json["meshes"][mesh]["primitives"][primitive]
+                        ["attributes"]
+                        .as_object_mut()
+                        .expect("primitive attributes");
+                    for value in attributes.values_mut() {
+                        if value.as_u64() == Some(accessor) {
+                            *value = json!(claimed);
+                        }
+                    }
+                }
+            }
+
+            let primitive_json = &mut gltf.json["meshes"][mesh]["primitives"][primitive];
+            if let Some(extensions) = primitive_json["extensions"].as_object_mut() {
+                extensions.remove(DRACO_EXTENSION);
+                if extensions.is_empty() {
+                    primitive_json
+                        .as_object_mut()
+                        .expect("primitive object")
+                        .remove("extensions");
+                }
+            }
+        }
+    }
+
+    gltf.remove_extension(DRACO_EXTENSION);
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::Engine;
+    use serde_json::Value;
+
+    use super::super::super::document::{build_glb, split_glb, FLOAT, UNSIGNED_SHORT};
+    use super::*;
+
+    fn decompress_draco_gltf(
+        data: &[u8],
+        load_buffer: impl FnMut(&str) -> Option<Vec<u8>>,
+    ) -> Option<Vec<u8>> {
+        let mut gltf = GltfDocument::parse(data, load_buffer).unwrap();
+        decompress_draco_primitives(&mut gltf)
+            .unwrap()
+            .then(|| gltf.finish().unwrap())
+    }
+
+    const POSITIONS: [[f32; 3]; 3] = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.5], [0.0, 1.0, -0.5]];
+
+    /// One sequential triangle: float positions stored raw, u16 tex coords delta coded
+    fn draco_triangle() -> Vec<u8> {
+        let mut data = b"DRACO".to_vec();
+        data.extend_from_slice(&[2, 2, 1, 0, 0, 0]);
+        // 1 face, 3 points, raw u8 indices
+        data.extend_from_slice(&[1, 3, 1, 0, 1, 2]);
+        // One attributes decoder: float32 POSITION (id 0) and uint16 TEX_COORD (id 1)
+        data.extend_from_slice(&[1, 2, 0, 9, 3, 0, 0, 3, 4, 2, 0, 1]);
+        // Generic and integer decoders
+        data.extend_from_slice(&[0, 1]);
+        for position in POSITIONS.iter().flatten() {
+            data.extend_from_slice(&position.to_le_bytes());
+        }
+        // Tex coords [5, 6], [7, 8], [9, 10]: difference prediction with wrap transform,
+        // uncompressed 1 byte corrections, zigzag coded
+        data.extend_from_slice(&[0, 1, 0, 1, 0, 2, 4, 4, 4, 4]);
+        data.extend_from_slice(&5i32.to_le_bytes());
+        data.extend_from_slice(&10i32.to_le_bytes());
+        data
+    }
+
+    fn gltf_json(buffer: Value) -> Value {
+        json!({
+            "asset": { "version": "2.0" },
+            "extensionsUsed": [DRACO_EXTENSION],
+            "extensionsRequired": [DRACO_EXTENSION],
+            "buffers": [buffer],
+            "bufferViews": [{ "buffer": 0, "byteLength": draco_triangle().len() }],
+            "accessors": [
+                { "componentType": UNSIGNED_SHORT, "count": 3, "type": "SCALAR" },
+                { "componentType": FLOAT, "count": 3, "type": "VEC3", "min": [0, 0, 0], "max": [0, 0, 0] },
+                { "componentType": FLOAT, "count": 3, "type": "VEC2" },
+            ],
+            "meshes": [{ "primitives": [{
+                "attributes": { "POSITION": 1, "TEXCOORD_0": 2 },
+                "indices": 0,
+                "extensions": { DRACO_EXTENSION: {
+                    "bufferView": 0,
+                    "attributes": { "POSITION": 0, "TEXCOORD_0": 1 },
+                } },
+            }] }],
+        })
+    }
+
+    fn accessor_floats(json: &Value, bin: &[u8], accessor: usize) -> Vec<f32> {
+        let view = &json["bufferViews"]
+            [json["accessors"][accessor]["bufferView"].as_u64().unwrap() as usize];
+        let offset = view["byteOffset"].as_u64().unwrap() as usize;
+        let length = view["byteLength"].as_u64().unwrap() as usize;
+        bin[offset..offset + length]
+            .chunks_exact(4)
+            .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+            .collect()
+    }
+
+    fn check_decoded(json: &Value, bin: &[u8]) {
+        assert!(json.get("extensionsUsed").is_none());
+        assert!(json.get("extensionsRequired").is_none());
+        let primitive = &json["meshes"][0]["primitives"][0];
+        assert!(primitive.get("extensions").is_none());
+
+        let indices = &json["accessors"][0];
+        assert_eq!(indices["componentType"], UNSIGNED_SHORT);
+        assert_eq!(indices["count"], 3);
+        let view = &json["bufferViews"][indices["bufferView"].as_u64().unwrap() as usize];
+        let offset = view["byteOffset"].as_u64().unwrap() as usize;
+        assert_eq!(&bin[offset..offset + 6], &[0, 0, 1, 0, 2, 0]);
+
+        let positions = accessor_floats(json, bin, 1);
+        assert_eq!(
+            positions,
+            POSITIONS.iter().flatten().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(json["accessors"][1]["min"], json!([0.0, 0.0, -0.5]));
+        assert_eq!(json["accessors"][1]["max"], json!([1.0, 1.0, 0.5]));
+        assert_eq!(
+            accessor_floats(json, bin, 2),
+            vec![5.0, 6.0, 7.0, 8.0, 9.0, 10.0]
+        );
+    }
+
+    #[test]
+    fn decodes_gltf_with_external_buffer() {
+        let gltf = gltf_json(json!({ "uri": "mesh.bin", "byteLength": draco_triangle().len() }));
+        let output = decompress_draco_gltf(&serde_json::to_vec(&gltf).unwrap(), |uri| {
+            (uri == "mesh.bin").then(draco_triangle)
+        })
+        .unwrap();
+
+        let json: Value = serde_json::from_slice(&output).unwrap();
+        // The original buffer is kept, the decoded data is embedded in a new one
+        assert_eq!(json["buffers"][0]["uri"], "mesh.bin");
+        let uri = json["buffers"][1]["uri"].as_str().unwrap();
+        let bin = base64::engine::general_purpose::STANDARD
+            .decode(uri.split_once(',').unwrap().1)
+            .unwrap();
+        assert_eq!(json["buffers"][1]["byteLength"], bin.len());
+        check_decoded(&json, &bin);
+    }
+
+    #[test]
+    fn decodes_glb_into_binary_chunk() {
+        let draco = draco_triangle();
+        let glb = build_glb(&gltf_json(json!({ "byteLength": draco.len() })), &draco);
+
+        let output = decompress_draco_gltf(&glb, |_| None).unwrap();
+        assert_eq!(
+            u32::from_le_bytes(output[8..12].try_into().unwrap()) as usize,
+            output.len()
+        );
+        let (json, bin) = split_glb(&output).unwrap();
+        let json: Value = serde_json::from_slice(json).unwrap();
+        let bin = bin.unwrap();
+        assert_eq!(json["buffers"].as_array().unwrap().len(), 1);
+        assert_eq!(json["buffers"][0]["byteLength"], bin.len());
+        assert_eq!(&bin[..draco.len()], draco.as_slice());
+        check_decoded(&json, bin);
+
+        // Files without Draco are left alone
+        assert!(decompress_draco_gltf(&output, |_| None).is_none());
+    }
+}
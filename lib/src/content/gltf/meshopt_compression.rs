@@ -0,0 +1,289 @@
+//! Decodes `EXT_meshopt_compression` buffer views into plain ones, so importers without the
+//! extension (Godot's `GltfDocument`) can read them. The decoders are meshoptimizer's own.
+
+use serde_json::{json, Value};
+
+use super::document::{GltfDocument, MAX_DECODED_SIZE};
+
+pub const MESHOPT_EXTENSION: &str = "EXT_meshopt_compression";
+
+/// Largest vertex size the meshoptimizer vertex codec accepts
+const MAX_VERTEX_SIZE: usize = 256;
+
+/// Decodes every compressed buffer view of the document, returns whether it used the
+/// extension.
+///
+/// The decoded data goes to the output buffer (the GLB binary chunk), the compressed data and
+/// the fallback buffers are left in place, unreferenced.
+pub fn decompress_meshopt_buffer_views(gltf: &mut GltfDocument) -> Result<bool, anyhow::Error> {
+    if !gltf.uses_extension(MESHOPT_EXTENSION) {
+        return Ok(false);
+    }
+
+    let num_views = gltf.array("bufferViews").len();
+    for view in 0..num_views {
+        let extension = gltf.json["bufferViews"][view]["extensions"][MESHOPT_EXTENSION].clone();
+        if extension.is_null() {
+            continue;
+        }
+        let compressed = gltf.buffer_range(&extension)?;
+        let byte_length = gltf.json["bufferViews"][view]["byteLength"]
+            .as_u64()
+            .unwrap_or(0);
+        let data = decode_buffer_view(&extension, &compressed, byte_length)
+            .map_err(|err| anyhow::anyhow!("bufferView {view}: {err}"))?;
+        let (buffer, offset) = gltf.push_data(&data);
+
+        let view_json = gltf.json["bufferViews"][view]
+            .as_object_mut()
+            .expect("bufferView object");
+        view_json.insert("buffer".to_string(), json!(buffer));
+        view_json.insert("byteOffset".to_string(), json!(offset));
+        view_json.insert("byteLength".to_string(), json!(data.len()));
+        remove_object_extension(&mut gltf.json["bufferViews"][view]);
+    }
+
+    // Fallback buffers are flagged with the extension too
+    for buffer in gltf.array_mut("buffers") {
+        remove_object_extension(buffer);
+    }
+    gltf.remove_extension(MESHOPT_EXTENSION);
+    Ok(true)
+}
+
+fn remove_object_extension(object: &mut Value) {
+    if let Some(extensions) = object["extensions"].as_object_mut() {
+        extensions.remove(MESHOPT_EXTENSION);
+        if extensions.is_empty() {
+            object
+                .as_object_mut()
+                .expect("gltf object")
+                .remove("extensions");
+        }
+    }
+}
+
+/// Decodes the `count` elements of `byteStride` bytes of a compressed view, then reverses its
+/// filter. `byte_length` is the length of the view it replaces, which the decoded data must
+/// match.
+fn decode_buffer_view(
+    extension: &Value,
+    compressed: &[u8],
+    byte_length: u64,
+) -> Result<Vec<u8>, anyhow::Error> {
+    let count = extension["count"].as_u64().unwrap_or(0) as usize;
+    let stride = extension["byteStride"].as_u64().unwrap_or(0) as usize;
+    let mode = extension["mode"].as_str().unwrap_or_default();
+    let filter = extension["filter"].as_str().unwrap_or("NONE");
+    let size = count
+        .checked_mul(stride)
+        .ok_or_else(|| anyhow::anyhow!("decoded size overflows"))?;
+    if size as u64 != byte_length {
+        anyhow::bail!("decoded size {size} doesn't match the byteLength {byte_length}");
+    }
+    if size > MAX_DECODED_SIZE {
+        anyhow::bail!("decoded size {size} too large");
+    }
+    let mut data = vec![0u8; size];
+
+    // The decoders validate the stream, the sizes they assert on are checked here
+    let result = match mode {
+        "ATTRIBUTES" => {
+            if stride == 0 || stride > MAX_VERTEX_SIZE || !stride.is_multiple_of(4) {
+                anyhow::bail!("invalid attribute stride {stride}");
+            }
+            unsafe {
+                meshopt::ffi::meshopt_decodeVertexBuffer(
+                    data.as_mut_ptr().cast(),
+                    count,
+                    stride,
+                    compressed.as_ptr(),
+                    compressed.len(),
+                )
+            }
+        }
+        "TRIANGLES" | "INDICES" => {
+            if stride != 2 && stride != 4 {
+                anyhow::bail!("invalid index stride {stride}");
+            }
+            if mode == "TRIANGLES" {
+                if !count.is_multiple_of(3) {
+                    anyhow::bail!("triangle index count {count} isn't a multiple of 3");
+                }
+                unsafe {
+                    meshopt::ffi::meshopt_decodeIndexBuffer(
+                        data.as_mut_ptr().cast(),
+                        count,
+                        stride,
+                        compressed.as_ptr(),
+                        compressed.len(),
+                    )
+                }
+            } else {
+                unsafe {
+                    meshopt::ffi::meshopt_decodeIndexSequence(
+                        data.as_mut_ptr().cast(),
+                        count,
+                        stride,
+                        compressed.as_ptr(),
+                        compressed.len(),
+                    )
+                }
+            }
+        }
+        other => anyhow::bail!("unsupported mode {other:?}"),
+    };
+    if result != 0 {
+        anyhow::bail!("meshopt decoding failed ({result})");
+    }
+
+    match filter {
+        "NONE" => {}
+        "OCTAHEDRAL" if mode == "ATTRIBUTES" && (stride == 4 || stride == 8) => unsafe {
+            meshopt::ffi::meshopt_decodeFilterOct(data.as_mut_ptr().cast(), count, stride);
+        },
+        "QUATERNION" if mode == "ATTRIBUTES" && stride == 8 => unsafe {
+            meshopt::ffi::meshopt_decodeFilterQuat(data.as_mut_ptr().cast(), count, stride);
+        },
+        "EXPONENTIAL" if mode == "ATTRIBUTES" => unsafe {
+            meshopt::ffi::meshopt_decodeFilterExp(data.as_mut_ptr().cast(), count, stride);
+        },
+        other => anyhow::bail!("unsupported filter {other:?} with stride {stride}"),
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::document::{build_glb, ARRAY_BUFFER, FLOAT};
+    use super::*;
+
+    const POSITIONS: [[f32; 3]; 4] = [
+        [0.0, 0.0, 0.0],
+        [1.0, 0.0, 0.5],
+        [0.0, 1.0, -0.5],
+        [1.0, 1.0, 0.0],
+    ];
+
+    #[test]
+    fn decodes_compressed_views_into_binary_chunk() {
+        let vertices = meshopt::encode_vertex_buffer(&POSITIONS).unwrap();
+        let indices = meshopt::encode_index_buffer(&[0, 1, 2, 2, 1, 3], 4).unwrap();
+        let mut bin = vertices.clone();
+        bin.extend_from_slice(&indices);
+        let gltf = json!({
+            "asset": { "version": "2.0" },
+            "extensionsUsed": [MESHOPT_EXTENSION],
+            "extensionsRequired": [MESHOPT_EXTENSION],
+            "buffers": [
+                { "byteLength": bin.len() },
+                { "byteLength": 72, "extensions": { MESHOPT_EXTENSION: { "fallback": true } } },
+            ],
+            "bufferViews": [
+                {
+                    "buffer": 1,
+                    "byteLength": 48,
+                    "byteStride": 12,
+                    "target": ARRAY_BUFFER,
+                    "extensions": { MESHOPT_EXTENSION: {
+                        "buffer": 0,
+                        "byteLength": vertices.len(),
+                        "byteStride": 12,
+                        "count": 4,
+                        "mode": "ATTRIBUTES",
+                    } },
+                },
+                {
+                    "buffer": 1,
+                    "byteOffset": 48,
+                    "byteLength": 24,
+                    "extensions": { MESHOPT_EXTENSION: {
+                        "buffer": 0,
+                        "byteOffset": vertices.len(),
+                        "byteLength": indices.len(),
+                        "byteStride": 4,
+                        "count": 6,
+                        "mode": "TRIANGLES",
+                    } },
+                },
+            ],
+            "accessors": [
+                { "bufferView": 0, "componentType": FLOAT, "count": 4, "type": "VEC3" },
+                { "bufferView": 1, "componentType": 5125, "count": 6, "type": "SCALAR" },
+            ],
+        });
+
+        let mut document = GltfDocument::parse(&build_glb(&gltf, &bin), |_| None).unwrap();
+        assert!(decompress_meshopt_buffer_views(&mut document).unwrap());
+        assert!(document.json.get("extensionsUsed").is_none());
+        assert!(document.json.get("extensionsRequired").is_none());
+        assert!(document.json["buffers"][1].get("extensions").is_none());
+        for view in document.array("bufferViews") {
+            assert!(view.get("extensions").is_none());
+            assert_eq!(view["buffer"], 0);
+        }
+        assert_eq!(
+            document.read_accessor(0).unwrap(),
+            POSITIONS
+                .iter()
+                .flatten()
+                .map(|value| f64::from(*value))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            document.read_accessor(1).unwrap(),
+            vec![0.0, 1.0, 2.0, 2.0, 1.0, 3.0]
+        );
+
+        let output = document.finish().unwrap();
+        let mut document = GltfDocument::parse(&output, |_| None).unwrap();
+        assert_eq!(document.array("buffers").len(), 2);
+        assert_eq!(document.read_accessor(1).unwrap().len(), 6);
+        // Files without the extension are left alone
+        assert!(!decompress_meshopt_buffer_views(&mut document).unwrap());
+    }
+
+    #[test]
+    fn rejects_sizes_the_data_cannot_back() {
+        let vertices = meshopt::encode_vertex_buffer(&POSITIONS).unwrap();
+        let compressed = |count: u64, byte_length: u64| {
+            json!({
+                "asset": { "version": "2.0" },
+                "extensionsUsed": [MESHOPT_EXTENSION],
+                "buffers": [{ "byteLength": vertices.len() }],
+                "bufferViews": [{
+                    "buffer": 0,
+                    "byteLength": byte_length,
+                    "extensions": { MESHOPT_EXTENSION: {
+                        "buffer": 0,
+                        "byteLength": vertices.len(),
+                        "byteStride": 12,
+                        "count": count,
+                        "mode": "ATTRIBUTES",
+                    } },
+                }],
+            })
+        };
+        for (count, byte_length) in [(1 << 40, 48), (1 << 40, 12 << 40), (u64::MAX, 48)] {
+            let gltf = compressed(count, byte_length);
+            let mut document = GltfDocument::parse(&build_glb(&gltf, &vertices), |_| None).unwrap();
+            assert!(decompress_meshopt_buffer_views(&mut document).is_err());
+        }
+
+        // Accessors past the end of their view fail before allocating
+        let gltf = json!({
+            "asset": { "version": "2.0" },
+            "buffers": [{ "byteLength": 48 }],
+            "bufferViews": [{ "buffer": 0, "byteLength": 48 }, { "buffer": 0, "byteLength": 48 }],
+            "accessors": [
+                { "bufferView": 0, "componentType": FLOAT, "count": 5, "type": "VEC3" },
+                { "bufferView": 7, "componentType": FLOAT, "count": 4, "type": "VEC3" },
+                { "componentType": FLOAT, "count": 1u64 << 40, "type": "VEC3" },
+            ],
+        });
+        let mut document = GltfDocument::parse(&build_glb(&gltf, &[0; 48]), |_| None).unwrap();
+        for accessor in 0..3 {
+            assert!(document.read_accessor(accessor).is_err());
+        }
+    }
+}
@@ -4,12 +4,16 @@
 //! for the Decentraland explorer.
 
 mod common;
+mod document;
+mod draco;
 mod emote;
+mod meshopt_compression;
+mod quantization;
 mod scene;
 mod wearable;
 
 // Re-export public API (maintains compatibility with content_provider.rs)
-pub use common::get_dependencies;
+pub use common::{decoded_gltf_path, get_dependencies};
 pub use emote::{
     build_dcl_emote_gltf, get_last_16_alphanumeric, load_and_save_emote_gltf, DclEmoteGltf,
 };
@@ -0,0 +1,145 @@
+//! Converts the integer vertex attributes `KHR_mesh_quantization` allows back to floats, the
+//! types the core spec (and Godot's `GltfDocument`) expects.
+
+use std::collections::BTreeSet;
+
+use serde_json::json;
+
+use super::document::{normalization_scale, num_components, GltfDocument, FLOAT};
+
+pub const QUANTIZATION_EXTENSION: &str = "KHR_mesh_quantization";
+
+/// Attributes the extension lets use integer types, other semantics were integers already
+fn is_quantized_semantic(semantic: &str) -> bool {
+    matches!(semantic, "POSITION" | "NORMAL" | "TANGENT") || semantic.starts_with("TEXCOORD_")
+}
+
+/// Rewrites the quantized attributes (and morph targets) as float accessors, returns whether
+/// the document used the extension. Node transforms undoing the quantization are kept as is.
+pub fn dequantize_attributes(gltf: &mut GltfDocument) -> Result<bool, anyhow::Error> {
+    if !gltf.uses_extension(QUANTIZATION_EXTENSION) {
+        return Ok(false);
+    }
+
+    let mut accessors = BTreeSet::new();
+    for mesh in gltf.array("meshes") {
+        for primitive in mesh["primitives"].as_array().into_iter().flatten() {
+            let targets = primitive["targets"].as_array().into_iter().flatten();
+            for attributes in std::iter::once(&primitive["attributes"]).chain(targets) {
+                for (semantic, accessor) in attributes.as_object().into_iter().flatten() {
+                    if let Some(accessor) = accessor.as_u64() {
+                        if is_quantized_semantic(semantic) {
+                            accessors.insert(accessor as usize);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    for accessor in accessors {
+        let Some(json) = gltf.array("accessors").get(accessor) else {
+            anyhow::bail!("invalid accessor {accessor}");
+        };
+        let component_type = json["componentType"].as_u64().unwrap_or(FLOAT);
+        if component_type == FLOAT {
+            continue;
+        }
+        let normalized = json["normalized"].as_bool().unwrap_or(false);
+        let num_components = num_components(json["type"].as_str())?;
+        let count = json["count"].as_u64().unwrap_or(0) as usize;
+
+        let mut values = gltf
+            .read_accessor(accessor)
+            .map_err(|err| anyhow::anyhow!("quantized accessor {accessor}: {err}"))?;
+        if normalized {
+            let scale = normalization_scale(component_type);
+            for value in &mut values {
+                *value = (*value / scale).max(-1.0);
+            }
+        }
+
+        let json = gltf.array_mut("accessors")[accessor]
+            .as_object_mut()
+            .expect("accessor object");
+        json.insert("componentType".to_string(), json!(FLOAT));
+        json.remove("normalized");
+        gltf.write_attribute(accessor, count, None, |element| {
+            Ok(&values[element * num_components..(element + 1) * num_components])
+        })?;
+    }
+
+    gltf.remove_extension(QUANTIZATION_EXTENSION);
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::document::{build_glb, BYTE, UNSIGNED_SHORT};
+    use super::*;
+
+    #[test]
+    fn converts_quantized_attributes_to_floats() {
+        let mut bin = Vec::new();
+        // u16 positions, 8 byte stride
+        for position in [[0u16, 0, 0], [100, 200, 300]] {
+            for component in position {
+                bin.extend_from_slice(&component.to_le_bytes());
+            }
+            bin.extend_from_slice(&[0, 0]);
+        }
+        // Normalized i8 normals, 4 byte stride
+        bin.extend_from_slice(&[0, 127, 0, 0, 0, 0, 0x80, 0]);
+        let gltf = json!({
+            "asset": { "version": "2.0" },
+            "extensionsUsed": [QUANTIZATION_EXTENSION],
+            "extensionsRequired": [QUANTIZATION_EXTENSION],
+            "buffers": [{ "byteLength": bin.len() }],
+            "bufferViews": [
+                { "buffer": 0, "byteLength": 16, "byteStride": 8 },
+                { "buffer": 0, "byteOffset": 16, "byteLength": 8, "byteStride": 4 },
+            ],
+            "accessors": [
+                {
+                    "bufferView": 0,
+                    "componentType": UNSIGNED_SHORT,
+                    "count": 2,
+                    "type": "VEC3",
+                    "min": [0, 0, 0],
+                    "max": [100, 200, 300],
+                },
+                {
+                    "bufferView": 1,
+                    "componentType": BYTE,
+                    "normalized": true,
+                    "count": 2,
+                    "type": "VEC3",
+                },
+            ],
+            "meshes": [{ "primitives": [{ "attributes": { "POSITION": 0, "NORMAL": 1 } }] }],
+        });
+
+        let mut document = GltfDocument::parse(&build_glb(&gltf, &bin), |_| None).unwrap();
+        assert!(dequantize_attributes(&mut document).unwrap());
+        assert!(document.json.get("extensionsUsed").is_none());
+        assert!(document.json.get("extensionsRequired").is_none());
+
+        let positions = &document.array("accessors")[0];
+        assert_eq!(positions["componentType"], FLOAT);
+        assert_eq!(positions["max"], json!([100.0, 200.0, 300.0]));
+        let normals = &document.array("accessors")[1];
+        assert_eq!(normals["componentType"], FLOAT);
+        assert!(normals.get("normalized").is_none());
+
+        let output = document.finish().unwrap();
+        let mut document = GltfDocument::parse(&output, |_| None).unwrap();
+        assert_eq!(
+            document.read_accessor(0).unwrap(),
+            vec![0.0, 0.0, 0.0, 100.0, 200.0, 300.0]
+        );
+        assert_eq!(
+            document.read_accessor(1).unwrap(),
+            vec![0.0, 1.0, 0.0, 0.0, 0.0, -1.0]
+        );
+    }
+}
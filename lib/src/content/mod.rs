@@ -1,5 +1,7 @@
 mod audio;
 pub mod cache_file_name;
+pub mod cache_index;
+pub mod content_integrity;
 pub mod content_mapping;
 pub mod content_notificator;
 pub mod content_provider;
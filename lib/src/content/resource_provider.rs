@@ -4,25 +4,34 @@ use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::fs;
 use tokio::io::{self, AsyncReadExt, AsyncWriteExt};
 use tokio::sync::{Notify, OnceCell, RwLock};
-use tokio::time::{timeout, Instant};
+use tokio::time::timeout;
 
 #[cfg(feature = "use_resource_tracking")]
 use super::resource_download_tracking::ResourceDownloadTracking;
 use crate::content::cache_file_name::cache_file_name;
+use crate::content::cache_index::{
+    AppendLogCacheIndex, CacheIndexEntry, CacheIndexError, CacheIndexOp, CacheIndexStore,
+    CACHE_INDEX_FILE_NAME,
+};
+use crate::content::content_integrity::{ContentVerifier, IntegrityCheck};
 use crate::content::semaphore_ext::CappedSemaphore;
 
 pub struct FileMetadata {
     file_size: i64,
-    last_accessed: Instant,
+    /// Unix time in ms, unique per file so the LRU order is total
+    last_accessed: u64,
+    base_name: String,
 }
 
 pub struct ResourceProvider {
     cache_folder: PathBuf,
     existing_files: RwLock<HashMap<String, FileMetadata>>,
+    index: Box<dyn CacheIndexStore>,
+    last_access_time: AtomicU64,
     max_cache_size: AtomicI64,
     downloaded_size: AtomicU64,
     pending_downloads: RwLock<HashMap<String, Arc<Notify>>>,
@@ -36,6 +45,18 @@ pub struct ResourceProvider {
 
 const UPDATE_THRESHOLD: u64 = 1_024 * 1_024; // 1 MB threshold
 
+// Downloads whose bytes don't match their content hash are retried this many times in total
+const MAX_DOWNLOAD_ATTEMPTS: usize = 2;
+
+#[derive(PartialEq, Eq)]
+enum DownloadOutcome {
+    Stored,
+    HashMismatch,
+}
+
+// The index log is compacted once it has this many records and at least 4 per tracked file
+const INDEX_COMPACT_MIN_RECORDS: usize = 10_000;
+
 // Asset-download timeouts (F-1 / RC-1). These bound *dead* connections without killing
 // *slow-but-alive* downloads: on a constrained link a single legitimate asset was observed
 // taking ~198s of wire time, so we deliberately set NO total-request timeout. Instead we
@@ -76,16 +97,39 @@ fn size_for_base_names(
     base_names: &HashSet<String>,
 ) -> i64 {
     existing_files
-        .iter()
-        .filter_map(|(file_path, metadata)| {
-            let file_name = Path::new(file_path).file_name()?.to_str()?;
-            base_names
-                .contains(cache_file_base_name(file_name))
-                .then_some(metadata.file_size)
-        })
+        .values()
+        .filter(|metadata| base_names.contains(&metadata.base_name))
+        .map(|metadata| metadata.file_size)
         .sum()
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CacheVerifyReport {
+    pub valid: usize,
+    pub corrupt: usize,
+    pub skipped: usize,
+}
+
+async fn verify_file(file_path: &str, mut verifier: ContentVerifier) -> io::Result<IntegrityCheck> {
+    let mut file = fs::File::open(file_path).await?;
+    let mut chunk = vec![0; 64 * 1024];
+    loop {
+        let read = file.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(verifier.finish());
+        }
+        verifier.update(&chunk[..read]);
+    }
+}
+
+fn base_name_of(file_path: &str) -> String {
+    let file_name = Path::new(file_path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(file_path);
+    cache_file_base_name(file_name).to_string()
+}
+
 impl ResourceProvider {
     // Synchronous constructor that sets up the ResourceProvider
     pub fn new(
@@ -93,10 +137,31 @@ impl ResourceProvider {
         max_cache_size: i64,
         max_concurrent_downloads: usize,
         #[cfg(feature = "use_resource_tracking")] download_tracking: Arc<ResourceDownloadTracking>,
+    ) -> Self {
+        let index = Box::new(AppendLogCacheIndex::new(Path::new(cache_folder)));
+        Self::with_index(
+            cache_folder,
+            index,
+            max_cache_size,
+            max_concurrent_downloads,
+            #[cfg(feature = "use_resource_tracking")]
+            download_tracking,
+        )
+    }
+
+    /// Same as `new` with a custom storage for the cache index
+    pub fn with_index(
+        cache_folder: &str,
+        index: Box<dyn CacheIndexStore>,
+        max_cache_size: i64,
+        max_concurrent_downloads: usize,
+        #[cfg(feature = "use_resource_tracking")] download_tracking: Arc<ResourceDownloadTracking>,
     ) -> Self {
         ResourceProvider {
             cache_folder: PathBuf::from(cache_folder),
             existing_files: RwLock::new(HashMap::new()),
+            index,
+            last_access_time: AtomicU64::new(0),
             max_cache_size: AtomicI64::new(max_cache_size),
             pending_downloads: RwLock::new(HashMap::new()),
             client: Client::builder()
@@ -116,6 +181,67 @@ impl ResourceProvider {
     async fn initialize(&self) -> Result<(), io::Error> {
         let mut existing_files = self.existing_files.write().await;
         fs::create_dir_all(&self.cache_folder).await?;
+        match self.index.load() {
+            Ok(entries) => {
+                for entry in entries {
+                    self.last_access_time
+                        .fetch_max(entry.last_accessed, Ordering::Relaxed);
+                    existing_files.insert(
+                        self.path_from_index_name(&entry.file_name),
+                        FileMetadata {
+                            file_size: entry.file_size,
+                            last_accessed: entry.last_accessed,
+                            base_name: entry.base_name,
+                        },
+                    );
+                }
+                // files written before a crash without reaching the index are
+                // kept as the least recently used (access times below any real one)
+                let mut unindexed = 0;
+                for (file_path, file_size) in self.scan_cache_folder().await? {
+                    if !existing_files.contains_key(&file_path) {
+                        existing_files.insert(
+                            file_path.clone(),
+                            FileMetadata {
+                                file_size,
+                                last_accessed: unindexed,
+                                base_name: base_name_of(&file_path),
+                            },
+                        );
+                        unindexed += 1;
+                    }
+                }
+                if unindexed > 0 {
+                    tracing::debug!("indexed {unindexed} cache files missing from the index");
+                    self.index.rewrite(&self.index_entries(&existing_files));
+                }
+            }
+            Err(err) => {
+                if !matches!(err, CacheIndexError::Missing) {
+                    tracing::warn!("{err}, rebuilding it from the cache folder");
+                }
+                // access times are lost so the files end up in directory order
+                existing_files.clear();
+                for (file_path, file_size) in self.scan_cache_folder().await? {
+                    existing_files.insert(
+                        file_path.clone(),
+                        FileMetadata {
+                            file_size,
+                            last_accessed: self.next_access_time(),
+                            base_name: base_name_of(&file_path),
+                        },
+                    );
+                }
+                self.index.rewrite(&self.index_entries(&existing_files));
+            }
+        }
+        self.ensure_space_for(&mut existing_files, 0).await;
+        Ok(())
+    }
+
+    /// Path and size of every cached file, leftover `.tmp` downloads are deleted.
+    async fn scan_cache_folder(&self) -> Result<Vec<(String, i64)>, io::Error> {
+        let mut files = Vec::new();
         let dir = std::fs::read_dir(&self.cache_folder)?;
         for entry in dir {
             let entry = entry?;
@@ -126,15 +252,73 @@ impl ResourceProvider {
                     fs::remove_file(&file_path).await?;
                     continue;
                 }
+                if entry.file_name() == CACHE_INDEX_FILE_NAME {
+                    continue;
+                }
                 let metadata = entry.metadata()?;
-                let file_size = metadata.len() as i64;
-                let file_path_str = file_path.to_str().unwrap().to_string();
-                self.add_file(&mut existing_files, file_path_str, file_size)
-                    .await;
+                files.push((
+                    file_path.to_str().unwrap().to_string(),
+                    metadata.len() as i64,
+                ));
             }
         }
-        self.ensure_space_for(&mut existing_files, 0).await;
-        Ok(())
+        Ok(files)
+    }
+
+    /// Unix time in ms, bumped when needed so no two accesses share a time.
+    fn next_access_time(&self) -> u64 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default();
+        let previous = self
+            .last_access_time
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |last| {
+                Some(now.max(last + 1))
+            })
+            .unwrap_or_default();
+        now.max(previous + 1)
+    }
+
+    /// Files in the cache folder are indexed by their relative path so the
+    /// index survives the folder being moved (e.g. app container changes on iOS).
+    fn index_name<'a>(&self, file_path: &'a str) -> &'a str {
+        Path::new(file_path)
+            .strip_prefix(&self.cache_folder)
+            .ok()
+            .and_then(|relative| relative.to_str())
+            .unwrap_or(file_path)
+    }
+
+    fn path_from_index_name(&self, file_name: &str) -> String {
+        self.cache_folder
+            .join(file_name)
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    fn index_entries(
+        &self,
+        existing_files: &HashMap<String, FileMetadata>,
+    ) -> Vec<CacheIndexEntry> {
+        existing_files
+            .iter()
+            .map(|(file_path, metadata)| CacheIndexEntry {
+                file_name: self.index_name(file_path).to_string(),
+                base_name: metadata.base_name.clone(),
+                file_size: metadata.file_size,
+                last_accessed: metadata.last_accessed,
+            })
+            .collect()
+    }
+
+    fn record_index(&self, existing_files: &HashMap<String, FileMetadata>, op: CacheIndexOp<'_>) {
+        self.index.record(op);
+        let threshold = INDEX_COMPACT_MIN_RECORDS.max(existing_files.len() * 4);
+        if self.index.pending_records() > threshold {
+            self.index.rewrite(&self.index_entries(existing_files));
+        }
     }
 
     async fn ensure_space_for(
@@ -159,9 +343,17 @@ impl ResourceProvider {
     ) {
         let metadata = FileMetadata {
             file_size,
-            last_accessed: Instant::now(),
+            last_accessed: self.next_access_time(),
+            base_name: base_name_of(&file_path),
+        };
+        let entry = CacheIndexEntry {
+            file_name: self.index_name(&file_path).to_string(),
+            base_name: metadata.base_name.clone(),
+            file_size,
+            last_accessed: metadata.last_accessed,
         };
         existing_files.insert(file_path, metadata);
+        self.record_index(existing_files, CacheIndexOp::Put(&entry));
     }
 
     async fn remove_file(
@@ -171,6 +363,8 @@ impl ResourceProvider {
     ) -> Option<FileMetadata> {
         if let Some(metadata) = existing_files.remove(file_path) {
             let _ = fs::remove_file(file_path).await;
+            let file_name = self.index_name(file_path);
+            self.record_index(existing_files, CacheIndexOp::Remove { file_name });
             Some(metadata)
         } else {
             None
@@ -218,7 +412,16 @@ impl ResourceProvider {
 
     fn touch_file(&self, existing_files: &mut HashMap<String, FileMetadata>, file_path: &str) {
         if let Some(metadata) = existing_files.get_mut(file_path) {
-            metadata.last_accessed = Instant::now();
+            metadata.last_accessed = self.next_access_time();
+            let last_accessed = metadata.last_accessed;
+            let file_name = self.index_name(file_path);
+            self.record_index(
+                existing_files,
+                CacheIndexOp::Touch {
+                    file_name,
+                    last_accessed,
+                },
+            );
         }
     }
 
@@ -247,12 +450,42 @@ impl ResourceProvider {
         }
     }
 
-    async fn download_file(
+    /// Downloads `url` into `dest`, retrying when the bytes don't match `file_hash`.
+    async fn download_file(&self, url: &str, dest: &Path, file_hash: &str) -> Result<(), String> {
+        // tracked over every attempt, and ended however they finish
+        #[cfg(feature = "use_resource_tracking")]
+        self.download_tracking.start(file_hash.to_string()).await;
+
+        let mut result = Err(format!(
+            "Downloaded content doesn't match its hash {file_hash}: {url}"
+        ));
+        for attempt in 1..=MAX_DOWNLOAD_ATTEMPTS {
+            match self.try_download_file(url, dest, file_hash).await {
+                Ok(DownloadOutcome::Stored) => {
+                    result = Ok(());
+                    break;
+                }
+                Ok(DownloadOutcome::HashMismatch) => tracing::warn!(
+                    "Content of {file_hash} doesn't match its hash (attempt {attempt}): {url}"
+                ),
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                }
+            }
+        }
+
+        #[cfg(feature = "use_resource_tracking")]
+        self.download_tracking.end(file_hash.to_string()).await;
+        result
+    }
+
+    async fn try_download_file(
         &self,
         url: &str,
         dest: &Path,
-        #[cfg(feature = "use_resource_tracking")] file_hash: String,
-    ) -> Result<(), String> {
+        file_hash: &str,
+    ) -> Result<DownloadOutcome, String> {
         tracing::debug!("[HTTP] GET {}", url);
         let tmp_dest = dest.with_extension("tmp");
         let response = timeout(RESPONSE_TIMEOUT, self.client.get(url).send())
@@ -260,9 +493,6 @@ impl ResourceProvider {
             .map_err(|_| format!("Response timeout ({RESPONSE_TIMEOUT:?}): {url}"))?
             .map_err(|e| format!("Request error: {:?}", e))?;
 
-        #[cfg(feature = "use_resource_tracking")]
-        self.download_tracking.start(file_hash.clone()).await;
-
         #[cfg(feature = "use_resource_tracking")]
         let mut current_size = 0;
 
@@ -274,6 +504,7 @@ impl ResourceProvider {
             .await
             .map_err(|e| format!("File creation error: {:?}", e))?;
         let mut stream = response.bytes_stream();
+        let mut verifier = ContentVerifier::for_hash(file_hash);
 
         let mut accumulated_size = 0;
 
@@ -285,6 +516,9 @@ impl ResourceProvider {
             file.write_all(&chunk)
                 .await
                 .map_err(|e| format!("File write error: {:?}", e))?;
+            if let Some(verifier) = verifier.as_mut() {
+                verifier.update(&chunk);
+            }
 
             accumulated_size += chunk.len() as u64;
             if accumulated_size > UPDATE_THRESHOLD {
@@ -294,7 +528,7 @@ impl ResourceProvider {
                 {
                     current_size += accumulated_size;
                     self.download_tracking
-                        .report_progress(file_hash.clone(), current_size)
+                        .report_progress(file_hash.to_string(), current_size)
                         .await;
                 }
                 accumulated_size = 0;
@@ -308,11 +542,19 @@ impl ResourceProvider {
             {
                 current_size += accumulated_size;
                 self.download_tracking
-                    .report_progress(file_hash.clone(), current_size)
+                    .report_progress(file_hash.to_string(), current_size)
                     .await;
             }
         }
 
+        file.flush()
+            .await
+            .map_err(|e| format!("File write error: {:?}", e))?;
+        if verifier.is_some_and(|verifier| verifier.finish() == IntegrityCheck::Mismatch) {
+            let _ = fs::remove_file(&tmp_dest).await;
+            return Ok(DownloadOutcome::HashMismatch);
+        }
+
         fs::rename(&tmp_dest, dest).await.map_err(|e| {
             format!(
                 "Failed to rename file: {:?} from: {:?} to: {:?}",
@@ -320,18 +562,53 @@ impl ResourceProvider {
             )
         })?;
 
-        #[cfg(feature = "use_resource_tracking")]
-        self.download_tracking.end(file_hash).await;
-
-        Ok(())
+        Ok(DownloadOutcome::Stored)
     }
 
+    /// Same as `download_file`, also returning the downloaded bytes.
     async fn download_file_with_buffer(
         &self,
         url: &str,
         dest: &Path,
-        #[cfg(feature = "use_resource_tracking")] file_hash: String,
+        file_hash: &str,
     ) -> Result<Vec<u8>, String> {
+        #[cfg(feature = "use_resource_tracking")]
+        self.download_tracking.start(file_hash.to_string()).await;
+
+        let mut result = Err(format!(
+            "Downloaded content doesn't match its hash {file_hash}: {url}"
+        ));
+        for attempt in 1..=MAX_DOWNLOAD_ATTEMPTS {
+            match self
+                .try_download_file_with_buffer(url, dest, file_hash)
+                .await
+            {
+                Ok(Some(buffer)) => {
+                    result = Ok(buffer);
+                    break;
+                }
+                Ok(None) => tracing::warn!(
+                    "Content of {file_hash} doesn't match its hash (attempt {attempt}): {url}"
+                ),
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                }
+            }
+        }
+
+        #[cfg(feature = "use_resource_tracking")]
+        self.download_tracking.end(file_hash.to_string()).await;
+        result
+    }
+
+    /// None when the content doesn't match `file_hash`
+    async fn try_download_file_with_buffer(
+        &self,
+        url: &str,
+        dest: &Path,
+        file_hash: &str,
+    ) -> Result<Option<Vec<u8>>, String> {
         tracing::debug!("[HTTP] GET {}", url);
         let tmp_dest = dest.with_extension("tmp");
         let response = timeout(RESPONSE_TIMEOUT, self.client.get(url).send())
@@ -343,8 +620,6 @@ impl ResourceProvider {
             return Err(format!("Failed to download file: {:?}", response.status()));
         }
 
-        #[cfg(feature = "use_resource_tracking")]
-        self.download_tracking.start(file_hash.clone()).await;
         #[cfg(feature = "use_resource_tracking")]
         let mut current_size = 0;
 
@@ -353,6 +628,7 @@ impl ResourceProvider {
             .map_err(|e| format!("File creation error: {:?}", e))?;
         let mut stream = response.bytes_stream();
         let mut buffer = Vec::new();
+        let mut verifier = ContentVerifier::for_hash(file_hash);
 
         let mut accumulated_size = 0;
 
@@ -364,6 +640,9 @@ impl ResourceProvider {
             file.write_all(&chunk)
                 .await
                 .map_err(|e| format!("File write error: {:?}", e))?;
+            if let Some(verifier) = verifier.as_mut() {
+                verifier.update(&chunk);
+            }
             buffer.extend_from_slice(&chunk);
 
             accumulated_size += chunk.len() as u64;
@@ -374,7 +653,7 @@ impl ResourceProvider {
                 {
                     current_size += accumulated_size;
                     self.download_tracking
-                        .report_progress(file_hash.clone(), current_size)
+                        .report_progress(file_hash.to_string(), current_size)
                         .await;
                 }
                 accumulated_size = 0;
@@ -388,11 +667,19 @@ impl ResourceProvider {
             {
                 current_size += accumulated_size;
                 self.download_tracking
-                    .report_progress(file_hash.clone(), current_size)
+                    .report_progress(file_hash.to_string(), current_size)
                     .await;
             }
         }
 
+        file.flush()
+            .await
+            .map_err(|e| format!("File write error: {:?}", e))?;
+        if verifier.is_some_and(|verifier| verifier.finish() == IntegrityCheck::Mismatch) {
+            let _ = fs::remove_file(&tmp_dest).await;
+            return Ok(None);
+        }
+
         fs::rename(&tmp_dest, dest).await.map_err(|e| {
             format!(
                 "Failed to rename file: {:?} from: {:?} to: {:?}",
@@ -400,10 +687,7 @@ impl ResourceProvider {
             )
         })?;
 
-        #[cfg(feature = "use_resource_tracking")]
-        self.download_tracking.end(file_hash.clone()).await;
-
-        Ok(buffer)
+        Ok(Some(buffer))
     }
 
     async fn ensure_initialized(&self) -> Result<(), String> {
@@ -415,11 +699,30 @@ impl ResourceProvider {
 
     async fn handle_existing_file(&self, absolute_file_path: &String) -> Result<Vec<u8>, String> {
         let mut existing_files = self.existing_files.write().await;
-        self.touch_file(&mut existing_files, absolute_file_path);
 
-        let mut file = fs::File::open(absolute_file_path)
-            .await
-            .map_err(|e| format!("Failed to open file: {:?}", e))?;
+        let open_result = fs::File::open(absolute_file_path).await;
+        let mut file = match open_result {
+            Ok(file) => file,
+            Err(e) => {
+                // the index can outlive files deleted behind our back
+                if e.kind() == io::ErrorKind::NotFound {
+                    self.remove_file(&mut existing_files, absolute_file_path)
+                        .await;
+                }
+                return Err(format!("Failed to open file: {:?}", e));
+            }
+        };
+
+        if existing_files.contains_key(absolute_file_path) {
+            self.touch_file(&mut existing_files, absolute_file_path);
+        } else if let Ok(metadata) = file.metadata().await {
+            // written before a crash without reaching the index
+            let file_size = metadata.len() as i64;
+            self.ensure_space_for(&mut existing_files, file_size).await;
+            self.add_file(&mut existing_files, absolute_file_path.clone(), file_size)
+                .await;
+        }
+
         let mut buffer = Vec::new();
         file.read_to_end(&mut buffer)
             .await
@@ -483,9 +786,7 @@ impl ResourceProvider {
     /// Touch a file to update its last_access time for LRU (async version).
     pub async fn touch_file_async(&self, file_path: &str) {
         let mut existing_files = self.existing_files.write().await;
-        if let Some(metadata) = existing_files.get_mut(file_path) {
-            metadata.last_accessed = Instant::now();
-        }
+        self.touch_file(&mut existing_files, file_path);
     }
 
     pub async fn store_file(&self, file_hash: &str, bytes: &[u8]) -> Result<(), String> {
@@ -561,13 +862,8 @@ impl ResourceProvider {
         // slot below — success or failure — so a failed download can't poison the hash (F-2).
         let result: Result<(), String> = async {
             if tokio::fs::metadata(&absolute_file_path).await.is_err() {
-                self.download_file(
-                    &url,
-                    Path::new(&absolute_file_path),
-                    #[cfg(feature = "use_resource_tracking")]
-                    file_hash.clone(),
-                )
-                .await?;
+                self.download_file(&url, Path::new(&absolute_file_path), &file_hash)
+                    .await?;
 
                 let metadata = tokio::fs::metadata(&absolute_file_path)
                     .await
@@ -613,13 +909,8 @@ impl ResourceProvider {
         // Always release the pending-download slot below, success or failure (F-2).
         let result: Result<(), String> = async {
             if tokio::fs::metadata(&absolute_file_path).await.is_err() {
-                self.download_file(
-                    &url,
-                    Path::new(&absolute_file_path),
-                    #[cfg(feature = "use_resource_tracking")]
-                    file_hash.clone(),
-                )
-                .await?;
+                self.download_file(&url, Path::new(&absolute_file_path), &file_hash)
+                    .await?;
 
                 let metadata = tokio::fs::metadata(&absolute_file_path)
                     .await
@@ -662,12 +953,7 @@ impl ResourceProvider {
         let result: Result<Vec<u8>, String> = async {
             if tokio::fs::metadata(&absolute_file_path).await.is_err() {
                 let data = self
-                    .download_file_with_buffer(
-                        url,
-                        Path::new(absolute_file_path),
-                        #[cfg(feature = "use_resource_tracking")]
-                        file_hash.clone(),
-                    )
+                    .download_file_with_buffer(url, Path::new(absolute_file_path), file_hash)
                     .await?;
                 let metadata = tokio::fs::metadata(absolute_file_path)
                     .await
@@ -706,6 +992,50 @@ impl ResourceProvider {
         for file_path in file_paths {
             self.remove_file(&mut existing_files, &file_path).await;
         }
+        self.index.rewrite(&[]);
+    }
+
+    /// Checks the cached files named by a CID against their content and
+    /// deletes the corrupt ones. Meant to run in the background.
+    pub async fn verify_cached_files(&self) -> CacheVerifyReport {
+        let mut report = CacheVerifyReport::default();
+        if self.ensure_initialized().await.is_err() {
+            return report;
+        }
+
+        let file_paths: Vec<String> = self.existing_files.read().await.keys().cloned().collect();
+        for file_path in file_paths {
+            let file_name = Path::new(&file_path)
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or_default();
+            let Some(verifier) = ContentVerifier::for_hash(file_name) else {
+                report.skipped += 1;
+                continue;
+            };
+            let check = match verify_file(&file_path, verifier).await {
+                Ok(check) => check,
+                // evicted or deleted meanwhile
+                Err(_) => {
+                    report.skipped += 1;
+                    continue;
+                }
+            };
+            match check {
+                IntegrityCheck::Valid => report.valid += 1,
+                IntegrityCheck::Unverifiable => report.skipped += 1,
+                IntegrityCheck::Mismatch => {
+                    // a download in flight will replace it
+                    if self.pending_downloads.read().await.contains_key(file_name) {
+                        continue;
+                    }
+                    tracing::warn!("Deleting corrupt cache file {file_path}");
+                    self.delete_file(&file_path).await;
+                    report.corrupt += 1;
+                }
+            }
+        }
+        report
     }
 
     pub fn consume_download_size(&self) -> u64 {
@@ -781,7 +1111,8 @@ mod tests {
                 path.to_string(),
                 FileMetadata {
                     file_size: size,
-                    last_accessed: Instant::now(),
+                    last_accessed: 0,
+                    base_name: base_name_of(path),
                 },
             );
         }
@@ -793,6 +1124,87 @@ mod tests {
         assert_eq!(size_for_base_names(&files, &HashSet::new()), 0);
     }
 
+    #[tokio::test]
+    async fn test_index_survives_restart() {
+        let dir = std::env::temp_dir().join(format!("dcl-rp-index-test-{}", std::process::id()));
+        let path = dir.to_str().expect("temp dir path is valid utf-8");
+        let _ = tokio::fs::remove_dir_all(path).await;
+        setup_cache_folder(path)
+            .await
+            .expect("Failed to create cache folder");
+
+        let new_provider = |max_cache_size| {
+            ResourceProvider::new(
+                path,
+                max_cache_size,
+                2,
+                #[cfg(feature = "use_resource_tracking")]
+                Arc::new(ResourceDownloadTracking::new()),
+            )
+        };
+        let file_path = |hash: &str| format!("{}/{}", path, hash);
+
+        {
+            let provider = new_provider(1024);
+            for hash in ["bafkfirst", "bafksecond", "bafkthird"] {
+                provider.store_file(hash, &[0; 100]).await.unwrap();
+            }
+            // the oldest file becomes the most recently used
+            provider.touch_file_async(&file_path("bafkfirst")).await;
+        }
+
+        // a file left by a crash between the write and the index Put
+        tokio::fs::write(file_path("bafkunknown"), [0; 10])
+            .await
+            .unwrap();
+
+        {
+            let provider = new_provider(1024);
+            provider.ensure_initialized().await.unwrap();
+            let mut existing_files = provider.existing_files.write().await;
+            assert_eq!(existing_files.len(), 4);
+            assert_eq!(provider.total_size(&existing_files), 310);
+            assert_eq!(
+                existing_files[&file_path("bafkthird")].base_name,
+                "bafkthird"
+            );
+            // unindexed files are evicted first
+            provider.remove_less_used(&mut existing_files).await;
+            assert!(!existing_files.contains_key(&file_path("bafkunknown")));
+            assert!(tokio::fs::metadata(file_path("bafkunknown")).await.is_err());
+            provider.remove_less_used(&mut existing_files).await;
+            assert!(!existing_files.contains_key(&file_path("bafksecond")));
+            assert!(existing_files.contains_key(&file_path("bafkfirst")));
+        }
+
+        // and they're in the index from then on
+        tokio::fs::write(file_path("bafkunknown"), [0; 10])
+            .await
+            .unwrap();
+        {
+            let provider = new_provider(1024);
+            provider.ensure_initialized().await.unwrap();
+        }
+        let indexed = AppendLogCacheIndex::new(&dir).load().unwrap();
+        assert!(indexed.iter().any(|entry| entry.file_name == "bafkunknown"));
+
+        // a corrupt index is rebuilt from the folder
+        let index_path = dir.join(CACHE_INDEX_FILE_NAME);
+        tokio::fs::write(&index_path, "garbage\n").await.unwrap();
+        {
+            let provider = new_provider(1024);
+            provider.ensure_initialized().await.unwrap();
+            let existing_files = provider.existing_files.read().await;
+            assert!(existing_files.contains_key(&file_path("bafkfirst")));
+            assert!(existing_files.contains_key(&file_path("bafkunknown")));
+            assert!(!existing_files.contains_key(index_path.to_str().unwrap()));
+            assert_eq!(provider.total_size(&existing_files), 210);
+        }
+        assert!(AppendLogCacheIndex::new(&dir).load().is_ok());
+
+        let _ = tokio::fs::remove_dir_all(path).await;
+    }
+
     async fn setup_cache_folder(path: &str) -> Result<()> {
         if tokio::fs::metadata(path).await.is_err() {
             tokio::fs::create_dir_all(path).await?;
@@ -800,16 +1212,28 @@ mod tests {
         Ok(())
     }
 
-    /// Spawn an ephemeral localhost HTTP server that returns a fixed image body for any GET.
+    /// Body served by the fake image server for `path`
+    fn fake_image(path: &str) -> Vec<u8> {
+        [
+            b"\x89PNG\r\n\x1a\n--fake-image-bytes-for-".as_slice(),
+            path.as_bytes(),
+        ]
+        .concat()
+    }
+
+    /// CIDv1 of `fake_image(path)`, downloads are checked against it
+    fn fake_image_hash(path: &str) -> String {
+        use multihash_codetable::MultihashDigest;
+        let digest = multihash_codetable::Code::Sha2_256.digest(&fake_image(path));
+        cid::Cid::new_v1(0x55, digest).to_string()
+    }
+
+    /// Spawn an ephemeral localhost HTTP server that returns `fake_image(path)` for any GET.
     /// Replaces the old dependency on the public `httpbin.org` service, which made this test
-    /// flake whenever that service returned a transient 5xx. `ResourceProvider` keys cached
-    /// entries by the caller-supplied file hash (it never hashes the bytes), so an arbitrary
-    /// non-empty body is a valid stand-in for the image.
+    /// flake whenever that service returned a transient 5xx. Returns the base url.
     async fn spawn_fake_image_server() -> String {
         use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
-        const BODY: &[u8] = b"\x89PNG\r\n\x1a\n--fake-image-bytes-for-resource-provider-test--";
-
         let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
             .await
             .expect("bind ephemeral test server");
@@ -818,21 +1242,24 @@ mod tests {
         tokio::spawn(async move {
             while let Ok((mut socket, _)) = listener.accept().await {
                 tokio::spawn(async move {
-                    // Read and discard the request; we serve the same body regardless.
+                    // Only the path of the request line matters.
                     let mut buf = [0u8; 1024];
-                    let _ = socket.read(&mut buf).await;
+                    let read = socket.read(&mut buf).await.unwrap_or(0);
+                    let request = String::from_utf8_lossy(&buf[..read]);
+                    let path = request.split(' ').nth(1).unwrap_or("/");
+                    let body = fake_image(path);
                     let head = format!(
                         "HTTP/1.1 200 OK\r\nContent-Type: image/png\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
-                        BODY.len()
+                        body.len()
                     );
                     let _ = socket.write_all(head.as_bytes()).await;
-                    let _ = socket.write_all(BODY).await;
+                    let _ = socket.write_all(&body).await;
                     let _ = socket.flush().await;
                 });
             }
         });
 
-        format!("http://{}/image.png", addr)
+        format!("http://{}", addr)
     }
 
     /// Spawn a localhost server that answers every GET with `500 Internal Server Error`, so
@@ -884,7 +1311,7 @@ mod tests {
         ));
         provider.clear().await;
 
-        let file_hash = "bafkreibmrvrdgqthfrvehyell552sk7ivuas2ozzjdmlojbzttqlcrxiya".to_string();
+        let file_hash = fake_image_hash("/image.png");
         let absolute_file_path = format!("{}/{}", path, file_hash);
 
         // First attempt fails (server 500).
@@ -908,7 +1335,7 @@ mod tests {
 
         // A retry against a working server must now succeed (proves the hash isn't poisoned
         // and no waiter is stranded). This would hang forever before the fix.
-        let good_url = spawn_fake_image_server().await;
+        let good_url = format!("{}/image.png", spawn_fake_image_server().await);
         provider
             .fetch_resource(good_url, file_hash.clone(), absolute_file_path.clone())
             .await
@@ -918,6 +1345,73 @@ mod tests {
         let _ = tokio::fs::remove_dir_all(path).await;
     }
 
+    #[tokio::test]
+    async fn test_content_hash_verification() {
+        let base_url = spawn_fake_image_server().await;
+
+        let dir =
+            std::env::temp_dir().join(format!("dcl-rp-integrity-test-{}", std::process::id()));
+        let path = dir.to_str().expect("temp dir path is valid utf-8");
+        setup_cache_folder(path)
+            .await
+            .expect("Failed to create cache folder");
+
+        let provider = ResourceProvider::new(
+            path,
+            1024 * 1024,
+            2,
+            #[cfg(feature = "use_resource_tracking")]
+            Arc::new(ResourceDownloadTracking::new()),
+        );
+        provider.clear().await;
+
+        // the server answers with other content than the one requested
+        let file_hash = fake_image_hash("/expected.png");
+        let absolute_file_path = format!("{}/{}", path, file_hash);
+        let result = provider
+            .fetch_resource(
+                format!("{base_url}/other.png"),
+                file_hash.clone(),
+                absolute_file_path.clone(),
+            )
+            .await;
+        assert!(
+            result.is_err(),
+            "content with the wrong hash must be rejected"
+        );
+        assert!(!provider.file_exists(&file_hash).await);
+        assert!(tokio::fs::metadata(&absolute_file_path).await.is_err());
+
+        provider
+            .fetch_resource(
+                format!("{base_url}/expected.png"),
+                file_hash.clone(),
+                absolute_file_path.clone(),
+            )
+            .await
+            .expect("content matching its hash is stored");
+
+        // corrupted on disk afterwards, the sweep deletes it
+        tokio::fs::write(&absolute_file_path, b"half-writ")
+            .await
+            .unwrap();
+        provider.store_file("not-a-cid", b"kept").await.unwrap();
+        let report = provider.verify_cached_files().await;
+        assert_eq!(
+            report,
+            CacheVerifyReport {
+                valid: 0,
+                corrupt: 1,
+                skipped: 1,
+            }
+        );
+        assert!(!provider.file_exists(&file_hash).await);
+        assert!(provider.file_exists("not-a-cid").await);
+
+        provider.clear().await;
+        let _ = tokio::fs::remove_dir_all(path).await;
+    }
+
     #[tokio::test]
     async fn test_fetch_resource_or_wait() {
         // Serve the image bytes from localhost instead of the public httpbin.org service,
@@ -947,28 +1441,16 @@ mod tests {
         ));
         provider.clear().await;
 
-        let files_to_download = vec![
-            (
-                base_url.clone(),
-                "bafkreibmrvrdgqthfrvehyell552sk7ivuas2ozzjdmlojbzttqlcrxiya",
-            ),
-            (
-                base_url.clone(),
-                "bafkreic4osvzsjzyqutwjxt2xmyd4hjrwukrxzclvixke3putyrihggmam",
-            ),
-            (
-                base_url.clone(),
-                "bafkreibhjuitdcu3jwu7khjcg2fo6xf2h3hilnfv4liy4p5h2olxj6tcce",
-            ),
-        ];
+        let files_to_download: Vec<_> = ["/a.png", "/b.png", "/c.png"]
+            .into_iter()
+            .map(|path| (format!("{base_url}{path}"), fake_image_hash(path)))
+            .collect();
 
         // Create a vector to hold the handles of the spawned tasks
         let handles: Vec<_> = files_to_download
             .clone()
             .into_iter()
             .map(|(url, file_hash)| {
-                let url = url.to_string();
-                let file_hash = file_hash.to_string();
                 let absolute_file_path = format!("{}/{}", path, file_hash);
 
                 let provider_clone = provider.clone();
@@ -990,7 +1472,7 @@ mod tests {
         // Extract file hashes from the files_to_download vector
         let file_hashes: Vec<_> = files_to_download
             .iter()
-            .map(|(_, file_hash)| *file_hash)
+            .map(|(_, file_hash)| file_hash.as_str())
             .collect();
 
         // Check if all files have been downloaded
@@ -82,6 +82,10 @@ pub struct SceneEntityMetadata {
     pub spawn_points: Option<Vec<SpawnPoint>>,
     pub authoritative_multiplayer: Option<bool>,
     pub landscape_terrain: Option<bool>,
+    #[serde(default)]
+    pub required_permissions: Vec<String>,
+    #[serde(default)]
+    pub allowed_media_hostnames: Vec<String>,
     #[serde(flatten)]
     pub extra: HashMap<String, serde_json::Value>,
 }
@@ -302,6 +302,119 @@ fn deserialize_transform(data: &[u8]) -> Option<serde_json::Value> {
     }))
 }
 
+/// Serialize a component from the JSON shape `deserialize_component_to_json`
+/// produces, back to its binary data. Used by the Scene Inspector write-back
+/// commands; missing fields take their defaults.
+pub fn serialize_component_from_json(
+    component_id: u32,
+    json: serde_json::Value,
+) -> Result<Vec<u8>, String> {
+    match component_id {
+        1 => serialize_transform(&json),
+        _ => serialize_json_to_proto_component(component_id, json)
+            .ok_or_else(|| format!("unknown component id {component_id}"))?
+            .map_err(|err| err.to_string()),
+    }
+}
+
+/// Inverse of `deserialize_transform`. Defaults to the identity transform
+/// parented to the root entity.
+fn serialize_transform(json: &serde_json::Value) -> Result<Vec<u8>, String> {
+    if !json.is_object() {
+        return Err("transform must be an object".to_string());
+    }
+    let read = |field: &str, axis: &str, default: f32| -> Result<f32, String> {
+        match &json[field][axis] {
+            serde_json::Value::Null => Ok(default),
+            value => value
+                .as_f64()
+                .map(|value| value as f32)
+                .ok_or_else(|| format!("{field}.{axis} must be a number")),
+        }
+    };
+
+    let mut data = Vec::with_capacity(44);
+    for (field, axes, default) in [
+        ("position", &["x", "y", "z"][..], 0.0),
+        ("rotation", &["x", "y", "z"][..], 0.0),
+        ("rotation", &["w"][..], 1.0),
+        ("scale", &["x", "y", "z"][..], 1.0),
+    ] {
+        for axis in axes {
+            data.extend_from_slice(&read(field, axis, default)?.to_le_bytes());
+        }
+    }
+
+    let parent = match &json["parent"] {
+        serde_json::Value::Null => 0,
+        value => value
+            .as_u64()
+            .and_then(|value| u32::try_from(value).ok())
+            .ok_or_else(|| "parent must be an entity id".to_string())?,
+    };
+    data.extend_from_slice(&(parent as u16).to_le_bytes());
+    data.extend_from_slice(&((parent >> 16) as u16).to_le_bytes());
+    Ok(data)
+}
+
+#[cfg(test)]
+mod inspector_json_tests {
+    use super::*;
+
+    #[test]
+    fn transform_json_roundtrip() {
+        let json = serde_json::json!({
+            "position": { "x": 1.0, "y": 2.0, "z": 3.0 },
+            "rotation": { "x": 0.0, "y": 0.5, "z": 0.0, "w": 0.5 },
+            "scale": { "x": 2.0, "y": 2.0, "z": 2.0 },
+            "parent": (3 << 16) | 512
+        });
+        let data = serialize_component_from_json(1, json.clone()).unwrap();
+        assert_eq!(data.len(), 44);
+        assert_eq!(deserialize_component_to_json(1, &data), Some(json));
+
+        // Only the fields a command sets are required
+        let data = serialize_transform(&serde_json::json!({ "position": { "y": 4.0 } })).unwrap();
+        let json = deserialize_component_to_json(1, &data).unwrap();
+        assert_eq!(json["position"]["y"], 4.0);
+        assert_eq!(json["rotation"]["w"], 1.0);
+        assert_eq!(json["scale"]["x"], 1.0);
+        assert_eq!(json["parent"], 0);
+    }
+
+    #[test]
+    fn proto_component_json_roundtrip() {
+        use crate::dcl::components::SceneComponentId;
+        use prost::Message;
+
+        let id = SceneComponentId::BILLBOARD.0;
+        #[allow(clippy::needless_update)]
+        let billboard = sdk::components::PbBillboard {
+            billboard_mode: Some(2),
+            ..Default::default()
+        };
+        let json = deserialize_component_to_json(id, &billboard.encode_to_vec()).unwrap();
+        let data = serialize_component_from_json(id, json).unwrap();
+        assert_eq!(
+            sdk::components::PbBillboard::decode(data.as_slice()).unwrap(),
+            billboard
+        );
+
+        // Missing fields take their defaults, mistyped ones are rejected
+        let data = serialize_component_from_json(id, serde_json::json!({})).unwrap();
+        assert_eq!(
+            sdk::components::PbBillboard::decode(data.as_slice()).unwrap(),
+            Default::default()
+        );
+        assert!(
+            serialize_component_from_json(id, serde_json::json!({ "billboard_mode": "x" }))
+                .is_err()
+        );
+        assert!(serialize_component_from_json(1, serde_json::json!([])).is_err());
+        assert!(serialize_component_from_json(u32::MAX, serde_json::json!({})).is_err());
+    }
+}
+
 // Exercises the build_quant.rs-generated accessors against the grids the Pulse
 // server bakes from the same .proto options — a drift here means wrong world
 // positions on the wire with no compile error anywhere else.
@@ -94,6 +94,27 @@ impl SceneEntityContainer {
         &self.entity_version[entity_number as usize]
     }
 
+    /// Every entity number that was ever used, as `(number, version, live)`.
+    pub fn used_entities(&self) -> impl Iterator<Item = (u16, u16, bool)> + '_ {
+        self.entity_version
+            .iter()
+            .enumerate()
+            .filter(|(_, (version, live))| *live || *version > 0)
+            .map(|(number, (version, live))| (number as u16, *version, *live))
+    }
+
+    /// Restores a generation slot (e.g. from a snapshot). Live entities are
+    /// reported as born on the next `take_dirty`.
+    pub fn restore(&mut self, number: u16, version: u16, live: bool) {
+        self.entity_version[number as usize] = (version, live);
+        let entity = SceneEntityId::new(number, version);
+        if live {
+            self.new_entities_created.insert(entity);
+        } else {
+            self.new_entities_created.remove(&entity);
+        }
+    }
+
     pub fn take_dirty(&mut self) -> DirtyEntities {
         DirtyEntities {
             born: std::mem::take(&mut self.new_entities_created),
@@ -30,6 +30,9 @@ pub trait GenericGrowOnlySetComponent {
         element_index: usize,
         writer: &mut DclWriter,
     ) -> Result<(), String>;
+
+    fn entities(&self) -> Vec<SceneEntityId>;
+    fn element_count(&self, entity: SceneEntityId) -> usize;
 }
 
 pub trait GenericGrowOnlySetComponentOperation<T: 'static + FromDclReader + ToDclWriter> {
@@ -105,6 +108,14 @@ impl<T: 'static + FromDclReader + ToDclWriter> GenericGrowOnlySetComponent for G
             Err("Entity not found".into())
         }
     }
+
+    fn entities(&self) -> Vec<SceneEntityId> {
+        self.values.keys().cloned().collect()
+    }
+
+    fn element_count(&self, entity: SceneEntityId) -> usize {
+        self.values.get(&entity).map_or(0, |queue| queue.len())
+    }
 }
 
 const APPEND_SIZE: usize = 100;
@@ -48,6 +48,8 @@ pub trait GenericLastWriteWinsComponent {
 
     fn remove(&mut self, entity: SceneEntityId);
     fn remove_without_dirty(&mut self, entity: SceneEntityId);
+
+    fn entities(&self) -> Vec<SceneEntityId>;
 }
 
 impl<T> LastWriteWins<T> {
@@ -154,6 +156,10 @@ impl<T: 'static + FromDclReader + ToDclWriter> GenericLastWriteWinsComponent for
         self.values.remove(&entity);
         self.dirty.insert(entity);
     }
+
+    fn entities(&self) -> Vec<SceneEntityId> {
+        self.values.keys().cloned().collect()
+    }
 }
 
 mod test {
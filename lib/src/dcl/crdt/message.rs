@@ -259,17 +259,87 @@ fn log_crdt_message(
     );
 }
 
-const CRDT_PUT_COMPONENT_HEADER_SIZE: usize = CRDT_HEADER_SIZE + 20;
-const CRDT_DELETE_COMPONENT_HEADER_SIZE: usize = CRDT_HEADER_SIZE + 16;
-const CRDT_DELETE_ENTITY_HEADER_SIZE: usize = CRDT_HEADER_SIZE + 4;
+/// PutComponent and AppendValue messages without the value: header, entity,
+/// component, timestamp and value length.
+const CRDT_COMPONENT_VALUE_MESSAGE_SIZE: usize = CRDT_HEADER_SIZE + 16;
+/// DeleteComponent messages: header, entity, component and timestamp.
+const CRDT_DELETE_COMPONENT_MESSAGE_SIZE: usize = CRDT_HEADER_SIZE + 12;
+/// DeleteEntity messages: header and entity.
+const CRDT_DELETE_ENTITY_MESSAGE_SIZE: usize = CRDT_HEADER_SIZE + 4;
 
-#[allow(dead_code)]
 pub fn delete_entity(entity_id: &SceneEntityId, writer: &mut DclWriter) {
-    writer.write_u32(CRDT_DELETE_ENTITY_HEADER_SIZE as u32);
+    writer.write_u32(CRDT_DELETE_ENTITY_MESSAGE_SIZE as u32);
     writer.write(&CrdtMessageType::DeleteEntity);
     writer.write(entity_id);
 }
 
+/// Writes a PutComponent message with an already serialized value.
+pub fn put_component(
+    entity_id: &SceneEntityId,
+    component_id: &SceneComponentId,
+    timestamp: &SceneCrdtTimestamp,
+    value: &[u8],
+    writer: &mut DclWriter,
+) {
+    write_component_value(
+        CrdtMessageType::PutComponent,
+        entity_id,
+        component_id,
+        timestamp,
+        value,
+        writer,
+    );
+}
+
+/// Writes an AppendValue message with an already serialized value.
+pub fn append_value(
+    entity_id: &SceneEntityId,
+    component_id: &SceneComponentId,
+    timestamp: &SceneCrdtTimestamp,
+    value: &[u8],
+    writer: &mut DclWriter,
+) {
+    write_component_value(
+        CrdtMessageType::AppendValue,
+        entity_id,
+        component_id,
+        timestamp,
+        value,
+        writer,
+    );
+}
+
+fn write_component_value(
+    crdt_type: CrdtMessageType,
+    entity_id: &SceneEntityId,
+    component_id: &SceneComponentId,
+    timestamp: &SceneCrdtTimestamp,
+    value: &[u8],
+    writer: &mut DclWriter,
+) {
+    writer.write_u32((CRDT_COMPONENT_VALUE_MESSAGE_SIZE + value.len()) as u32);
+    writer.write(&crdt_type);
+    writer.write(entity_id);
+    writer.write(component_id);
+    writer.write(timestamp);
+    writer.write_u32(value.len() as u32);
+    writer.write_raw(value);
+}
+
+/// Writes a DeleteComponent message.
+pub fn delete_component(
+    entity_id: &SceneEntityId,
+    component_id: &SceneComponentId,
+    timestamp: &SceneCrdtTimestamp,
+    writer: &mut DclWriter,
+) {
+    writer.write_u32(CRDT_DELETE_COMPONENT_MESSAGE_SIZE as u32);
+    writer.write(&CrdtMessageType::DeleteComponent);
+    writer.write(entity_id);
+    writer.write(component_id);
+    writer.write(timestamp);
+}
+
 pub fn put_or_delete_lww_component(
     scene_crdt_state: &SceneCrdtState,
     entity_id: &SceneEntityId,
@@ -291,23 +361,15 @@ pub fn put_or_delete_lww_component(
         let mut component_writer = DclWriter::new(&mut component_buf);
         component_definition.to_binary(*entity_id, &mut component_writer)?;
 
-        let content_length = component_buf.len();
-        let length = CRDT_DELETE_COMPONENT_HEADER_SIZE + component_buf.len();
-
-        writer.write_u32(length as u32);
-        writer.write(&CrdtMessageType::PutComponent);
-        writer.write(entity_id);
-        writer.write(component_id);
-        writer.write(&opaque_value.timestamp);
-
-        writer.write_u32(content_length as u32);
-        writer.write_raw(&component_buf)
+        put_component(
+            entity_id,
+            component_id,
+            &opaque_value.timestamp,
+            &component_buf,
+            writer,
+        );
     } else {
-        writer.write_u32(CRDT_PUT_COMPONENT_HEADER_SIZE as u32);
-        writer.write(&CrdtMessageType::DeleteComponent);
-        writer.write(entity_id);
-        writer.write(component_id);
-        writer.write(&opaque_value.timestamp);
+        delete_component(entity_id, component_id, &opaque_value.timestamp, writer);
     }
 
     Ok(())
@@ -332,17 +394,13 @@ pub fn append_gos_component(
         let mut component_writer = DclWriter::new(&mut component_buf);
         component_definition.to_binary(*entity_id, i, &mut component_writer)?;
 
-        let content_length = component_buf.len();
-        let length = CRDT_DELETE_COMPONENT_HEADER_SIZE + component_buf.len();
-
-        writer.write_u32(length as u32);
-        writer.write(&CrdtMessageType::AppendValue);
-        writer.write(entity_id);
-        writer.write(component_id);
-        writer.write(&SceneCrdtTimestamp(0));
-
-        writer.write_u32(content_length as u32);
-        writer.write_raw(&component_buf)
+        append_value(
+            entity_id,
+            component_id,
+            &SceneCrdtTimestamp(0),
+            &component_buf,
+            writer,
+        );
     }
 
     Ok(())
@@ -419,3 +477,96 @@ pub fn filter_known_crdt_messages(raw: &[u8], scene_crdt_state: &SceneCrdtState)
 
     out
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dcl::{
+        components::proto_components::sdk::components::{PbMeshRenderer, PbPointerEventsResult},
+        crdt::{
+            grow_only_set::GenericGrowOnlySetComponentOperation,
+            last_write_wins::LastWriteWinsComponentOperation, SceneCrdtStateProtoComponents,
+        },
+    };
+
+    #[test]
+    fn messages_have_the_sdk_lengths_and_parse_back() {
+        let put = SceneEntityId::new(512, 0);
+        let deleted = SceneEntityId::new(513, 0);
+        let killed = SceneEntityId::new(514, 0);
+
+        let mut source = SceneCrdtState::from_proto();
+        let mesh_renderer = SceneCrdtStateProtoComponents::get_mesh_renderer_mut(&mut source);
+        mesh_renderer.set(put, SceneCrdtTimestamp(5), Some(PbMeshRenderer::default()));
+        mesh_renderer.set(deleted, SceneCrdtTimestamp(4), None);
+        let pointer_results =
+            SceneCrdtStateProtoComponents::get_pointer_events_result_mut(&mut source);
+        for timestamp in 0..2 {
+            pointer_results.append(
+                put,
+                PbPointerEventsResult {
+                    timestamp,
+                    ..Default::default()
+                },
+            );
+        }
+
+        let mut buf = Vec::new();
+        let mut writer = DclWriter::new(&mut buf);
+        for entity in [put, deleted] {
+            put_or_delete_lww_component(
+                &source,
+                &entity,
+                &SceneComponentId::MESH_RENDERER,
+                &mut writer,
+            )
+            .unwrap();
+        }
+        append_gos_component(
+            &source,
+            &put,
+            &SceneComponentId::POINTER_EVENTS_RESULT,
+            &2,
+            &mut writer,
+        )
+        .unwrap();
+        delete_entity(&killed, &mut writer);
+
+        // lengths include the 8 byte header, values add their own length
+        let mut pos = 0;
+        let mut types = Vec::new();
+        while pos < buf.len() {
+            let length = u32::from_le_bytes(buf[pos..pos + 4].try_into().unwrap()) as usize;
+            let crdt_type = u32::from_le_bytes(buf[pos + 4..pos + 8].try_into().unwrap());
+            let expected = match crdt_type {
+                1 | 4 => {
+                    24 + u32::from_le_bytes(buf[pos + 20..pos + 24].try_into().unwrap()) as usize
+                }
+                2 => 20,
+                3 => 12,
+                _ => unreachable!(),
+            };
+            assert_eq!(length, expected, "message type {crdt_type}");
+            types.push(crdt_type);
+            pos += length;
+        }
+        assert_eq!(pos, buf.len());
+        assert_eq!(types, vec![1, 2, 4, 4, 3]);
+
+        let mut target = SceneCrdtState::from_proto();
+        target.entities.try_init(killed);
+        process_many_messages(&mut DclReader::new(&buf), &mut target);
+
+        let mesh_renderer = SceneCrdtStateProtoComponents::get_mesh_renderer(&target);
+        let entry = mesh_renderer.get(&put).unwrap();
+        assert_eq!(entry.timestamp, SceneCrdtTimestamp(5));
+        assert!(entry.value.is_some());
+        let entry = mesh_renderer.get(&deleted).unwrap();
+        assert_eq!(entry.timestamp, SceneCrdtTimestamp(4));
+        assert!(entry.value.is_none());
+
+        let pointer_results = SceneCrdtStateProtoComponents::get_pointer_events_result(&target);
+        assert_eq!(pointer_results.get(&put).unwrap().len(), 2);
+        assert!(target.entities.is_dead(&killed));
+    }
+}
@@ -2,6 +2,7 @@ pub mod entity;
 pub mod grow_only_set;
 pub mod last_write_wins;
 pub mod message;
+pub mod snapshot;
 
 pub use message::{process_many_messages_with_logging, CrdtLoggingContext};
 
@@ -0,0 +1,447 @@
+//! Binary snapshot of a whole `SceneCrdtState`: the entity generation table
+//! plus every LWW entry (with its timestamp, deletions included) and every GOS
+//! queue. Component values use the same binary encoding as the CRDT protocol.
+//!
+//! Layout (little endian):
+//! ```text
+//! "DCLS" u32:version
+//! u32:count { u16:number u16:version u8:live }
+//! u32:count { u32:component u32:count { u32:entity u32:timestamp u8:has_value [u32:len bytes] } }
+//! u32:count { u32:component u32:count { u32:entity u32:count { u32:len bytes } } }
+//! ```
+//! GOS elements are stored oldest first.
+
+use std::path::Path;
+
+use super::{
+    message::{append_value, delete_component, delete_entity, put_or_delete_lww_component},
+    DirtyCrdtState, SceneCrdtState,
+};
+use crate::dcl::{
+    components::{SceneComponentId, SceneCrdtTimestamp, SceneEntityId},
+    serialization::{
+        reader::{DclReader, DclReaderError},
+        writer::DclWriter,
+    },
+};
+
+const SNAPSHOT_MAGIC: &[u8; 4] = b"DCLS";
+const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Debug)]
+pub enum CrdtSnapshotError {
+    InvalidHeader,
+    UnsupportedVersion(u32),
+    Truncated,
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for CrdtSnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidHeader => write!(f, "not a CRDT snapshot"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported CRDT snapshot version {version}")
+            }
+            Self::Truncated => write!(f, "truncated CRDT snapshot"),
+            Self::Io(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl From<DclReaderError> for CrdtSnapshotError {
+    fn from(_: DclReaderError) -> Self {
+        Self::Truncated
+    }
+}
+
+impl From<std::io::Error> for CrdtSnapshotError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+/// `DclReader::take_slice` panics past the end, so lengths are checked first.
+fn read_bytes<'a>(reader: &'a mut DclReader, len: usize) -> Result<&'a [u8], CrdtSnapshotError> {
+    if reader.len() < len {
+        return Err(CrdtSnapshotError::Truncated);
+    }
+    Ok(reader.take_slice(len))
+}
+
+fn sorted_entities(mut entities: Vec<SceneEntityId>) -> Vec<SceneEntityId> {
+    entities.sort_by_key(|entity| (entity.number, entity.version));
+    entities
+}
+
+/// `<folder>/<scene entity id>.crdtsnap`, if that file exists.
+pub fn existing_snapshot_path(folder: &str, scene_entity_id: &str) -> Option<String> {
+    let path = Path::new(folder).join(format!("{scene_entity_id}.crdtsnap"));
+    path.is_file().then(|| path.to_string_lossy().into_owned())
+}
+
+impl SceneCrdtState {
+    fn sorted_component_ids(&self) -> Vec<SceneComponentId> {
+        let mut component_ids: Vec<SceneComponentId> = self.components.keys().cloned().collect();
+        component_ids.sort_by_key(|component_id| component_id.0);
+        component_ids
+    }
+
+    /// Serializes the full state. Output is deterministic, so two snapshots of
+    /// the same state can be compared byte by byte.
+    pub fn write_snapshot(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        let mut writer = DclWriter::new(&mut buf);
+        writer.write_raw(SNAPSHOT_MAGIC);
+        writer.write_u32(SNAPSHOT_VERSION);
+
+        let used_entities: Vec<_> = self.entities.used_entities().collect();
+        writer.write_u32(used_entities.len() as u32);
+        for (number, version, live) in used_entities {
+            writer.write_u16(number);
+            writer.write_u16(version);
+            writer.write_u8(live as u8);
+        }
+
+        let component_ids = self.sorted_component_ids();
+        let lww_components: Vec<_> = component_ids
+            .iter()
+            .filter_map(|component_id| {
+                self.get_lww_component_definition(*component_id)
+                    .map(|definition| (*component_id, definition))
+            })
+            .collect();
+        writer.write_u32(lww_components.len() as u32);
+        for (component_id, definition) in lww_components {
+            let entities = sorted_entities(definition.entities());
+            writer.write(&component_id);
+            writer.write_u32(entities.len() as u32);
+            for entity in entities {
+                let Some(entry) = definition.get_opaque(entity) else {
+                    continue;
+                };
+                writer.write(&entity);
+                writer.write(&entry.timestamp);
+                writer.write_u8(entry.value.is_some() as u8);
+                if entry.value.is_some() {
+                    let mut value_buf = Vec::new();
+                    let _ = definition.to_binary(entity, &mut DclWriter::new(&mut value_buf));
+                    writer.write_u32(value_buf.len() as u32);
+                    writer.write_raw(&value_buf);
+                }
+            }
+        }
+
+        let gos_components: Vec<_> = component_ids
+            .iter()
+            .filter_map(|component_id| {
+                self.get_gos_component_definition(*component_id)
+                    .map(|definition| (*component_id, definition))
+            })
+            .collect();
+        writer.write_u32(gos_components.len() as u32);
+        for (component_id, definition) in gos_components {
+            let entities = sorted_entities(definition.entities());
+            writer.write(&component_id);
+            writer.write_u32(entities.len() as u32);
+            for entity in entities {
+                let count = definition.element_count(entity);
+                writer.write(&entity);
+                writer.write_u32(count as u32);
+                // `to_binary` indexes from the newest element
+                for reverse_index in (0..count).rev() {
+                    let mut value_buf = Vec::new();
+                    let _ = definition.to_binary(
+                        entity,
+                        reverse_index,
+                        &mut DclWriter::new(&mut value_buf),
+                    );
+                    writer.write_u32(value_buf.len() as u32);
+                    writer.write_raw(&value_buf);
+                }
+            }
+        }
+
+        buf
+    }
+
+    /// Loads a snapshot into this state, which should be fresh (`from_proto`).
+    /// Everything restored is marked dirty, so the next `take_dirty` returns
+    /// the whole world as born entities and dirty components. Components the
+    /// snapshot has but this build doesn't know are skipped.
+    pub fn restore_snapshot(&mut self, data: &[u8]) -> Result<(), CrdtSnapshotError> {
+        let mut reader = DclReader::new(data);
+        if read_bytes(&mut reader, SNAPSHOT_MAGIC.len())? != SNAPSHOT_MAGIC {
+            return Err(CrdtSnapshotError::InvalidHeader);
+        }
+        let version = reader.read_u32()?;
+        if version != SNAPSHOT_VERSION {
+            return Err(CrdtSnapshotError::UnsupportedVersion(version));
+        }
+
+        let entity_count = reader.read_u32()?;
+        for _ in 0..entity_count {
+            let number = reader.read_u16()?;
+            let version = reader.read_u16()?;
+            let live = reader.read_u8()? != 0;
+            self.entities.restore(number, version, live);
+        }
+
+        let lww_count = reader.read_u32()?;
+        for _ in 0..lww_count {
+            let component_id: SceneComponentId = reader.read()?;
+            let entry_count = reader.read_u32()?;
+            for _ in 0..entry_count {
+                let entity: SceneEntityId = reader.read()?;
+                let timestamp: SceneCrdtTimestamp = reader.read()?;
+                let has_value = reader.read_u8()? != 0;
+                let value = if has_value {
+                    let len = reader.read_u32()? as usize;
+                    Some(read_bytes(&mut reader, len)?)
+                } else {
+                    None
+                };
+
+                let Some(definition) = self.get_lww_component_definition_mut(component_id) else {
+                    continue;
+                };
+                match value {
+                    Some(value) => {
+                        definition.set_from_binary(entity, timestamp, &mut DclReader::new(value));
+                    }
+                    None => {
+                        definition.set_none(entity, timestamp);
+                    }
+                }
+            }
+        }
+
+        let gos_count = reader.read_u32()?;
+        for _ in 0..gos_count {
+            let component_id: SceneComponentId = reader.read()?;
+            let entity_count = reader.read_u32()?;
+            for _ in 0..entity_count {
+                let entity: SceneEntityId = reader.read()?;
+                let element_count = reader.read_u32()?;
+                for _ in 0..element_count {
+                    let len = reader.read_u32()? as usize;
+                    let value = read_bytes(&mut reader, len)?;
+                    if let Some(definition) = self.get_gos_component_definition_mut(component_id) {
+                        definition.append_from_binary(
+                            entity,
+                            SceneCrdtTimestamp(0),
+                            &mut DclReader::new(value),
+                        );
+                    }
+                }
+            }
+        }
+
+        if !reader.is_empty() {
+            tracing::warn!(
+                "CRDT snapshot: {} trailing bytes ignored",
+                reader.as_slice().len()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Builds a fresh state from a snapshot, returning it together with the
+    /// dirty state the renderer needs to build the restored world.
+    pub fn from_snapshot(data: &[u8]) -> Result<(Self, DirtyCrdtState), CrdtSnapshotError> {
+        let mut crdt_state = Self::from_proto();
+        crdt_state.restore_snapshot(data)?;
+        let dirty = crdt_state.take_dirty();
+        Ok((crdt_state, dirty))
+    }
+
+    pub fn save_snapshot_to_file(&self, path: &Path) -> Result<(), CrdtSnapshotError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, self.write_snapshot())?;
+        Ok(())
+    }
+
+    /// CRDT messages equivalent to the current state, used to hand a restored
+    /// state to the scene's JS instead of `main.crdt`: deleted entities first,
+    /// so the SDK doesn't reuse their generations, then every LWW entry and
+    /// GOS element.
+    pub fn to_crdt_messages(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        let mut writer = DclWriter::new(&mut buf);
+
+        for (number, version, live) in self.entities.used_entities() {
+            if !live && version > 0 {
+                delete_entity(&SceneEntityId::new(number, version - 1), &mut writer);
+            }
+        }
+
+        for component_id in self.sorted_component_ids() {
+            if let Some(definition) = self.get_lww_component_definition(component_id) {
+                for entity in sorted_entities(definition.entities()) {
+                    let Some(entry) = definition.get_opaque(entity) else {
+                        continue;
+                    };
+                    if entry.value.is_some() {
+                        let _ =
+                            put_or_delete_lww_component(self, &entity, &component_id, &mut writer);
+                    } else {
+                        // tombstones keep their timestamp so the SDK doesn't
+                        // write below it
+                        delete_component(&entity, &component_id, &entry.timestamp, &mut writer);
+                    }
+                }
+            } else if let Some(definition) = self.get_gos_component_definition(component_id) {
+                for entity in sorted_entities(definition.entities()) {
+                    for reverse_index in (0..definition.element_count(entity)).rev() {
+                        let mut value_buf = Vec::new();
+                        if definition
+                            .to_binary(entity, reverse_index, &mut DclWriter::new(&mut value_buf))
+                            .is_err()
+                        {
+                            continue;
+                        }
+                        append_value(
+                            &entity,
+                            &component_id,
+                            &SceneCrdtTimestamp(0),
+                            &value_buf,
+                            &mut writer,
+                        );
+                    }
+                }
+            }
+        }
+
+        buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dcl::{
+        components::proto_components::sdk::components::{PbMeshRenderer, PbPointerEventsResult},
+        crdt::{
+            grow_only_set::GenericGrowOnlySetComponentOperation,
+            last_write_wins::LastWriteWinsComponentOperation, message::process_many_messages,
+            SceneCrdtStateProtoComponents,
+        },
+    };
+
+    fn sample_state() -> SceneCrdtState {
+        let mut crdt_state = SceneCrdtState::from_proto();
+        let alive = SceneEntityId::new(512, 1);
+        let deleted = SceneEntityId::new(513, 0);
+        crdt_state.entities.try_init(alive);
+        crdt_state.entities.try_init(deleted);
+        crdt_state.entities.kill(deleted);
+        crdt_state.entities.try_init(SceneEntityId::new(514, 0));
+
+        let mesh_renderer = SceneCrdtStateProtoComponents::get_mesh_renderer_mut(&mut crdt_state);
+        mesh_renderer.set(
+            alive,
+            SceneCrdtTimestamp(7),
+            Some(PbMeshRenderer::default()),
+        );
+        mesh_renderer.set(SceneEntityId::new(514, 0), SceneCrdtTimestamp(3), None);
+
+        let pointer_results =
+            SceneCrdtStateProtoComponents::get_pointer_events_result_mut(&mut crdt_state);
+        for timestamp in 0..3 {
+            pointer_results.append(
+                alive,
+                PbPointerEventsResult {
+                    timestamp,
+                    ..Default::default()
+                },
+            );
+        }
+
+        crdt_state.take_dirty();
+        crdt_state
+    }
+
+    #[test]
+    fn snapshot_round_trip() {
+        let crdt_state = sample_state();
+        let data = crdt_state.write_snapshot();
+
+        let (restored, dirty) = SceneCrdtState::from_snapshot(&data).unwrap();
+        assert_eq!(restored.write_snapshot(), data);
+
+        // generation table
+        assert!(restored.entities.is_dead(&SceneEntityId::new(513, 0)));
+        assert_eq!(
+            restored.entities.get_entity_stat(512),
+            crdt_state.entities.get_entity_stat(512)
+        );
+
+        // LWW keeps timestamps and deletions
+        let mesh_renderer = SceneCrdtStateProtoComponents::get_mesh_renderer(&restored);
+        let entry = mesh_renderer.get(&SceneEntityId::new(512, 1)).unwrap();
+        assert_eq!(entry.timestamp, SceneCrdtTimestamp(7));
+        assert!(entry.value.is_some());
+        assert!(mesh_renderer
+            .get(&SceneEntityId::new(514, 0))
+            .unwrap()
+            .value
+            .is_none());
+
+        // GOS order is preserved
+        let pointer_results = SceneCrdtStateProtoComponents::get_pointer_events_result(&restored);
+        let timestamps: Vec<u32> = pointer_results
+            .get(&SceneEntityId::new(512, 1))
+            .unwrap()
+            .iter()
+            .map(|result| result.timestamp)
+            .collect();
+        assert_eq!(timestamps, vec![0, 1, 2]);
+
+        // the dirty state describes the whole restored world
+        assert!(dirty.entities.born.contains(&SceneEntityId::new(512, 1)));
+        assert_eq!(
+            dirty
+                .lww
+                .get(&SceneComponentId::MESH_RENDERER)
+                .map(Vec::len),
+            Some(2)
+        );
+        assert_eq!(
+            dirty
+                .gos
+                .get(&SceneComponentId::POINTER_EVENTS_RESULT)
+                .and_then(|entities| entities.get(&SceneEntityId::new(512, 1))),
+            Some(&3)
+        );
+    }
+
+    #[test]
+    fn crdt_messages_rebuild_the_state() {
+        let crdt_state = sample_state();
+        let messages = crdt_state.to_crdt_messages();
+
+        let mut replayed = SceneCrdtState::from_proto();
+        process_many_messages(&mut DclReader::new(&messages), &mut replayed);
+        replayed.take_dirty();
+
+        assert_eq!(replayed.write_snapshot(), crdt_state.write_snapshot());
+    }
+
+    #[test]
+    fn rejects_invalid_snapshots() {
+        let mut crdt_state = SceneCrdtState::from_proto();
+        assert!(matches!(
+            crdt_state.restore_snapshot(b"nope"),
+            Err(CrdtSnapshotError::InvalidHeader)
+        ));
+
+        let data = sample_state().write_snapshot();
+        assert!(matches!(
+            SceneCrdtState::from_snapshot(&data[..data.len() - 3]),
+            Err(CrdtSnapshotError::Truncated)
+        ));
+    }
+}
@@ -7,6 +7,7 @@ use serde::Serialize;
 use tokio::sync::Semaphore;
 
 use crate::{
+    dcl::scene_permissions::ScenePermission,
     realm::scene_definition::SceneEntityDefinition,
     tools::network_inspector::{
         NetworkInspectEvent, NetworkInspectRequestPayload, NetworkInspectResponsePayload,
@@ -84,6 +85,12 @@ async fn op_fetch_custom(
     #[string] _redirect: String, // TODO: unimplemented
     timeout: u32,
 ) -> Result<FetchResponse, AnyError> {
+    super::check_scene_permission(
+        &mut op_state.borrow_mut(),
+        ScenePermission::UseFetch,
+        || format!("fetch {url}"),
+    )?;
+
     let maybe_network_inspector_sender = op_state
         .borrow()
         .try_borrow::<NetworkInspectorSender>()
@@ -0,0 +1,22 @@
+// Scene-scoped voice channel: while the player is inside the scene, voice is only
+// sent to and received from the other players in the scene room.
+module.exports.setSceneChannel = async function (body) {
+    Deno.core.ops.op_voice_set_scene_channel(!!body.enabled);
+    return {}
+}
+
+// Moderation: whether the local player can talk in the scene channel
+module.exports.setMicrophoneAllowed = async function (body) {
+    Deno.core.ops.op_voice_set_microphone_allowed(!!body.allowed);
+    return {}
+}
+
+// Moderation: silence a player in the scene channel (only for the local player)
+module.exports.setPlayerMuted = async function (body) {
+    Deno.core.ops.op_voice_set_player_muted(body.userId, !!body.muted);
+    return {}
+}
+
+module.exports.getState = async function (body) {
+    return await Deno.core.ops.op_voice_get_state();
+}
@@ -12,6 +12,7 @@ mod restricted_actions;
 mod runtime;
 mod scene_inspector_ops;
 mod testing;
+mod voice_chat;
 mod websocket;
 
 use crate::comms::truncate_utf8_safe;
@@ -23,8 +24,13 @@ use crate::dcl::scene_apis::{LocalCall, RpcCall};
 
 use super::crdt::{
     message::{process_many_messages, process_many_messages_with_logging},
+    snapshot::CrdtSnapshotError,
     CrdtLoggingContext, SceneCrdtState,
 };
+use super::scene_permissions::{ScenePermission, ScenePermissions};
+use super::scene_quotas::{
+    count_poll_time, watch_tick_time, SceneQuotaBreach, SceneQuotaGuard, SceneQuotas,
+};
 use super::serialization::reader::DclReader;
 use super::{RendererResponse, SceneId, SceneResponse, SpawnDclSceneData};
 use scene_inspector_ops::SceneDebugFlag;
@@ -40,7 +46,9 @@ use deno_core::{
     error::{generic_error, AnyError},
     include_js_files, op2, Extension, OpState, RuntimeOptions,
 };
-use deno_core::{JsRuntime, OpDecl, PollEventLoopOptions};
+use deno_core::{
+    JsRuntime, OpDecl, OpMetricsEvent, OpMetricsFactoryFn, OpMetricsFn, PollEventLoopOptions,
+};
 use once_cell::sync::Lazy;
 use serde::Serialize;
 use tokio::sync::mpsc::Receiver;
@@ -97,7 +105,10 @@ pub fn init_runtime() {
     let _ = deno_core::v8::Platform::new(1, false);
 }
 
-pub fn create_runtime(inspect: bool) -> (deno_core::JsRuntime, Option<InspectorServer>) {
+pub fn create_runtime(
+    inspect: bool,
+    quota_guard: Option<Arc<SceneQuotaGuard>>,
+) -> (deno_core::JsRuntime, Option<InspectorServer>) {
     let mut ops = vec![op_require(), op_log(), op_error()];
 
     let op_sets: Vec<Vec<deno_core::OpDecl>> = vec![
@@ -114,6 +125,7 @@ pub fn create_runtime(inspect: bool) -> (deno_core::JsRuntime, Option<InspectorS
         ethereum_controller::ops(),
         comms::ops(),
         scene_inspector_ops::ops(),
+        voice_chat::ops(),
     ];
 
     // add plugin registrations
@@ -148,14 +160,52 @@ pub fn create_runtime(inspect: bool) -> (deno_core::JsRuntime, Option<InspectorS
         ..Default::default()
     };
 
+    let quotas = quota_guard
+        .as_ref()
+        .map(|guard| *guard.quotas())
+        .unwrap_or(SceneQuotas::UNLIMITED);
+
+    let create_params = (quotas.max_heap_mb > 0).then(|| {
+        v8::CreateParams::default().heap_limits(0, quotas.max_heap_mb as usize * 1024 * 1024)
+    });
+
+    let op_metrics_factory_fn = quota_guard
+        .clone()
+        .filter(|_| quotas.max_op_calls_per_tick > 0)
+        .map(|guard| {
+            let metrics_fn: OpMetricsFn = Rc::new(move |_, event, _| {
+                if matches!(event, OpMetricsEvent::Dispatched) {
+                    guard.on_op_call();
+                }
+            });
+            Box::new(move |_, _, _| Some(metrics_fn.clone())) as OpMetricsFactoryFn
+        });
+
     // create runtime
     #[allow(unused_mut)]
     let mut runtime = deno_core::JsRuntime::new(RuntimeOptions {
         extensions: vec![ext],
         inspector: inspect,
+        create_params,
+        op_metrics_factory_fn,
         ..Default::default()
     });
 
+    if let Some(guard) = quota_guard {
+        let vm_handle = runtime.v8_isolate().thread_safe_handle();
+        guard.set_terminate(move || {
+            vm_handle.terminate_execution();
+        });
+
+        if quotas.max_heap_mb > 0 {
+            // the limit is raised so the isolate can unwind instead of aborting the process
+            runtime.add_near_heap_limit_callback(move |current_limit, _initial_limit| {
+                guard.on_near_heap_limit();
+                current_limit * 2
+            });
+        }
+    }
+
     #[cfg(feature = "enable_inspector")]
     if inspect {
         tracing::debug!(
@@ -189,6 +239,68 @@ fn send_remove_godot_scene(state: &Rc<RefCell<OpState>>, scene_id: SceneId) {
     let _ = sender.send(SceneResponse::RemoveGodotScene(scene_id, logs.0));
 }
 
+/// Uncaught script errors also go to the scene logs, so they reach the
+/// renderer side (console, headless scene runner) and not only the tracing log.
+fn push_uncaught_error_log(state: &Rc<RefCell<OpState>>, message: String) {
+    if !is_scene_log_enabled() {
+        return;
+    }
+    let time = state.borrow().borrow::<SceneElapsedTime>().0;
+    state
+        .borrow_mut()
+        .borrow_mut::<SceneLogs>()
+        .0
+        .push(SceneLogMessage {
+            timestamp: time as f64,
+            level: SceneLogLevel::SystemError,
+            message,
+        });
+}
+
+/// Errors when the scene didn't declare `permission`, the denial is also
+/// reported to the scene console and the Scene Inspector.
+fn check_scene_permission(
+    state: &mut OpState,
+    permission: ScenePermission,
+    action: impl FnOnce() -> String,
+) -> Result<(), AnyError> {
+    let Err(denied) = state.borrow::<ScenePermissions>().check(permission, action) else {
+        return Ok(());
+    };
+
+    let scene_id = *state.borrow::<SceneId>();
+    tracing::debug!("scene {scene_id:?}: {denied}");
+    crate::tools::scene_inspector::log_permission_denied(
+        scene_id.0,
+        denied.permission.as_str(),
+        &denied.action,
+    );
+    if is_scene_log_enabled() {
+        let time = state.borrow::<SceneElapsedTime>().0;
+        state.borrow_mut::<SceneLogs>().0.push(SceneLogMessage {
+            timestamp: time as f64,
+            level: SceneLogLevel::SystemError,
+            message: denied.to_string(),
+        });
+    }
+    Err(generic_error(denied.to_string()))
+}
+
+/// The runtime was already terminated by the guard; the renderer gets the
+/// reason and the scene goes down through the `SceneDying` path.
+fn report_quota_breach(
+    state: &Rc<RefCell<OpState>>,
+    log_info: &SceneLogInfo,
+    breach: SceneQuotaBreach,
+) {
+    // scene-caused, it's in the scene console already
+    tracing::info!("{} killed, {}", log_info.prefix(), breach);
+    let mut op_state = state.borrow_mut();
+    op_state.put(SceneDying(true));
+    let sender = op_state.borrow::<std::sync::mpsc::SyncSender<SceneResponse>>();
+    let _ = sender.send(SceneResponse::QuotaExceeded(log_info.scene_id, breach));
+}
+
 // main scene processing thread - constructs an isolate and runs the scene
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn scene_thread(
@@ -221,6 +333,7 @@ pub(crate) fn scene_thread(
     let log_info = SceneLogInfo::new(scene_id, &scene_entity_definition);
     let local_main_js_file_path = spawn_dcl_scene_data.local_main_js_file_path;
     let local_main_crdt_file_path = spawn_dcl_scene_data.local_main_crdt_file_path;
+    let local_crdt_snapshot_file_path = spawn_dcl_scene_data.local_crdt_snapshot_file_path;
     let content_mapping = spawn_dcl_scene_data.content_mapping;
     let thread_sender_to_main = spawn_dcl_scene_data.thread_sender_to_main;
     let testing_mode = spawn_dcl_scene_data.testing_mode;
@@ -229,9 +342,58 @@ pub(crate) fn scene_thread(
     let ephemeral_wallet = spawn_dcl_scene_data.ephemeral_wallet;
     let realm_info = spawn_dcl_scene_data.realm_info;
     let maybe_network_inspector_sender = spawn_dcl_scene_data.network_inspector_sender;
+    let permissions = spawn_dcl_scene_data.permissions;
+
+    // a CRDT snapshot replaces main.crdt: the renderer gets the restored world
+    // and the JS gets it re-encoded as CRDT messages
+    if !local_crdt_snapshot_file_path.is_empty() {
+        let restored = std::fs::read(&local_crdt_snapshot_file_path)
+            .map_err(CrdtSnapshotError::from)
+            .and_then(|data| {
+                let mut scene_crdt_state = scene_crdt.lock().unwrap();
+                scene_crdt_state.restore_snapshot(&data)?;
+                Ok((
+                    scene_crdt_state.take_dirty(),
+                    scene_crdt_state.to_crdt_messages(),
+                ))
+            });
+
+        match restored {
+            Ok((dirty, messages)) => {
+                tracing::info!(
+                    "{} restored CRDT snapshot {}: entities_born={}",
+                    log_info.prefix(),
+                    local_crdt_snapshot_file_path,
+                    dirty.entities.born.len()
+                );
+                scene_main_crdt = Some(messages);
+
+                thread_sender_to_main
+                    .send(SceneResponse::Ok {
+                        scene_id,
+                        dirty_crdt_state: Box::new(dirty),
+                        logs: Vec::new(),
+                        delta: 0.0,
+                        rpc_calls: Vec::new(),
+                        deno_memory_stats: None,
+                    })
+                    .expect("error sending scene response!!");
+            }
+            Err(err) => {
+                tracing::warn!(
+                    "{} CRDT snapshot {} not restored, using main.crdt: {}",
+                    log_info.prefix(),
+                    local_crdt_snapshot_file_path,
+                    err
+                );
+                // a partially restored state is discarded
+                *scene_crdt.lock().unwrap() = SceneCrdtState::from_proto();
+            }
+        }
+    }
 
     // on main.crdt detected
-    if !local_main_crdt_file_path.is_empty() {
+    if scene_main_crdt.is_none() && !local_main_crdt_file_path.is_empty() {
         let file = godot::classes::FileAccess::open(
             &godot::prelude::GString::from(local_main_crdt_file_path.as_str()),
             godot::classes::file_access::ModeFlags::READ,
@@ -338,7 +500,15 @@ pub(crate) fn scene_thread(
         file.unwrap().get_as_text()
     );
 
-    let (mut runtime, inspector) = create_runtime(spawn_dcl_scene_data.inspect);
+    // a paused debugger would look like a stuck tick
+    let mut quotas = spawn_dcl_scene_data.quotas;
+    if spawn_dcl_scene_data.inspect {
+        quotas.max_tick_time_ms = 0;
+    }
+    let quota_guard = SceneQuotaGuard::new(quotas);
+    let (mut runtime, inspector) =
+        create_runtime(spawn_dcl_scene_data.inspect, Some(quota_guard.clone()));
+    watch_tick_time(&quota_guard);
 
     // store handle
     let vm_handle = runtime.v8_isolate().thread_safe_handle();
@@ -360,9 +530,11 @@ pub(crate) fn scene_thread(
 
     state.borrow_mut().put(scene_id);
     state.borrow_mut().put(scene_crdt);
+    state.borrow_mut().put(quota_guard.clone());
 
     state.borrow_mut().put(ephemeral_wallet);
     state.borrow_mut().put(scene_entity_definition);
+    state.borrow_mut().put(permissions);
 
     state.borrow_mut().put(realm_info);
 
@@ -428,6 +600,7 @@ pub(crate) fn scene_thread(
     let script = match script {
         Err(e) => {
             tracing::error!("{} script load error: {}", log_info.prefix(), e);
+            push_uncaught_error_log(&state, format!("script load error: {e}"));
             send_remove_godot_scene(&state, scene_id);
             return;
         }
@@ -451,10 +624,17 @@ pub(crate) fn scene_thread(
         );
     }
 
+    quota_guard.begin_tick();
     let result =
         rt.block_on(async { run_script(&mut runtime, &script, "onStart", |_| Vec::new()).await });
+    quota_guard.pause();
     if let Err(e) = result {
-        tracing::error!("{} script onStart error: {}", log_info.prefix(), e);
+        if let Some(breach) = quota_guard.take_breach() {
+            report_quota_breach(&state, &log_info, breach);
+        } else {
+            tracing::error!("{} script onStart error: {}", log_info.prefix(), e);
+        }
+        push_uncaught_error_log(&state, format!("script onStart error: {e}"));
 
         if should_debug {
             crate::tools::scene_inspector::log_lifecycle_event(
@@ -527,17 +707,25 @@ pub(crate) fn scene_thread(
         }
 
         // run the onUpdate function
+        quota_guard.begin_tick();
         let result = rt.block_on(async {
             run_script(&mut runtime, &script, "onUpdate", |scope| {
                 vec![v8::Number::new(scope, dt.as_secs_f64()).into()]
             })
             .await
         });
+        quota_guard.pause();
+
+        if let Some(breach) = quota_guard.take_breach() {
+            report_quota_breach(&state, &log_info, breach);
+            break;
+        }
 
         if let Err(e) = result {
             reported_error_filter += 1;
             if reported_error_filter <= 10 {
                 let err_str = format!("{:?}", e);
+                push_uncaught_error_log(&state, format!("script error onUpdate: {err_str}"));
 
                 if should_debug {
                     crate::tools::scene_inspector::log_lifecycle_event(
@@ -679,10 +867,16 @@ async fn run_script(
     };
 
     let f = runtime.resolve(promise);
-    runtime
-        .with_event_loop_promise(f, PollEventLoopOptions::default())
-        .await
-        .map(|_| ())
+    let quota_guard = op_state
+        .borrow()
+        .try_borrow::<Arc<SceneQuotaGuard>>()
+        .cloned();
+    count_poll_time(
+        quota_guard.as_deref(),
+        runtime.with_event_loop_promise(f, PollEventLoopOptions::default()),
+    )
+    .await
+    .map(|_| ())
 }
 
 // synchronously returns a string containing JS code from the file system
@@ -717,6 +911,7 @@ fn op_require(
         "~system/Testing" => Ok(include_str!("js_modules/Testing.js").to_owned()),
         "~system/UserActionModule" => Ok(include_str!("js_modules/UserActionModule.js").to_owned()),
         "~system/UserIdentity" => Ok(include_str!("js_modules/UserIdentity.js").to_owned()),
+        "~system/VoiceChat" => Ok(include_str!("js_modules/VoiceChat.js").to_owned()),
         "~system/CommsApi" => Ok(include_str!("js_modules/CommsApi.js").to_owned()),
         "~system/AdaptationLayerHelper" => {
             Ok(include_str!("js_modules/AdaptationLayerHelper.js").to_owned())
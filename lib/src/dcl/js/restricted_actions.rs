@@ -3,7 +3,9 @@ use std::{cell::RefCell, rc::Rc};
 use deno_core::{anyhow::anyhow, error::AnyError, op2, OpDecl, OpState};
 use http::Uri;
 
-use crate::dcl::scene_apis::RpcCall;
+use crate::dcl::{scene_apis::RpcCall, scene_permissions::ScenePermission};
+
+use super::check_scene_permission;
 
 pub fn ops() -> Vec<OpDecl> {
     vec![
@@ -69,6 +71,11 @@ async fn op_open_external_url(
         Ok(_) => return Err(anyhow!("URL does not use HTTPS")),
         Err(_) => return Err(anyhow!("Invalid URL")),
     };
+    check_scene_permission(
+        &mut op_state.borrow_mut(),
+        ScenePermission::OpenExternalLink,
+        || format!("opening {url}"),
+    )?;
 
     let (sx, rx) = tokio::sync::oneshot::channel::<Result<(), String>>();
 
@@ -98,7 +105,13 @@ fn op_move_player_to(
     avatar_x: f32,
     avatar_y: f32,
     avatar_z: f32,
-) {
+) -> Result<(), AnyError> {
+    check_scene_permission(
+        &mut op_state.borrow_mut(),
+        ScenePermission::MovePlayer,
+        || "movePlayerTo".to_string(),
+    )?;
+
     let position_target = [position_x, position_y, position_z];
     let camera_target = if camera_x.is_nan() || camera_y.is_nan() || camera_z.is_nan() {
         None
@@ -119,6 +132,7 @@ fn op_move_player_to(
             camera_target,
             avatar_target,
         });
+    Ok(())
 }
 
 #[op2(async)]
@@ -143,11 +157,18 @@ async fn op_teleport_to(
 }
 
 #[op2(fast)]
-fn op_trigger_emote(op_state: Rc<RefCell<OpState>>, #[string] emote_id: String) {
+fn op_trigger_emote(
+    op_state: Rc<RefCell<OpState>>,
+    #[string] emote_id: String,
+) -> Result<(), AnyError> {
+    let mut op_state = op_state.borrow_mut();
+    check_scene_permission(&mut op_state, ScenePermission::TriggerAvatarEmote, || {
+        format!("triggerEmote {emote_id}")
+    })?;
     op_state
-        .borrow_mut()
         .borrow_mut::<Vec<RpcCall>>()
         .push(RpcCall::TriggerEmote { emote_id });
+    Ok(())
 }
 
 #[op2(fast)]
@@ -155,9 +176,13 @@ fn op_trigger_scene_emote(
     op_state: Rc<RefCell<OpState>>,
     #[string] emote_src: String,
     looping: bool,
-) {
+) -> Result<(), AnyError> {
+    let mut op_state = op_state.borrow_mut();
+    check_scene_permission(&mut op_state, ScenePermission::TriggerAvatarEmote, || {
+        format!("triggerSceneEmote {emote_src}")
+    })?;
     op_state
-        .borrow_mut()
         .borrow_mut::<Vec<RpcCall>>()
         .push(RpcCall::TriggerSceneEmote { emote_src, looping });
+    Ok(())
 }
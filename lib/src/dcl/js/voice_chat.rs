@@ -0,0 +1,66 @@
+use std::{cell::RefCell, rc::Rc};
+
+use deno_core::{anyhow::anyhow, error::AnyError, op2, OpDecl, OpState};
+
+use crate::{
+    auth::wallet::AsH160,
+    dcl::scene_apis::{RpcCall, SceneVoiceState},
+};
+
+// list of op declarations
+pub fn ops() -> Vec<OpDecl> {
+    vec![
+        op_voice_set_scene_channel(),
+        op_voice_set_microphone_allowed(),
+        op_voice_set_player_muted(),
+        op_voice_get_state(),
+    ]
+}
+
+/// Restricts the voice chat to the players inside the scene while the player is in it
+#[op2(fast)]
+fn op_voice_set_scene_channel(state: &mut OpState, enabled: bool) {
+    state
+        .borrow_mut::<Vec<RpcCall>>()
+        .push(RpcCall::VoiceSetSceneChannel { enabled });
+}
+
+/// Lets the local player talk in the scene channel (e.g. only the speakers on a stage)
+#[op2(fast)]
+fn op_voice_set_microphone_allowed(state: &mut OpState, allowed: bool) {
+    state
+        .borrow_mut::<Vec<RpcCall>>()
+        .push(RpcCall::VoiceSetMicrophoneAllowed { allowed });
+}
+
+/// Silences a player in the scene channel, for the local player only
+#[op2]
+fn op_voice_set_player_muted(
+    state: &mut OpState,
+    #[string] user_id: String,
+    muted: bool,
+) -> Result<(), AnyError> {
+    let address = user_id
+        .as_str()
+        .as_h160()
+        .ok_or_else(|| anyhow!("invalid user id {user_id}"))?;
+    state
+        .borrow_mut::<Vec<RpcCall>>()
+        .push(RpcCall::VoiceSetPlayerMuted { address, muted });
+    Ok(())
+}
+
+#[op2(async)]
+#[serde]
+async fn op_voice_get_state(state: Rc<RefCell<OpState>>) -> SceneVoiceState {
+    let (sx, rx) = tokio::sync::oneshot::channel::<SceneVoiceState>();
+
+    state
+        .borrow_mut()
+        .borrow_mut::<Vec<RpcCall>>()
+        .push(RpcCall::VoiceGetState {
+            response: sx.into(),
+        });
+
+    rx.await.unwrap_or_default()
+}
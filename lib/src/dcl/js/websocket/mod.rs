@@ -8,6 +8,8 @@ use serde::{Deserialize, Serialize};
 use tokio_tungstenite::tungstenite::protocol::{CloseFrame, WebSocketConfig};
 use tokio_tungstenite::tungstenite::{client::IntoClientRequest, Message};
 
+use crate::dcl::scene_permissions::ScenePermission;
+
 pub fn ops() -> Vec<OpDecl> {
     vec![op_ws_create(), op_ws_cleanup(), op_ws_send(), op_ws_poll()]
 }
@@ -254,8 +256,13 @@ fn op_ws_create(
         });
     }
 
-    let (ws_resource_id, recv_send_data, send_ondata, over_cap) = {
+    let (ws_resource_id, recv_send_data, send_ondata, over_cap, denied) = {
         let mut state = op_state.borrow_mut();
+        let denied =
+            super::check_scene_permission(&mut state, ScenePermission::UseWebsocket, || {
+                format!("WebSocket {url}")
+            })
+            .err();
         let ws_state = state.borrow_mut::<WsState>();
         // Count live connections before allocating a new id.
         let over_cap = ws_state.ws_sender.len() >= MAX_CONNECTIONS_PER_SCENE;
@@ -268,11 +275,13 @@ fn op_ws_create(
         ws_state.ws_receiver.insert(id, receiver);
         ws_state.ws_sender.insert(id, sender);
 
-        (id, recv_send_data, send_ondata, over_cap)
+        (id, recv_send_data, send_ondata, over_cap, denied)
     };
 
     tokio::spawn(async move {
-        if over_cap {
+        if let Some(err) = denied {
+            let _ = send_ondata.send(WsReceiveData::Error(err)).await;
+        } else if over_cap {
             let _ = send_ondata
                 .send(WsReceiveData::Error(anyhow::Error::msg(
                     "too many WebSocket connections open for this scene",
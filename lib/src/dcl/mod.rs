@@ -4,6 +4,8 @@ pub mod crdt;
 #[cfg(feature = "use_deno")]
 pub mod js;
 pub mod scene_apis;
+pub mod scene_permissions;
+pub mod scene_quotas;
 pub mod serialization;
 pub mod ui_text_tags;
 
@@ -24,6 +26,8 @@ use self::{
     },
     crdt::{DirtyCrdtState, SceneCrdtState},
     scene_apis::{RpcCall, RpcResultSender},
+    scene_permissions::ScenePermissions,
+    scene_quotas::{SceneQuotaBreach, SceneQuotas},
 };
 
 #[cfg(feature = "use_deno")]
@@ -95,6 +99,8 @@ pub enum SceneResponse {
         deno_memory_stats: Option<DenoMemoryStats>,
     },
     RemoveGodotScene(SceneId, Vec<SceneLogMessage>),
+    // the scene runtime was terminated, RemoveGodotScene follows
+    QuotaExceeded(SceneId, SceneQuotaBreach),
     TakeSnapshot {
         scene_id: SceneId,
         src_stored_snapshot: String,
@@ -133,6 +139,8 @@ pub struct SpawnDclSceneData {
     pub local_main_js_file_path: String,
     // Path to the main CRDT file
     pub local_main_crdt_file_path: String,
+    // Path to a CRDT state snapshot that replaces main.crdt (empty = none)
+    pub local_crdt_snapshot_file_path: String,
     // Content mapping and URL reference
     pub content_mapping: ContentMappingAndUrlRef,
     // Sender to send messages to the main thread (renderer)
@@ -154,6 +162,10 @@ pub struct SpawnDclSceneData {
     // When true, the scene runtime instruments CRDT and op-call activity into the
     // global Scene Inspector (initialized lazily on the first debugged scene).
     pub should_debug: bool,
+    // Limits enforced on the scene runtime
+    pub quotas: SceneQuotas,
+    // What the scene declared it may do (restricted actions, fetch, websockets)
+    pub permissions: ScenePermissions,
 }
 
 impl DclScene {
@@ -59,6 +59,18 @@ pub struct ContentMapping {
     pub hash: String,
 }
 
+/// Voice chat as seen by a scene (`~system/VoiceChat.getState`)
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SceneVoiceState {
+    /// The scene asked for a scene-scoped voice channel
+    pub scene_channel: bool,
+    /// The player is inside the scene, so the channel is the one in use
+    pub active: bool,
+    pub microphone_allowed: bool,
+    pub muted_players: Vec<String>,
+}
+
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct GetSceneInformationResponse {
@@ -174,6 +186,20 @@ pub enum RpcCall {
         src: String,
         response: RpcResultSender<Result<Vector2, String>>,
     },
+    // Voice Chat
+    VoiceSetSceneChannel {
+        enabled: bool,
+    },
+    VoiceSetMicrophoneAllowed {
+        allowed: bool,
+    },
+    VoiceSetPlayerMuted {
+        address: H160,
+        muted: bool,
+    },
+    VoiceGetState {
+        response: RpcResultSender<SceneVoiceState>,
+    },
 }
 
 #[derive(Debug)]
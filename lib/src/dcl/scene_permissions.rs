@@ -0,0 +1,208 @@
+//! Permissions a scene declares in its scene.json (`requiredPermissions` and
+//! `allowedMediaHostnames`).
+//!
+//! They're only enforced in realms that opt in with
+//! `configurations.enforceScenePermissions` in their /about, scenes deployed
+//! to Genesis City before the permissions existed don't declare them. Global
+//! scenes are never restricted.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScenePermission {
+    MovePlayer,
+    TriggerAvatarEmote,
+    OpenExternalLink,
+    UseFetch,
+    UseWebsocket,
+    MediaHostnames,
+}
+
+impl ScenePermission {
+    const ALL: [ScenePermission; 6] = [
+        ScenePermission::MovePlayer,
+        ScenePermission::TriggerAvatarEmote,
+        ScenePermission::OpenExternalLink,
+        ScenePermission::UseFetch,
+        ScenePermission::UseWebsocket,
+        ScenePermission::MediaHostnames,
+    ];
+
+    /// Name in scene.json `requiredPermissions`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScenePermission::MovePlayer => "ALLOW_TO_MOVE_PLAYER_INSIDE_SCENE",
+            ScenePermission::TriggerAvatarEmote => "ALLOW_TO_TRIGGER_AVATAR_EMOTE",
+            ScenePermission::OpenExternalLink => "OPEN_EXTERNAL_LINK",
+            ScenePermission::UseFetch => "USE_FETCH",
+            ScenePermission::UseWebsocket => "USE_WEBSOCKET",
+            ScenePermission::MediaHostnames => "ALLOW_MEDIA_HOSTNAMES",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|permission| permission.as_str() == name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionDenied {
+    pub permission: ScenePermission,
+    /// What the scene tried to do (e.g. the fetched url)
+    pub action: String,
+}
+
+impl fmt::Display for PermissionDenied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.permission {
+            ScenePermission::MediaHostnames => write!(
+                f,
+                "permission denied: {} isn't in the scene allowedMediaHostnames",
+                self.action
+            ),
+            permission => write!(
+                f,
+                "permission denied: {} requires {} in the scene requiredPermissions",
+                self.action,
+                permission.as_str()
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ScenePermissions {
+    enforced: bool,
+    granted: Vec<ScenePermission>,
+    allowed_media_hostnames: Vec<String>,
+}
+
+impl ScenePermissions {
+    pub const UNRESTRICTED: ScenePermissions = ScenePermissions {
+        enforced: false,
+        granted: Vec::new(),
+        allowed_media_hostnames: Vec::new(),
+    };
+
+    pub fn new(
+        required_permissions: &[String],
+        allowed_media_hostnames: &[String],
+        enforced: bool,
+    ) -> Self {
+        let granted = required_permissions
+            .iter()
+            .filter_map(|name| {
+                let permission = ScenePermission::from_name(name);
+                if permission.is_none() {
+                    tracing::debug!("unknown scene permission {name}");
+                }
+                permission
+            })
+            .collect();
+        Self {
+            enforced,
+            granted,
+            allowed_media_hostnames: allowed_media_hostnames
+                .iter()
+                .map(|hostname| hostname.trim().to_ascii_lowercase())
+                .collect(),
+        }
+    }
+
+    pub fn check(
+        &self,
+        permission: ScenePermission,
+        action: impl FnOnce() -> String,
+    ) -> Result<(), PermissionDenied> {
+        if !self.enforced || self.granted.contains(&permission) {
+            Ok(())
+        } else {
+            Err(PermissionDenied {
+                permission,
+                action: action(),
+            })
+        }
+    }
+
+    /// Content files of the scene and comms streams are always allowed,
+    /// remote urls need their hostname in `allowedMediaHostnames`.
+    pub fn check_media_url(&self, url: &str) -> Result<(), PermissionDenied> {
+        if !self.enforced {
+            return Ok(());
+        }
+        let Ok(parsed) = url::Url::parse(url) else {
+            // a relative path into the scene content
+            return Ok(());
+        };
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Ok(());
+        }
+
+        let allowed = self.granted.contains(&ScenePermission::MediaHostnames)
+            && parsed.host_str().is_some_and(|host| {
+                let host = host.to_ascii_lowercase();
+                self.allowed_media_hostnames.contains(&host)
+            });
+        if allowed {
+            Ok(())
+        } else {
+            Err(PermissionDenied {
+                permission: ScenePermission::MediaHostnames,
+                action: url.to_string(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn permissions(required: &[&str], hostnames: &[&str]) -> ScenePermissions {
+        let to_strings = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        ScenePermissions::new(&to_strings(required), &to_strings(hostnames), true)
+    }
+
+    #[test]
+    fn declared_permissions_are_granted() {
+        let permissions = permissions(&["USE_FETCH", "NOT_A_PERMISSION"], &[]);
+        assert!(permissions
+            .check(ScenePermission::UseFetch, String::new)
+            .is_ok());
+        let denied = permissions
+            .check(ScenePermission::UseWebsocket, || "wss://a.b".to_string())
+            .unwrap_err();
+        assert_eq!(denied.permission, ScenePermission::UseWebsocket);
+        assert_eq!(
+            denied.to_string(),
+            "permission denied: wss://a.b requires USE_WEBSOCKET in the scene requiredPermissions"
+        );
+
+        assert!(ScenePermissions::UNRESTRICTED
+            .check(ScenePermission::MovePlayer, String::new)
+            .is_ok());
+    }
+
+    #[test]
+    fn media_hostnames() {
+        let permissions = permissions(&["ALLOW_MEDIA_HOSTNAMES"], &["Videos.Example.com"]);
+        assert!(permissions
+            .check_media_url("https://videos.example.com/a.m3u8")
+            .is_ok());
+        assert!(permissions
+            .check_media_url("https://evil.example.com/a.mp4")
+            .is_err());
+        assert!(permissions.check_media_url("videos/local.mp4").is_ok());
+        assert!(permissions
+            .check_media_url("livekit-video://current-stream")
+            .is_ok());
+
+        // the hostnames are ignored without the permission
+        let permissions = self::permissions(&[], &["videos.example.com"]);
+        assert!(permissions
+            .check_media_url("https://videos.example.com/a.m3u8")
+            .is_err());
+    }
+}
@@ -0,0 +1,344 @@
+//! Per-scene resource quotas for the JS runtime.
+//!
+//! A [`SceneQuotaGuard`] is shared between the scene thread (tick bookkeeping,
+//! op-call counting, V8 heap callback) and a single watchdog thread that checks
+//! the wall time of the running ticks. Only the time the scene's event loop is
+//! being polled counts (see [`count_poll_time`]), so awaiting fetches, timers or
+//! the renderer doesn't. The first breach terminates the isolate; the scene
+//! thread then reports it and shuts down through `SceneDying`.
+
+use std::{
+    fmt,
+    future::Future,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex, Once, OnceLock, Weak,
+    },
+    time::{Duration, Instant},
+};
+
+use once_cell::sync::Lazy;
+
+pub const DEFAULT_MAX_HEAP_MB: u32 = 512;
+pub const DEFAULT_MAX_OP_CALLS_PER_TICK: u32 = 20_000;
+pub const DEFAULT_MAX_TICK_TIME_MS: u32 = 10_000;
+
+const WATCHDOG_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Limits applied to a single scene runtime, 0 disables a limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SceneQuotas {
+    pub max_heap_mb: u32,
+    pub max_op_calls_per_tick: u32,
+    /// Time the JS runs synchronously without yielding to an async op (waiting
+    /// for fetches, timers or the renderer response doesn't count)
+    pub max_tick_time_ms: u32,
+}
+
+impl Default for SceneQuotas {
+    fn default() -> Self {
+        Self {
+            max_heap_mb: DEFAULT_MAX_HEAP_MB,
+            max_op_calls_per_tick: DEFAULT_MAX_OP_CALLS_PER_TICK,
+            max_tick_time_ms: DEFAULT_MAX_TICK_TIME_MS,
+        }
+    }
+}
+
+impl SceneQuotas {
+    pub const UNLIMITED: SceneQuotas = SceneQuotas {
+        max_heap_mb: 0,
+        max_op_calls_per_tick: 0,
+        max_tick_time_ms: 0,
+    };
+
+    /// Builds the quotas from settings where a negative value means the default.
+    pub fn from_settings(
+        max_heap_mb: i32,
+        max_op_calls_per_tick: i32,
+        max_tick_time_ms: i32,
+    ) -> Self {
+        let or_default = |value: i32, default: u32| u32::try_from(value).unwrap_or(default);
+        Self {
+            max_heap_mb: or_default(max_heap_mb, DEFAULT_MAX_HEAP_MB),
+            max_op_calls_per_tick: or_default(max_op_calls_per_tick, DEFAULT_MAX_OP_CALLS_PER_TICK),
+            max_tick_time_ms: or_default(max_tick_time_ms, DEFAULT_MAX_TICK_TIME_MS),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SceneQuotaBreach {
+    HeapLimit { limit_mb: u32 },
+    OpCallRate { limit: u32 },
+    TickTime { limit_ms: u32 },
+}
+
+impl SceneQuotaBreach {
+    /// Short identifier for analytics
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SceneQuotaBreach::HeapLimit { .. } => "heap",
+            SceneQuotaBreach::OpCallRate { .. } => "op_calls",
+            SceneQuotaBreach::TickTime { .. } => "tick_time",
+        }
+    }
+}
+
+impl fmt::Display for SceneQuotaBreach {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneQuotaBreach::HeapLimit { limit_mb } => {
+                write!(f, "V8 heap limit of {limit_mb} MB exceeded")
+            }
+            SceneQuotaBreach::OpCallRate { limit } => {
+                write!(f, "more than {limit} op calls in a single tick")
+            }
+            SceneQuotaBreach::TickTime { limit_ms } => {
+                write!(f, "JS ran for more than {limit_ms} ms without yielding")
+            }
+        }
+    }
+}
+
+type TerminateFn = Box<dyn Fn() + Send + Sync>;
+
+pub struct SceneQuotaGuard {
+    quotas: SceneQuotas,
+    terminate: OnceLock<TerminateFn>,
+    breach: Mutex<Option<SceneQuotaBreach>>,
+    running_since: Mutex<Option<Instant>>,
+    op_calls: AtomicU32,
+}
+
+impl SceneQuotaGuard {
+    pub fn new(quotas: SceneQuotas) -> Arc<Self> {
+        Arc::new(Self {
+            quotas,
+            terminate: OnceLock::new(),
+            breach: Mutex::new(None),
+            running_since: Mutex::new(None),
+            op_calls: AtomicU32::new(0),
+        })
+    }
+
+    pub fn quotas(&self) -> &SceneQuotas {
+        &self.quotas
+    }
+
+    /// Sets how the runtime is stopped on a breach (the isolate handle).
+    pub fn set_terminate(&self, terminate: impl Fn() + Send + Sync + 'static) {
+        let _ = self.terminate.set(Box::new(terminate));
+    }
+
+    pub fn begin_tick(&self) {
+        self.op_calls.store(0, Ordering::Relaxed);
+        self.resume();
+    }
+
+    /// The JS is running again
+    pub fn resume(&self) {
+        *self.running_since.lock().unwrap() = Some(Instant::now());
+    }
+
+    /// The JS yielded (end of the tick or waiting on async ops)
+    pub fn pause(&self) {
+        *self.running_since.lock().unwrap() = None;
+    }
+
+    pub fn on_op_call(&self) {
+        let limit = self.quotas.max_op_calls_per_tick;
+        if limit > 0 && self.op_calls.fetch_add(1, Ordering::Relaxed) == limit {
+            self.breach(SceneQuotaBreach::OpCallRate { limit });
+        }
+    }
+
+    pub fn on_near_heap_limit(&self) {
+        self.breach(SceneQuotaBreach::HeapLimit {
+            limit_mb: self.quotas.max_heap_mb,
+        });
+    }
+
+    pub fn check_tick_time(&self, now: Instant) {
+        let limit_ms = self.quotas.max_tick_time_ms;
+        if limit_ms == 0 {
+            return;
+        }
+        let running_since = *self.running_since.lock().unwrap();
+        if let Some(running_since) = running_since {
+            if now.saturating_duration_since(running_since) > Duration::from_millis(limit_ms as u64)
+            {
+                self.breach(SceneQuotaBreach::TickTime { limit_ms });
+            }
+        }
+    }
+
+    /// Only the first breach is kept, and the runtime is terminated once.
+    fn breach(&self, breach: SceneQuotaBreach) {
+        let mut current = self.breach.lock().unwrap();
+        if current.is_some() {
+            return;
+        }
+        *current = Some(breach);
+        drop(current);
+
+        if let Some(terminate) = self.terminate.get() {
+            terminate();
+        }
+    }
+
+    pub fn take_breach(&self) -> Option<SceneQuotaBreach> {
+        self.breach.lock().unwrap().take()
+    }
+}
+
+/// Polls `future` (the scene's event loop) with the tick clock running only
+/// while it's being polled: it stops whenever the JS waits on pending async ops.
+pub async fn count_poll_time<F: Future>(guard: Option<&SceneQuotaGuard>, future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    std::future::poll_fn(|cx| {
+        if let Some(guard) = guard {
+            guard.resume();
+        }
+        let poll = future.as_mut().poll(cx);
+        if let Some(guard) = guard {
+            guard.pause();
+        }
+        poll
+    })
+    .await
+}
+
+static WATCHED_GUARDS: Lazy<Mutex<Vec<Weak<SceneQuotaGuard>>>> = Lazy::new(Default::default);
+static WATCHDOG_STARTED: Once = Once::new();
+
+/// Registers the guard in the tick-time watchdog, it's dropped from it with the guard.
+pub fn watch_tick_time(guard: &Arc<SceneQuotaGuard>) {
+    if guard.quotas.max_tick_time_ms == 0 {
+        return;
+    }
+    WATCHED_GUARDS.lock().unwrap().push(Arc::downgrade(guard));
+
+    WATCHDOG_STARTED.call_once(|| {
+        let spawned = std::thread::Builder::new()
+            .name("scene-quota-watchdog".to_string())
+            .spawn(|| loop {
+                std::thread::sleep(WATCHDOG_INTERVAL);
+                let now = Instant::now();
+                WATCHED_GUARDS.lock().unwrap().retain(|guard| {
+                    let Some(guard) = guard.upgrade() else {
+                        return false;
+                    };
+                    guard.check_tick_time(now);
+                    true
+                });
+            });
+        if let Err(err) = spawned {
+            tracing::error!("failed to spawn the scene quota watchdog: {err}");
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+
+    fn guard_with_flag(quotas: SceneQuotas) -> (Arc<SceneQuotaGuard>, Arc<AtomicBool>) {
+        let guard = SceneQuotaGuard::new(quotas);
+        let terminated = Arc::new(AtomicBool::new(false));
+        let flag = terminated.clone();
+        guard.set_terminate(move || flag.store(true, Ordering::Relaxed));
+        (guard, terminated)
+    }
+
+    #[test]
+    fn settings_defaults_and_disabled() {
+        assert_eq!(
+            SceneQuotas::from_settings(-1, -1, -1),
+            SceneQuotas::default()
+        );
+        let quotas = SceneQuotas::from_settings(0, 100, -1);
+        assert_eq!(quotas.max_heap_mb, 0);
+        assert_eq!(quotas.max_op_calls_per_tick, 100);
+        assert_eq!(quotas.max_tick_time_ms, DEFAULT_MAX_TICK_TIME_MS);
+    }
+
+    #[test]
+    fn op_calls_are_counted_per_tick() {
+        let (guard, terminated) = guard_with_flag(SceneQuotas {
+            max_op_calls_per_tick: 3,
+            ..Default::default()
+        });
+
+        for _ in 0..2 {
+            guard.begin_tick();
+            (0..3).for_each(|_| guard.on_op_call());
+        }
+        assert!(!terminated.load(Ordering::Relaxed));
+
+        guard.on_op_call();
+        assert!(terminated.load(Ordering::Relaxed));
+        assert_eq!(
+            guard.take_breach(),
+            Some(SceneQuotaBreach::OpCallRate { limit: 3 })
+        );
+    }
+
+    #[test]
+    fn tick_time_ignores_paused_time() {
+        let (guard, terminated) = guard_with_flag(SceneQuotas {
+            max_tick_time_ms: 100,
+            ..Default::default()
+        });
+        let later = |guard: &SceneQuotaGuard| {
+            let since = guard.running_since.lock().unwrap().unwrap();
+            since + Duration::from_millis(101)
+        };
+
+        guard.begin_tick();
+        let now = later(&guard);
+        guard.pause();
+        guard.check_tick_time(now);
+        assert!(!terminated.load(Ordering::Relaxed));
+
+        guard.resume();
+        guard.check_tick_time(later(&guard));
+        assert!(terminated.load(Ordering::Relaxed));
+
+        // the first breach wins
+        guard.on_near_heap_limit();
+        assert_eq!(
+            guard.take_breach(),
+            Some(SceneQuotaBreach::TickTime { limit_ms: 100 })
+        );
+    }
+
+    #[test]
+    fn awaiting_async_ops_is_not_tick_time() {
+        let (guard, terminated) = guard_with_flag(SceneQuotas {
+            max_tick_time_ms: 10,
+            ..Default::default()
+        });
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+
+        guard.begin_tick();
+        rt.block_on(count_poll_time(Some(&guard), async {
+            // a slow fetch
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            guard.check_tick_time(Instant::now());
+        }));
+        assert!(!terminated.load(Ordering::Relaxed));
+        assert!(guard.running_since.lock().unwrap().is_none());
+
+        // synchronous work between two awaits still counts
+        rt.block_on(count_poll_time(Some(&guard), async {
+            let since = guard.running_since.lock().unwrap().unwrap();
+            guard.check_tick_time(since + Duration::from_millis(11));
+        }));
+        assert!(terminated.load(Ordering::Relaxed));
+    }
+}
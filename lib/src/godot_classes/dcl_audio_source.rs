@@ -1,6 +1,8 @@
 use godot::classes::AudioStreamPlayer3D;
 use godot::prelude::*;
 
+use crate::av::audio_analysis::{AudioAnalysisResult, AudioAnalysisTap};
+
 #[derive(GodotClass)]
 #[class(init, base=AudioStreamPlayer3D)]
 pub struct DclAudioSource {
@@ -36,8 +38,34 @@ pub struct DclAudioSource {
     #[var]
     dcl_scene_id: i32,
 
-    _base: Base<AudioStreamPlayer3D>,
+    /// Present while the entity has a `PBAudioAnalysis` component.
+    analysis_tap: Option<AudioAnalysisTap>,
+
+    base: Base<AudioStreamPlayer3D>,
 }
 
 #[godot_api]
 impl DclAudioSource {}
+
+impl DclAudioSource {
+    /// Routes the player through an analysis bus. No-op if already analyzed.
+    pub fn enable_analysis(&mut self, band_count: usize) {
+        if self.analysis_tap.is_some() {
+            return;
+        }
+        let original_bus = self.base().get_bus();
+        let tap = AudioAnalysisTap::new(self.base().instance_id(), original_bus, band_count);
+        self.base_mut().set_bus(tap.bus_name());
+        self.analysis_tap = Some(tap);
+    }
+
+    pub fn disable_analysis(&mut self) {
+        if let Some(tap) = self.analysis_tap.take() {
+            self.base_mut().set_bus(tap.original_bus());
+        }
+    }
+
+    pub fn analyze_audio(&mut self) -> Option<AudioAnalysisResult> {
+        self.analysis_tap.as_mut().map(AudioAnalysisTap::analyze)
+    }
+}
@@ -1,5 +1,7 @@
 use godot::{classes::AudioStreamPlayer, prelude::*};
 
+use crate::av::audio_analysis::{AudioAnalysisResult, AudioAnalysisTap};
+
 #[derive(GodotClass)]
 #[class(init, base=AudioStreamPlayer)]
 pub struct DclAudioStream {
@@ -10,6 +12,9 @@ pub struct DclAudioStream {
     #[export]
     dcl_url: GString,
 
+    /// Present while the entity has a `PBAudioAnalysis` component.
+    analysis_tap: Option<AudioAnalysisTap>,
+
     base: Base<AudioStreamPlayer>,
 }
 
@@ -23,4 +28,25 @@ impl DclAudioStream {
             self.base_mut().set_volume_db(db_volume);
         }
     }
+
+    /// Routes the player through an analysis bus. No-op if already analyzed.
+    pub fn enable_analysis(&mut self, band_count: usize) {
+        if self.analysis_tap.is_some() {
+            return;
+        }
+        let original_bus = self.base().get_bus();
+        let tap = AudioAnalysisTap::new(self.base().instance_id(), original_bus, band_count);
+        self.base_mut().set_bus(tap.bus_name());
+        self.analysis_tap = Some(tap);
+    }
+
+    pub fn disable_analysis(&mut self) {
+        if let Some(tap) = self.analysis_tap.take() {
+            self.base_mut().set_bus(tap.original_bus());
+        }
+    }
+
+    pub fn analyze_audio(&mut self) -> Option<AudioAnalysisResult> {
+        self.analysis_tap.as_mut().map(AudioAnalysisTap::analyze)
+    }
 }
@@ -53,6 +53,8 @@ pub struct DclCli {
     #[var(get)]
     pub clear_cache_startup: bool,
     #[var(get)]
+    pub verify_cache_startup: bool,
+    #[var(get)]
     pub raycast_debugger: bool,
     #[var(get)]
     pub network_debugger: bool,
@@ -101,6 +103,32 @@ pub struct DclCli {
     #[var(get)]
     pub scene_inspector_file: bool,
     #[var(get)]
+    pub crdt_snapshot_folder: GString,
+    #[var(get)]
+    pub crdt_replay: GString,
+    #[var(get)]
+    pub crdt_replay_scene: i32,
+    #[var(get)]
+    pub crdt_replay_at: GString,
+    #[var(get)]
+    pub crdt_replay_output: GString,
+    #[var(get)]
+    pub headless_scene: GString,
+    #[var(get)]
+    pub headless_scene_ticks: i32,
+    #[var(get)]
+    pub headless_scene_report: GString,
+    #[var(get)]
+    pub scene_max_heap_mb: i32,
+    #[var(get)]
+    pub scene_max_op_calls: i32,
+    #[var(get)]
+    pub scene_max_tick_ms: i32,
+    #[var(get)]
+    pub software_video: bool,
+    #[var(get)]
+    pub mock_comms: GString,
+    #[var(get)]
     pub test_logging: bool,
     #[var(get)]
     pub low_spec_warning: bool,
@@ -194,6 +222,18 @@ pub struct DclCli {
     // Dev/testing switch; deeplink `livekit=false` is the runtime equivalent.
     #[var(get)]
     pub no_livekit: bool,
+    // Capture the Pulse traffic to this file for offline replay (comms/pulse/recording.rs).
+    #[var(get)]
+    pub pulse_record: GString,
+    // Simulated latency/jitter/loss on the incoming comms messages (comms/adapter/network_simulator.rs).
+    #[var(get)]
+    pub network_sim: GString,
+    // Batch the scene messages of each tick for the peers that support it (comms/adapter/scene_batching.rs).
+    #[var(get)]
+    pub scene_batching: bool,
+    // Deflate threshold in bytes for batched scene messages, 0 uses the default (implies --scene-batching).
+    #[var(get)]
+    pub scene_compress: i32,
 }
 
 impl DclCli {
@@ -405,6 +445,12 @@ impl DclCli {
                 arg_type: ArgType::Flag,
                 category: "Maintenance".to_string(),
             },
+            ArgDefinition {
+                name: "--verify-cache-startup".to_string(),
+                description: "Check the cached content against its hashes in the background and delete corrupt files".to_string(),
+                arg_type: ArgType::Flag,
+                category: "Maintenance".to_string(),
+            },
             // Asset Loading
             ArgDefinition {
                 name: "--only-optimized".to_string(),
@@ -543,6 +589,84 @@ impl DclCli {
                 arg_type: ArgType::Flag,
                 category: "Debugging".to_string(),
             },
+            ArgDefinition {
+                name: "--crdt-snapshot-folder".to_string(),
+                description: "Start scenes from `<folder>/<scene entity id>.crdtsnap` when present, instead of main.crdt. Snapshots are written with SceneManager.save_scene_crdt_snapshot".to_string(),
+                arg_type: ArgType::Value("<folder>".to_string()),
+                category: "Debugging".to_string(),
+            },
+            ArgDefinition {
+                name: "--crdt-replay".to_string(),
+                description: "Replay the CRDT messages of a Scene Inspector JSONL session, dump the scene state as JSON and quit".to_string(),
+                arg_type: ArgType::Value("<session.jsonl>".to_string()),
+                category: "Debugging".to_string(),
+            },
+            ArgDefinition {
+                name: "--crdt-replay-scene".to_string(),
+                description: "Scene id to replay (default: every scene in the session)".to_string(),
+                arg_type: ArgType::Value("<id>".to_string()),
+                category: "Debugging".to_string(),
+            },
+            ArgDefinition {
+                name: "--crdt-replay-at".to_string(),
+                description: "Where the replay stops: `end` (default), `tick:<n>` or `t:<ms>`".to_string(),
+                arg_type: ArgType::Value("<point>".to_string()),
+                category: "Debugging".to_string(),
+            },
+            ArgDefinition {
+                name: "--crdt-replay-output".to_string(),
+                description: "Output file for the replayed state (default: <session>.state.json)".to_string(),
+                arg_type: ArgType::Value("<file>".to_string()),
+                category: "Debugging".to_string(),
+            },
+            ArgDefinition {
+                name: "--headless-scene".to_string(),
+                description: "Run the scene in a local folder without rendering it, report JS exceptions and invalid CRDT state, and quit (use with --headless)".to_string(),
+                arg_type: ArgType::Value("<folder>".to_string()),
+                category: "Testing".to_string(),
+            },
+            ArgDefinition {
+                name: "--headless-scene-ticks".to_string(),
+                description: "Number of ticks the headless scene runs (default: 300)".to_string(),
+                arg_type: ArgType::Value("<n>".to_string()),
+                category: "Testing".to_string(),
+            },
+            ArgDefinition {
+                name: "--headless-scene-report".to_string(),
+                description: "Write the headless scene run report as JSON to this file".to_string(),
+                arg_type: ArgType::Value("<file>".to_string()),
+                category: "Testing".to_string(),
+            },
+            ArgDefinition {
+                name: "--scene-max-heap-mb".to_string(),
+                description: "V8 heap limit per scene, the scene is killed when exceeded (default: 512, 0 = unlimited)".to_string(),
+                arg_type: ArgType::Value("<mb>".to_string()),
+                category: "Debugging".to_string(),
+            },
+            ArgDefinition {
+                name: "--scene-max-op-calls".to_string(),
+                description: "Op calls allowed per scene tick, the scene is killed when exceeded (default: 20000, 0 = unlimited)".to_string(),
+                arg_type: ArgType::Value("<n>".to_string()),
+                category: "Debugging".to_string(),
+            },
+            ArgDefinition {
+                name: "--scene-max-tick-ms".to_string(),
+                description: "Time a scene tick can run without yielding to the renderer, the scene is killed when exceeded (default: 10000, 0 = unlimited)".to_string(),
+                arg_type: ArgType::Value("<ms>".to_string()),
+                category: "Debugging".to_string(),
+            },
+            ArgDefinition {
+                name: "--software-video".to_string(),
+                description: "Play VideoPlayer MP4s with the experimental software decoder on desktop Linux (downloads Cisco's OpenH264 on first use)".to_string(),
+                arg_type: ArgType::Flag,
+                category: "Debugging".to_string(),
+            },
+            ArgDefinition {
+                name: "--mock-comms".to_string(),
+                description: "Connect to an in-process loopback comms server that plays the fake peers of this JSON script instead of the realm comms".to_string(),
+                arg_type: ArgType::Value("<script.json>".to_string()),
+                category: "Testing".to_string(),
+            },
             ArgDefinition {
                 name: "--test-logging".to_string(),
                 description: "Run the logging self-test on startup: every component logs at all levels and every form in its stack (Rust/GDScript/Swift/ObjC/Kotlin), to verify the unified channel + Sentry pipeline. Also via deeplink (?test-logging=true)".to_string(),
@@ -601,6 +725,30 @@ impl DclCli {
                 arg_type: ArgType::Flag,
                 category: "Comms".to_string(),
             },
+            ArgDefinition {
+                name: "--pulse-record".to_string(),
+                description: "Record the Pulse session (server messages, sends and connection status) to a file for replay".to_string(),
+                arg_type: ArgType::Value("<file>".to_string()),
+                category: "Comms".to_string(),
+            },
+            ArgDefinition {
+                name: "--network-sim".to_string(),
+                description: "Simulate a bad network on incoming comms messages: a preset (mobile, bad) and/or rules like 'latency=200,jitter=80;movement:loss=0.1'".to_string(),
+                arg_type: ArgType::Value("<spec>".to_string()),
+                category: "Comms".to_string(),
+            },
+            ArgDefinition {
+                name: "--scene-batching".to_string(),
+                description: "Batch the scene messages of each frame into one packet for peers that support it".to_string(),
+                arg_type: ArgType::Flag,
+                category: "Comms".to_string(),
+            },
+            ArgDefinition {
+                name: "--scene-compress".to_string(),
+                description: "Deflate batched scene messages of at least this many bytes (implies --scene-batching)".to_string(),
+                arg_type: ArgType::Value("<bytes>".to_string()),
+                category: "Comms".to_string(),
+            },
             ArgDefinition {
                 name: "--no-livekit".to_string(),
                 description: "Pulse-only mode: skip all LiveKit rooms (main/island/scene — no chat, voice or scene messages). Dev/testing".to_string(),
@@ -728,6 +876,7 @@ impl INode for DclCli {
         let client_test_mode = args_map.contains_key("--client-test");
         let test_runner = args_map.contains_key("--test-runner");
         let clear_cache_startup = args_map.contains_key("--clear-cache-startup");
+        let verify_cache_startup = args_map.contains_key("--verify-cache-startup");
         let raycast_debugger = args_map.contains_key("--raycast-debugger");
         let network_debugger = args_map.contains_key("--network-debugger");
         let spawn_avatars = args_map.contains_key("--spawn-avatars");
@@ -761,6 +910,62 @@ impl INode for DclCli {
             })
             .unwrap_or_default();
         let scene_inspector_file = args_map.contains_key("--scene-inspector-file");
+        let crdt_snapshot_folder = args_map
+            .get("--crdt-snapshot-folder")
+            .and_then(|v| v.as_ref())
+            .map(GString::from)
+            .unwrap_or_default();
+        let crdt_replay = args_map
+            .get("--crdt-replay")
+            .and_then(|v| v.as_ref())
+            .map(GString::from)
+            .unwrap_or_default();
+        let crdt_replay_scene = args_map
+            .get("--crdt-replay-scene")
+            .and_then(|v| v.as_ref().map(|s| s.parse::<i32>().unwrap_or(-1)))
+            .unwrap_or(-1);
+        let crdt_replay_at = args_map
+            .get("--crdt-replay-at")
+            .and_then(|v| v.as_ref())
+            .map(GString::from)
+            .unwrap_or_else(|| GString::from("end"));
+        let crdt_replay_output = args_map
+            .get("--crdt-replay-output")
+            .and_then(|v| v.as_ref())
+            .map(GString::from)
+            .unwrap_or_default();
+        let headless_scene = args_map
+            .get("--headless-scene")
+            .and_then(|v| v.as_ref())
+            .map(GString::from)
+            .unwrap_or_default();
+        let headless_scene_ticks = args_map
+            .get("--headless-scene-ticks")
+            .and_then(|v| v.as_ref().map(|s| s.parse::<i32>().unwrap_or(-1)))
+            .unwrap_or(-1);
+        let headless_scene_report = args_map
+            .get("--headless-scene-report")
+            .and_then(|v| v.as_ref())
+            .map(GString::from)
+            .unwrap_or_default();
+        let scene_max_heap_mb = args_map
+            .get("--scene-max-heap-mb")
+            .and_then(|v| v.as_ref().map(|s| s.parse::<i32>().unwrap_or(-1)))
+            .unwrap_or(-1);
+        let scene_max_op_calls = args_map
+            .get("--scene-max-op-calls")
+            .and_then(|v| v.as_ref().map(|s| s.parse::<i32>().unwrap_or(-1)))
+            .unwrap_or(-1);
+        let scene_max_tick_ms = args_map
+            .get("--scene-max-tick-ms")
+            .and_then(|v| v.as_ref().map(|s| s.parse::<i32>().unwrap_or(-1)))
+            .unwrap_or(-1);
+        let software_video = args_map.contains_key("--software-video");
+        let mock_comms = args_map
+            .get("--mock-comms")
+            .and_then(|v| v.as_ref())
+            .map(GString::from)
+            .unwrap_or_default();
         let test_logging = args_map.contains_key("--test-logging");
         let low_spec_warning = args_map.contains_key("--low-spec-warning");
         let fi_benchmark_size = args_map
@@ -882,6 +1087,22 @@ impl INode for DclCli {
         let pulse_explicit = args_map.contains_key("--pulse") || !pulse_server.is_empty();
         let no_livekit_movement = args_map.contains_key("--no-livekit-movement");
         let no_livekit = args_map.contains_key("--no-livekit");
+        let pulse_record = args_map
+            .get("--pulse-record")
+            .and_then(|v| v.as_ref())
+            .map(GString::from)
+            .unwrap_or_default();
+        let network_sim = args_map
+            .get("--network-sim")
+            .and_then(|v| v.as_ref())
+            .map(GString::from)
+            .unwrap_or_default();
+        let scene_batching = args_map.contains_key("--scene-batching");
+        let scene_compress = args_map
+            .get("--scene-compress")
+            .and_then(|v| v.as_ref())
+            .and_then(|s| s.parse::<i32>().ok())
+            .unwrap_or(0);
 
         // Convert combined args back to PackedStringArray for storage
         let args: PackedStringArray = args_vec.iter().cloned().collect();
@@ -902,6 +1123,7 @@ impl INode for DclCli {
             client_test_mode,
             test_runner,
             clear_cache_startup,
+            verify_cache_startup,
             raycast_debugger,
             network_debugger,
             spawn_avatars,
@@ -926,6 +1148,19 @@ impl INode for DclCli {
             asset_server,
             scene_inspector,
             scene_inspector_file,
+            crdt_snapshot_folder,
+            crdt_replay,
+            crdt_replay_scene,
+            crdt_replay_at,
+            crdt_replay_output,
+            headless_scene,
+            headless_scene_ticks,
+            headless_scene_report,
+            scene_max_heap_mb,
+            scene_max_op_calls,
+            scene_max_tick_ms,
+            software_video,
+            mock_comms,
             test_logging,
             low_spec_warning,
             fi_benchmark_size,
@@ -952,6 +1187,10 @@ impl INode for DclCli {
             pulse_server,
             no_livekit_movement,
             no_livekit,
+            pulse_record,
+            network_sim,
+            scene_batching,
+            scene_compress,
         }
     }
 }
@@ -26,6 +26,9 @@ pub struct DclRealm {
     realm_global_scene_urns: Array<VarDictionary>,
     #[var]
     realm_city_loader_content_base_url: GString,
+    /// Custom realms opt in to restricting scenes to their scene.json permissions
+    #[var]
+    enforce_scene_permissions: bool,
 
     lambda_server_base_url: GString,
 
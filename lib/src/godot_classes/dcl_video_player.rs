@@ -1,5 +1,10 @@
-use crate::av::backend::BackendType;
-use godot::classes::{AudioStreamPlayer, ImageTexture};
+use crate::av::backend::{
+    av_sinks, update_video_texture, AudioFrames, AudioSink, BackendType, VideoSink,
+};
+use crate::av::stream_processor::{AVCommand, StreamStateData};
+use godot::classes::{
+    AudioStreamGenerator, AudioStreamGeneratorPlayback, AudioStreamPlayer, ImageTexture,
+};
 use godot::prelude::*;
 
 /// Video state constants (matching GDScript and SDK VideoState)
@@ -57,6 +62,9 @@ pub struct DclVideoPlayer {
     /// This is set by Rust when frames arrive
     #[var]
     last_frame_time: f64,
+
+    /// Decoder sinks for the software backend, drained by `software_poll`
+    software_sinks: Option<(VideoSink, AudioSink)>,
 }
 
 #[godot_api]
@@ -98,6 +106,7 @@ impl DclVideoPlayer {
             0 => BackendType::LiveKit,
             1 => BackendType::ExoPlayer,
             2 => BackendType::AVPlayer,
+            4 => BackendType::Software,
             _ => BackendType::Noop,
         };
         self.dcl_source = source.clone();
@@ -110,6 +119,13 @@ impl DclVideoPlayer {
             looping
         );
 
+        // The software decoder is owned by Rust; it's created before the GDScript
+        // init so a local file resolved right away can't miss the sender.
+        self.dispose_software_sinks();
+        if self.backend_type == BackendType::Software {
+            self.init_software_sinks(source.to_string(), playing, looping);
+        }
+
         // Call the GDScript implementation to actually initialize the backend
         // Note: We use source.clone() above and pass source here to avoid borrow issues
         self.base_mut().call(
@@ -188,6 +204,7 @@ impl DclVideoPlayer {
     #[func]
     pub fn backend_dispose(&mut self) {
         self.base_mut().call("_backend_dispose", &[]);
+        self.dispose_software_sinks();
         self.backend_type = BackendType::Noop;
     }
 
@@ -198,4 +215,177 @@ impl DclVideoPlayer {
         let result = self.base_mut().call("_get_backend_texture", &[]);
         result.try_to::<Gd<godot::classes::Texture2D>>().ok()
     }
+
+    /// Software backend: resume playback
+    #[func]
+    pub fn software_play(&mut self) {
+        self.send_software_command(AVCommand::Play);
+    }
+
+    /// Software backend: pause playback (keeps the last frame)
+    #[func]
+    pub fn software_pause(&mut self) {
+        self.send_software_command(AVCommand::Pause);
+    }
+
+    /// Software backend: enable or disable looping
+    #[func]
+    pub fn software_set_looping(&mut self, looping: bool) {
+        self.send_software_command(AVCommand::Repeat(looping));
+    }
+
+    /// Software backend: seek to a position in seconds
+    #[func]
+    pub fn software_seek(&mut self, position: f64) {
+        self.send_software_command(AVCommand::Seek(position));
+    }
+
+    /// Software backend: called every frame from GDScript `_process`.
+    /// Uploads the newest decoded frame, feeds the decoded audio into the
+    /// `AudioStreamGenerator` and maps `StreamStateData` to the video_* vars.
+    #[func]
+    pub fn software_poll(&mut self) {
+        let Some((video_sink, audio_sink)) = self.software_sinks.as_mut() else {
+            return;
+        };
+
+        let mut latest_frame = None;
+        while let Ok(frame) = video_sink.frame_receiver.try_recv() {
+            latest_frame = Some(frame);
+        }
+        if let Some(frame) = latest_frame {
+            video_sink.size = (frame.width, frame.height);
+            update_video_texture(&mut video_sink.texture, &frame);
+        }
+
+        let mut states = Vec::new();
+        while let Ok(state) = video_sink.stream_data_state_receiver.try_recv() {
+            match &state {
+                StreamStateData::Ready { length } => video_sink.length = Some(*length),
+                StreamStateData::Playing { position }
+                | StreamStateData::Buffering { position }
+                | StreamStateData::Paused { position } => video_sink.current_time = *position,
+                _ => {}
+            }
+            states.push(state);
+        }
+
+        let mut audio_frames = Vec::new();
+        if let Some(frames_receiver) = audio_sink.frames_receiver.as_mut() {
+            while let Ok(frames) = frames_receiver.try_recv() {
+                audio_frames.push(frames);
+            }
+        }
+
+        for state in states {
+            self.apply_software_state(state);
+        }
+        for frames in audio_frames {
+            self.push_software_audio(frames);
+        }
+    }
+
+    fn init_software_sinks(&mut self, source: String, playing: bool, looping: bool) {
+        let wait_for_resource = if source.starts_with("http://") || source.starts_with("https://") {
+            None
+        } else {
+            // GDScript fetches the file and calls `resolve_resource` with its path
+            let (sender, receiver) = tokio::sync::oneshot::channel();
+            self.resolve_resource_sender = Some(sender);
+            Some(receiver)
+        };
+
+        let audio_stream_player = self.to_gd().upcast::<AudioStreamPlayer>();
+        let (video_sink, audio_sink) = av_sinks(
+            source,
+            self.dcl_texture.clone(),
+            audio_stream_player,
+            playing,
+            looping,
+            wait_for_resource,
+        );
+        self.software_sinks = video_sink.map(|video_sink| (video_sink, audio_sink));
+    }
+
+    fn dispose_software_sinks(&mut self) {
+        if let Some((video_sink, _)) = self.software_sinks.take() {
+            let _ = video_sink.command_sender.try_send(AVCommand::Dispose);
+        }
+    }
+
+    fn send_software_command(&mut self, command: AVCommand) {
+        if let Some((video_sink, _)) = self.software_sinks.as_ref() {
+            let _ = video_sink.command_sender.try_send(command);
+        }
+    }
+
+    fn apply_software_state(&mut self, state: StreamStateData) {
+        match state {
+            StreamStateData::Ready { length } => {
+                self.video_length = length;
+                if self.video_state == VIDEO_STATE_LOADING {
+                    self.video_state = VIDEO_STATE_READY;
+                }
+            }
+            StreamStateData::Playing { position } => {
+                self.video_state = VIDEO_STATE_PLAYING;
+                self.video_position = position;
+            }
+            StreamStateData::Buffering { position } => {
+                self.video_state = VIDEO_STATE_BUFFERING;
+                self.video_position = position;
+            }
+            StreamStateData::Seeking {} => {
+                self.video_state = VIDEO_STATE_SEEKING;
+                // Audio queued before the seek belongs to the old position
+                if let Some(mut playback) = self.generator_playback() {
+                    playback.clear_buffer();
+                }
+            }
+            StreamStateData::Paused { position } => {
+                self.video_state = VIDEO_STATE_PAUSED;
+                self.video_position = position;
+            }
+            StreamStateData::Error { message } => {
+                tracing::warn!(
+                    "software video backend error ({}): {}",
+                    self.dcl_source,
+                    message
+                );
+                self.video_state = VIDEO_STATE_ERROR;
+            }
+        }
+    }
+
+    fn generator_playback(&mut self) -> Option<Gd<AudioStreamGeneratorPlayback>> {
+        self.base_mut()
+            .get_stream_playback()
+            .and_then(|playback| playback.try_cast::<AudioStreamGeneratorPlayback>().ok())
+    }
+
+    fn push_software_audio(&mut self, frames: AudioFrames) {
+        let Some(mut generator) = self
+            .base()
+            .get_stream()
+            .and_then(|stream| stream.try_cast::<AudioStreamGenerator>().ok())
+        else {
+            return;
+        };
+
+        // The generator's mix rate can only change while stopped
+        if generator.get_mix_rate() as u32 != frames.sample_rate {
+            self.base_mut().stop();
+            generator.set_mix_rate(frames.sample_rate as f32);
+        }
+        if !self.base().is_playing() {
+            self.base_mut().play();
+        }
+
+        let Some(mut playback) = self.generator_playback() else {
+            return;
+        };
+        if playback.get_frames_available() as usize >= frames.frames.len() {
+            playback.push_buffer(&PackedVector2Array::from(frames.frames.as_slice()));
+        }
+    }
 }
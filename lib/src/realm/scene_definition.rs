@@ -4,7 +4,10 @@ use godot::builtin::{Vector2i, Vector3};
 
 use crate::{
     content::content_mapping::{ContentMappingAndUrl, ContentMappingAndUrlRef},
-    dcl::common::{content_entity::EntityDefinitionJson, scene::SceneEntityMetadata},
+    dcl::{
+        common::{content_entity::EntityDefinitionJson, scene::SceneEntityMetadata},
+        scene_permissions::ScenePermissions,
+    },
 };
 #[derive(Debug, Clone, PartialEq)]
 pub struct EntityBase {
@@ -68,6 +71,18 @@ impl SceneEntityDefinition {
         }
     }
 
+    /// Permissions declared in the scene.json, global scenes aren't restricted.
+    pub fn get_permissions(&self, enforced: bool) -> ScenePermissions {
+        if self.is_global {
+            return ScenePermissions::UNRESTRICTED;
+        }
+        ScenePermissions::new(
+            &self.scene_meta_scene.required_permissions,
+            &self.scene_meta_scene.allowed_media_hostnames,
+            enforced,
+        )
+    }
+
     pub fn get_base_parcel(&self) -> Vector2i {
         self.scene_meta_scene.scene.base
     }
@@ -0,0 +1,162 @@
+use crate::{
+    av::audio_analysis::AudioAnalysisResult,
+    dcl::{
+        components::{
+            proto_components::sdk::components::{PbAudioAnalysis, PbAudioAnalysisMode},
+            SceneComponentId,
+        },
+        crdt::{
+            last_write_wins::LastWriteWinsComponentOperation, SceneCrdtState,
+            SceneCrdtStateProtoComponents,
+        },
+    },
+    scene_runner::scene::Scene,
+};
+
+/// `PBAudioAnalysis` carries a fixed `band_0..band_7` set.
+const AUDIO_ANALYSIS_BANDS: usize = 8;
+
+/// Results are written from the renderer into the same component the scene
+/// uses to configure the analysis (mode and gains), so the scene can read them
+/// back with `AudioAnalysis.readIntoView`.
+pub fn update_audio_analysis(scene: &mut Scene, crdt_state: &mut SceneCrdtState) {
+    let dirty_lww_components = &scene.current_dirty.lww_components;
+    let audio_analysis_component = SceneCrdtStateProtoComponents::get_audio_analysis(crdt_state);
+
+    if let Some(audio_analysis_dirty) = dirty_lww_components.get(&SceneComponentId::AUDIO_ANALYSIS)
+    {
+        for entity in audio_analysis_dirty {
+            let has_value = audio_analysis_component
+                .get(entity)
+                .is_some_and(|entry| entry.value.is_some());
+            if has_value {
+                scene.audio_analysis.insert(*entity);
+                continue;
+            }
+
+            scene.audio_analysis.remove(entity);
+            if let Some(audio_source) = scene.audio_sources.get_mut(entity) {
+                audio_source.bind_mut().disable_analysis();
+            }
+            if let Some(audio_stream) = scene.audio_streams.get_mut(entity) {
+                audio_stream.bind_mut().disable_analysis();
+            }
+        }
+    }
+
+    if scene.audio_analysis.is_empty() {
+        return;
+    }
+
+    let audio_analysis_component =
+        SceneCrdtStateProtoComponents::get_audio_analysis_mut(crdt_state);
+    for entity in scene.audio_analysis.iter() {
+        let Some(current) = audio_analysis_component
+            .get(entity)
+            .and_then(|entry| entry.value.as_ref())
+        else {
+            continue;
+        };
+
+        // The AudioSource/AudioStream may be added after the AudioAnalysis,
+        // so the tap is attached lazily.
+        let result = if let Some(audio_source) = scene.audio_sources.get_mut(entity) {
+            let mut audio_source = audio_source.bind_mut();
+            audio_source.enable_analysis(AUDIO_ANALYSIS_BANDS);
+            audio_source.analyze_audio()
+        } else if let Some(audio_stream) = scene.audio_streams.get_mut(entity) {
+            let mut audio_stream = audio_stream.bind_mut();
+            audio_stream.enable_analysis(AUDIO_ANALYSIS_BANDS);
+            audio_stream.analyze_audio()
+        } else {
+            None
+        };
+        let Some(result) = result else {
+            continue;
+        };
+
+        let new_value = apply_analysis_result(current, &result);
+        if new_value != *current {
+            audio_analysis_component.put(*entity, Some(new_value));
+        }
+    }
+}
+
+/// Copies the analysis into the component, keeping the scene's parameters.
+/// `MODE_LOGARITHMIC` maps the gained values onto a perceptual 0..1 scale; raw
+/// mode writes RMS and band magnitudes as measured.
+fn apply_analysis_result(
+    current: &PbAudioAnalysis,
+    result: &AudioAnalysisResult,
+) -> PbAudioAnalysis {
+    let logarithmic = current.mode == PbAudioAnalysisMode::ModeLogarithmic as i32;
+    let amplitude_gain = current.amplitude_gain.unwrap_or(1.0);
+    let bands_gain = current.bands_gain.unwrap_or(1.0);
+
+    let map = |value: f32, gain: f32| {
+        if logarithmic {
+            // log10(1 + 9x) maps [0, 1] onto [0, 1] with a perceptual curve
+            (1.0 + 9.0 * (value * gain).max(0.0))
+                .log10()
+                .clamp(0.0, 1.0)
+        } else {
+            value
+        }
+    };
+    let band = |index: usize| map(result.bands.get(index).copied().unwrap_or(0.0), bands_gain);
+
+    PbAudioAnalysis {
+        amplitude: map(result.amplitude, amplitude_gain),
+        band_0: band(0),
+        band_1: band(1),
+        band_2: band(2),
+        band_3: band(3),
+        band_4: band(4),
+        band_5: band(5),
+        band_6: band(6),
+        band_7: band(7),
+        ..current.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_mode_copies_measurements() {
+        let current = PbAudioAnalysis {
+            mode: PbAudioAnalysisMode::ModeRaw as i32,
+            amplitude_gain: Some(10.0),
+            ..Default::default()
+        };
+        let result = AudioAnalysisResult {
+            amplitude: 0.25,
+            bands: vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8],
+        };
+        let new_value = apply_analysis_result(&current, &result);
+        assert_eq!(new_value.amplitude, 0.25);
+        assert_eq!(new_value.band_7, 0.8);
+        // parameters are preserved
+        assert_eq!(new_value.amplitude_gain, Some(10.0));
+    }
+
+    #[test]
+    fn logarithmic_mode_is_normalized() {
+        let current = PbAudioAnalysis {
+            mode: PbAudioAnalysisMode::ModeLogarithmic as i32,
+            amplitude_gain: Some(100.0),
+            bands_gain: None,
+            ..Default::default()
+        };
+        let result = AudioAnalysisResult {
+            amplitude: 0.5,
+            bands: vec![0.0; 3],
+        };
+        let new_value = apply_analysis_result(&current, &result);
+        assert_eq!(new_value.amplitude, 1.0);
+        assert_eq!(new_value.band_0, 0.0);
+        // missing bands read as silence
+        assert_eq!(new_value.band_7, 0.0);
+    }
+}
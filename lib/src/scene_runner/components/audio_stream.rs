@@ -9,6 +9,7 @@ use crate::{
     },
     godot_classes::dcl_audio_stream::DclAudioStream,
     scene_runner::scene::{Scene, SceneType},
+    tools::scene_inspector::log_permission_denied,
 };
 use godot::{
     classes::{AudioStream, AudioStreamGenerator, AudioStreamPlayer},
@@ -42,6 +43,15 @@ pub fn update_audio_stream(
             };
 
             if let Some(next_value) = next_value {
+                if let Err(denied) = scene.permissions.check_media_url(&next_value.url) {
+                    tracing::debug!("scene {}: {denied}", scene.scene_id.0);
+                    log_permission_denied(
+                        scene.scene_id.0,
+                        denied.permission.as_str(),
+                        &denied.action,
+                    );
+                    continue;
+                }
                 let muted_by_current_scene = if let SceneType::Parcel = scene.scene_type {
                     scene.scene_id != *current_parcel_scene_id
                 } else {
@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+
+use godot::{
+    classes::{light_3d::Param, Light3D, OmniLight3D, SpotLight3D},
+    prelude::*,
+};
+
+use crate::{
+    dcl::{
+        components::{
+            proto_components::sdk::components::{pb_light_source, PbLightSource},
+            SceneComponentId, SceneEntityId,
+        },
+        crdt::{
+            last_write_wins::LastWriteWinsComponentOperation, SceneCrdtState,
+            SceneCrdtStateProtoComponents,
+        },
+    },
+    scene_runner::scene::Scene,
+};
+
+/// SDK default intensity (candela). Mapped to Godot's `light_energy = 1.0`.
+const DEFAULT_INTENSITY: f32 = 16_000.0;
+/// Upper clamp for the Godot energy; anything brighter just blows out the tonemapper.
+const MAX_ENERGY: f32 = 16.0;
+/// SDK default range in meters.
+const DEFAULT_RANGE: f32 = 10.0;
+/// A light never needs to reach beyond a large scene; also bounds the clustered
+/// light cost on mobile.
+const MAX_RANGE: f32 = 100.0;
+/// SDK spot defaults (full cone angles, degrees).
+const DEFAULT_SPOT_INNER_ANGLE: f32 = 21.8;
+const DEFAULT_SPOT_OUTER_ANGLE: f32 = 30.0;
+/// Godot's `spot_angle` is a half-angle and must stay below 90°.
+const MAX_SPOT_HALF_ANGLE: f32 = 89.0;
+
+/// Default per-scene budget: lights beyond it (farthest from the camera first) are hidden.
+pub const DEFAULT_MAX_LIGHTS_PER_SCENE: i32 = 32;
+/// Default per-scene budget of lights allowed to cast shadows.
+pub const DEFAULT_MAX_SHADOWED_LIGHTS_PER_SCENE: i32 = 4;
+/// Mobile GPUs stall quickly with omni shadows (6 shadow-map faces each).
+pub const MOBILE_MAX_LIGHTS_PER_SCENE: i32 = 8;
+pub const MOBILE_MAX_SHADOWED_LIGHTS_PER_SCENE: i32 = 1;
+
+pub struct LightSourceItem {
+    pub node: Gd<Light3D>,
+    pub is_spot: bool,
+    /// `active` flag from the component; inactive lights never count against the budget.
+    pub active: bool,
+    /// Whether the scene asked for shadows. The budget decides if it actually gets them.
+    pub wants_shadow: bool,
+}
+
+/// Outcome of the budget pass for one light.
+#[derive(Debug, PartialEq)]
+pub struct LightBudgetSlot {
+    pub entity: SceneEntityId,
+    pub enabled: bool,
+    pub shadow: bool,
+}
+
+pub fn update_light_source(scene: &mut Scene, crdt_state: &mut SceneCrdtState) {
+    let dirty_lww_components = &scene.current_dirty.lww_components;
+    let light_source_component = SceneCrdtStateProtoComponents::get_light_source(crdt_state);
+
+    let Some(light_source_dirty) = dirty_lww_components.get(&SceneComponentId::LIGHT_SOURCE) else {
+        return;
+    };
+
+    for entity in light_source_dirty {
+        let new_value = light_source_component
+            .get(entity)
+            .and_then(|entry| entry.value.clone());
+
+        let Some(new_value) = new_value else {
+            remove_light_source(&mut scene.light_sources, entity);
+            continue;
+        };
+
+        let is_spot = matches!(new_value.r#type, Some(pb_light_source::Type::Spot(_)));
+
+        // Omni <-> spot switches need a different node class, so drop the old one.
+        if scene
+            .light_sources
+            .get(entity)
+            .is_some_and(|item| item.is_spot != is_spot)
+        {
+            remove_light_source(&mut scene.light_sources, entity);
+        }
+
+        let (_godot_entity_node, mut node_3d) = scene.godot_dcl_scene.ensure_node_3d(entity);
+
+        let item = scene.light_sources.entry(*entity).or_insert_with(|| {
+            let mut node = if is_spot {
+                SpotLight3D::new_alloc().upcast::<Light3D>()
+            } else {
+                OmniLight3D::new_alloc().upcast::<Light3D>()
+            };
+            node.set_name("LightSource");
+            // Hidden until the budget pass in SceneManager enables it.
+            node.set_visible(false);
+            node_3d.add_child(&node.clone().upcast::<Node>());
+            LightSourceItem {
+                node,
+                is_spot,
+                active: false,
+                wants_shadow: false,
+            }
+        });
+
+        apply_light_source(item, &new_value);
+    }
+}
+
+fn apply_light_source(item: &mut LightSourceItem, value: &PbLightSource) {
+    item.active = value.active.unwrap_or(true);
+    item.wants_shadow = value.shadow.unwrap_or(false);
+
+    let node = &mut item.node;
+    let color = value
+        .color
+        .as_ref()
+        .map(|color| color.to_godot())
+        .unwrap_or(Color::WHITE);
+    node.set_color(color);
+    node.set_param(Param::ENERGY, intensity_to_energy(value.intensity));
+    node.set_param(Param::RANGE, sanitize_range(value.range));
+
+    if let Some(pb_light_source::Type::Spot(spot)) = &value.r#type {
+        let (spot_angle, spot_attenuation) = spot_params(spot.inner_angle, spot.outer_angle);
+        node.set_param(Param::SPOT_ANGLE, spot_angle);
+        node.set_param(Param::SPOT_ATTENUATION, spot_attenuation);
+    }
+
+    if !item.active {
+        node.set_visible(false);
+        node.set_shadow(false);
+    }
+}
+
+fn remove_light_source(
+    light_sources: &mut HashMap<SceneEntityId, LightSourceItem>,
+    entity: &SceneEntityId,
+) {
+    if let Some(mut item) = light_sources.remove(entity) {
+        if let Some(mut parent) = item.node.get_parent() {
+            parent.remove_child(&item.node.clone().upcast::<Node>());
+        }
+        item.node.queue_free();
+    }
+}
+
+fn intensity_to_energy(intensity: Option<f32>) -> f32 {
+    let intensity = intensity.unwrap_or(DEFAULT_INTENSITY);
+    if !intensity.is_finite() {
+        return 1.0;
+    }
+    (intensity / DEFAULT_INTENSITY).clamp(0.0, MAX_ENERGY)
+}
+
+fn sanitize_range(range: Option<f32>) -> f32 {
+    match range {
+        // Negative (or missing) range means "let the renderer decide".
+        Some(range) if range.is_finite() && range > 0.0 => range.min(MAX_RANGE),
+        _ => DEFAULT_RANGE,
+    }
+}
+
+/// Converts the SDK full cone angles into Godot's half `spot_angle` and the
+/// `spot_angle_attenuation` exponent. Godot has no inner cone, so the
+/// inner/outer ratio drives how hard the edge is: an inner angle close to the
+/// outer one gives a sharp falloff near the border.
+fn spot_params(inner_angle: Option<f32>, outer_angle: Option<f32>) -> (f32, f32) {
+    let outer = outer_angle
+        .filter(|angle| angle.is_finite())
+        .unwrap_or(DEFAULT_SPOT_OUTER_ANGLE)
+        .clamp(0.0, 179.0);
+    // Inner can't exceed outer; the SDK clamps it to the same value.
+    let inner = inner_angle
+        .filter(|angle| angle.is_finite())
+        .unwrap_or(DEFAULT_SPOT_INNER_ANGLE)
+        .clamp(0.0, outer);
+
+    let spot_angle = (outer * 0.5).min(MAX_SPOT_HALF_ANGLE);
+    let ratio = if outer > 0.0 { inner / outer } else { 1.0 };
+    let attenuation = (1.0 / (1.0 - ratio).max(1.0 / 16.0)).clamp(0.5, 16.0);
+    (spot_angle, attenuation)
+}
+
+/// Picks which lights stay on and which may cast shadows. Candidates are
+/// `(entity, squared distance to the camera, wants_shadow)` for active lights;
+/// the nearest ones win both budgets.
+pub fn select_budgeted_lights(
+    mut candidates: Vec<(SceneEntityId, f32, bool)>,
+    max_lights: usize,
+    max_shadowed_lights: usize,
+) -> Vec<LightBudgetSlot> {
+    candidates.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    let mut shadowed = 0;
+    candidates
+        .into_iter()
+        .enumerate()
+        .map(|(index, (entity, _, wants_shadow))| {
+            let enabled = index < max_lights;
+            let shadow = enabled && wants_shadow && shadowed < max_shadowed_lights;
+            if shadow {
+                shadowed += 1;
+            }
+            LightBudgetSlot {
+                entity,
+                enabled,
+                shadow,
+            }
+        })
+        .collect()
+}
+
+/// Applies the per-scene light budget. Called by the SceneManager every frame
+/// for scenes that own lights, since the nearest lights change as the camera moves.
+pub fn enforce_light_budget(
+    scene: &mut Scene,
+    camera_position: Vector3,
+    max_lights: usize,
+    max_shadowed_lights: usize,
+) {
+    let candidates = scene
+        .light_sources
+        .iter()
+        .filter(|(_, item)| item.active && item.node.is_inside_tree())
+        .map(|(entity, item)| {
+            let distance_squared = item
+                .node
+                .get_global_position()
+                .distance_squared_to(camera_position);
+            (*entity, distance_squared, item.wants_shadow)
+        })
+        .collect::<Vec<_>>();
+
+    for slot in select_budgeted_lights(candidates, max_lights, max_shadowed_lights) {
+        let Some(item) = scene.light_sources.get_mut(&slot.entity) else {
+            continue;
+        };
+        if item.node.is_visible() != slot.enabled {
+            item.node.set_visible(slot.enabled);
+        }
+        if item.node.has_shadow() != slot.shadow {
+            item.node.set_shadow(slot.shadow);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spot_params_clamp_inner_to_outer() {
+        let (angle, attenuation) = spot_params(Some(60.0), Some(40.0));
+        assert_eq!(angle, 20.0);
+        // inner == outer => hardest edge
+        assert_eq!(attenuation, 16.0);
+    }
+
+    #[test]
+    fn spot_params_defaults() {
+        let (angle, attenuation) = spot_params(None, None);
+        assert_eq!(angle, DEFAULT_SPOT_OUTER_ANGLE * 0.5);
+        assert!(attenuation > 1.0 && attenuation < 16.0);
+    }
+
+    #[test]
+    fn range_and_energy_are_sanitized() {
+        assert_eq!(sanitize_range(None), DEFAULT_RANGE);
+        assert_eq!(sanitize_range(Some(-1.0)), DEFAULT_RANGE);
+        assert_eq!(sanitize_range(Some(1000.0)), MAX_RANGE);
+        assert_eq!(intensity_to_energy(None), 1.0);
+        assert_eq!(intensity_to_energy(Some(f32::MAX)), MAX_ENERGY);
+        assert_eq!(intensity_to_energy(Some(-5.0)), 0.0);
+    }
+
+    #[test]
+    fn budget_prefers_nearest_lights() {
+        let far = SceneEntityId::new(600, 0);
+        let near = SceneEntityId::new(601, 0);
+        let mid = SceneEntityId::new(602, 0);
+        let slots = select_budgeted_lights(
+            vec![(far, 100.0, true), (near, 1.0, true), (mid, 10.0, true)],
+            2,
+            1,
+        );
+
+        assert_eq!(
+            slots,
+            vec![
+                LightBudgetSlot {
+                    entity: near,
+                    enabled: true,
+                    shadow: true
+                },
+                LightBudgetSlot {
+                    entity: mid,
+                    enabled: true,
+                    shadow: false
+                },
+                LightBudgetSlot {
+                    entity: far,
+                    enabled: false,
+                    shadow: false
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn shadow_budget_skips_lights_without_shadows() {
+        let a = SceneEntityId::new(600, 0);
+        let b = SceneEntityId::new(601, 0);
+        let slots = select_budgeted_lights(vec![(a, 1.0, false), (b, 2.0, true)], 8, 1);
+        assert!(!slots[0].shadow);
+        assert!(slots[1].shadow);
+    }
+}
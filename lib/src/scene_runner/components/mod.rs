@@ -1,5 +1,6 @@
 pub mod animator;
 pub mod asset_load;
+pub mod audio_analysis;
 pub mod audio_source;
 pub mod audio_stream;
 pub mod avatar_attach;
@@ -12,6 +13,7 @@ pub mod camera_mode_area;
 pub mod gltf_container;
 pub mod gltf_node_modifiers;
 pub mod input_modifier;
+pub mod light_source;
 pub mod material;
 pub mod mesh_collider;
 pub mod mesh_renderer;
@@ -11,7 +11,7 @@ use crate::{
         components::{
             proto_components::sdk::components::{
                 pb_tween::Mode, EasingFunction, PbTween, PbTweenState, TextureMovementType,
-                TweenStateStatus,
+                TweenLoop, TweenStateStatus,
             },
             transform_and_parent::DclTransformAndParent,
             SceneComponentId,
@@ -40,6 +40,18 @@ pub struct Tween {
     /// removes ~50 % of all `dirty_lww_entries/frame` in GP (measured
     /// 2026-05-06: TweenState=135/frame, ≈51 % of recv pressure).
     pub last_emitted_state: Option<i32>,
+    /// The entity's own `PBTween` value. `data` holds the step currently
+    /// playing, which differs from it while a `PBTweenSequence` is running.
+    pub base: PbTween,
+    /// Sequence step being played: 0 is `base`, N is `sequence[N - 1]`.
+    pub sequence_index: usize,
+    /// Playing the current step backwards (return leg of a yoyo loop).
+    pub reversed: bool,
+    /// When the current step finished, if there's a next step to advance to.
+    /// The advance happens on the following tick so the scene observes the
+    /// step's `TsCompleted`; starting the next step at this instant (instead of
+    /// `now`) keeps the motion continuous.
+    pub completed_at: Option<std::time::Instant>,
 }
 
 impl Tween {
@@ -48,6 +60,91 @@ impl Tween {
     }
 }
 
+/// Tweens queued after the entity's `PBTween` by a `PBTweenSequence`.
+pub struct TweenSequence {
+    pub sequence: Vec<PbTween>,
+    pub loop_mode: Option<TweenLoop>,
+}
+
+/// Returns the `(sequence_index, reversed)` to play after the current step,
+/// or `None` when the sequence is over. `len` counts the base tween too.
+///
+/// - no loop: plays every step once.
+/// - `TlRestart`: jumps back to the base tween after the last step.
+/// - `TlYoyo`: plays every step backwards in reverse order, then forwards again.
+fn next_sequence_step(
+    index: usize,
+    reversed: bool,
+    len: usize,
+    loop_mode: Option<TweenLoop>,
+) -> Option<(usize, bool)> {
+    if len == 0 {
+        return None;
+    }
+    if reversed {
+        return if index > 0 {
+            Some((index - 1, true))
+        } else {
+            Some((0, false))
+        };
+    }
+    if index + 1 < len {
+        return Some((index + 1, false));
+    }
+    match loop_mode {
+        None => None,
+        Some(TweenLoop::TlRestart) => Some((0, false)),
+        Some(TweenLoop::TlYoyo) => Some((len - 1, true)),
+    }
+}
+
+/// Moves `tween` to the next step of its sequence. Returns false when the
+/// sequence has finished and the tween should stay completed.
+fn advance_sequence(
+    tween: &mut Tween,
+    sequence: &TweenSequence,
+    completed_at: std::time::Instant,
+) -> bool {
+    let len = sequence.sequence.len() + 1;
+    let Some((index, reversed)) = next_sequence_step(
+        tween.sequence_index,
+        tween.reversed,
+        len,
+        sequence.loop_mode,
+    ) else {
+        return false;
+    };
+
+    let step = if index == 0 {
+        tween.base.clone()
+    } else {
+        sequence.sequence[index - 1].clone()
+    };
+
+    let offset_time_ms = step.duration * step.current_time();
+    tween.start_time = completed_at - std::time::Duration::from_millis(offset_time_ms as u64);
+    tween.ease_fn = get_ease_fn(
+        EasingFunction::from_i32(step.easing_function).unwrap_or(EasingFunction::EfLinear),
+    );
+    tween.data = PbTween {
+        // Pausing is controlled by the entity's own PBTween for the whole sequence.
+        playing: tween.base.playing,
+        ..step
+    };
+    tween.playing = tween.data.playing;
+    tween.sequence_index = index;
+    tween.reversed = reversed;
+    tween.last_emitted_state = None;
+    true
+}
+
+/// A PBTween update that only pauses/resumes keeps the running sequence step.
+fn only_playing_changed(old: &PbTween, new: &PbTween) -> bool {
+    let mut old = old.clone();
+    old.playing = new.playing;
+    old == *new
+}
+
 fn get_ease_fn(ease_type: EasingFunction) -> fn(f32) -> f32 {
     match ease_type {
         EasingFunction::EfLinear => simple_easing::linear,
@@ -159,6 +256,31 @@ pub fn update_tween(scene: &mut Scene, crdt_state: &mut SceneCrdtState) {
     let mut tweens_to_delete = Vec::new();
     let mut texture_animations_to_apply = Vec::new();
 
+    if let Some(tween_sequence_dirty) = dirty_lww_components.get(&SceneComponentId::TWEEN_SEQUENCE)
+    {
+        let tween_sequence_component =
+            SceneCrdtStateProtoComponents::get_tween_sequence(crdt_state);
+        for entity in tween_sequence_dirty {
+            let new_value = tween_sequence_component
+                .get(entity)
+                .and_then(|entry| entry.value.as_ref());
+            match new_value {
+                Some(new_value) => {
+                    scene.tween_sequences.insert(
+                        *entity,
+                        TweenSequence {
+                            sequence: new_value.sequence.clone(),
+                            loop_mode: new_value.r#loop.and_then(TweenLoop::from_i32),
+                        },
+                    );
+                }
+                None => {
+                    scene.tween_sequences.remove(entity);
+                }
+            }
+        }
+    }
+
     if let Some(tween_dirty) = dirty_lww_components.get(&SceneComponentId::TWEEN) {
         for entity in tween_dirty {
             let new_value = tween_component.get(entity);
@@ -194,9 +316,20 @@ pub fn update_tween(scene: &mut Scene, crdt_state: &mut SceneCrdtState) {
                         }
                     }
 
-                    // reset tween when the mode changes, or we have a new current time
+                    let in_sequence_step =
+                        existing_tween.sequence_index != 0 || existing_tween.reversed;
+                    if in_sequence_step && only_playing_changed(&existing_tween.base, &new_value) {
+                        // pause/resume of a running sequence: keep the current step
+                        existing_tween.data.playing = new_value.playing;
+                        existing_tween.base = new_value;
+                        continue;
+                    }
+
+                    // reset tween when the mode changes, we have a new current time,
+                    // or a sequence step was playing (any other change restarts the sequence)
                     let reset_tween = existing_tween.data.mode != new_value.mode
-                        || new_value.current_time.is_some();
+                        || new_value.current_time.is_some()
+                        || in_sequence_step;
                     if reset_tween {
                         existing_tween.start_time = now - offset_time;
                         // Force re-emission of TweenState on the next tick.
@@ -209,9 +342,17 @@ pub fn update_tween(scene: &mut Scene, crdt_state: &mut SceneCrdtState) {
                         // completes again, breaking SDK consumers that
                         // re-arm the same entity.
                         existing_tween.last_emitted_state = None;
+                        existing_tween.sequence_index = 0;
+                        existing_tween.reversed = false;
+                        existing_tween.completed_at = None;
+                        existing_tween.ease_fn = get_ease_fn(
+                            EasingFunction::from_i32(new_value.easing_function)
+                                .unwrap_or(EasingFunction::EfLinear),
+                        );
                     }
 
                     // copy new tween values
+                    existing_tween.base = new_value.clone();
                     existing_tween.data.current_time = new_value.current_time;
                     existing_tween.data.mode = new_value.mode;
                     existing_tween.data.duration = new_value.duration;
@@ -231,12 +372,16 @@ pub fn update_tween(scene: &mut Scene, crdt_state: &mut SceneCrdtState) {
                             ease_fn: get_ease_fn(
                                 EasingFunction::from_i32(new_value.easing_function).unwrap(),
                             ),
+                            base: new_value.clone(),
                             data: new_value,
                             start_time: now - offset_time,
                             paused_time,
                             playing: None,
                             last_update: now,
                             last_emitted_state: None,
+                            sequence_index: 0,
+                            reversed: false,
+                            completed_at: None,
                         },
                     );
                 };
@@ -254,6 +399,13 @@ pub fn update_tween(scene: &mut Scene, crdt_state: &mut SceneCrdtState) {
     }
 
     for (entity, tween) in &mut scene.tweens {
+        // The previous step of a sequence completed last tick: start the next one.
+        if let Some(completed_at) = tween.completed_at.take() {
+            if let Some(sequence) = scene.tween_sequences.get(entity) {
+                advance_sequence(tween, sequence, completed_at);
+            }
+        }
+
         if tween.playing == Some(false) {
             continue;
         }
@@ -281,6 +433,21 @@ pub fn update_tween(scene: &mut Scene, crdt_state: &mut SceneCrdtState) {
             if elapsed_time >= duration {
                 tween.playing = Some(false);
                 current_tween_state = TweenStateStatus::TsCompleted;
+                if tween.last_emitted_state != Some(TweenStateStatus::TsCompleted as i32) {
+                    if let Some(sequence) = scene.tween_sequences.get(entity) {
+                        let len = sequence.sequence.len() + 1;
+                        if next_sequence_step(
+                            tween.sequence_index,
+                            tween.reversed,
+                            len,
+                            sequence.loop_mode,
+                        )
+                        .is_some()
+                        {
+                            tween.completed_at = Some(tween.start_time + duration);
+                        }
+                    }
+                }
                 1.0 // finished
             } else {
                 tween.get_progress(elapsed_time)
@@ -326,15 +493,25 @@ pub fn update_tween(scene: &mut Scene, crdt_state: &mut SceneCrdtState) {
             .and_then(|transform| transform.value.clone())
             .unwrap_or_default();
 
-        // calculate new transform with the tween
-        let ease_value = (tween.ease_fn)(progress);
+        // calculate new transform with the tween; a reversed (yoyo) step plays
+        // the same curve backwards
+        let curve_position = if tween.reversed {
+            1.0 - progress
+        } else {
+            progress
+        };
+        let ease_value = (tween.ease_fn)(curve_position);
         let new_transform = match &tween.data.mode {
             Some(Mode::Move(data)) => {
                 let start = data.start.clone().unwrap().to_godot();
                 let end = data.end.clone().unwrap().to_godot();
 
                 if data.face_direction == Some(true) {
-                    let direction = (end - start).normalized();
+                    let direction = if tween.reversed {
+                        (start - end).normalized()
+                    } else {
+                        (end - start).normalized()
+                    };
                     let basis = if direction.is_zero_approx() {
                         Basis::IDENTITY
                     } else {
@@ -527,3 +704,70 @@ pub fn update_tween(scene: &mut Scene, crdt_state: &mut SceneCrdtState) {
         apply_texture_animation_to_entity(scene, &entity, &tex_anim);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sequence_without_loop_ends_after_last_step() {
+        assert_eq!(next_sequence_step(0, false, 3, None), Some((1, false)));
+        assert_eq!(next_sequence_step(1, false, 3, None), Some((2, false)));
+        assert_eq!(next_sequence_step(2, false, 3, None), None);
+    }
+
+    #[test]
+    fn sequence_restart_goes_back_to_base_tween() {
+        assert_eq!(
+            next_sequence_step(2, false, 3, Some(TweenLoop::TlRestart)),
+            Some((0, false))
+        );
+        // a lone PBTween with a restart loop just replays itself
+        assert_eq!(
+            next_sequence_step(0, false, 1, Some(TweenLoop::TlRestart)),
+            Some((0, false))
+        );
+    }
+
+    #[test]
+    fn sequence_yoyo_plays_back_in_reverse_order() {
+        let yoyo = Some(TweenLoop::TlYoyo);
+        let mut step = (0, false);
+        let mut visited = vec![step];
+        for _ in 0..6 {
+            step = next_sequence_step(step.0, step.1, 3, yoyo).unwrap();
+            visited.push(step);
+        }
+        assert_eq!(
+            visited,
+            vec![
+                (0, false),
+                (1, false),
+                (2, false),
+                (2, true),
+                (1, true),
+                (0, true),
+                (0, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn pause_toggle_is_detected() {
+        let tween = PbTween {
+            duration: 1000.0,
+            playing: Some(true),
+            ..Default::default()
+        };
+        let paused = PbTween {
+            playing: Some(false),
+            ..tween.clone()
+        };
+        let longer = PbTween {
+            duration: 2000.0,
+            ..tween.clone()
+        };
+        assert!(only_playing_changed(&tween, &paused));
+        assert!(!only_playing_changed(&tween, &longer));
+    }
+}
@@ -20,6 +20,7 @@ use crate::{
         godot_dcl_scene::VideoPlayerData,
         scene::{Scene, SceneType},
     },
+    tools::scene_inspector::log_permission_denied,
 };
 
 /// Position change threshold in seconds - emit event if position changes by more than this
@@ -68,6 +69,15 @@ pub fn update_video_player(
                 .and_then(|v| v.value.as_ref());
 
             if let Some(next_value) = next_value {
+                if let Err(denied) = scene.permissions.check_media_url(&next_value.src) {
+                    tracing::debug!("scene {}: {denied}", scene.scene_id.0);
+                    log_permission_denied(
+                        scene.scene_id.0,
+                        denied.permission.as_str(),
+                        &denied.action,
+                    );
+                    continue;
+                }
                 let target_src = next_value.src.clone();
 
                 tracing::debug!(
@@ -43,7 +43,9 @@ pub fn update_deleted_entities(scene: &mut Scene, pools: &mut PoolManager) {
         godot_dcl_scene.entities.remove(deleted_entity);
 
         scene.audio_sources.remove(deleted_entity);
+        scene.audio_analysis.remove(deleted_entity);
         scene.particle_systems.remove(deleted_entity);
+        scene.light_sources.remove(deleted_entity);
         scene.audio_streams.remove(deleted_entity);
         scene.video_players.remove(deleted_entity);
         scene.dup_animator.remove(deleted_entity);
@@ -56,6 +58,7 @@ pub fn update_deleted_entities(scene: &mut Scene, pools: &mut PoolManager) {
         }
         scene.continuos_raycast.remove(deleted_entity);
         scene.tweens.remove(deleted_entity);
+        scene.tween_sequences.remove(deleted_entity);
         scene.texture_animations.remove(deleted_entity);
         // Release any PBAssetLoad preloads this entity held (drops refcounts and
         // the retained PackedScene, and stops per-tick iteration over it).
@@ -0,0 +1,686 @@
+use std::{
+    collections::{BTreeSet, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use godot::prelude::*;
+use serde::Serialize;
+use tokio::sync::mpsc::error::TrySendError;
+
+use crate::{
+    auth::ethereum_provider::EthereumProvider,
+    dcl::{
+        common::{set_scene_log_enabled, SceneLogLevel},
+        components::{
+            proto_components::sdk::components::{
+                common::{InputAction, PointerEventType},
+                PbCameraMode, PbEngineInfo, PbPointerEventsResult, PbPointerLock, PbRaycastResult,
+                PbUiCanvasInformation,
+            },
+            transform_and_parent::DclTransformAndParent,
+            SceneComponentId, SceneEntityId,
+        },
+        crdt::{
+            grow_only_set::GenericGrowOnlySetComponentOperation,
+            last_write_wins::LastWriteWinsComponentOperation, DirtyCrdtState, SceneCrdtState,
+            SceneCrdtStateProtoComponents,
+        },
+        scene_apis::RpcCall,
+        scene_permissions::ScenePermissions,
+        scene_quotas::SceneQuotas,
+        DclScene, DclSceneRealmData, RendererResponse, SceneResponse, SpawnDclSceneData,
+    },
+    realm::scene_definition::SceneEntityDefinition,
+    scene_runner::scene::Scene,
+};
+
+const DEFAULT_MAX_TICKS: u32 = 300;
+/// Wall-clock limit, a scene stuck in an infinite loop never reaches max_ticks
+const HEADLESS_SCENE_TIMEOUT: Duration = Duration::from_secs(120);
+/// Tick in which a click (down + up) is simulated on every entity with PointerEvents
+const SIMULATED_CLICK_TICK: u32 = 5;
+const NOT_AVAILABLE: &str = "not available in headless mode";
+
+#[derive(Serialize, Default)]
+struct HeadlessTestResult {
+    name: String,
+    ok: bool,
+    error: Option<String>,
+}
+
+#[derive(Serialize, Default)]
+struct HeadlessSceneReport {
+    scene: String,
+    ticks: u32,
+    finish_reason: String,
+    success: bool,
+    exceptions: Vec<String>,
+    errors: Vec<String>,
+    crdt_issues: BTreeSet<String>,
+    entities_alive: usize,
+    test_plan: Vec<String>,
+    test_results: Vec<HeadlessTestResult>,
+}
+
+struct HeadlessRun {
+    dcl_scene: DclScene,
+    receiver: std::sync::mpsc::Receiver<SceneResponse>,
+    report_path: String,
+    max_ticks: u32,
+    start_time: Instant,
+    tick_number: u32,
+    continuous_raycasts: HashSet<SceneEntityId>,
+    pending_response: Option<RendererResponse>,
+    report: HeadlessSceneReport,
+}
+
+/// Runs a single scene from a local folder without rendering it: the scene
+/// JS runs in its own thread as usual, and this node plays the renderer part
+/// by feeding EngineInfo ticks, player/camera state, empty raycast results and
+/// a simulated click. Used by `--headless-scene` to smoke-test scene bundles in CI.
+#[derive(GodotClass)]
+#[class(init, base=Node)]
+pub struct DclHeadlessSceneRunner {
+    run: Option<HeadlessRun>,
+    base: Base<Node>,
+}
+
+#[godot_api]
+impl INode for DclHeadlessSceneRunner {
+    fn process(&mut self, _delta: f64) {
+        let Some(run) = self.run.as_mut() else {
+            return;
+        };
+
+        let finish_reason = run.poll();
+        if let Some(reason) = finish_reason {
+            let success = self.finish(reason);
+            self.base_mut()
+                .emit_signal("finished", &[success.to_variant()]);
+        }
+    }
+}
+
+#[godot_api]
+impl DclHeadlessSceneRunner {
+    #[signal]
+    fn finished(success: bool);
+
+    /// Spawns the scene in `scene_folder` (the folder with the scene.json).
+    /// The run ends after `max_ticks` ticks (<= 0 uses the default), and the
+    /// JSON report is written to `report_path` when it isn't empty.
+    #[func]
+    fn start(&mut self, scene_folder: GString, max_ticks: i32, report_path: GString) -> bool {
+        let scene_folder = PathBuf::from(scene_folder.to_string());
+        match spawn_headless_scene(&scene_folder) {
+            Ok((dcl_scene, receiver)) => {
+                self.run = Some(HeadlessRun {
+                    dcl_scene,
+                    receiver,
+                    report_path: report_path.to_string(),
+                    max_ticks: if max_ticks > 0 {
+                        max_ticks as u32
+                    } else {
+                        DEFAULT_MAX_TICKS
+                    },
+                    start_time: Instant::now(),
+                    tick_number: 0,
+                    continuous_raycasts: HashSet::new(),
+                    pending_response: None,
+                    report: HeadlessSceneReport {
+                        scene: scene_folder.to_string_lossy().to_string(),
+                        ..Default::default()
+                    },
+                });
+                true
+            }
+            Err(err) => {
+                tracing::error!("headless scene {}: {err}", scene_folder.display());
+                false
+            }
+        }
+    }
+
+    fn finish(&mut self, reason: String) -> bool {
+        let Some(mut run) = self.run.take() else {
+            return false;
+        };
+
+        if let Err(err) = run
+            .dcl_scene
+            .main_sender_to_thread
+            .try_send(RendererResponse::Kill)
+        {
+            tracing::debug!("headless scene kill not delivered: {err}");
+        }
+
+        if let Ok(crdt_state) = run.dcl_scene.scene_crdt.try_lock() {
+            run.report.entities_alive = crdt_state
+                .entities
+                .used_entities()
+                .filter(|(_, _, live)| *live)
+                .count();
+        }
+
+        let report = &mut run.report;
+        report.ticks = run.tick_number;
+        report.success = (reason == "max_ticks")
+            && report.exceptions.is_empty()
+            && report.crdt_issues.is_empty()
+            && report.test_results.iter().all(|result| result.ok);
+        report.finish_reason = reason;
+
+        tracing::info!(
+            "headless scene finished ({}) after {} ticks: {} exceptions, {} errors, {} crdt issues",
+            report.finish_reason,
+            report.ticks,
+            report.exceptions.len(),
+            report.errors.len(),
+            report.crdt_issues.len()
+        );
+
+        if !run.report_path.is_empty() {
+            let json = serde_json::to_string_pretty(&run.report).unwrap_or_default();
+            if let Err(err) = std::fs::write(&run.report_path, json) {
+                tracing::error!("failed to write report {}: {err}", run.report_path);
+            }
+        }
+
+        run.report.success
+    }
+}
+
+impl HeadlessRun {
+    /// Processes the pending scene messages, returns the finish reason when the run is over.
+    fn poll(&mut self) -> Option<String> {
+        if let Some(response) = self.pending_response.take() {
+            if let Err(reason) = self.send(response) {
+                return Some(reason);
+            }
+        }
+
+        while self.pending_response.is_none() {
+            let response = match self.receiver.try_recv() {
+                Ok(response) => response,
+                Err(std::sync::mpsc::TryRecvError::Empty) => break,
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    return Some("scene_thread_exited".to_string());
+                }
+            };
+
+            match response {
+                SceneResponse::Ok {
+                    dirty_crdt_state,
+                    logs,
+                    rpc_calls,
+                    ..
+                } => {
+                    self.collect_logs(logs.iter().map(|log| (log.level, &log.message)));
+                    self.process_rpcs(rpc_calls);
+
+                    if self.tick_number >= self.max_ticks {
+                        return Some("max_ticks".to_string());
+                    }
+
+                    let response = {
+                        let mut crdt_state = self.dcl_scene.scene_crdt.lock().unwrap();
+                        if dirty_crdt_state
+                            .lww
+                            .contains_key(&SceneComponentId::TRANSFORM)
+                            || !dirty_crdt_state.entities.died.is_empty()
+                        {
+                            self.report
+                                .crdt_issues
+                                .extend(validate_transform_tree(&crdt_state));
+                        }
+                        self.build_renderer_response(&mut crdt_state, &dirty_crdt_state)
+                    };
+                    self.tick_number += 1;
+
+                    if let Err(reason) = self.send(response) {
+                        return Some(reason);
+                    }
+                }
+                SceneResponse::Error(_, message) => {
+                    self.report.exceptions.push(message.clone());
+                    return Some(format!("scene_error: {message}"));
+                }
+                SceneResponse::QuotaExceeded(_, breach) => {
+                    self.report
+                        .exceptions
+                        .push(format!("scene killed, {breach}"));
+                }
+                SceneResponse::RemoveGodotScene(_, logs) => {
+                    self.collect_logs(logs.iter().map(|log| (log.level, &log.message)));
+                    return Some("scene_removed".to_string());
+                }
+                SceneResponse::TakeSnapshot { response, .. } => {
+                    response.send(Err(NOT_AVAILABLE.to_string()));
+                }
+            }
+        }
+
+        if self.start_time.elapsed() > HEADLESS_SCENE_TIMEOUT {
+            return Some("timeout".to_string());
+        }
+        None
+    }
+
+    fn send(&mut self, response: RendererResponse) -> Result<(), String> {
+        match self.dcl_scene.main_sender_to_thread.try_send(response) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(response)) => {
+                self.pending_response = Some(response);
+                Ok(())
+            }
+            Err(TrySendError::Closed(_)) => Err("scene_thread_exited".to_string()),
+        }
+    }
+
+    fn collect_logs<'a>(&mut self, logs: impl Iterator<Item = (SceneLogLevel, &'a String)>) {
+        for (level, message) in logs {
+            match level {
+                SceneLogLevel::Log => {}
+                SceneLogLevel::SceneError => self.report.errors.push(message.clone()),
+                SceneLogLevel::SystemError => self.report.exceptions.push(message.clone()),
+            }
+        }
+    }
+
+    fn process_rpcs(&mut self, rpc_calls: Vec<RpcCall>) {
+        for rpc_call in rpc_calls {
+            match rpc_call {
+                RpcCall::ChangeRealm { response, .. }
+                | RpcCall::TeleportTo { response, .. }
+                | RpcCall::OpenNftDialog { response, .. }
+                | RpcCall::OpenExternalUrl { response, .. } => {
+                    response.send(Err(NOT_AVAILABLE.to_string()));
+                }
+                RpcCall::SpawnPortable { response, .. } => {
+                    response.send(Err(NOT_AVAILABLE.to_string()));
+                }
+                RpcCall::KillPortable { response, .. } => response.send(false),
+                RpcCall::ListPortables { response } => response.send(Vec::new()),
+                RpcCall::SendAsync { response, .. } => {
+                    response.send(Err(NOT_AVAILABLE.to_string()));
+                }
+                RpcCall::GetTextureSize { response, .. } => {
+                    response.send(Err(NOT_AVAILABLE.to_string()));
+                }
+                RpcCall::VoiceGetState { response } => response.send(Default::default()),
+                RpcCall::SceneTestPlan { body } => {
+                    self.report.test_plan = body.tests.into_iter().map(|test| test.name).collect();
+                }
+                RpcCall::SceneTestResult { body } => {
+                    self.report.test_results.push(HeadlessTestResult {
+                        name: body.name,
+                        ok: body.ok,
+                        error: body.error,
+                    });
+                }
+                RpcCall::MovePlayerTo { .. }
+                | RpcCall::TriggerEmote { .. }
+                | RpcCall::TriggerSceneEmote { .. }
+                | RpcCall::SendCommsMessage { .. }
+                | RpcCall::VoiceSetSceneChannel { .. }
+                | RpcCall::VoiceSetMicrophoneAllowed { .. }
+                | RpcCall::VoiceSetPlayerMuted { .. } => {}
+            }
+        }
+    }
+
+    fn build_renderer_response(
+        &mut self,
+        crdt_state: &mut SceneCrdtState,
+        scene_dirty: &DirtyCrdtState,
+    ) -> RendererResponse {
+        let tick_number = self.tick_number;
+
+        SceneCrdtStateProtoComponents::get_engine_info_mut(crdt_state).put(
+            SceneEntityId::ROOT,
+            Some(PbEngineInfo {
+                tick_number,
+                frame_number: tick_number,
+                total_runtime: self.start_time.elapsed().as_secs_f32(),
+            }),
+        );
+
+        if tick_number == 0 {
+            // the player stands still in the middle of the base parcel
+            let player_transform = DclTransformAndParent {
+                translation: Vector3::new(8.0, 0.0, 8.0),
+                ..Default::default()
+            };
+            let camera_transform = DclTransformAndParent {
+                translation: Vector3::new(8.0, 1.75, 8.0),
+                ..Default::default()
+            };
+            let transform = crdt_state.get_transform_mut();
+            transform.put(SceneEntityId::PLAYER, Some(player_transform));
+            transform.put(SceneEntityId::CAMERA, Some(camera_transform));
+
+            SceneCrdtStateProtoComponents::get_camera_mode_mut(crdt_state)
+                .put(SceneEntityId::CAMERA, Some(PbCameraMode { mode: 0 }));
+            SceneCrdtStateProtoComponents::get_pointer_lock_mut(crdt_state).put(
+                SceneEntityId::CAMERA,
+                Some(PbPointerLock {
+                    is_pointer_locked: false,
+                }),
+            );
+            SceneCrdtStateProtoComponents::get_ui_canvas_information_mut(crdt_state).put(
+                SceneEntityId::ROOT,
+                Some(PbUiCanvasInformation {
+                    device_pixel_ratio: 1.0,
+                    width: 1280,
+                    height: 720,
+                    interactable_area: None,
+                    screen_inset_area: None,
+                }),
+            );
+        }
+
+        self.update_raycasts(crdt_state, scene_dirty);
+        if tick_number == SIMULATED_CLICK_TICK {
+            simulate_click(crdt_state, tick_number);
+        }
+
+        RendererResponse::Ok {
+            dirty_crdt_state: Box::new(crdt_state.take_dirty()),
+            incoming_comms_message: Vec::new(),
+        }
+    }
+
+    /// Every raycast misses: there is no physics world to hit.
+    fn update_raycasts(&mut self, crdt_state: &mut SceneCrdtState, scene_dirty: &DirtyCrdtState) {
+        let raycast_component = SceneCrdtStateProtoComponents::get_raycast(crdt_state);
+        let mut one_shot_raycasts = Vec::new();
+        if let Some(dirty) = scene_dirty.lww.get(&SceneComponentId::RAYCAST) {
+            for entity in dirty {
+                match raycast_component
+                    .get(entity)
+                    .and_then(|entry| entry.value.as_ref())
+                {
+                    Some(raycast) if raycast.continuous() => {
+                        self.continuous_raycasts.insert(*entity);
+                    }
+                    Some(_) => {
+                        self.continuous_raycasts.remove(entity);
+                        one_shot_raycasts.push(*entity);
+                    }
+                    None => {
+                        self.continuous_raycasts.remove(entity);
+                    }
+                }
+            }
+        }
+
+        let results: Vec<_> = one_shot_raycasts
+            .iter()
+            .chain(self.continuous_raycasts.iter())
+            .filter_map(|entity| {
+                let raycast = raycast_component.get(entity)?.value.as_ref()?;
+                Some((
+                    *entity,
+                    PbRaycastResult {
+                        timestamp: raycast.timestamp,
+                        tick_number: self.tick_number,
+                        ..Default::default()
+                    },
+                ))
+            })
+            .collect();
+
+        let raycast_result_component =
+            SceneCrdtStateProtoComponents::get_raycast_result_mut(crdt_state);
+        for (entity, result) in results {
+            raycast_result_component.put(entity, Some(result));
+        }
+    }
+}
+
+/// Appends a down and an up result for each PET_DOWN/PET_UP listener.
+fn simulate_click(crdt_state: &mut SceneCrdtState, tick_number: u32) {
+    let mut results = Vec::new();
+    let pointer_events = SceneCrdtStateProtoComponents::get_pointer_events(crdt_state);
+    for (entity, entry) in pointer_events.values.iter() {
+        let Some(value) = entry.value.as_ref() else {
+            continue;
+        };
+        for pointer_event in value.pointer_events.iter() {
+            if pointer_event.event_type != PointerEventType::PetDown as i32
+                && pointer_event.event_type != PointerEventType::PetUp as i32
+            {
+                continue;
+            }
+            let button = pointer_event
+                .event_info
+                .as_ref()
+                .and_then(|info| info.button)
+                .unwrap_or(InputAction::IaPointer as i32);
+            results.push((
+                *entity,
+                PbPointerEventsResult {
+                    button,
+                    hit: None,
+                    state: pointer_event.event_type,
+                    timestamp: results.len() as u32,
+                    analog: None,
+                    tick_number,
+                },
+            ));
+        }
+    }
+
+    let pointer_events_result =
+        SceneCrdtStateProtoComponents::get_pointer_events_result_mut(crdt_state);
+    for (entity, result) in results {
+        pointer_events_result.append(entity, result);
+    }
+}
+
+/// Reports transforms the renderer can't lay out: self parenting, parent
+/// cycles and parents that were already deleted.
+fn validate_transform_tree(crdt_state: &SceneCrdtState) -> Vec<String> {
+    let transforms = crdt_state.get_transform();
+    let parent_of = |entity: &SceneEntityId| {
+        transforms
+            .get(entity)
+            .and_then(|entry| entry.value.as_ref())
+            .map(|value| value.parent)
+    };
+
+    let mut issues = Vec::new();
+    for (entity, entry) in transforms.values.iter() {
+        let Some(parent) = entry.value.as_ref().map(|value| value.parent) else {
+            continue;
+        };
+
+        if parent == *entity {
+            issues.push(format!("entity {entity} is its own parent"));
+            continue;
+        }
+
+        if parent != SceneEntityId::ROOT && crdt_state.entities.is_dead(&parent) {
+            issues.push(format!(
+                "entity {entity} has the deleted entity {parent} as parent"
+            ));
+        }
+
+        // walk up the hierarchy, the cycle is reported once by its lowest entity
+        let mut visited = vec![*entity];
+        let mut current = parent;
+        while current != SceneEntityId::ROOT && current != *entity {
+            if visited.contains(&current) {
+                break;
+            }
+            visited.push(current);
+            let Some(next) = parent_of(&current) else {
+                break;
+            };
+            current = next;
+        }
+        if current == *entity && visited.iter().min() == Some(entity) {
+            let cycle = visited
+                .iter()
+                .map(|entity| entity.to_string())
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            issues.push(format!("parent cycle {cycle} -> {entity}"));
+        }
+    }
+    issues
+}
+
+/// Builds an entity definition out of a local scene folder, the content
+/// hashes are the file paths relative to the folder.
+fn local_scene_entity_definition(scene_folder: &Path) -> Result<SceneEntityDefinition, String> {
+    let scene_json_path = scene_folder.join("scene.json");
+    let scene_json = std::fs::read_to_string(&scene_json_path)
+        .map_err(|err| format!("can't read {}: {err}", scene_json_path.display()))?;
+    let metadata: serde_json::Value =
+        serde_json::from_str(&scene_json).map_err(|err| format!("invalid scene.json: {err}"))?;
+
+    let pointers = metadata["scene"]["parcels"].clone();
+    let mut content = Vec::new();
+    collect_scene_files(scene_folder, scene_folder, &mut content)
+        .map_err(|err| format!("can't list {}: {err}", scene_folder.display()))?;
+
+    let folder_name = scene_folder
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    SceneEntityDefinition::from_json_ex(
+        Some(format!("headless-{folder_name}")),
+        String::new(),
+        false,
+        serde_json::json!({
+            "pointers": pointers,
+            "content": content,
+            "metadata": metadata,
+            "type": "scene",
+        }),
+    )
+    .map_err(|err| format!("invalid scene definition: {err}"))
+}
+
+fn collect_scene_files(
+    root: &Path,
+    dir: &Path,
+    content: &mut Vec<serde_json::Value>,
+) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if name == "node_modules" || name.starts_with('.') {
+            continue;
+        }
+        if path.is_dir() {
+            collect_scene_files(root, &path, content)?;
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            let file = relative.to_string_lossy().replace('\\', "/");
+            content.push(serde_json::json!({ "file": file, "hash": file }));
+        }
+    }
+    Ok(())
+}
+
+fn spawn_headless_scene(
+    scene_folder: &Path,
+) -> Result<(DclScene, std::sync::mpsc::Receiver<SceneResponse>), String> {
+    let scene_entity_definition = Arc::new(local_scene_entity_definition(scene_folder)?);
+
+    let main_js_path = scene_folder.join(&scene_entity_definition.scene_meta_scene.main);
+    if !main_js_path.is_file() {
+        return Err(format!("main file {} not found", main_js_path.display()));
+    }
+    let main_crdt_path = scene_folder.join("main.crdt");
+    let local_main_crdt_file_path = if main_crdt_path.is_file() {
+        main_crdt_path.to_string_lossy().to_string()
+    } else {
+        String::new()
+    };
+
+    // exceptions reach the report through the scene logs
+    set_scene_log_enabled(true);
+
+    let (sender, receiver) = std::sync::mpsc::sync_channel(100);
+    let dcl_scene = DclScene::spawn_new_js_dcl_scene(SpawnDclSceneData {
+        scene_id: Scene::new_id(),
+        content_mapping: scene_entity_definition.content_mapping.clone(),
+        scene_entity_definition,
+        local_main_js_file_path: main_js_path.to_string_lossy().to_string(),
+        local_main_crdt_file_path,
+        local_crdt_snapshot_file_path: String::new(),
+        thread_sender_to_main: sender,
+        testing_mode: true,
+        fixed_skybox_time: true,
+        ethereum_provider: Arc::new(EthereumProvider::new()),
+        ephemeral_wallet: None,
+        realm_info: DclSceneRealmData {
+            base_url: String::new(),
+            realm_name: "headless".to_string(),
+            network_id: 1,
+            comms_adapter: String::new(),
+            is_preview: true,
+        },
+        inspect: false,
+        network_inspector_sender: None,
+        should_debug: false,
+        quotas: SceneQuotas::default(),
+        permissions: ScenePermissions::UNRESTRICTED,
+    });
+
+    Ok((dcl_scene, receiver))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn put_parent(crdt_state: &mut SceneCrdtState, entity: SceneEntityId, parent: SceneEntityId) {
+        crdt_state.entities.try_init(entity);
+        crdt_state.get_transform_mut().put(
+            entity,
+            Some(DclTransformAndParent {
+                parent,
+                ..Default::default()
+            }),
+        );
+    }
+
+    #[test]
+    fn valid_transform_tree() {
+        let mut crdt_state = SceneCrdtState::from_proto();
+        let a = SceneEntityId::new(512, 0);
+        let b = SceneEntityId::new(513, 0);
+        put_parent(&mut crdt_state, a, SceneEntityId::ROOT);
+        put_parent(&mut crdt_state, b, a);
+        assert!(validate_transform_tree(&crdt_state).is_empty());
+    }
+
+    #[test]
+    fn invalid_transform_tree() {
+        let mut crdt_state = SceneCrdtState::from_proto();
+        let a = SceneEntityId::new(512, 0);
+        let b = SceneEntityId::new(513, 0);
+        let c = SceneEntityId::new(514, 0);
+        let dead = SceneEntityId::new(515, 0);
+        put_parent(&mut crdt_state, a, b);
+        put_parent(&mut crdt_state, b, a);
+        put_parent(&mut crdt_state, c, c);
+        crdt_state.entities.try_init(dead);
+        crdt_state.entities.kill(dead);
+        put_parent(&mut crdt_state, SceneEntityId::new(516, 0), dead);
+
+        let issues = validate_transform_tree(&crdt_state);
+        assert_eq!(issues.len(), 3, "{issues:?}");
+        assert!(issues.contains(&format!("parent cycle {a} -> {b} -> {a}")));
+        assert!(issues.contains(&format!("entity {c} is its own parent")));
+        assert!(issues
+            .iter()
+            .any(|issue| issue.contains(&format!("deleted entity {dead}"))));
+    }
+}
@@ -92,6 +92,8 @@ pub struct LoadingFunnel {
     asset_failures: i64,
     /// Whether a realm-change failure was observed during this load (feeds `dismissed_by=error`).
     saw_realm_fail: bool,
+    /// Kind of each scene runtime killed for exceeding its quotas during the load.
+    scene_quota_breaches: Vec<&'static str>,
 }
 
 impl LoadingFunnel {
@@ -136,6 +138,7 @@ impl LoadingFunnel {
         self.last_pulse = Some(now);
         self.asset_failures = 0;
         self.saw_realm_fail = false;
+        self.scene_quota_breaches.clear();
 
         SegmentEventLoading {
             when: Some(self.when.clone()),
@@ -147,7 +150,9 @@ impl LoadingFunnel {
     }
 
     /// End the current load and produce its funnel event(s): the `type="completed"` event, plus a
-    /// `type="asset_failure"` aggregate when any asset group failed. Empty if no load is active.
+    /// `type="asset_failure"` aggregate when any asset group failed and a
+    /// `type="scene_quota_exceeded"` one when any scene runtime was killed. Empty if no load is
+    /// active.
     ///
     /// `realm` is the caller's end-time reading, used **only** as a fallback when the load began
     /// without a known realm (a background `auto` episode, or a caller that passed none).
@@ -203,6 +208,20 @@ impl LoadingFunnel {
                 ..SegmentEventLoading::base("asset_failure", self.id, realm_bucket(&self.realm))
             });
         }
+        if !self.scene_quota_breaches.is_empty() {
+            let mut kinds = self.scene_quota_breaches.clone();
+            kinds.sort_unstable();
+            kinds.dedup();
+            out.push(SegmentEventLoading {
+                reason: Some(kinds.join(",")),
+                count: Some(self.scene_quota_breaches.len() as i64),
+                ..SegmentEventLoading::base(
+                    "scene_quota_exceeded",
+                    self.id,
+                    realm_bucket(&self.realm),
+                )
+            });
+        }
 
         self.active = false;
         out
@@ -366,6 +385,13 @@ impl LoadingFunnel {
         }
     }
 
+    /// A scene runtime was killed for exceeding a quota (`SceneQuotaBreach::kind`).
+    pub fn note_scene_quota_breach(&mut self, kind: &'static str) {
+        if self.active {
+            self.scene_quota_breaches.push(kind);
+        }
+    }
+
     // --- internals ---------------------------------------------------------------------------
 
     fn mark(&mut self, which: Milestone, now: Instant) {
@@ -549,6 +575,27 @@ mod tests {
         assert_eq!(out[0].dismissed_by.as_deref(), Some("superseded"));
     }
 
+    /// Quota kills are aggregated into one event per load, with the distinct kinds as reason.
+    #[test]
+    fn scene_quota_breaches_are_aggregated() {
+        let mut f = LoadingFunnel::default();
+        let t0 = Instant::now();
+        f.note_scene_quota_breach("heap"); // no load active: dropped
+        f.begin(ctx("on_teleport", "genesis city"), t0);
+        f.note_scene_quota_breach("tick_time");
+        f.note_scene_quota_breach("heap");
+        f.note_scene_quota_breach("tick_time");
+        let out = f.end("hidden", "", 0, 0, 200, t0 + Duration::from_millis(1000));
+        assert_eq!(out.len(), 2);
+        assert_eq!(out[1].event_type, "scene_quota_exceeded");
+        assert_eq!(out[1].reason.as_deref(), Some("heap,tick_time"));
+        assert_eq!(out[1].count, Some(3));
+
+        f.begin(ctx("on_teleport", "genesis city"), t0);
+        let out = f.end("hidden", "", 0, 0, 200, t0 + Duration::from_millis(1000));
+        assert_eq!(out.len(), 1);
+    }
+
     /// Counters must not bleed across loads: a throttled load followed by a fast one.
     #[test]
     fn network_counters_reset_between_loads() {
@@ -358,10 +358,7 @@ pub fn trigger_scene_emote(
     );
 
     // Build scene-emote URN (same format used for comms)
-    let scene_emote_urn = format!(
-        "urn:decentraland:off-chain:scene-emote:{}-{}-{}",
-        scene.scene_entity_definition.id, emote_hash.glb_hash, looping
-    );
+    let scene_emote_urn = emote_hash.urn(&scene.scene_entity_definition.id, *looping);
 
     let mut avatar_node = get_avatar_node(scene);
 
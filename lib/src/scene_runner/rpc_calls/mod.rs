@@ -113,6 +113,32 @@ pub fn process_rpcs(scene: &mut Scene, current_parcel_scene_id: &SceneId, rpc_ca
                 let mut communication_manager = comms.bind_mut();
                 communication_manager.send_scene_message(scene_id, body, recipient);
             }
+            RpcCall::VoiceSetSceneChannel { enabled } => {
+                let scene_id = scene.scene_entity_definition.id.clone();
+                let mut comms = DclGlobal::singleton().bind().get_comms();
+                comms.bind_mut().set_scene_voice_channel(scene_id, enabled);
+            }
+            RpcCall::VoiceSetMicrophoneAllowed { allowed } => {
+                let scene_id = scene.scene_entity_definition.id.clone();
+                let mut comms = DclGlobal::singleton().bind().get_comms();
+                comms
+                    .bind_mut()
+                    .set_scene_voice_microphone_allowed(scene_id, allowed);
+            }
+            RpcCall::VoiceSetPlayerMuted { address, muted } => {
+                let scene_id = scene.scene_entity_definition.id.clone();
+                let mut comms = DclGlobal::singleton().bind().get_comms();
+                comms
+                    .bind_mut()
+                    .set_scene_voice_player_muted(scene_id, address, muted);
+            }
+            RpcCall::VoiceGetState { response } => {
+                let comms = DclGlobal::singleton().bind().get_comms();
+                let state = comms
+                    .bind()
+                    .scene_voice_state(&scene.scene_entity_definition.id);
+                response.send(state);
+            }
             RpcCall::GetTextureSize { src, response } => {
                 let mut rpc_sender = DclRpcSenderGetTextureSize::new_gd();
                 rpc_sender.bind_mut().set_sender(response);
@@ -26,6 +26,7 @@ use crate::{
         },
         crdt::{DirtyEntities, DirtyGosComponents, DirtyLwwComponents},
         scene_apis::RpcCall,
+        scene_permissions::ScenePermissions,
         DclScene, RendererResponse, SceneId,
     },
     godot_classes::{
@@ -38,8 +39,12 @@ use crate::{
 
 use super::{
     components::{
-        asset_load::AssetLoadState, gltf_node_modifiers::GltfNodeModifierState,
-        particle_system::ParticleSystemItem, trigger_area::TriggerAreaState, tween::Tween,
+        asset_load::AssetLoadState,
+        gltf_node_modifiers::GltfNodeModifierState,
+        light_source::LightSourceItem,
+        particle_system::ParticleSystemItem,
+        trigger_area::TriggerAreaState,
+        tween::{Tween, TweenSequence},
     },
     godot_dcl_scene::GodotDclScene,
 };
@@ -136,7 +141,9 @@ pub enum SceneUpdateState {
     TriggerArea,
     VirtualCameras,
     AudioSource,
+    AudioAnalysis,
     ParticleSystem,
+    LightSource,
     ProcessRpcs,
     ComputeCrdtState,
     SendToThread,
@@ -180,8 +187,10 @@ impl SceneUpdateState {
             Self::SkyboxTime => Self::TriggerArea,
             Self::TriggerArea => Self::VirtualCameras,
             Self::VirtualCameras => Self::AudioSource,
-            Self::AudioSource => Self::ParticleSystem,
-            Self::ParticleSystem => Self::AvatarAttach,
+            Self::AudioSource => Self::AudioAnalysis,
+            Self::AudioAnalysis => Self::ParticleSystem,
+            Self::ParticleSystem => Self::LightSource,
+            Self::LightSource => Self::AvatarAttach,
             Self::AvatarAttach => Self::SceneUi,
             Self::SceneUi => Self::ProcessRpcs,
             Self::ProcessRpcs => Self::ComputeCrdtState,
@@ -222,6 +231,8 @@ pub struct Scene {
     pub godot_dcl_scene: GodotDclScene,
     pub dcl_scene: DclScene,
     pub scene_entity_definition: Arc<SceneEntityDefinition>,
+    /// Checked by the components the renderer runs for the scene (media urls)
+    pub permissions: ScenePermissions,
     pub tick_number: u32,
 
     pub state: SceneState,
@@ -244,6 +255,9 @@ pub struct Scene {
 
     pub current_dirty: Dirty,
     pub enqueued_dirty: Vec<Dirty>,
+    /// Components the Scene Inspector wrote into the CRDT state, rendered with
+    /// the next `Dirty` from the scene thread.
+    pub inspector_dirty: DirtyLwwComponents,
     pub distance: f32,
 
     pub start_time: Instant,
@@ -256,8 +270,14 @@ pub struct Scene {
     pub particle_systems: HashMap<SceneEntityId, ParticleSystemItem>,
     pub dirty_particle_systems: bool,
 
+    /// Godot lights backing `PBLightSource`. Visibility and shadows are owned by
+    /// the SceneManager's per-scene light budget, not by the component update.
+    pub light_sources: HashMap<SceneEntityId, LightSourceItem>,
+
     pub scene_type: SceneType,
     pub audio_sources: HashMap<SceneEntityId, Gd<DclAudioSource>>,
+    // Entities with PBAudioAnalysis, analyzed every tick
+    pub audio_analysis: HashSet<SceneEntityId>,
 
     // Used by VideoPlayer and AudioStream
     pub audio_streams: HashMap<SceneEntityId, Gd<DclAudioStream>>,
@@ -272,6 +292,8 @@ pub struct Scene {
 
     // Tween
     pub tweens: HashMap<SceneEntityId, Tween>,
+    // Steps queued after each entity's Tween by PBTweenSequence
+    pub tween_sequences: HashMap<SceneEntityId, TweenSequence>,
     // Entities with active tweens or repeated transform writes — their colliders should be KINEMATIC
     pub kinematic_entities: HashSet<SceneEntityId>,
     // Entities that have had at least one transform applied (second write = movement)
@@ -369,6 +391,7 @@ impl Scene {
         content_mapping: ContentMappingAndUrlRef,
         scene_type: SceneType,
         parent_ui_node: Gd<DclUiControl>,
+        permissions: ScenePermissions,
     ) -> Self {
         let godot_dcl_scene =
             GodotDclScene::new(scene_entity_definition.clone(), &scene_id, parent_ui_node);
@@ -378,6 +401,7 @@ impl Scene {
             tick_number: 0,
             godot_dcl_scene,
             scene_entity_definition,
+            permissions,
             dcl_scene,
             state: SceneState::Alive,
 
@@ -393,6 +417,7 @@ impl Scene {
                 rpc_calls: Vec::new(),
             },
             enqueued_dirty: Vec::new(),
+            inspector_dirty: DirtyLwwComponents::default(),
             distance: 0.0,
             next_tick_us: 0,
             last_tick_us: 0,
@@ -408,7 +433,9 @@ impl Scene {
             dirty_materials: false,
             particle_systems: HashMap::new(),
             dirty_particle_systems: false,
+            light_sources: HashMap::new(),
             audio_sources: HashMap::new(),
+            audio_analysis: HashSet::new(),
             audio_streams: HashMap::new(),
             video_players: HashMap::new(),
             livekit_video_player_entities: HashSet::new(),
@@ -417,6 +444,7 @@ impl Scene {
             scene_tests: HashMap::new(),
             scene_test_plan_received: false,
             tweens: HashMap::new(),
+            tween_sequences: HashMap::new(),
             kinematic_entities: HashSet::new(),
             transform_initialized: HashSet::new(),
             texture_animations: HashMap::new(),
@@ -461,9 +489,11 @@ impl Scene {
             godot_dcl_scene,
             tick_number: 0,
             scene_entity_definition,
+            permissions: ScenePermissions::UNRESTRICTED,
             dcl_scene,
             state: SceneState::Alive,
             enqueued_dirty: Vec::new(),
+            inspector_dirty: DirtyLwwComponents::default(),
             content_mapping: Arc::new(ContentMappingAndUrl::new()),
             current_dirty: Dirty {
                 waiting_process: true,
@@ -490,8 +520,10 @@ impl Scene {
             dirty_materials: false,
             particle_systems: HashMap::new(),
             dirty_particle_systems: false,
+            light_sources: HashMap::new(),
             scene_type: SceneType::Parcel,
             audio_sources: HashMap::new(),
+            audio_analysis: HashSet::new(),
             audio_streams: HashMap::new(),
             video_players: HashMap::new(),
             livekit_video_player_entities: HashSet::new(),
@@ -499,6 +531,7 @@ impl Scene {
             scene_tests: HashMap::new(),
             scene_test_plan_received: false,
             tweens: HashMap::new(),
+            tween_sequences: HashMap::new(),
             kinematic_entities: HashSet::new(),
             transform_initialized: HashSet::new(),
             texture_animations: HashMap::new(),
@@ -631,6 +664,12 @@ impl Scene {
             node.queue_free();
         }
 
+        // Free lights
+        for (_, item) in self.light_sources.drain() {
+            let mut node = item.node;
+            node.queue_free();
+        }
+
         // Free audio streams
         for (_, mut audio_stream) in self.audio_streams.drain() {
             audio_stream.queue_free();
@@ -649,6 +688,8 @@ impl Scene {
         self.gltf_loading.clear();
         self.materials.clear();
         self.tweens.clear();
+        self.audio_analysis.clear();
+        self.tween_sequences.clear();
         self.dup_animator.clear();
         self.livekit_video_player_entities.clear();
 
@@ -1,5 +1,5 @@
 use crate::{
-    content::content_mapping::DclContentMappingAndUrl,
+    content::content_mapping::{ContentMappingAndUrlRef, DclContentMappingAndUrl},
     dcl::{
         common::SceneLogLevel,
         components::{
@@ -13,6 +13,8 @@ use crate::{
             },
             SceneEntityId,
         },
+        crdt::snapshot::existing_snapshot_path,
+        scene_quotas::SceneQuotas,
         DclScene, DclSceneRealmData, RendererResponse, SceneId, SceneResponse, SpawnDclSceneData,
     },
     godot_classes::{
@@ -23,7 +25,12 @@ use crate::{
         JsonGodotClass,
     },
     realm::dcl_scene_entity_definition::DclSceneEntityDefinition,
-    tools::network_inspector::NETWORK_INSPECTOR_ENABLE,
+    tools::{
+        network_inspector::NETWORK_INSPECTOR_ENABLE,
+        scene_inspector::commands::{
+            apply_component_command, merge_dirty_lww, resolve_component, ComponentCommand,
+        },
+    },
 };
 use godot::{
     classes::{
@@ -41,9 +48,12 @@ use std::{
 use tokio::sync::mpsc::error::TrySendError;
 
 use super::{
-    components::pointer_events::{
-        entity_player_distance, event_info_in_range, find_active_proximity_entity,
-        get_entity_pointer_event, pointer_events_system,
+    components::{
+        light_source,
+        pointer_events::{
+            entity_player_distance, event_info_in_range, find_active_proximity_entity,
+            get_entity_pointer_event, pointer_events_system,
+        },
     },
     input::InputState,
     loading_funnel::{LoadingBeginContext, LoadingFunnel},
@@ -162,6 +172,14 @@ pub struct SceneManager {
     bench_disable_tweens: bool,
     #[var(get, set)]
     bench_disable_transforms: bool,
+
+    // Per-scene PBLightSource budget. Lights beyond it (farthest from the camera
+    // first) are hidden, and only the nearest shadowed lights keep their shadows,
+    // so a scene can't stall the GPU by spawning hundreds of shadowed lights.
+    #[var(get, set)]
+    max_lights_per_scene: i32,
+    #[var(get, set)]
+    max_shadowed_lights_per_scene: i32,
 }
 
 // This value is the current global tick number, is used for marking the cronolgy of lamport timestamp
@@ -261,6 +279,7 @@ impl SceneManager {
         let realm_name = realm.get_realm_name().to_string();
         let base_url = realm.get_realm_url().to_string();
         let network_id = realm.get_network_id();
+        let enforce_scene_permissions = realm.get_enforce_scene_permissions();
 
         let is_preview = dcl_global.bind().get_preview_mode();
         let should_debug = dcl_global.bind().scene_inspector_active;
@@ -285,11 +304,33 @@ impl SceneManager {
             .map(|u| u.origin().ascii_serialization())
             .unwrap_or(base_url);
 
+        let crdt_snapshot_folder = dcl_global.bind().cli.bind().crdt_snapshot_folder.clone();
+        let local_crdt_snapshot_file_path = if crdt_snapshot_folder.is_empty() {
+            String::new()
+        } else {
+            let folder = godot::classes::ProjectSettings::singleton()
+                .globalize_path(&crdt_snapshot_folder)
+                .to_string();
+            existing_snapshot_path(&folder, &scene_entity_definition.id).unwrap_or_default()
+        };
+
+        let quotas = {
+            let cli = dcl_global.bind().cli.clone();
+            let cli = cli.bind();
+            SceneQuotas::from_settings(
+                cli.scene_max_heap_mb,
+                cli.scene_max_op_calls,
+                cli.scene_max_tick_ms,
+            )
+        };
+        let permissions = scene_entity_definition.get_permissions(enforce_scene_permissions);
+
         let dcl_scene = DclScene::spawn_new_js_dcl_scene(SpawnDclSceneData {
             scene_id: new_scene_id,
             scene_entity_definition: scene_entity_definition.clone(),
             local_main_js_file_path: local_main_js_file_path.to_string(),
             local_main_crdt_file_path: local_main_crdt_file_path.to_string(),
+            local_crdt_snapshot_file_path,
             content_mapping: content_mapping.clone(),
             thread_sender_to_main: self.thread_sender_to_main.clone(),
             testing_mode: testing_mode_active,
@@ -306,6 +347,8 @@ impl SceneManager {
             inspect,
             network_inspector_sender,
             should_debug,
+            quotas,
+            permissions: permissions.clone(),
         });
 
         let new_scene = Scene::new(
@@ -315,6 +358,7 @@ impl SceneManager {
             content_mapping.clone(),
             scene_type.clone(),
             self.base_ui.clone(),
+            permissions,
         );
 
         self.base_mut().add_child(
@@ -1136,6 +1180,18 @@ impl SceneManager {
         VarDictionary::new()
     }
 
+    /// Content of a loaded scene by its entity id, used to resolve the scene emotes
+    /// remote players trigger.
+    pub fn get_content_mapping_by_entity_id(
+        &self,
+        scene_entity_id: &str,
+    ) -> Option<ContentMappingAndUrlRef> {
+        self.scenes
+            .values()
+            .find(|scene| scene.scene_entity_definition.id == scene_entity_id)
+            .map(|scene| scene.content_mapping.clone())
+    }
+
     #[func]
     fn get_scene_is_paused(&self, scene_id: i32) -> bool {
         if let Some(scene) = self.scenes.get(&SceneId(scene_id)) {
@@ -1192,6 +1248,29 @@ impl SceneManager {
         out
     }
 
+    /// Debug: writes the scene's whole CRDT state to `file_path` (`user://`
+    /// paths allowed). Starting with `--crdt-snapshot-folder` pointing at the
+    /// folder restores it instead of replaying main.crdt.
+    #[func]
+    fn save_scene_crdt_snapshot(&self, scene_id: i32, file_path: GString) -> bool {
+        let Some(scene) = self.scenes.get(&SceneId(scene_id)) else {
+            return false;
+        };
+        let path = godot::classes::ProjectSettings::singleton()
+            .globalize_path(&file_path)
+            .to_string();
+        let Ok(crdt) = scene.dcl_scene.scene_crdt.lock() else {
+            return false;
+        };
+        match crdt.save_snapshot_to_file(std::path::Path::new(&path)) {
+            Ok(()) => true,
+            Err(err) => {
+                tracing::warn!("failed to save CRDT snapshot to {path}: {err}");
+                false
+            }
+        }
+    }
+
     /// Debug: list every alive entity id in a scene's CRDT state.
     /// Returns an empty array if the scene is not loaded.
     #[func]
@@ -1428,6 +1507,79 @@ impl SceneManager {
         GString::from(out.to_string().as_str())
     }
 
+    /// Debug: Scene Inspector write-back. Puts `value_json` (the JSON shape
+    /// `debug_get_entity_components_json` returns) as the `component` (name or
+    /// id) of an entity; the renderer applies it and the scene's JS receives it
+    /// with the next tick. Returns the error, or an empty string on success.
+    #[func]
+    fn debug_put_component(
+        &mut self,
+        scene_id: i32,
+        entity_id: i32,
+        component: GString,
+        value_json: GString,
+    ) -> GString {
+        let result = serde_json::from_str(&value_json.to_string())
+            .map_err(|err| format!("invalid JSON value: {err}"))
+            .and_then(|value| {
+                self.apply_inspector_command(
+                    scene_id,
+                    entity_id,
+                    &component.to_string(),
+                    ComponentCommand::Put(value),
+                )
+            });
+        GString::from(result.err().unwrap_or_default().as_str())
+    }
+
+    /// Debug: Scene Inspector write-back, deletes a component of an entity.
+    /// Returns the error, or an empty string on success.
+    #[func]
+    fn debug_delete_component(
+        &mut self,
+        scene_id: i32,
+        entity_id: i32,
+        component: GString,
+    ) -> GString {
+        let result = self.apply_inspector_command(
+            scene_id,
+            entity_id,
+            &component.to_string(),
+            ComponentCommand::Delete,
+        );
+        GString::from(result.err().unwrap_or_default().as_str())
+    }
+
+    fn apply_inspector_command(
+        &mut self,
+        scene_id: i32,
+        entity_id: i32,
+        component: &str,
+        command: ComponentCommand,
+    ) -> Result<(), String> {
+        let Some(scene) = self.scenes.get_mut(&SceneId(scene_id)) else {
+            return Err(format!("scene {scene_id} is not loaded"));
+        };
+        let entity = SceneEntityId::from_i32(entity_id);
+        let component = resolve_component(component)?;
+        let is_lww = {
+            let Ok(mut crdt) = scene.dcl_scene.scene_crdt.lock() else {
+                return Err("scene CRDT state is poisoned".to_string());
+            };
+            apply_component_command(&mut crdt, entity, component, command)?;
+            crdt.get_lww_component_definition(component).is_some()
+        };
+
+        // Grow-only values are results for the scene, the renderer doesn't read them
+        if is_lww {
+            merge_dirty_lww(
+                &mut scene.inspector_dirty,
+                HashMap::from([(component, vec![entity])]),
+            );
+        }
+        Ok(())
+    }
+
     fn compute_scene_distance(&mut self) {
         self.current_parcel_scene_id = SceneId::INVALID;
 
@@ -1627,6 +1779,8 @@ impl SceneManager {
             }
         }
 
+        self.enforce_light_budgets(camera_global_transform.origin);
+
         // Process loading session updates from all scenes
         self.update_loading_session_from_scenes();
 
@@ -1643,6 +1797,25 @@ impl SceneManager {
         }
     }
 
+    /// Re-evaluates which lights of each scene fit the light budget. Runs every
+    /// frame (not per scene tick) because the nearest lights change as the camera
+    /// moves, even when the scene itself is idle.
+    fn enforce_light_budgets(&mut self, camera_position: Vector3) {
+        let max_lights = self.max_lights_per_scene.max(0) as usize;
+        let max_shadowed_lights = self.max_shadowed_lights_per_scene.max(0) as usize;
+        for scene in self.scenes.values_mut() {
+            if scene.light_sources.is_empty() || !matches!(scene.state, SceneState::Alive) {
+                continue;
+            }
+            light_source::enforce_light_budget(
+                scene,
+                camera_position,
+                max_lights,
+                max_shadowed_lights,
+            );
+        }
+    }
+
     /// Consume the pressure level published by the background memory monitor
     /// (issue #2002) and free memory before the OS kills the app. Detection runs
     /// off-thread so it survives a main-thread freeze; this acts the instant the
@@ -1889,6 +2062,11 @@ impl SceneManager {
         // Drop the scene's external-content bookkeeping so a reload restarts
         // its counters from zero.
         crate::content::external_content::clear_scene(&scene.scene_entity_definition.id);
+        DclGlobal::singleton()
+            .bind()
+            .get_comms()
+            .bind_mut()
+            .remove_scene_voice_policy(&scene.scene_entity_definition.id);
 
         // Cleanup trigger areas and release RIDs back to pool
         scene
@@ -1965,10 +2143,15 @@ impl SceneManager {
                                 scene.deno_memory_stats = deno_memory_stats;
                             }
 
+                            let mut lww_components = dirty_crdt_state.lww;
+                            merge_dirty_lww(
+                                &mut lww_components,
+                                std::mem::take(&mut scene.inspector_dirty),
+                            );
                             let dirty = Dirty {
                                 waiting_process: true,
                                 entities: dirty_crdt_state.entities,
-                                lww_components: dirty_crdt_state.lww,
+                                lww_components,
                                 gos_components: dirty_crdt_state.gos,
                                 logs,
                                 renderer_response: None,
@@ -1983,6 +2166,16 @@ impl SceneManager {
                             }
                         }
                     }
+                    SceneResponse::QuotaExceeded(scene_id, breach) => {
+                        self.loading_funnel.note_scene_quota_breach(breach.kind());
+
+                        let mut arguments = VarArray::new();
+                        arguments.push(&(scene_id.0).to_variant());
+                        arguments.push(&(SceneLogLevel::SystemError as i32).to_variant());
+                        arguments.push(&self.total_time_seconds_time.to_variant());
+                        arguments.push(&format!("scene killed, {breach}").to_godot().to_variant());
+                        self.console.callv(&arguments);
+                    }
                     SceneResponse::RemoveGodotScene(scene_id, logs) => {
                         if let Some(scene) = self.scenes.get_mut(&scene_id) {
                             scene.state = SceneState::Dead;
@@ -2655,6 +2848,19 @@ impl INode for SceneManager {
 
         let canvas_size = base_ui.get_size();
 
+        let (max_lights_per_scene, max_shadowed_lights_per_scene) =
+            if godot::classes::Os::singleton().has_feature("mobile") {
+                (
+                    light_source::MOBILE_MAX_LIGHTS_PER_SCENE,
+                    light_source::MOBILE_MAX_SHADOWED_LIGHTS_PER_SCENE,
+                )
+            } else {
+                (
+                    light_source::DEFAULT_MAX_LIGHTS_PER_SCENE,
+                    light_source::DEFAULT_MAX_SHADOWED_LIGHTS_PER_SCENE,
+                )
+            };
+
         SceneManager {
             base,
             base_ui,
@@ -2706,6 +2912,8 @@ impl INode for SceneManager {
             loading_funnel: LoadingFunnel::default(),
             bench_disable_tweens: false,
             bench_disable_transforms: false,
+            max_lights_per_scene,
+            max_shadowed_lights_per_scene,
         }
     }
 
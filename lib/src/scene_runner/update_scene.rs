@@ -106,7 +106,9 @@ fn state_name(state: &super::scene::SceneUpdateState) -> &'static str {
         S::TriggerArea => "TriggerArea",
         S::VirtualCameras => "VirtualCameras",
         S::AudioSource => "AudioSource",
+        S::AudioAnalysis => "AudioAnalysis",
         S::ParticleSystem => "ParticleSystem",
+        S::LightSource => "LightSource",
         S::ProcessRpcs => "ProcessRpcs",
         S::ComputeCrdtState => "ComputeCrdtState",
         S::SendToThread => "SendToThread",
@@ -118,6 +120,7 @@ use super::{
     components::{
         animator::update_animator,
         asset_load::{sync_asset_load_loading_state, update_asset_load},
+        audio_analysis::update_audio_analysis,
         audio_source::update_audio_source,
         avatar_attach::update_avatar_attach,
         avatar_data::update_avatar_scene_updates,
@@ -131,6 +134,7 @@ use super::{
             update_gltf_node_modifiers, update_modifier_textures, update_modifier_video_textures,
         },
         input_modifier::update_input_modifier,
+        light_source::update_light_source,
         material::{update_material, update_video_material_textures},
         mesh_collider::update_mesh_collider,
         mesh_renderer::update_mesh_renderer,
@@ -551,10 +555,18 @@ pub fn _process_scene(
                     update_audio_source(scene, crdt_state, current_parcel_scene_id);
                     false
                 }
+                SceneUpdateState::AudioAnalysis => {
+                    update_audio_analysis(scene, crdt_state);
+                    false
+                }
                 SceneUpdateState::ParticleSystem => {
                     update_particle_system(scene, crdt_state, current_parcel_scene_id);
                     false
                 }
+                SceneUpdateState::LightSource => {
+                    update_light_source(scene, crdt_state);
+                    false
+                }
                 SceneUpdateState::SceneUi => {
                     update_scene_ui(
                         scene,
@@ -0,0 +1,200 @@
+//! Scene Inspector write-back commands.
+//!
+//! `put_component`/`delete_component` commands are encoded as regular CRDT
+//! messages and processed into the renderer's `SceneCrdtState`, timestamped one
+//! past the current value so they win over it. The state keeps them dirty, so
+//! they're echoed to the scene's JS with the next renderer response; the
+//! `SceneManager` also queues them for the renderer update (see
+//! `merge_dirty_lww`).
+
+use crate::dcl::{
+    components::{
+        component_name_to_id, proto_components::serialize_component_from_json, SceneComponentId,
+        SceneCrdtTimestamp, SceneEntityId,
+    },
+    crdt::{
+        message::{append_value, delete_component, process_many_messages, put_component},
+        DirtyLwwComponents, SceneCrdtState,
+    },
+    serialization::{reader::DclReader, writer::DclWriter},
+};
+
+pub enum ComponentCommand {
+    /// The component value, in the JSON shape the inspector streams.
+    Put(serde_json::Value),
+    Delete,
+}
+
+/// Accepts a component name (`Transform`, `MeshRenderer`...) or its numeric id.
+pub fn resolve_component(component: &str) -> Result<SceneComponentId, String> {
+    component_name_to_id(component)
+        .or_else(|| component.parse().ok())
+        .map(SceneComponentId)
+        .ok_or_else(|| format!("unknown component `{component}`"))
+}
+
+/// Applies a command to `state`. Puts on grow-only components append a value,
+/// those can't be deleted.
+pub fn apply_component_command(
+    state: &mut SceneCrdtState,
+    entity: SceneEntityId,
+    component: SceneComponentId,
+    command: ComponentCommand,
+) -> Result<(), String> {
+    let payload = match command {
+        ComponentCommand::Put(mut value) => {
+            integral_floats_to_integers(&mut value);
+            Some(serialize_component_from_json(component.0, value)?)
+        }
+        ComponentCommand::Delete => None,
+    };
+
+    let mut buf = Vec::new();
+    let mut writer = DclWriter::new(&mut buf);
+    let timestamp = if let Some(definition) = state.get_lww_component_definition(component) {
+        let timestamp = SceneCrdtTimestamp(
+            definition
+                .get_opaque(entity)
+                .map_or(0, |entry| entry.timestamp.0 + 1),
+        );
+        match &payload {
+            Some(payload) => put_component(&entity, &component, &timestamp, payload, &mut writer),
+            None => delete_component(&entity, &component, &timestamp, &mut writer),
+        }
+        timestamp
+    } else if state.get_gos_component_definition(component).is_some() {
+        let Some(payload) = &payload else {
+            return Err(format!(
+                "component {} is grow-only and can't be deleted",
+                component.0
+            ));
+        };
+        let timestamp = SceneCrdtTimestamp(0);
+        append_value(&entity, &component, &timestamp, payload, &mut writer);
+        timestamp
+    } else {
+        return Err(format!(
+            "component {} isn't in the scene state",
+            component.0
+        ));
+    };
+    process_many_messages(&mut DclReader::new(&buf), state);
+
+    // Messages for dead entities are dropped silently
+    let applied = match state.get_lww_component_definition(component) {
+        Some(definition) => definition
+            .get_opaque(entity)
+            .is_some_and(|entry| entry.timestamp == timestamp),
+        None => state
+            .get_gos_component_definition(component)
+            .is_some_and(|definition| definition.element_count(entity) > 0),
+    };
+    if !applied {
+        return Err(format!("entity {entity} was deleted"));
+    }
+    Ok(())
+}
+
+/// Godot parses every JSON number of a command as a float, which serde
+/// refuses for the integer fields (enums, entity ids...).
+fn integral_floats_to_integers(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Number(number) => {
+            if let Some(float) = number.as_f64().filter(|_| number.is_f64()) {
+                if float as i64 as f64 == float {
+                    *number = (float as i64).into();
+                }
+            }
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(integral_floats_to_integers),
+        serde_json::Value::Object(fields) => {
+            fields.values_mut().for_each(integral_floats_to_integers)
+        }
+        _ => {}
+    }
+}
+
+/// Adds the entities of `from` missing in `into`.
+pub fn merge_dirty_lww(into: &mut DirtyLwwComponents, from: DirtyLwwComponents) {
+    for (component, entities) in from {
+        let dirty_entities = into.entry(component).or_default();
+        for entity in entities {
+            if !dirty_entities.contains(&entity) {
+                dirty_entities.push(entity);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::scene_inspector::replay::crdt_state_to_json;
+
+    #[test]
+    fn put_and_delete_components() {
+        let mut state = SceneCrdtState::from_proto();
+        let entity = SceneEntityId::new(512, 0);
+        let transform = resolve_component("Transform").unwrap();
+        let put = |x: f32| ComponentCommand::Put(serde_json::json!({ "position": { "x": x } }));
+
+        apply_component_command(&mut state, entity, transform, put(1.0)).unwrap();
+        apply_component_command(&mut state, entity, transform, put(2.0)).unwrap();
+        let json = crdt_state_to_json(&state);
+        assert_eq!(json["dcl_512v0"]["Transform"]["position"]["x"], 2.0);
+        let definition = state.get_lww_component_definition(transform).unwrap();
+        assert_eq!(definition.get_opaque(entity).unwrap().timestamp.0, 1);
+
+        // Left dirty so the renderer response echoes it to the scene
+        let dirty = state.take_dirty();
+        assert_eq!(dirty.lww[&transform], vec![entity]);
+
+        apply_component_command(&mut state, entity, transform, ComponentCommand::Delete).unwrap();
+        assert!(crdt_state_to_json(&state).is_empty());
+
+        let pointer_events_result = SceneComponentId::POINTER_EVENTS_RESULT;
+        assert!(apply_component_command(
+            &mut state,
+            entity,
+            pointer_events_result,
+            ComponentCommand::Delete
+        )
+        .is_err());
+        assert!(apply_component_command(
+            &mut state,
+            entity,
+            transform,
+            ComponentCommand::Put(serde_json::json!({ "position": { "x": "far" } }))
+        )
+        .is_err());
+        // Numbers forwarded by Godot are all floats
+        apply_component_command(
+            &mut state,
+            entity,
+            transform,
+            ComponentCommand::Put(serde_json::json!({ "parent": 513.0 })),
+        )
+        .unwrap();
+        assert_eq!(
+            crdt_state_to_json(&state)["dcl_512v0"]["Transform"]["parent"],
+            513
+        );
+
+        assert!(resolve_component("NotAComponent").is_err());
+        assert_eq!(resolve_component("1").unwrap(), transform);
+    }
+
+    #[test]
+    fn rejects_dead_entities() {
+        let mut state = SceneCrdtState::from_proto();
+        let entity = SceneEntityId::new(512, 0);
+        state.entities.kill(entity);
+        let result = apply_component_command(
+            &mut state,
+            entity,
+            SceneComponentId::TRANSFORM,
+            ComponentCommand::Put(serde_json::json!({})),
+        );
+        assert!(result.is_err());
+    }
+}
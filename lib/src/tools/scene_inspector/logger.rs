@@ -32,6 +32,9 @@ pub enum SceneInspectorEntry {
     /// inspector. Additive, like `log`.
     #[serde(rename = "network")]
     Network(NetworkEntry),
+    /// A scene action blocked by its scene.json permissions. Additive, like `log`.
+    #[serde(rename = "permission_denied")]
+    PermissionDenied(PermissionDeniedEntry),
 }
 
 /// CRDT operation type.
@@ -244,6 +247,16 @@ pub struct NetworkEntry {
     pub error: Option<String>,
 }
 
+/// A scene action blocked because the scene didn't declare the permission
+/// (`requiredPermissions` / `allowedMediaHostnames` in scene.json).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionDeniedEntry {
+    pub scene_id: i32,
+    pub timestamp_ms: u64,
+    pub permission: String,
+    pub action: String,
+}
+
 /// Sender half of the Scene Inspector channel.
 pub type SceneInspectorSender = mpsc::Sender<SceneInspectorEntry>;
 
@@ -1,22 +1,28 @@
 //! Scene Inspector
 //!
 //! Captures CRDT messages, JS op-calls, lifecycle events, and performance
-//! snapshots from Decentraland scenes; also receives inspector commands.
+//! snapshots from Decentraland scenes; also receives inspector commands, including
+//! write-back ones that edit a scene's components live (`commands`).
 //! Data is dispatched to GDScript via a signal, which then routes to:
 //! - WebSocket (preview channel or dedicated target)
 //! - JSONL files (optional, when scene-inspector-file is enabled)
+//!
+//! Saved JSONL sessions can be replayed offline with `replay`.
 
+pub mod commands;
 pub mod config;
 pub mod dispatcher;
 pub mod logger;
+pub mod replay;
 pub mod storage;
 
 pub use config::SceneInspectorConfig;
 pub use dispatcher::SceneInspectorDispatcher;
 pub use logger::{
     current_timestamp_ms, CrdtDirection, CrdtLogEntry, CrdtOperation, LogEntry, NetworkEntry,
-    OpCallEndEntry, OpCallStartEntry, SceneInspectorEntry, SceneInspectorSender,
-    SceneLifecycleEntry, SceneLifecycleEvent, SessionEndEntry, SessionStartEntry,
+    OpCallEndEntry, OpCallStartEntry, PermissionDeniedEntry, SceneInspectorEntry,
+    SceneInspectorSender, SceneLifecycleEntry, SceneLifecycleEvent, SessionEndEntry,
+    SessionStartEntry,
 };
 pub use storage::StorageManager;
 
@@ -279,6 +285,20 @@ pub fn emit_network(entry: NetworkEntry) {
     }
 }
 
+/// Logs a scene action blocked by its permissions. No-op if the Scene
+/// Inspector is not initialized.
+pub fn log_permission_denied(scene_id: i32, permission: &str, action: &str) {
+    if let Some(sender) = get_logger_sender() {
+        let entry = PermissionDeniedEntry {
+            scene_id,
+            timestamp_ms: current_timestamp_ms(),
+            permission: permission.to_string(),
+            action: action.to_string(),
+        };
+        try_send_entry(&sender, SceneInspectorEntry::PermissionDenied(entry));
+    }
+}
+
 /// Logs a scene lifecycle event. No-op if the Scene Inspector is not
 /// initialized. Per-tick events (`OnUpdate` / `OnUpdateEnd`) are additionally
 /// gated by `LIFECYCLE_VERBOSE` so they can be suppressed without affecting
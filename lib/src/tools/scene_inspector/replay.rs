@@ -0,0 +1,483 @@
+//! Offline CRDT replay of Scene Inspector JSONL sessions.
+//!
+//! Rebuilds a scene's `SceneCrdtState` message by message from the logged
+//! `CrdtLogEntry` records, and dumps it as JSON at any tick or timestamp.
+//! Put/Append entries can only be replayed when the raw payload (`bin`) was
+//! captured; the inspector always attaches it when the payload can't be decoded,
+//! and for every entry with `set_include_bin_payload(true)`. Entries without it
+//! are counted as skipped.
+
+use std::{
+    collections::BTreeMap,
+    io::{self, BufRead, BufReader},
+    path::Path,
+};
+
+use ethers_core::utils::hex;
+use godot::prelude::*;
+use serde::Serialize;
+
+use super::{CrdtLogEntry, CrdtOperation, SceneInspectorEntry};
+use crate::dcl::{
+    components::{
+        component_id_to_name, component_name_to_id,
+        proto_components::deserialize_component_to_json, SceneComponentId, SceneCrdtTimestamp,
+        SceneEntityId,
+    },
+    crdt::{
+        message::{
+            append_value, delete_component, delete_entity, process_many_messages, put_component,
+        },
+        SceneCrdtState,
+    },
+    serialization::{reader::DclReader, writer::DclWriter},
+};
+
+/// Where to stop the replay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayPoint {
+    End,
+    /// Every message logged up to (and including) this scene tick.
+    Tick(u32),
+    /// Every message logged up to (and including) this wall-clock time.
+    TimestampMs(u64),
+}
+
+impl ReplayPoint {
+    /// Parses `end`, `tick:<n>` or `t:<ms>`.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        if value.is_empty() || value == "end" {
+            return Ok(Self::End);
+        }
+        let parsed = if let Some(tick) = value.strip_prefix("tick:") {
+            tick.parse().map(Self::Tick).ok()
+        } else if let Some(timestamp) = value.strip_prefix("t:") {
+            timestamp.parse().map(Self::TimestampMs).ok()
+        } else {
+            None
+        };
+        parsed.ok_or_else(|| format!("invalid replay point `{value}` (end, tick:<n>, t:<ms>)"))
+    }
+
+    fn includes(&self, entry: &CrdtLogEntry) -> bool {
+        match self {
+            Self::End => true,
+            Self::Tick(tick) => entry.tick <= *tick,
+            Self::TimestampMs(timestamp) => entry.timestamp_ms <= *timestamp,
+        }
+    }
+}
+
+/// The CRDT entries of a session, in log order.
+pub struct CrdtReplay {
+    entries: Vec<CrdtLogEntry>,
+    /// Lines that weren't valid Scene Inspector entries.
+    pub invalid_lines: usize,
+}
+
+impl CrdtReplay {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Self::from_jsonl(BufReader::new(std::fs::File::open(path)?))
+    }
+
+    /// Non-CRDT entries (op calls, lifecycle, logs...) are ignored.
+    pub fn from_jsonl(reader: impl BufRead) -> io::Result<Self> {
+        let mut entries = Vec::new();
+        let mut invalid_lines = 0;
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<SceneInspectorEntry>(&line) {
+                Ok(SceneInspectorEntry::CrdtMessage(entry)) => entries.push(entry),
+                Ok(_) => {}
+                Err(_) => invalid_lines += 1,
+            }
+        }
+        Ok(Self {
+            entries,
+            invalid_lines,
+        })
+    }
+
+    pub fn scene_ids(&self) -> Vec<i32> {
+        let mut scene_ids: Vec<i32> = self.entries.iter().map(|entry| entry.scene_id).collect();
+        scene_ids.sort_unstable();
+        scene_ids.dedup();
+        scene_ids
+    }
+
+    pub fn cursor(&self, scene_id: i32) -> ReplayCursor<'_> {
+        ReplayCursor {
+            entries: &self.entries,
+            scene_id,
+            position: 0,
+            current_tick: None,
+            state: SceneCrdtState::from_proto(),
+            applied: 0,
+            skipped: 0,
+        }
+    }
+
+    /// Replays `scene_id` up to `until`.
+    pub fn replay(&self, scene_id: i32, until: ReplayPoint) -> ReplayCursor<'_> {
+        let mut cursor = self.cursor(scene_id);
+        cursor.advance_to(until);
+        cursor
+    }
+}
+
+/// A scene's state at some point of the session.
+pub struct ReplayCursor<'a> {
+    entries: &'a [CrdtLogEntry],
+    scene_id: i32,
+    position: usize,
+    current_tick: Option<u32>,
+    state: SceneCrdtState,
+    applied: usize,
+    skipped: usize,
+}
+
+impl<'a> ReplayCursor<'a> {
+    pub fn state(&self) -> &SceneCrdtState {
+        &self.state
+    }
+
+    fn next_entry_index(&self) -> Option<usize> {
+        (self.position..self.entries.len())
+            .find(|index| self.entries[*index].scene_id == self.scene_id)
+    }
+
+    /// Applies the next message of the scene and returns it.
+    pub fn step(&mut self) -> Option<&'a CrdtLogEntry> {
+        let index = self.next_entry_index()?;
+        let entries = self.entries;
+        let entry = &entries[index];
+        self.position = index + 1;
+
+        // the runtime collects dead entities' components once per tick
+        if self.current_tick.is_some_and(|tick| tick != entry.tick) {
+            self.state.take_dirty();
+        }
+        self.current_tick = Some(entry.tick);
+
+        match encode_crdt_message(entry) {
+            Some(message) => {
+                process_many_messages(&mut DclReader::new(&message), &mut self.state);
+                self.applied += 1;
+            }
+            None => self.skipped += 1,
+        }
+        Some(entry)
+    }
+
+    pub fn advance_to(&mut self, until: ReplayPoint) {
+        while let Some(index) = self.next_entry_index() {
+            if !until.includes(&self.entries[index]) {
+                break;
+            }
+            self.step();
+        }
+        self.state.take_dirty();
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let last = self
+            .position
+            .checked_sub(1)
+            .map(|index| &self.entries[index]);
+        serde_json::to_value(ReplayDump {
+            scene_id: self.scene_id,
+            tick: last.map(|entry| entry.tick),
+            timestamp_ms: last.map(|entry| entry.timestamp_ms),
+            applied_messages: self.applied,
+            skipped_messages: self.skipped,
+            entities: crdt_state_to_json(&self.state),
+        })
+        .unwrap_or_default()
+    }
+}
+
+#[derive(Serialize)]
+struct ReplayDump {
+    scene_id: i32,
+    tick: Option<u32>,
+    timestamp_ms: Option<u64>,
+    applied_messages: usize,
+    skipped_messages: usize,
+    entities: BTreeMap<String, BTreeMap<String, serde_json::Value>>,
+}
+
+/// Re-encodes a logged entry as a binary CRDT message, `None` when the payload
+/// wasn't captured or the component is unknown.
+fn encode_crdt_message(entry: &CrdtLogEntry) -> Option<Vec<u8>> {
+    let entity = SceneEntityId::from_i32(entry.entity_id as i32);
+    let mut buf = Vec::new();
+    let mut writer = DclWriter::new(&mut buf);
+
+    if entry.operation == CrdtOperation::DeleteEntity {
+        delete_entity(&entity, &mut writer);
+        return Some(buf);
+    }
+
+    let component_id = SceneComponentId(component_name_to_id(&entry.component_name)?);
+    let timestamp = SceneCrdtTimestamp(entry.crdt_timestamp);
+    match entry.operation {
+        CrdtOperation::Put => {
+            let payload = hex::decode(entry.bin_payload.as_deref()?).ok()?;
+            put_component(&entity, &component_id, &timestamp, &payload, &mut writer);
+        }
+        CrdtOperation::Append => {
+            let payload = hex::decode(entry.bin_payload.as_deref()?).ok()?;
+            append_value(&entity, &component_id, &timestamp, &payload, &mut writer);
+        }
+        CrdtOperation::Delete => {
+            delete_component(&entity, &component_id, &timestamp, &mut writer);
+        }
+        CrdtOperation::DeleteEntity => unreachable!(),
+    }
+    Some(buf)
+}
+
+/// `{ "<entity>": { "<component>": value } }` for every live entity; GOS
+/// components are arrays (oldest first), values that can't be decoded are
+/// shown as `{ "bin": "<hex>" }`.
+pub fn crdt_state_to_json(
+    state: &SceneCrdtState,
+) -> BTreeMap<String, BTreeMap<String, serde_json::Value>> {
+    let mut entities: BTreeMap<String, BTreeMap<String, serde_json::Value>> = BTreeMap::new();
+    let decode = |component_id: SceneComponentId, bytes: Vec<u8>| {
+        deserialize_component_to_json(component_id.0, &bytes)
+            .unwrap_or_else(|| serde_json::json!({ "bin": hex::encode(bytes) }))
+    };
+
+    for component_id in state.components.keys() {
+        let name = component_id_to_name(component_id.0).to_string();
+        if let Some(definition) = state.get_lww_component_definition(*component_id) {
+            for entity in definition.entities() {
+                let mut bytes = Vec::new();
+                if definition
+                    .to_binary(entity, &mut DclWriter::new(&mut bytes))
+                    .is_err()
+                {
+                    continue;
+                }
+                entities
+                    .entry(entity.to_string())
+                    .or_default()
+                    .insert(name.clone(), decode(*component_id, bytes));
+            }
+        } else if let Some(definition) = state.get_gos_component_definition(*component_id) {
+            for entity in definition.entities() {
+                let values = (0..definition.element_count(entity))
+                    .rev()
+                    .filter_map(|reverse_index| {
+                        let mut bytes = Vec::new();
+                        definition
+                            .to_binary(entity, reverse_index, &mut DclWriter::new(&mut bytes))
+                            .ok()
+                            .map(|_| decode(*component_id, bytes))
+                    })
+                    .collect();
+                entities
+                    .entry(entity.to_string())
+                    .or_default()
+                    .insert(name.clone(), serde_json::Value::Array(values));
+            }
+        }
+    }
+
+    entities
+}
+
+/// Replays a session file and writes the state of `scene_id` (every scene when
+/// negative) at `until` to `output_path` as pretty JSON.
+pub fn dump_session(
+    session_path: &Path,
+    scene_id: i32,
+    until: ReplayPoint,
+    output_path: &Path,
+) -> Result<(), String> {
+    let replay = CrdtReplay::open(session_path)
+        .map_err(|err| format!("can't read {}: {err}", session_path.display()))?;
+    let scene_ids = if scene_id < 0 {
+        replay.scene_ids()
+    } else {
+        vec![scene_id]
+    };
+
+    let scenes: Vec<serde_json::Value> = scene_ids
+        .into_iter()
+        .map(|scene_id| replay.replay(scene_id, until).to_json())
+        .collect();
+    let output = serde_json::json!({
+        "session": session_path.display().to_string(),
+        "invalid_lines": replay.invalid_lines,
+        "scenes": scenes,
+    });
+    let output = serde_json::to_string_pretty(&output).map_err(|err| err.to_string())?;
+    std::fs::write(output_path, output)
+        .map_err(|err| format!("can't write {}: {err}", output_path.display()))
+}
+
+/// GDScript entry point, used by `--crdt-replay` (see `cargo run -- crdt-replay`).
+#[derive(GodotClass)]
+#[class(init, base=RefCounted)]
+pub struct DclCrdtReplay {
+    _base: Base<RefCounted>,
+}
+
+#[godot_api]
+impl DclCrdtReplay {
+    /// `at` is `end`, `tick:<n>` or `t:<ms>`; a negative `scene_id` dumps every scene.
+    /// Returns the error, empty on success.
+    #[func]
+    fn dump_session(
+        session_path: GString,
+        scene_id: i32,
+        at: GString,
+        output_path: GString,
+    ) -> GString {
+        let result = ReplayPoint::parse(&at.to_string()).and_then(|until| {
+            dump_session(
+                Path::new(&session_path.to_string()),
+                scene_id,
+                until,
+                Path::new(&output_path.to_string()),
+            )
+        });
+        match result {
+            Ok(()) => GString::new(),
+            Err(err) => GString::from(&err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::scene_inspector::CrdtDirection;
+
+    fn entry(
+        tick: u32,
+        entity_id: u32,
+        component_name: &str,
+        operation: CrdtOperation,
+        crdt_timestamp: u32,
+        bin_payload: Option<&[u8]>,
+    ) -> String {
+        let entry = SceneInspectorEntry::CrdtMessage(CrdtLogEntry {
+            scene_id: 7,
+            tick,
+            timestamp_ms: 1000 + tick as u64 * 16,
+            direction: CrdtDirection::SceneToRenderer,
+            entity_id,
+            component_name: component_name.to_string().into(),
+            operation,
+            crdt_timestamp,
+            payload: None,
+            bin_payload: bin_payload.map(hex::encode),
+            raw_size_bytes: 0,
+        });
+        serde_json::to_string(&entry).unwrap()
+    }
+
+    fn transform_bytes(x: f32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut writer = DclWriter::new(&mut bytes);
+        writer.write_float3(&[x, 0.0, 0.0]);
+        writer.write_float4(&[0.0, 0.0, 0.0, 1.0]);
+        writer.write_float3(&[1.0, 1.0, 1.0]);
+        writer.write_u32(0);
+        bytes
+    }
+
+    fn session() -> CrdtReplay {
+        let lines = [
+            entry(
+                1,
+                512,
+                "Transform",
+                CrdtOperation::Put,
+                1,
+                Some(&transform_bytes(1.0)),
+            ),
+            entry(
+                1,
+                513,
+                "Transform",
+                CrdtOperation::Put,
+                1,
+                Some(&transform_bytes(5.0)),
+            ),
+            entry(
+                2,
+                512,
+                "Transform",
+                CrdtOperation::Put,
+                2,
+                Some(&transform_bytes(2.0)),
+            ),
+            // no payload captured
+            entry(2, 512, "MeshRenderer", CrdtOperation::Put, 1, None),
+            entry(3, 513, "", CrdtOperation::DeleteEntity, 0, None),
+            r#"{"type":"session_end","session_id":"x","timestamp_ms":0}"#.to_string(),
+            "not json".to_string(),
+        ];
+        CrdtReplay::from_jsonl(lines.join("\n").as_bytes()).unwrap()
+    }
+
+    fn transform_x(cursor: &ReplayCursor, entity: &str) -> Option<f64> {
+        cursor.to_json()["entities"][entity]["Transform"]["position"]["x"].as_f64()
+    }
+
+    #[test]
+    fn replays_up_to_a_point() {
+        let replay = session();
+        assert_eq!(replay.scene_ids(), vec![7]);
+        assert_eq!(replay.invalid_lines, 1);
+
+        let cursor = replay.replay(7, ReplayPoint::Tick(1));
+        assert_eq!(transform_x(&cursor, "dcl_512v0"), Some(1.0));
+        assert_eq!(transform_x(&cursor, "dcl_513v0"), Some(5.0));
+
+        let cursor = replay.replay(7, ReplayPoint::TimestampMs(1032));
+        assert_eq!(transform_x(&cursor, "dcl_512v0"), Some(2.0));
+        assert_eq!(cursor.to_json()["skipped_messages"], 1);
+
+        let cursor = replay.replay(7, ReplayPoint::End);
+        assert!(cursor.to_json()["entities"].get("dcl_513v0").is_none());
+        assert_eq!(cursor.to_json()["tick"], 3);
+    }
+
+    #[test]
+    fn steps_message_by_message() {
+        let replay = session();
+        let mut cursor = replay.cursor(7);
+        assert_eq!(cursor.step().map(|entry| entry.entity_id), Some(512));
+        assert_eq!(transform_x(&cursor, "dcl_512v0"), Some(1.0));
+        assert!(cursor.step().is_some());
+        assert!(cursor.step().is_some());
+        assert_eq!(transform_x(&cursor, "dcl_512v0"), Some(2.0));
+        assert!(cursor.step().is_some());
+        assert!(cursor.step().is_some());
+        assert!(cursor.step().is_none());
+
+        // other scenes replay nothing
+        assert!(replay.cursor(8).step().is_none());
+    }
+
+    #[test]
+    fn parses_replay_points() {
+        assert_eq!(ReplayPoint::parse("end"), Ok(ReplayPoint::End));
+        assert_eq!(ReplayPoint::parse("tick:12"), Ok(ReplayPoint::Tick(12)));
+        assert_eq!(
+            ReplayPoint::parse("t:1700000000000"),
+            Ok(ReplayPoint::TimestampMs(1700000000000))
+        );
+        assert!(ReplayPoint::parse("tick:").is_err());
+        assert!(ReplayPoint::parse("12").is_err());
+    }
+}
@@ -0,0 +1,58 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+
+use crate::consts::GODOT_PROJECT_FOLDER;
+use crate::path::get_godot_path;
+use crate::ui::{print_message, print_section, MessageType};
+
+/// Replays the CRDT messages of a Scene Inspector JSONL session (written with
+/// `--scene-inspector-file`) in a headless Godot, and writes the scene state
+/// at `at` (`end`, `tick:<n>` or `t:<ms>`) as JSON.
+pub fn run_crdt_replay(
+    session: &str,
+    scene_id: Option<&str>,
+    at: &str,
+    output: Option<&str>,
+) -> anyhow::Result<()> {
+    print_section("CRDT Replay");
+
+    // Godot runs from the project folder, so every path is made absolute
+    let session = std::fs::canonicalize(session)
+        .with_context(|| format!("session file not found: {session}"))?;
+    let output = match output {
+        Some(output) => std::env::current_dir()?.join(output),
+        None => session.with_extension("state.json"),
+    };
+    let output = output.to_string_lossy().to_string();
+
+    let mut args = vec![
+        "--path".to_string(),
+        GODOT_PROJECT_FOLDER.to_string(),
+        "--headless".to_string(),
+        "--crdt-replay".to_string(),
+        session.to_string_lossy().to_string(),
+        "--crdt-replay-at".to_string(),
+        at.to_string(),
+        "--crdt-replay-output".to_string(),
+        output.clone(),
+    ];
+    if let Some(scene_id) = scene_id {
+        args.push("--crdt-replay-scene".to_string());
+        args.push(scene_id.to_string());
+    }
+
+    print_message(
+        MessageType::Step,
+        &format!("Replaying {} up to {at}...", session.display()),
+    );
+    let status = std::process::Command::new(get_godot_path())
+        .args(&args)
+        .status()?;
+
+    if !status.success() || !PathBuf::from(&output).exists() {
+        anyhow::bail!("CRDT replay failed ({status})");
+    }
+    print_message(MessageType::Success, &format!("State written to {output}"));
+    Ok(())
+}
@@ -6,7 +6,7 @@ use base64::{engine::general_purpose::STANDARD, Engine};
 
 use crate::consts::RUST_LIB_PROJECT_FOLDER;
 use crate::helpers::BinPaths;
-use crate::image_comparison::compare_images_similarity;
+use crate::image_comparison::{compare_images, diff_image_path, SnapshotMask};
 use crate::ui::{self, format_duration, MessageType, SummaryRow};
 use crate::{check_gdscript, run, tests, version_check};
 
@@ -260,7 +260,7 @@ fn update_local_snapshots() -> Result<(), anyhow::Error> {
 
     let mut total_copied = 0;
 
-    for (base_dir, _) in SNAPSHOT_DIRS {
+    for (base_dir, _, _) in SNAPSHOT_DIRS {
         let comparison_dir = Path::new(base_dir).join("comparison");
         if !comparison_dir.exists() {
             continue;
@@ -397,7 +397,7 @@ pub fn run_full_tests(
         ui::print_message(MessageType::Step, "Phase 4: Visual Tests");
 
         // Clean stale comparison dirs to avoid leftover files from previous runs
-        for (dir, _) in SNAPSHOT_DIRS {
+        for (dir, _, _) in SNAPSHOT_DIRS {
             let comparison_dir = Path::new(dir).join("comparison");
             if comparison_dir.exists() {
                 let _ = fs::remove_dir_all(&comparison_dir);
@@ -448,17 +448,22 @@ pub fn run_full_tests(
 
 // ── HTML Report Generation ──
 
-const SNAPSHOT_DIRS: &[(&str, &str)] = &[
+/// Snapshot directory, report category and SSIM threshold (the ones `tests.rs` checks with).
+/// Scenes are looser: on their large flat areas ±2 render noise already scores 0.93, and
+/// antialiasing jitter drops single tiles to 0.78.
+const SNAPSHOT_DIRS: &[(&str, &str, f64)] = &[
     (
         "tests/snapshots/avatar-image-generation",
         "Avatar Image Generation",
+        0.95,
     ),
     (
         "tests/snapshots/scene-image-generation",
         "Scene Image Generation",
+        0.95,
     ),
-    ("tests/snapshots/scenes", "Scene Tests"),
-    ("tests/snapshots/client", "Client Tests"),
+    ("tests/snapshots/scenes", "Scene Tests", 0.90),
+    ("tests/snapshots/client", "Client Tests", 0.95),
 ];
 
 struct SnapshotComparison {
@@ -466,8 +471,11 @@ struct SnapshotComparison {
     category: String,
     baseline_path: PathBuf,
     comparison_path: PathBuf,
-    similarity: Option<f64>,
+    ssim: Option<f64>,
     error_msg: Option<String>,
+    /// Where the snapshot diverged, one line per failing area
+    divergences: Vec<String>,
+    diff_path: Option<PathBuf>,
     passed: bool,
 }
 
@@ -475,7 +483,7 @@ struct SnapshotComparison {
 fn collect_snapshot_comparisons() -> Vec<SnapshotComparison> {
     let mut comparisons = Vec::new();
 
-    for (dir, category) in SNAPSHOT_DIRS {
+    for (dir, category, threshold) in SNAPSHOT_DIRS {
         let comparison_dir = Path::new(dir).join("comparison");
         if !comparison_dir.exists() {
             continue;
@@ -501,24 +509,44 @@ fn collect_snapshot_comparisons() -> Vec<SnapshotComparison> {
             let file_name = path.file_name().unwrap();
             let baseline_path = Path::new(dir).join(file_name);
 
-            let (similarity, error_msg) = if baseline_path.exists() {
-                match compare_images_similarity(&baseline_path, &path) {
-                    Ok(s) => (Some(s), None),
-                    Err(e) => (None, Some(e)),
-                }
+            let comparison = if baseline_path.exists() {
+                SnapshotMask::load_for(&baseline_path)
+                    .and_then(|mask| compare_images(&baseline_path, &path, *threshold, None, &mask))
             } else {
-                (None, Some("Missing baseline".to_string()))
+                Err("Missing baseline".to_string())
             };
 
-            comparisons.push(SnapshotComparison {
+            let mut snapshot = SnapshotComparison {
                 name: file_name.to_string_lossy().to_string(),
                 category: category.to_string(),
                 baseline_path,
                 comparison_path: path,
-                similarity,
-                error_msg,
-                passed: similarity.map_or(false, |s| s >= 0.90),
-            });
+                ssim: None,
+                error_msg: None,
+                divergences: Vec::new(),
+                diff_path: None,
+                passed: false,
+            };
+            match comparison {
+                Ok(comparison) => {
+                    snapshot.ssim = Some(comparison.image.ssim);
+                    snapshot.passed = comparison.passed();
+                    snapshot.divergences = comparison
+                        .failures()
+                        .iter()
+                        .map(|failure| failure.to_string())
+                        .collect();
+                    if !snapshot.passed {
+                        let diff_path = diff_image_path(&snapshot.comparison_path);
+                        match comparison.write_diff_image(&diff_path) {
+                            Ok(()) => snapshot.diff_path = Some(diff_path),
+                            Err(e) => snapshot.error_msg = Some(e),
+                        }
+                    }
+                }
+                Err(e) => snapshot.error_msg = Some(e),
+            }
+            comparisons.push(snapshot);
         }
     }
 
@@ -576,20 +604,44 @@ fn generate_html_report(
     } else {
         for c in &comparisons {
             let status_class = if c.passed { "pass" } else { "fail" };
-            let similarity_pct = match (&c.similarity, &c.error_msg) {
-                (Some(s), _) => format!("{:.2}%", s * 100.0),
+            let similarity = match (&c.ssim, &c.error_msg) {
+                (Some(s), _) => format!("{:.4}", s),
                 (None, Some(e)) => html_escape(e),
                 (None, None) => "Unknown".to_string(),
             };
             let baseline_uri = image_to_data_uri(&c.baseline_path);
             let comparison_uri = image_to_data_uri(&c.comparison_path);
             let open_attr = if c.passed { "" } else { " open" };
+            let diff_html = match &c.diff_path {
+                Some(diff_path) => format!(
+                    r#"
+    <div class="snapshot-img">
+      <div class="img-label">Diff</div>
+      <img src="{}" />
+    </div>"#,
+                    image_to_data_uri(diff_path)
+                ),
+                None => String::new(),
+            };
+            let mut divergences_html = String::new();
+            for divergence in &c.divergences {
+                divergences_html.push_str(&format!(
+                    "<div class=\"error-detail\">Diverged: {}</div>",
+                    html_escape(divergence)
+                ));
+            }
+            if let (Some(_), Some(e)) = (&c.ssim, &c.error_msg) {
+                divergences_html.push_str(&format!(
+                    "<div class=\"error-detail\">{}</div>",
+                    html_escape(e)
+                ));
+            }
 
             snapshots_html.push_str(&format!(
                 r#"<details class="snapshot-card {status_class}"{open_attr}><summary class="snapshot-header">
     <span class="snapshot-name">{name}</span>
     <span class="snapshot-category">{category}</span>
-    <span class="snapshot-similarity">Similarity: {similarity}</span>
+    <span class="snapshot-similarity">SSIM: {similarity}</span>
   </summary>
   <div class="snapshot-images">
     <div class="snapshot-img">
@@ -599,17 +651,19 @@ fn generate_html_report(
     <div class="snapshot-img">
       <div class="img-label">New</div>
       <img src="{comparison}" />
-    </div>
-  </div>
+    </div>{diff}
+  </div>{divergences}
 </details>
 "#,
                 status_class = status_class,
                 open_attr = open_attr,
                 name = html_escape(&c.name),
                 category = html_escape(&c.category),
-                similarity = similarity_pct,
+                similarity = similarity,
                 baseline = baseline_uri,
                 comparison = comparison_uri,
+                diff = diff_html,
+                divergences = divergences_html,
             ));
         }
     }
@@ -1,47 +1,506 @@
-use image::{GenericImageView, Pixel};
+//! Perceptual comparison of snapshot images.
+//!
+//! Images are compared with SSIM (structural similarity) over 8x8 windows of each color channel,
+//! premultiplied by alpha so transparent backgrounds compare equal. SSIM tolerates the
+//! antialiasing noise a per-pixel distance flags, and besides the whole image the worst 32x32
+//! tile is checked too, so a small localized regression isn't averaged away.
+//!
+//! SSIM compares structure, so it hardly moves when the whole image is tinted or graded. Suites
+//! where that matters (lighting) also give a maximum shift of the mean color, in 0-255 levels of
+//! the channel that moved the most.
+//!
+//! A baseline `<name>.png` can have a `<name>.mask.json` next to it:
+//!
+//! ```json
+//! {
+//!   "threshold": 0.97,
+//!   "regions": [
+//!     { "name": "clock", "x": 0, "y": 0, "width": 64, "height": 20, "ignore": true },
+//!     { "name": "particles", "x": 200, "y": 120, "width": 96, "height": 96, "threshold": 0.6 }
+//!   ]
+//! }
+//! ```
+//!
+//! `threshold` overrides the suite's one for that image. Ignored regions aren't compared, regions
+//! with a threshold are checked against it instead of the image's. A failing comparison writes a
+//! `<name>.diff.png` heatmap next to the result image.
+
+use image::{Rgb, RgbImage, RgbaImage};
+use serde::Deserialize;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-// Function to compare two images and calculate similarity
-pub fn compare_images_similarity(image_path_1: &Path, image_path_2: &Path) -> Result<f64, String> {
-    let img1 = image::open(image_path_1)
-        .map_err(|_| format!("Failed to open image: {:?}", image_path_1))?;
-    let img2 = image::open(image_path_2)
-        .map_err(|_| format!("Failed to open image: {:?}", image_path_2))?;
+/// Side of the SSIM windows, in pixels
+const WINDOW: usize = 8;
+/// Side of the tiles checked on their own, in pixels
+const TILE: usize = 32;
+/// How much more dissimilar than the image a tile may be: with a 0.95 threshold, tiles fail
+/// below 0.8
+const TILE_TOLERANCE: f64 = 4.0;
+/// Tiles mostly masked out aren't checked, a handful of windows is too noisy
+const MIN_TILE_WINDOWS: usize = TILE * TILE / 4;
+
+const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    fn contains(&self, x: usize, y: usize) -> bool {
+        let (x, y) = (x as u64, y as u64);
+        x >= self.x as u64
+            && x < self.x as u64 + self.width as u64
+            && y >= self.y as u64
+            && y < self.y as u64 + self.height as u64
+    }
+}
 
-    if img1.dimensions() != img2.dimensions() {
-        return Err("Images have different dimensions".to_string());
+impl std::fmt::Display for Rect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}x{} at ({}, {})",
+            self.width, self.height, self.x, self.y
+        )
     }
+}
 
-    let (width, height) = img1.dimensions();
-    let mut total_diff = 0.0;
-    let mut total_pixels = 0.0;
+#[derive(Debug, Clone, Deserialize)]
+pub struct MaskRegion {
+    #[serde(default)]
+    pub name: String,
+    #[serde(flatten)]
+    pub rect: Rect,
+    /// Not compared at all (clocks, particles...)
+    #[serde(default)]
+    pub ignore: bool,
+    /// Minimum SSIM of the region, checked instead of the image's threshold
+    pub threshold: Option<f64>,
+}
 
-    for y in 0..height {
-        for x in 0..width {
-            let pixel1 = img1.get_pixel(x, y);
-            let pixel2 = img2.get_pixel(x, y);
+/// Per-snapshot settings, from the optional `<name>.mask.json` next to the baseline
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SnapshotMask {
+    /// Minimum SSIM of the image, instead of the suite's
+    pub threshold: Option<f64>,
+    #[serde(default)]
+    pub regions: Vec<MaskRegion>,
+}
+
+impl SnapshotMask {
+    pub fn load_for(baseline_path: &Path) -> Result<Self, String> {
+        let path = baseline_path.with_extension("mask.json");
+        match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| format!("Invalid mask {:?}: {}", path, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("Failed to read mask {:?}: {}", path, e)),
+        }
+    }
+}
+
+/// Mean SSIM of an area of the image against its threshold
+#[derive(Debug, Clone)]
+pub struct AreaScore {
+    pub name: String,
+    pub rect: Rect,
+    pub ssim: f64,
+    pub threshold: f64,
+}
+
+impl AreaScore {
+    pub fn passed(&self) -> bool {
+        self.ssim >= self.threshold
+    }
+}
+
+impl std::fmt::Display for AreaScore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}: SSIM {:.4} (threshold {:.4})",
+            self.name, self.rect, self.ssim, self.threshold
+        )
+    }
+}
+
+/// How far the mean color moved from the baseline's
+#[derive(Debug, Clone)]
+pub struct ColorShift {
+    /// Levels (0-255) of the channel that moved the most
+    pub levels: f64,
+    pub max_levels: f64,
+}
+
+impl ColorShift {
+    pub fn passed(&self) -> bool {
+        self.levels <= self.max_levels
+    }
+}
+
+impl std::fmt::Display for ColorShift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "mean color shifted {:.2} levels (max {:.2})",
+            self.levels, self.max_levels
+        )
+    }
+}
+
+pub struct ImageComparison {
+    width: usize,
+    height: usize,
+    /// SSIM of each window, by its top-left pixel
+    ssim_map: Vec<f32>,
+    /// Windows whose center is in an ignored region
+    ignored: Vec<bool>,
+    /// Luma of the result image, the heatmap background
+    background: Vec<u8>,
+    /// The image outside of the regions with their own threshold
+    pub image: AreaScore,
+    /// The tile with the lowest SSIM, where the images diverge the most
+    pub worst_tile: Option<AreaScore>,
+    pub regions: Vec<AreaScore>,
+    /// Only checked when the suite gives a maximum
+    pub color_shift: Option<ColorShift>,
+}
+
+impl ImageComparison {
+    pub fn passed(&self) -> bool {
+        self.failures().is_empty() && self.color_shift.as_ref().is_none_or(ColorShift::passed)
+    }
+
+    /// The areas under their threshold
+    pub fn failures(&self) -> Vec<&AreaScore> {
+        std::iter::once(&self.image)
+            .chain(&self.worst_tile)
+            .chain(&self.regions)
+            .filter(|area| !area.passed())
+            .collect()
+    }
+
+    /// Writes the heatmap of the dissimilarity over the dimmed result image: red where the
+    /// images diverge, blue over ignored regions, failing areas outlined in yellow.
+    pub fn write_diff_image(&self, path: &Path) -> Result<(), String> {
+        let map_width = self.width - WINDOW + 1;
+        let map_height = self.height - WINDOW + 1;
+        let mut image = RgbImage::new(self.width as u32, self.height as u32);
+        for (x, y, pixel) in image.enumerate_pixels_mut() {
+            let (x, y) = (x as usize, y as usize);
+            let window_x = x.saturating_sub(WINDOW / 2).min(map_width - 1);
+            let window_y = y.saturating_sub(WINDOW / 2).min(map_height - 1);
+            let window = window_y * map_width + window_x;
+            let gray = self.background[y * self.width + x] as f32 * 0.35;
+            *pixel = if self.ignored[window] {
+                Rgb([gray as u8, gray as u8, (gray + 80.0) as u8])
+            } else {
+                // Saturates at SSIM 0.5
+                let heat = ((1.0 - self.ssim_map[window]) * 2.0).clamp(0.0, 1.0);
+                let dimmed = (gray * (1.0 - heat)) as u8;
+                Rgb([(gray + heat * (255.0 - gray)) as u8, dimmed, dimmed])
+            };
+        }
+
+        for area in self.failures() {
+            let rect = area.rect;
+            if rect.width == 0
+                || rect.height == 0
+                || rect.x >= image.width()
+                || rect.y >= image.height()
+            {
+                continue;
+            }
+            let right = rect.x.saturating_add(rect.width).min(image.width()) - 1;
+            let bottom = rect.y.saturating_add(rect.height).min(image.height()) - 1;
+            for x in rect.x..=right {
+                image.put_pixel(x, rect.y, Rgb([255, 220, 0]));
+                image.put_pixel(x, bottom, Rgb([255, 220, 0]));
+            }
+            for y in rect.y..=bottom {
+                image.put_pixel(rect.x, y, Rgb([255, 220, 0]));
+                image.put_pixel(right, y, Rgb([255, 220, 0]));
+            }
+        }
+
+        image
+            .save(path)
+            .map_err(|e| format!("Failed to write diff image {:?}: {}", path, e))
+    }
+}
+
+/// `<name>.diff.png` next to the result image
+pub fn diff_image_path(result_path: &Path) -> PathBuf {
+    result_path.with_extension("diff.png")
+}
+
+pub fn compare_images(
+    baseline_path: &Path,
+    result_path: &Path,
+    threshold: f64,
+    max_color_shift: Option<f64>,
+    mask: &SnapshotMask,
+) -> Result<ImageComparison, String> {
+    let open = |path: &Path| {
+        image::open(path)
+            .map(|image| image.to_rgba8())
+            .map_err(|_| format!("Failed to open image: {:?}", path))
+    };
+    compare_rgba(
+        &open(baseline_path)?,
+        &open(result_path)?,
+        threshold,
+        max_color_shift,
+        mask,
+    )
+}
+
+fn compare_rgba(
+    baseline: &RgbaImage,
+    result: &RgbaImage,
+    threshold: f64,
+    max_color_shift: Option<f64>,
+    mask: &SnapshotMask,
+) -> Result<ImageComparison, String> {
+    if baseline.dimensions() != result.dimensions() {
+        return Err(format!(
+            "Images have different dimensions: {:?} and {:?}",
+            baseline.dimensions(),
+            result.dimensions()
+        ));
+    }
+    let (width, height) = baseline.dimensions();
+    let (width, height) = (width as usize, height as usize);
+    if width < WINDOW || height < WINDOW {
+        return Err(format!("Images are smaller than {WINDOW}x{WINDOW}"));
+    }
+    let baseline = premultiplied_channels(baseline);
+    let result = premultiplied_channels(result);
+
+    let map_width = width - WINDOW + 1;
+    let map_height = height - WINDOW + 1;
+    let mut ssim_map = vec![0.0f32; map_width * map_height];
+    for (a, b) in baseline.iter().zip(&result) {
+        let sum_a = window_sums(width, height, |i| a[i] as u32);
+        let sum_b = window_sums(width, height, |i| b[i] as u32);
+        let sum_aa = window_sums(width, height, |i| a[i] as u32 * a[i] as u32);
+        let sum_bb = window_sums(width, height, |i| b[i] as u32 * b[i] as u32);
+        let sum_ab = window_sums(width, height, |i| a[i] as u32 * b[i] as u32);
+        for (window, value) in ssim_map.iter_mut().enumerate() {
+            let ssim = window_ssim(
+                sum_a[window],
+                sum_b[window],
+                sum_aa[window],
+                sum_bb[window],
+                sum_ab[window],
+            );
+            *value += ssim as f32 / 3.0;
+        }
+    }
 
-            let diff = pixel1
-                .channels()
+    // Each window counts for the areas its center is in
+    let tiles_x = width.div_ceil(TILE);
+    let mut tiles = vec![(0.0, 0usize); tiles_x * height.div_ceil(TILE)];
+    let mut regions = vec![(0.0, 0usize); mask.regions.len()];
+    let mut image = (0.0, 0usize);
+    let mut ignored = vec![false; ssim_map.len()];
+    for (window, value) in ssim_map.iter().enumerate() {
+        let x = window % map_width + WINDOW / 2;
+        let y = window / map_width + WINDOW / 2;
+        let value = *value as f64;
+        if mask
+            .regions
+            .iter()
+            .any(|region| region.ignore && region.rect.contains(x, y))
+        {
+            ignored[window] = true;
+            continue;
+        }
+
+        let mut own_threshold = false;
+        for (region, sum) in mask.regions.iter().zip(&mut regions) {
+            if region.rect.contains(x, y) {
+                sum.0 += value;
+                sum.1 += 1;
+                own_threshold |= region.threshold.is_some();
+            }
+        }
+        if !own_threshold {
+            image.0 += value;
+            image.1 += 1;
+            let tile = &mut tiles[(y / TILE) * tiles_x + x / TILE];
+            tile.0 += value;
+            tile.1 += 1;
+        }
+    }
+
+    let mean = |(sum, count): (f64, usize)| if count == 0 { 1.0 } else { sum / count as f64 };
+    let threshold = mask.threshold.unwrap_or(threshold);
+    let tile_threshold = (1.0 - (1.0 - threshold) * TILE_TOLERANCE).max(0.0);
+    let worst_tile = tiles
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, count))| *count >= MIN_TILE_WINDOWS)
+        .map(|(tile, sum)| (tile, mean(*sum)))
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(tile, ssim)| {
+            let x = (tile % tiles_x * TILE) as u32;
+            let y = (tile / tiles_x * TILE) as u32;
+            AreaScore {
+                name: "tile".to_string(),
+                rect: Rect {
+                    x,
+                    y,
+                    width: TILE.min(width - x as usize) as u32,
+                    height: TILE.min(height - y as usize) as u32,
+                },
+                ssim,
+                threshold: tile_threshold,
+            }
+        });
+    let regions = mask
+        .regions
+        .iter()
+        .zip(regions)
+        .filter(|(region, _)| !region.ignore)
+        .map(|(region, sum)| AreaScore {
+            name: format!("region `{}`", region.name),
+            rect: region.rect,
+            ssim: mean(sum),
+            threshold: region.threshold.unwrap_or(threshold),
+        })
+        .collect();
+
+    let color_shift = max_color_shift.map(|max_levels| ColorShift {
+        levels: mean_color_shift(&baseline, &result, width, mask),
+        max_levels,
+    });
+
+    let background = result[0]
+        .iter()
+        .zip(&result[1])
+        .zip(&result[2])
+        .map(|((r, g), b)| ((*r as u32 * 299 + *g as u32 * 587 + *b as u32 * 114) / 1000) as u8)
+        .collect();
+
+    Ok(ImageComparison {
+        width,
+        height,
+        ssim_map,
+        ignored,
+        background,
+        image: AreaScore {
+            name: "image".to_string(),
+            rect: Rect {
+                x: 0,
+                y: 0,
+                width: width as u32,
+                height: height as u32,
+            },
+            ssim: mean(image),
+            threshold,
+        },
+        worst_tile,
+        regions,
+        color_shift,
+    })
+}
+
+/// Largest difference of the mean of a channel, outside of the ignored regions
+fn mean_color_shift(
+    baseline: &[Vec<u8>; 3],
+    result: &[Vec<u8>; 3],
+    width: usize,
+    mask: &SnapshotMask,
+) -> f64 {
+    let compared: Vec<bool> = (0..baseline[0].len())
+        .map(|i| {
+            !mask
+                .regions
                 .iter()
-                .zip(pixel2.channels().iter())
-                .map(|(p1, p2)| (*p1 as f64 - *p2 as f64).powi(2))
-                .sum::<f64>();
+                .any(|region| region.ignore && region.rect.contains(i % width, i / width))
+        })
+        .collect();
+    let count = compared.iter().filter(|compared| **compared).count();
+    if count == 0 {
+        return 0.0;
+    }
+    baseline
+        .iter()
+        .zip(result)
+        .map(|(a, b)| {
+            let difference: i64 = a
+                .iter()
+                .zip(b)
+                .zip(&compared)
+                .filter(|(_, compared)| **compared)
+                .map(|((a, b), _)| *b as i64 - *a as i64)
+                .sum();
+            (difference as f64 / count as f64).abs()
+        })
+        .fold(0.0, f64::max)
+}
 
-            total_diff += diff.sqrt();
-            total_pixels += 1.0;
+/// The R, G and B planes, premultiplied by alpha
+fn premultiplied_channels(image: &RgbaImage) -> [Vec<u8>; 3] {
+    let mut channels: [Vec<u8>; 3] = Default::default();
+    for pixel in image.pixels() {
+        let alpha = pixel[3] as u32;
+        for (channel, value) in channels.iter_mut().zip(pixel.0) {
+            channel.push(((value as u32 * alpha + 127) / 255) as u8);
         }
     }
+    channels
+}
 
-    let max_diff_per_pixel = (255.0_f64 * 3.0_f64).sqrt();
-    let average_diff = total_diff / total_pixels;
-    let similarity = 1.0 - (average_diff / max_diff_per_pixel);
+/// Sums of `value` over every `WINDOW`x`WINDOW` window, by its top-left pixel: the sums of each
+/// row first, then the sums of those down the columns
+fn window_sums(width: usize, height: usize, value: impl Fn(usize) -> u32) -> Vec<u32> {
+    let map_width = width - WINDOW + 1;
+    let map_height = height - WINDOW + 1;
 
-    Ok(similarity)
+    let mut rows = vec![0u32; map_width * height];
+    for y in 0..height {
+        let row = y * width;
+        let mut sum: u32 = (0..WINDOW).map(|x| value(row + x)).sum();
+        rows[y * map_width] = sum;
+        for x in 1..map_width {
+            sum = sum + value(row + x + WINDOW - 1) - value(row + x - 1);
+            rows[y * map_width + x] = sum;
+        }
+    }
+
+    let mut sums = vec![0u32; map_width * map_height];
+    for x in 0..map_width {
+        let mut sum: u32 = (0..WINDOW).map(|y| rows[y * map_width + x]).sum();
+        for y in 0..map_height {
+            if y > 0 {
+                sum = sum + rows[(y + WINDOW - 1) * map_width + x] - rows[(y - 1) * map_width + x];
+            }
+            sums[y * map_width + x] = sum;
+        }
+    }
+    sums
 }
 
-// Function to list all PNG files in a directory
+fn window_ssim(sum_a: u32, sum_b: u32, sum_aa: u32, sum_bb: u32, sum_ab: u32) -> f64 {
+    let n = (WINDOW * WINDOW) as f64;
+    let mean_a = sum_a as f64 / n;
+    let mean_b = sum_b as f64 / n;
+    let variance_a = (sum_aa as f64 / n - mean_a * mean_a).max(0.0);
+    let variance_b = (sum_bb as f64 / n - mean_b * mean_b).max(0.0);
+    let covariance = sum_ab as f64 / n - mean_a * mean_b;
+    ((2.0 * mean_a * mean_b + C1) * (2.0 * covariance + C2))
+        / ((mean_a * mean_a + mean_b * mean_b + C1) * (variance_a + variance_b + C2))
+}
+
+// Function to list all PNG files in a directory, without the diff images
 fn list_png_files(directory: &Path) -> Result<Vec<PathBuf>, String> {
     let mut files = vec![];
 
@@ -50,7 +509,11 @@ fn list_png_files(directory: &Path) -> Result<Vec<PathBuf>, String> {
     {
         let entry = entry.map_err(|_| "Failed to access entry in directory".to_string())?;
         let path = entry.path();
-        if path.extension().and_then(|ext| ext.to_str()) == Some("png") {
+        let is_diff = path
+            .file_name()
+            .and_then(|f| f.to_str())
+            .is_some_and(|f| f.ends_with(".diff.png"));
+        if path.extension().and_then(|ext| ext.to_str()) == Some("png") && !is_diff {
             files.push(path);
         }
     }
@@ -64,6 +527,7 @@ pub fn compare_images_folders(
     snapshot_folder: &Path,
     result_folder: &Path,
     similarity_threshold: f64,
+    max_color_shift: Option<f64>,
 ) -> Result<(), String> {
     let snapshot_files = list_png_files(snapshot_folder)?;
     let result_files = list_png_files(result_folder)?;
@@ -77,28 +541,207 @@ pub fn compare_images_folders(
 
     // Compare each corresponding file
     for (snapshot_file, result_file) in snapshot_files.iter().zip(result_files.iter()) {
-        let similarity = compare_images_similarity(snapshot_file, result_file)?;
+        let mask = SnapshotMask::load_for(snapshot_file)?;
+        let comparison = compare_images(
+            snapshot_file,
+            result_file,
+            similarity_threshold,
+            max_color_shift,
+            &mask,
+        )?;
+
+        println!(
+            "Files {:?} and {:?} have an SSIM of {:.5}.",
+            snapshot_file, result_file, comparison.image.ssim
+        );
 
-        // If similarity is less than the `similarity_threshold`, the test fails
-        if similarity < similarity_threshold {
-            failed_files.push((snapshot_file, result_file));
+        let diff_path = diff_image_path(result_file);
+        if comparison.passed() {
+            let _ = fs::remove_file(&diff_path);
+            continue;
         }
 
-        println!(
-            "Files {:?} and {:?} are {:.5}% similar.",
-            snapshot_file,
-            result_file,
-            similarity * 100.0
+        // Report where it diverged, the heatmap shows it
+        for failure in comparison.failures() {
+            println!("  diverged: {}", failure);
+        }
+        if let Some(color_shift) = comparison.color_shift.as_ref().filter(|c| !c.passed()) {
+            println!("  diverged: {}", color_shift);
+        }
+        comparison.write_diff_image(&diff_path)?;
+        println!("  diff image: {:?}", diff_path);
+        failed_files.push(
+            result_file
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy(),
         );
     }
 
     if !failed_files.is_empty() {
-        return Err(format!("{} files are too different!", failed_files.len()));
+        return Err(format!(
+            "{} files are too different: {}",
+            failed_files.len(),
+            failed_files.join(", ")
+        ));
     }
 
     println!(
-        "All files match with {:.2}% similarity or higher!",
-        similarity_threshold * 100.0
+        "All files match with an SSIM of {:.3} or higher!",
+        similarity_threshold
     );
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    /// Textured, so SSIM has structure to compare
+    fn pattern(x: u32, y: u32) -> Rgba<u8> {
+        let value = ((x * 7 + y * 13) % 64 * 4) as u8;
+        Rgba([value, 255 - value, (x * 2) as u8, 255])
+    }
+
+    fn with_block(block: Rect) -> RgbaImage {
+        RgbaImage::from_fn(256, 256, |x, y| {
+            if block.contains(x as usize, y as usize) {
+                Rgba([255, 0, 255, 255])
+            } else {
+                pattern(x, y)
+            }
+        })
+    }
+
+    const BLOCK: Rect = Rect {
+        x: 100,
+        y: 140,
+        width: 20,
+        height: 20,
+    };
+
+    #[test]
+    fn tolerates_noise_and_catches_local_changes() {
+        let baseline = RgbaImage::from_fn(256, 256, pattern);
+        let same =
+            compare_rgba(&baseline, &baseline, 0.95, None, &SnapshotMask::default()).unwrap();
+        assert!((same.image.ssim - 1.0).abs() < 1e-6);
+        assert!(same.passed());
+
+        // Antialiasing-like noise of a couple of levels
+        let noisy = RgbaImage::from_fn(256, 256, |x, y| {
+            let mut pixel = pattern(x, y);
+            pixel[0] = pixel[0].saturating_add(((x ^ y) % 3) as u8);
+            pixel
+        });
+        assert!(
+            compare_rgba(&baseline, &noisy, 0.95, None, &SnapshotMask::default())
+                .unwrap()
+                .passed()
+        );
+
+        // A small block changes the image SSIM little but fails its tile
+        let comparison = compare_rgba(
+            &baseline,
+            &with_block(BLOCK),
+            0.95,
+            None,
+            &SnapshotMask::default(),
+        )
+        .unwrap();
+        assert!(comparison.image.passed(), "{}", comparison.image);
+        assert!(!comparison.passed());
+        let tile = comparison.worst_tile.as_ref().unwrap();
+        assert!(!tile.passed());
+        assert!(tile.rect.contains(110, 150), "{}", tile);
+    }
+
+    #[test]
+    fn color_shift_catches_a_uniform_tint() {
+        let baseline = RgbaImage::from_fn(256, 256, pattern);
+        // 8% warmer, the kind of grade change SSIM barely sees
+        let tinted = RgbaImage::from_fn(256, 256, |x, y| {
+            let pixel = pattern(x, y);
+            Rgba([
+                (pixel[0] as f32 * 1.08).min(255.0) as u8,
+                pixel[1],
+                (pixel[2] as f32 * 0.92) as u8,
+                255,
+            ])
+        });
+        let comparison = compare_rgba(
+            &baseline,
+            &tinted,
+            0.95,
+            Some(2.0),
+            &SnapshotMask::default(),
+        )
+        .unwrap();
+        assert!(comparison.failures().is_empty(), "SSIM alone passes it");
+        let color_shift = comparison.color_shift.as_ref().unwrap();
+        assert!(!color_shift.passed(), "{}", color_shift);
+        assert!(!comparison.passed());
+
+        // zero-mean noise doesn't move the mean color
+        let noisy = RgbaImage::from_fn(256, 256, |x, y| {
+            let mut pixel = pattern(x, y);
+            pixel[0] = if (x + y) % 2 == 0 {
+                pixel[0].saturating_add(2)
+            } else {
+                pixel[0].saturating_sub(2)
+            };
+            pixel
+        });
+        assert!(
+            compare_rgba(&baseline, &noisy, 0.95, Some(2.0), &SnapshotMask::default())
+                .unwrap()
+                .passed()
+        );
+    }
+
+    #[test]
+    fn masks_ignore_regions_and_override_thresholds() {
+        let baseline = RgbaImage::from_fn(256, 256, pattern);
+        let result = with_block(BLOCK);
+        let mask: SnapshotMask = serde_json::from_str(
+            r#"{ "regions": [
+                { "name": "clock", "x": 90, "y": 130, "width": 40, "height": 40, "ignore": true }
+            ] }"#,
+        )
+        .unwrap();
+        let comparison = compare_rgba(&baseline, &result, 0.95, None, &mask).unwrap();
+        assert!(comparison.passed());
+        assert!(comparison.regions.is_empty());
+
+        let region = |threshold: f64| SnapshotMask {
+            threshold: None,
+            regions: vec![MaskRegion {
+                name: "particles".to_string(),
+                rect: Rect {
+                    x: 64,
+                    y: 128,
+                    width: 64,
+                    height: 64,
+                },
+                ignore: false,
+                threshold: Some(threshold),
+            }],
+        };
+        assert!(compare_rgba(&baseline, &result, 0.95, None, &region(0.1))
+            .unwrap()
+            .passed());
+        let comparison = compare_rgba(&baseline, &result, 0.95, None, &region(0.99)).unwrap();
+        assert_eq!(comparison.failures().len(), 1);
+        assert_eq!(comparison.regions[0].name, "region `particles`");
+
+        let diff_path =
+            std::env::temp_dir().join(format!("image_comparison_{}.diff.png", std::process::id()));
+        comparison.write_diff_image(&diff_path).unwrap();
+        assert_eq!(
+            image::open(&diff_path).unwrap().to_rgb8().dimensions(),
+            (256, 256)
+        );
+        fs::remove_file(&diff_path).unwrap();
+    }
+}
@@ -15,6 +15,7 @@ mod build_config;
 mod check_gdscript;
 mod consts;
 mod copy_files;
+mod crdt_replay;
 mod dependencies;
 mod doctor;
 mod download_file;
@@ -117,6 +118,36 @@ fn main() -> Result<(), anyhow::Error> {
         .subcommand(Command::new("doctor").about("Check system health and dependencies"))
         .subcommand(Command::new("check-gdscript").about("Validate all GDScript files for syntax errors"))
         .subcommand(Command::new("version-check").about("Check version consistency across files"))
+        .subcommand(
+            Command::new("crdt-replay")
+                .about("Rebuild a scene's CRDT state from a Scene Inspector JSONL session and dump it as JSON")
+                .arg(
+                    Arg::new("session")
+                        .help("Scene Inspector session file (.jsonl)")
+                        .required(true)
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::new("scene")
+                        .long("scene")
+                        .help("Scene id to replay (default: every scene in the session)")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::new("at")
+                        .long("at")
+                        .help("Where the replay stops: end, tick:<n> or t:<ms>")
+                        .default_value("end")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .help("Output JSON file (default: <session>.state.json)")
+                        .takes_value(true),
+                ),
+        )
         .subcommand(
             Command::new("fi-benchmark")
                 .about("Run floating islands memory benchmark with multiple client sessions")
@@ -380,6 +411,31 @@ fn main() -> Result<(), anyhow::Error> {
                         .help("Port for asset optimization server (default: 8080)")
                         .takes_value(true)
                         .default_value("8080"),
+                ).arg(
+                    Arg::new("headless-scene")
+                        .long("headless-scene")
+                        .help("Run the scene in this folder headless (no GPU) and exit non-zero on JS exceptions or invalid CRDT state")
+                        .takes_value(true),
+                ).arg(
+                    Arg::new("headless-scene-ticks")
+                        .long("headless-scene-ticks")
+                        .help("Number of ticks for --headless-scene (default: 300)")
+                        .takes_value(true),
+                ).arg(
+                    Arg::new("headless-scene-report")
+                        .long("headless-scene-report")
+                        .help("Write the --headless-scene report as JSON to this file")
+                        .takes_value(true),
+                ).arg(
+                    Arg::new("mock-comms")
+                        .long("mock-comms")
+                        .help("Replace the realm comms with a loopback server playing the fake peers of this JSON script (see tests/mock-comms-peers.json)")
+                        .takes_value(true),
+                ).arg(
+                    Arg::new("network-sim")
+                        .long("network-sim")
+                        .help("Simulate a bad network on incoming comms messages: 'mobile', 'bad' or rules like 'latency=200,jitter=80;movement:loss=0.1'")
+                        .takes_value(true),
                 ).arg(
                     Arg::new("deeplink")
                         .long("deeplink")
@@ -581,7 +637,8 @@ fn main() -> Result<(), anyhow::Error> {
         ("compare-image-folders", sm) => {
             let snapshot_folder = Path::new(sm.value_of("snapshots").unwrap());
             let result_folder = Path::new(sm.value_of("result").unwrap());
-            compare_images_folders(snapshot_folder, result_folder, 0.995)
+            // CI compares the avatar renders with it, same threshold as `test_avatar_generation`
+            compare_images_folders(snapshot_folder, result_folder, 0.95, None)
                 .map_err(|e| anyhow::anyhow!(e))
         }
         ("debug-hub", sm) => {
@@ -858,6 +915,40 @@ fn main() -> Result<(), anyhow::Error> {
                 }
             }
 
+            // Godot runs from the project folder, so the paths are made absolute
+            if let Some(scene_folder) = sm.value_of("headless-scene") {
+                let scene_folder = std::fs::canonicalize(scene_folder)
+                    .with_context(|| format!("scene folder not found: {scene_folder}"))?;
+                extras.push("--headless".to_string());
+                extras.push("--headless-scene".to_string());
+                extras.push(scene_folder.to_string_lossy().to_string());
+                if let Some(ticks) = sm.value_of("headless-scene-ticks") {
+                    extras.push("--headless-scene-ticks".to_string());
+                    extras.push(ticks.to_string());
+                }
+                if let Some(report) = sm.value_of("headless-scene-report") {
+                    extras.push("--headless-scene-report".to_string());
+                    extras.push(
+                        std::env::current_dir()?
+                            .join(report)
+                            .to_string_lossy()
+                            .to_string(),
+                    );
+                }
+            }
+
+            if let Some(script) = sm.value_of("mock-comms") {
+                let script = std::fs::canonicalize(script)
+                    .with_context(|| format!("mock comms script not found: {script}"))?;
+                extras.push("--mock-comms".to_string());
+                extras.push(script.to_string_lossy().to_string());
+            }
+
+            if let Some(spec) = sm.value_of("network-sim") {
+                extras.push("--network-sim".to_string());
+                extras.push(spec.to_string());
+            }
+
             run::run(
                 sm.is_present("editor"),
                 sm.is_present("itest"),
@@ -1007,6 +1098,12 @@ fn main() -> Result<(), anyhow::Error> {
         ),
         ("version-check", _) => version_check::run_version_check(),
         ("explorer-version", sm) => version::get_godot_explorer_version(sm.is_present("verbose")),
+        ("crdt-replay", sm) => crdt_replay::run_crdt_replay(
+            sm.value_of("session").unwrap(),
+            sm.value_of("scene"),
+            sm.value_of("at").unwrap(),
+            sm.value_of("output"),
+        ),
         ("fi-benchmark", sm) => fi_benchmark::run_fi_benchmark(sm.get_flag("headless")),
         ("avatar-impostor-benchmark", sm) => {
             let target: &str = sm
@@ -64,8 +64,10 @@ pub fn test_avatar_generation(
     // Move files
     move_dir_recursive(&avatar_output.canonicalize()?, &comparison_folder)?;
 
-    // Images comparison
-    compare_images_folders(&avatar_snapshot_folder, &comparison_folder, 0.90)
+    // Images comparison. The 0.90 of the old mean RGB similarity doesn't carry over to SSIM:
+    // on these baselines ±2 render noise scores 0.987 and antialiasing jitter keeps tiles above
+    // 0.918, while a missing 32px detail drops its tile to 0.65, under the 0.80 tile floor
+    compare_images_folders(&avatar_snapshot_folder, &comparison_folder, 0.95, None)
         .map_err(|e| anyhow::anyhow!(e))?;
 
     Ok(())
@@ -95,9 +97,15 @@ pub fn test_scene_generation(
     // Move files
     move_dir_recursive(&scene_output.canonicalize()?, &comparison_folder)?;
 
-    // Images comparison
-    compare_images_folders(&scene_renderer_snapshot_folder, &comparison_folder, 0.90)
-        .map_err(|e| anyhow::anyhow!(e))?;
+    // Images comparison, calibrated like the avatars: ±2 noise scores 0.963, antialiasing keeps
+    // tiles above 0.958 and a missing 32px detail drops its tile to 0.37
+    compare_images_folders(
+        &scene_renderer_snapshot_folder,
+        &comparison_folder,
+        0.95,
+        None,
+    )
+    .map_err(|e| anyhow::anyhow!(e))?;
 
     Ok(())
 }
@@ -134,10 +142,17 @@ pub fn test_lighting_generation(
         );
     }
 
-    // Stricter than scenes/avatars (0.90): lighting regressions are exactly
-    // the subtle color-grade shifts a loose threshold misses (issue #2516).
-    compare_images_folders(&lighting_snapshot_folder, &comparison_folder, 0.95)
-        .map_err(|e| anyhow::anyhow!(e))?;
+    // SSIM as strict as ±2 render noise allows (it scores 0.97 on these baselines) catches
+    // moved shadows and highlights. It barely sees a uniform grade (an 8% tint still scores
+    // above 0.99), so the mean color is held within 4 levels too: noise moves it 0.24 at most,
+    // a 4% tint 6.5 or more (issue #2516)
+    compare_images_folders(
+        &lighting_snapshot_folder,
+        &comparison_folder,
+        0.96,
+        Some(4.0),
+    )
+    .map_err(|e| anyhow::anyhow!(e))?;
 
     Ok(())
 }